@@ -29,7 +29,8 @@ DOCS := index intro tutorial guide guide-ffi guide-macros guide-ownership \
 	guide-tasks guide-container guide-pointers guide-testing \
 	guide-plugin guide-crates complement-bugreport guide-error-handling \
 	complement-lang-faq complement-design-faq complement-project-faq \
-    rustdoc guide-unsafe guide-strings reference
+    rustdoc guide-unsafe guide-strings reference save-analysis \
+    rustdoc-json
 
 PDF_DOCS := guide reference
 
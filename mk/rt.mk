@@ -52,6 +52,8 @@ NATIVE_DEPS_hoedown_$(1) := hoedown/src/autolink.c \
 			hoedown/src/version.c
 NATIVE_DEPS_miniz_$(1) = miniz.c
 NATIVE_DEPS_rust_builtin_$(1) := rust_builtin.c \
+			rust_coverage.c \
+			rust_pgo.c \
 			rust_android_dummy.c
 NATIVE_DEPS_rustrt_native_$(1) := \
 			rust_try.ll \
@@ -234,6 +236,32 @@ $$(COMPRT_LIB_$(1)): $$(COMPRT_DEPS) $$(MKFILE_DEPS)
 		triple-builtins
 	$$(Q)cp $$(COMPRT_BUILD_DIR_$(1))/triple/builtins/libcompiler_rt.a $$(COMPRT_LIB_$(1))
 
+# The profiling runtime of compiler-rt, linked into programs built with
+# -C profile-generate. It is a handful of C files and one C++ file without
+# any dependency on the C++ library, so it is built directly rather than
+# with compiler-rt's build system.
+PROFRT_NAME_$(1) := $$(call CFG_STATIC_LIB_NAME_$(1),profiler-rt)
+PROFRT_LIB_$(1) := $$(RT_OUTPUT_DIR_$(1))/$$(PROFRT_NAME_$(1))
+PROFRT_BUILD_DIR_$(1) := $$(RT_OUTPUT_DIR_$(1))/profile
+PROFRT_OBJS_$(1) := $$(patsubst $(S)src/compiler-rt/lib/profile/%.c, \
+                      $$(PROFRT_BUILD_DIR_$(1))/%.o, \
+                      $$(wildcard $(S)src/compiler-rt/lib/profile/*.c)) \
+                    $$(PROFRT_BUILD_DIR_$(1))/InstrProfilingRuntime.o
+
+$$(PROFRT_BUILD_DIR_$(1))/%.o: $(S)src/compiler-rt/lib/profile/%.c $$(MKFILE_DEPS)
+	@mkdir -p $$(@D)
+	@$$(call E, compile: $$@)
+	$$(Q)$$(call CFG_COMPILE_C_$(1), $$@, $$(RUNTIME_CFLAGS_$(1))) $$<
+
+$$(PROFRT_BUILD_DIR_$(1))/%.o: $(S)src/compiler-rt/lib/profile/%.cc $$(MKFILE_DEPS)
+	@mkdir -p $$(@D)
+	@$$(call E, compile: $$@)
+	$$(Q)$$(call CFG_COMPILE_CXX_$(1), $$@, -fno-exceptions -fno-rtti) $$<
+
+$$(PROFRT_LIB_$(1)): $$(PROFRT_OBJS_$(1))
+	@$$(call E, link: $$@)
+	$$(Q)$$(AR_$(1)) rcs $$@ $$^
+
 ################################################################################
 # libbacktrace
 #
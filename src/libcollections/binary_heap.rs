@@ -15,6 +15,10 @@
 //! complexity. A binary heap can also be converted to a sorted vector in-place, allowing it to
 //! be used for an `O(n log n)` in-place heapsort.
 //!
+//! `AddressableHeap` is a variant which hands out a `Handle` for every pushed element, so that
+//! the element can later be inspected, have its priority changed or be removed from the middle
+//! of the heap, each in `O(log n)` time.
+//!
 //! # Examples
 //!
 //! This is a larger example that implements [Dijkstra's algorithm][dijkstra]
@@ -155,7 +159,9 @@ use core::prelude::*;
 use core::default::Default;
 use core::iter::FromIterator;
 use core::mem::{zeroed, replace, swap};
+use core::num::Int;
 use core::ptr;
+use core::uint;
 
 use slice;
 use vec::{self, Vec};
@@ -216,11 +222,7 @@ impl<T: Ord> BinaryHeap<T> {
     /// ```
     pub fn from_vec(vec: Vec<T>) -> BinaryHeap<T> {
         let mut heap = BinaryHeap { data: vec };
-        let mut n = heap.len() / 2;
-        while n > 0 {
-            n -= 1;
-            heap.sift_down(n);
-        }
+        heap.rebuild();
         heap
     }
 
@@ -450,6 +452,66 @@ impl<T: Ord> BinaryHeap<T> {
         }
     }
 
+    /// Moves all the elements of `other` into `self`, leaving `other` empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BinaryHeap;
+    ///
+    /// let mut a = BinaryHeap::from_vec(vec![-10i, 1, 2, 3, 3]);
+    /// let mut b = BinaryHeap::from_vec(vec![-20i, 5, 43]);
+    ///
+    /// a.append(&mut b);
+    ///
+    /// assert_eq!(a.into_sorted_vec(), vec![-20, -10, 1, 2, 3, 3, 5, 43]);
+    /// assert!(b.is_empty());
+    /// ```
+    #[unstable = "recent addition"]
+    pub fn append(&mut self, other: &mut BinaryHeap<T>) {
+        if self.len() < other.len() {
+            swap(self, other);
+        }
+
+        if other.is_empty() {
+            return;
+        }
+
+        // Pushing every element costs `O(m log(n + m))`, while rebuilding the
+        // whole heap costs `O(n + m)`; pick whichever is cheaper.
+        let total = self.len() + other.len();
+        if other.len() * log2_floor(total) < total {
+            self.extend(other.drain());
+        } else {
+            self.data.extend(other.drain());
+            self.rebuild();
+        }
+    }
+
+    /// Retains only the elements specified by the predicate.
+    ///
+    /// In other words, remove all elements `e` such that `f(&e)` returns false.
+    /// The heap is rebuilt afterwards, which is `O(n)`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::BinaryHeap;
+    ///
+    /// let mut heap = BinaryHeap::from_vec(vec![1i, 2, 3, 4, 5, 6]);
+    /// heap.retain(|&x| x % 2 == 0);
+    ///
+    /// assert_eq!(heap.into_sorted_vec(), vec![2, 4, 6]);
+    /// ```
+    #[unstable = "recent addition"]
+    pub fn retain<F>(&mut self, f: F) where F: FnMut(&T) -> bool {
+        let len = self.len();
+        self.data.retain(f);
+        if self.len() != len {
+            self.rebuild();
+        }
+    }
+
     /// Consumes the `BinaryHeap` and returns the underlying vector
     /// in arbitrary order.
     ///
@@ -541,6 +603,15 @@ impl<T: Ord> BinaryHeap<T> {
         self.sift_down_range(pos, len);
     }
 
+    /// Restores the heap invariant over the whole of `data`.
+    fn rebuild(&mut self) {
+        let mut n = self.len() / 2;
+        while n > 0 {
+            n -= 1;
+            self.sift_down(n);
+        }
+    }
+
     /// Returns the length of the binary heap.
     #[stable]
     pub fn len(&self) -> uint { self.data.len() }
@@ -667,11 +738,347 @@ impl<T: Ord> Extend<T> for BinaryHeap<T> {
     }
 }
 
+/// Computes `floor(log2(x))` for a non-zero `x`.
+fn log2_floor(x: uint) -> uint {
+    uint::BITS - 1 - x.leading_zeros()
+}
+
+/// A handle to an element of an `AddressableHeap`.
+///
+/// Handles stay valid until the element they refer to is popped or removed;
+/// afterwards they are detected as stale, even if the underlying slot has been
+/// reused by a later push.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Show)]
+#[unstable = "recent addition"]
+pub struct Handle {
+    slot: uint,
+    generation: uint,
+}
+
+#[derive(Clone)]
+struct Slot {
+    // Position of the element in `AddressableHeap::data`, `None` when vacant.
+    pos: Option<uint>,
+    generation: uint,
+}
+
+/// A max-heap whose elements can be addressed after insertion.
+///
+/// Every `push` returns a `Handle` which can be used to `get`, `change_priority`
+/// or `remove` the element later on. This is what algorithms such as Dijkstra's
+/// need for their decrease-key operation, without leaving stale duplicates in
+/// the queue.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::binary_heap::AddressableHeap;
+///
+/// let mut heap = AddressableHeap::new();
+/// let a = heap.push(1i);
+/// let b = heap.push(5);
+/// heap.push(3);
+///
+/// assert_eq!(heap.peek(), Some(&5));
+///
+/// // Raise `a` above everything else.
+/// assert_eq!(heap.change_priority(a, 10), 1);
+/// assert_eq!(heap.peek(), Some(&10));
+///
+/// // Take `b` out of the middle of the heap.
+/// assert_eq!(heap.remove(b), Some(5));
+/// assert_eq!(heap.pop(), Some(10));
+/// assert_eq!(heap.pop(), Some(3));
+/// assert_eq!(heap.pop(), None);
+/// ```
+#[derive(Clone)]
+#[unstable = "recent addition"]
+pub struct AddressableHeap<T> {
+    // Each element is stored along with the index of its slot.
+    data: Vec<(T, uint)>,
+    slots: Vec<Slot>,
+    free: Vec<uint>,
+}
+
+#[unstable = "recent addition"]
+impl<T: Ord> Default for AddressableHeap<T> {
+    #[inline]
+    fn default() -> AddressableHeap<T> { AddressableHeap::new() }
+}
+
+impl<T: Ord> AddressableHeap<T> {
+    /// Creates an empty `AddressableHeap` as a max-heap.
+    #[unstable = "recent addition"]
+    pub fn new() -> AddressableHeap<T> {
+        AddressableHeap { data: vec![], slots: vec![], free: vec![] }
+    }
+
+    /// Creates an empty `AddressableHeap` with space for at least `capacity`
+    /// elements.
+    #[unstable = "recent addition"]
+    pub fn with_capacity(capacity: uint) -> AddressableHeap<T> {
+        AddressableHeap {
+            data: Vec::with_capacity(capacity),
+            slots: Vec::with_capacity(capacity),
+            free: vec![],
+        }
+    }
+
+    /// Returns the number of elements in the heap.
+    #[unstable = "recent addition"]
+    pub fn len(&self) -> uint { self.data.len() }
+
+    /// Checks if the heap is empty.
+    #[unstable = "recent addition"]
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+
+    /// Returns the greatest item in the heap, or `None` if it is empty.
+    #[unstable = "recent addition"]
+    pub fn peek(&self) -> Option<&T> {
+        self.data.get(0).map(|&(ref item, _)| item)
+    }
+
+    /// Returns the handle of the greatest item in the heap, or `None` if it is
+    /// empty.
+    #[unstable = "recent addition"]
+    pub fn peek_handle(&self) -> Option<Handle> {
+        self.data.get(0).map(|&(_, slot)| self.handle_for(slot))
+    }
+
+    /// Returns an iterator visiting all values in the heap, in arbitrary order.
+    #[unstable = "recent addition"]
+    pub fn iter(&self) -> AddressableIter<T> {
+        AddressableIter { iter: self.data.iter() }
+    }
+
+    /// Returns `true` if `handle` still refers to an element of this heap.
+    #[unstable = "recent addition"]
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.position(handle).is_some()
+    }
+
+    /// Returns a reference to the element referred to by `handle`, or `None`
+    /// if it has already been popped or removed.
+    #[unstable = "recent addition"]
+    pub fn get(&self, handle: Handle) -> Option<&T> {
+        self.position(handle).map(|pos| &self.data[pos].0)
+    }
+
+    /// Pushes an item onto the heap, returning a handle to it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::binary_heap::AddressableHeap;
+    ///
+    /// let mut heap = AddressableHeap::new();
+    /// let h = heap.push(3i);
+    /// assert_eq!(heap.get(h), Some(&3));
+    /// ```
+    #[unstable = "recent addition"]
+    pub fn push(&mut self, item: T) -> Handle {
+        let pos = self.len();
+        let slot = match self.free.pop() {
+            Some(slot) => {
+                self.slots[slot].pos = Some(pos);
+                slot
+            }
+            None => {
+                self.slots.push(Slot { pos: Some(pos), generation: 0 });
+                self.slots.len() - 1
+            }
+        };
+        self.data.push((item, slot));
+        self.sift_up(pos);
+        self.handle_for(slot)
+    }
+
+    /// Removes the greatest item from the heap and returns it, or `None` if it
+    /// is empty. Its handle becomes stale.
+    #[unstable = "recent addition"]
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.remove_at(0))
+        }
+    }
+
+    /// Removes the element referred to by `handle` and returns it, or `None`
+    /// if the handle is stale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::binary_heap::AddressableHeap;
+    ///
+    /// let mut heap = AddressableHeap::new();
+    /// let h = heap.push(3i);
+    /// heap.push(7);
+    ///
+    /// assert_eq!(heap.remove(h), Some(3));
+    /// assert_eq!(heap.remove(h), None);
+    /// assert_eq!(heap.len(), 1);
+    /// ```
+    #[unstable = "recent addition"]
+    pub fn remove(&mut self, handle: Handle) -> Option<T> {
+        self.position(handle).map(|pos| self.remove_at(pos))
+    }
+
+    /// Replaces the element referred to by `handle` with `item`, moving it up
+    /// or down the heap as needed, and returns the previous value. The handle
+    /// stays valid.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `handle` is stale.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::binary_heap::AddressableHeap;
+    ///
+    /// let mut heap = AddressableHeap::new();
+    /// let h = heap.push(3i);
+    /// heap.push(7);
+    ///
+    /// assert_eq!(heap.change_priority(h, 9), 3);
+    /// assert_eq!(heap.peek_handle(), Some(h));
+    /// ```
+    #[unstable = "recent addition"]
+    pub fn change_priority(&mut self, handle: Handle, item: T) -> T {
+        let pos = match self.position(handle) {
+            Some(pos) => pos,
+            None => panic!("AddressableHeap::change_priority: stale handle"),
+        };
+        let old = replace(&mut self.data[pos].0, item);
+        self.sift_up(pos);
+        let pos = self.slots[handle.slot].pos.unwrap();
+        self.sift_down(pos);
+        old
+    }
+
+    /// Drops all items from the heap, invalidating every handle.
+    #[unstable = "recent addition"]
+    pub fn clear(&mut self) {
+        while let Some(_) = self.pop() {}
+    }
+
+    /// Consumes the heap and returns its elements in arbitrary order.
+    #[unstable = "recent addition"]
+    pub fn into_vec(self) -> Vec<T> {
+        self.data.into_iter().map(|(item, _)| item).collect()
+    }
+
+    fn handle_for(&self, slot: uint) -> Handle {
+        Handle { slot: slot, generation: self.slots[slot].generation }
+    }
+
+    fn position(&self, handle: Handle) -> Option<uint> {
+        match self.slots.get(handle.slot) {
+            Some(s) if s.generation == handle.generation => s.pos,
+            _ => None,
+        }
+    }
+
+    fn remove_at(&mut self, pos: uint) -> T {
+        let last = self.len() - 1;
+        self.swap(pos, last);
+        let (item, slot) = self.data.pop().unwrap();
+
+        self.slots[slot].pos = None;
+        self.slots[slot].generation += 1;
+        self.free.push(slot);
+
+        if pos < self.len() {
+            self.sift_up(pos);
+            let moved = self.data[pos].1;
+            let pos = self.slots[moved].pos.unwrap();
+            self.sift_down(pos);
+        }
+        item
+    }
+
+    // Unlike `BinaryHeap`, every move must also update the slot table, so
+    // these are written in terms of `swap` rather than hole-shifting.
+    fn swap(&mut self, a: uint, b: uint) {
+        self.data.swap(a, b);
+        let slot_a = self.data[a].1;
+        let slot_b = self.data[b].1;
+        self.slots[slot_a].pos = Some(a);
+        self.slots[slot_b].pos = Some(b);
+    }
+
+    fn sift_up(&mut self, mut pos: uint) {
+        while pos > 0 {
+            let parent = (pos - 1) >> 1;
+            if self.data[pos].0 <= self.data[parent].0 { break; }
+            self.swap(pos, parent);
+            pos = parent;
+        }
+    }
+
+    fn sift_down(&mut self, mut pos: uint) {
+        let end = self.len();
+        loop {
+            let mut child = 2 * pos + 1;
+            if child >= end { break; }
+            let right = child + 1;
+            if right < end && !(self.data[child].0 > self.data[right].0) {
+                child = right;
+            }
+            if self.data[pos].0 >= self.data[child].0 { break; }
+            self.swap(pos, child);
+            pos = child;
+        }
+    }
+}
+
+/// `AddressableHeap` iterator.
+#[unstable = "recent addition"]
+pub struct AddressableIter<'a, T: 'a> {
+    iter: slice::Iter<'a, (T, uint)>,
+}
+
+#[unstable = "recent addition"]
+impl<'a, T> Iterator for AddressableIter<'a, T> {
+    type Item = &'a T;
+
+    #[inline]
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next().map(|&(ref item, _)| item)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (uint, Option<uint>) { self.iter.size_hint() }
+}
+
+#[unstable = "recent addition"]
+impl<'a, T> ExactSizeIterator for AddressableIter<'a, T> {}
+
+#[unstable = "recent addition"]
+impl<T: Ord> FromIterator<T> for AddressableHeap<T> {
+    fn from_iter<Iter: Iterator<Item=T>>(iter: Iter) -> AddressableHeap<T> {
+        let mut heap = AddressableHeap::new();
+        heap.extend(iter);
+        heap
+    }
+}
+
+#[unstable = "recent addition"]
+impl<T: Ord> Extend<T> for AddressableHeap<T> {
+    fn extend<Iter: Iterator<Item=T>>(&mut self, iter: Iter) {
+        for elem in iter {
+            self.push(elem);
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use prelude::*;
 
-    use super::BinaryHeap;
+    use super::{AddressableHeap, BinaryHeap};
 
     #[test]
     fn test_iterator() {
@@ -881,4 +1288,104 @@ mod tests {
 
         assert!(q.is_empty());
     }
+
+    #[test]
+    fn test_append() {
+        let mut a = BinaryHeap::from_vec(vec![-10i, 1, 2, 3, 3]);
+        let mut b = BinaryHeap::from_vec(vec![-20i, 5, 43]);
+
+        a.append(&mut b);
+
+        assert_eq!(a.into_sorted_vec(), vec![-20, -10, 1, 2, 3, 3, 5, 43]);
+        assert!(b.is_empty());
+    }
+
+    #[test]
+    fn test_append_to_empty() {
+        let mut a = BinaryHeap::new();
+        let mut b = BinaryHeap::from_vec(vec![-20i, 5, 43]);
+
+        a.append(&mut b);
+
+        assert_eq!(a.into_sorted_vec(), vec![-20, 5, 43]);
+        assert!(b.is_empty());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut heap = BinaryHeap::from_vec(vec![2u, 4, 6, 2, 1, 8, 10, 3, 5, 7, 0, 9, 1]);
+        heap.retain(|&x| x != 2 && x < 9);
+
+        assert_eq!(heap.into_sorted_vec(), vec![0, 1, 1, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_addressable_pop_order() {
+        let data = vec!(2u, 4, 6, 2, 1, 8, 10, 3, 5, 7, 0, 9, 1);
+        let mut sorted = data.clone();
+        sorted.sort();
+        let mut heap: AddressableHeap<uint> = data.into_iter().collect();
+        while !heap.is_empty() {
+            assert_eq!(heap.peek().unwrap(), sorted.last().unwrap());
+            assert_eq!(heap.pop().unwrap(), sorted.pop().unwrap());
+        }
+        assert!(heap.pop().is_none());
+    }
+
+    #[test]
+    fn test_addressable_change_priority() {
+        let mut heap = AddressableHeap::new();
+        let handles: Vec<_> = range(0i, 10).map(|x| heap.push(x)).collect();
+
+        // Decrease the current maximum, increase a minimum.
+        assert_eq!(heap.change_priority(handles[9], -1), 9);
+        assert_eq!(heap.change_priority(handles[2], 20), 2);
+        assert_eq!(heap.peek_handle(), Some(handles[2]));
+
+        for &h in handles.iter() {
+            assert!(heap.contains(h));
+        }
+        assert_eq!(heap.get(handles[9]), Some(&-1));
+
+        assert_eq!(heap.into_vec().len(), 10);
+    }
+
+    #[test]
+    fn test_addressable_remove() {
+        let mut heap = AddressableHeap::new();
+        let handles: Vec<_> = range(0i, 10).map(|x| heap.push(x)).collect();
+
+        assert_eq!(heap.remove(handles[4]), Some(4));
+        assert_eq!(heap.remove(handles[9]), Some(9));
+        assert_eq!(heap.remove(handles[4]), None);
+        assert!(!heap.contains(handles[4]));
+
+        let mut out = vec![];
+        while let Some(x) = heap.pop() {
+            out.push(x);
+        }
+        assert_eq!(out, vec![8, 7, 6, 5, 3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_addressable_stale_handle() {
+        let mut heap = AddressableHeap::new();
+        let a = heap.push(1i);
+        assert_eq!(heap.pop(), Some(1));
+
+        // The slot is reused, but the old handle must not see the new element.
+        let b = heap.push(2i);
+        assert!(a != b);
+        assert_eq!(heap.get(a), None);
+        assert_eq!(heap.get(b), Some(&2));
+    }
+
+    #[test]
+    #[should_fail]
+    fn test_addressable_change_priority_stale() {
+        let mut heap = AddressableHeap::new();
+        let a = heap.push(1i);
+        heap.remove(a);
+        heap.change_priority(a, 2);
+    }
 }
@@ -0,0 +1,203 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Lint configuration files, read with
+//! `-Z unstable-options --lint-config FILE`.
+//!
+//! A lint configuration file is a JSON object that sets lint levels without
+//! attributes in the source:
+//!
+//! ```json
+//! {
+//!     "lints": { "missing_docs": "warn", "unused": "deny" },
+//!     "modules": {
+//!         "net::ffi": { "non_camel_case_types": "allow" }
+//!     },
+//!     "caps": { "regex": "warn" }
+//! }
+//! ```
+//!
+//! `lints` sets levels for the whole crate, like `-A`/`-W`/`-D`/`-F` do.
+//! `modules` sets levels for the given module paths, relative to the crate
+//! root, and everything inside them, as an attribute on the module would.
+//! `caps` names crates, typically dependencies, that should never report a
+//! lint at a level above the one given, whatever their attributes say.
+//! All sections are optional. Lint names may be lint groups and use either
+//! underscores or dashes.
+//!
+//! Levels set on the command line take precedence over the levels from the
+//! file, both crate-wide and for modules, and attributes in the source over
+//! both. Lints report which part of the file set their level.
+
+use lint::Level;
+
+use std::io::File;
+
+use serialize::json::{self, Json};
+
+/// The lint levels one module path gets from the configuration file.
+#[derive(Clone, Show)]
+pub struct ModuleLints {
+    /// Path of the module from the crate root, e.g. `["net", "ffi"]`.
+    pub path: Vec<String>,
+    pub lints: Vec<(String, Level)>,
+}
+
+/// A parsed lint configuration file.
+#[derive(Clone, Show)]
+pub struct LintConfig {
+    /// Where the configuration was read from, for diagnostics.
+    pub path: Path,
+    pub lints: Vec<(String, Level)>,
+    /// Sorted by path, not in the order the file lists them.
+    pub modules: Vec<ModuleLints>,
+    pub caps: Vec<(String, Level)>,
+}
+
+impl LintConfig {
+    /// Reads and parses the configuration file at `path`.
+    pub fn load(path: &Path) -> Result<LintConfig, String> {
+        let src = match File::open(path).read_to_string() {
+            Ok(src) => src,
+            Err(e) => return Err(format!("couldn't read lint config `{}`: {}",
+                                         path.display(), e)),
+        };
+        LintConfig::parse(path, src.as_slice())
+    }
+
+    pub fn parse(path: &Path, src: &str) -> Result<LintConfig, String> {
+        let error = |&: msg: String| {
+            format!("invalid lint config `{}`: {}", path.display(), msg)
+        };
+
+        let json = match json::from_str(src) {
+            Ok(json) => json,
+            Err(e) => return Err(error(format!("{}", e))),
+        };
+        let root = match json {
+            Json::Object(root) => root,
+            _ => return Err(error("expected a JSON object".to_string())),
+        };
+
+        let mut config = LintConfig {
+            path: path.clone(),
+            lints: Vec::new(),
+            modules: Vec::new(),
+            caps: Vec::new(),
+        };
+
+        for (key, value) in root.iter() {
+            match key.as_slice() {
+                "lints" => {
+                    config.lints = try!(parse_levels("lints", value).map_err(|e| error(e)));
+                }
+                "caps" => {
+                    config.caps = try!(parse_levels("caps", value).map_err(|e| error(e)));
+                }
+                "modules" => {
+                    let modules = match value.as_object() {
+                        Some(modules) => modules,
+                        None => return Err(error("`modules` must be an object".to_string())),
+                    };
+                    for (path, levels) in modules.iter() {
+                        let what = format!("modules.{}", path);
+                        let lints = try!(parse_levels(what.as_slice(), levels)
+                                             .map_err(|e| error(e)));
+                        config.modules.push(ModuleLints {
+                            path: path.split_str("::")
+                                      .filter(|s| !s.is_empty())
+                                      .map(|s| s.to_string())
+                                      .collect(),
+                            lints: lints,
+                        });
+                    }
+                }
+                _ => return Err(error(format!("unknown section `{}`", key))),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// The level no lint may exceed when compiling the crate `crate_name`.
+    pub fn cap_for(&self, crate_name: &str) -> Option<Level> {
+        self.caps.iter()
+                 .find(|&&(ref name, _)| name.as_slice() == crate_name)
+                 .map(|&(_, level)| level)
+    }
+}
+
+fn parse_levels(what: &str, json: &Json) -> Result<Vec<(String, Level)>, String> {
+    let obj = match json.as_object() {
+        Some(obj) => obj,
+        None => return Err(format!("`{}` must be an object", what)),
+    };
+    let mut levels = Vec::new();
+    for (name, level) in obj.iter() {
+        match level.as_string().and_then(Level::from_str) {
+            Some(level) => levels.push((name.replace("-", "_"), level)),
+            None => {
+                return Err(format!("`{}.{}` must be one of \"allow\", \"warn\", \
+                                    \"deny\" or \"forbid\"", what, name))
+            }
+        }
+    }
+    Ok(levels)
+}
+
+#[cfg(test)]
+mod test {
+    use super::LintConfig;
+    use lint::{Allow, Warn, Deny};
+
+    fn parse(src: &str) -> Result<LintConfig, String> {
+        LintConfig::parse(&Path::new("lints.json"), src)
+    }
+
+    #[test]
+    fn sections() {
+        let config = parse(r#"{
+            "lints": { "missing-docs": "warn", "unused": "deny" },
+            "modules": {
+                "net::ffi": { "non_camel_case_types": "allow" }
+            },
+            "caps": { "regex": "warn" }
+        }"#).unwrap();
+
+        assert_eq!(config.lints, vec![("missing_docs".to_string(), Warn),
+                                      ("unused".to_string(), Deny)]);
+        assert_eq!(config.modules.len(), 1);
+        assert_eq!(config.modules[0].path, vec!["net".to_string(), "ffi".to_string()]);
+        assert_eq!(config.modules[0].lints, vec![("non_camel_case_types".to_string(), Allow)]);
+        assert_eq!(config.cap_for("regex"), Some(Warn));
+        assert_eq!(config.cap_for("log"), None);
+    }
+
+    #[test]
+    fn empty() {
+        let config = parse("{}").unwrap();
+        assert!(config.lints.is_empty());
+        assert!(config.modules.is_empty());
+        assert!(config.caps.is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(parse(r#"{ "lints": { "dead_code": "loud" } }"#).unwrap_err(),
+                   "invalid lint config `lints.json`: `lints.dead_code` must be one of \
+                    \"allow\", \"warn\", \"deny\" or \"forbid\"");
+        assert_eq!(parse(r#"{ "modules": { "a::b": "deny" } }"#).unwrap_err(),
+                   "invalid lint config `lints.json`: `modules.a::b` must be an object");
+        assert_eq!(parse(r#"{ "lint": {} }"#).unwrap_err(),
+                   "invalid lint config `lints.json`: unknown section `lint`");
+        assert!(parse("[]").is_err());
+        assert!(parse("{").is_err());
+    }
+}
@@ -29,7 +29,7 @@ use middle::privacy::ExportedItems;
 use middle::ty::{self, Ty};
 use session::{early_error, Session};
 use lint::{Level, LevelSource, Lint, LintId, LintArray, LintPass, LintPassObject};
-use lint::{Default, CommandLine, Node, Allow, Warn, Deny, Forbid};
+use lint::{Default, CommandLine, ConfigFile, Node, Allow, Warn, Deny, Forbid};
 use lint::builtin;
 use util::nodemap::FnvHashMap;
 
@@ -40,7 +40,7 @@ use syntax::attr::AttrMetaMethods;
 use syntax::attr;
 use syntax::codemap::Span;
 use syntax::visit::{Visitor, FnKind};
-use syntax::parse::token::InternedString;
+use syntax::parse::token::{self, InternedString};
 use syntax::{ast, ast_util, visit};
 
 /// Information about the registered lints.
@@ -66,6 +66,15 @@ pub struct LintStore {
     /// Map of registered lint groups to what lints they expand to. The bool
     /// is true if the lint group was added by a plugin.
     lint_groups: FnvHashMap<&'static str, (Vec<LintId>, bool)>,
+
+    /// Levels the lint configuration file sets for module paths, sorted by
+    /// path since the `modules` section is read as a JSON object. The index
+    /// of an entry is what `ConfigFile` sources refer to.
+    module_levels: Vec<(Vec<String>, Vec<(LintId, Level)>)>,
+
+    /// The highest level any lint may have in this crate, from the `caps`
+    /// of the lint configuration file.
+    cap: Option<Level>,
 }
 
 /// The targed of the `by_name` map, which accounts for renaming/deprecation.
@@ -85,7 +94,11 @@ impl LintStore {
         }
     }
 
-    fn set_level(&mut self, lint: LintId, lvlsrc: LevelSource) {
+    fn set_level(&mut self, lint: LintId, mut lvlsrc: LevelSource) {
+        match self.cap {
+            Some(cap) if lvlsrc.0 > cap => lvlsrc.0 = cap,
+            _ => {}
+        }
         if lvlsrc.0 == Allow {
             self.levels.remove(&lint);
         } else {
@@ -100,6 +113,8 @@ impl LintStore {
             by_name: FnvHashMap::new(),
             levels: FnvHashMap::new(),
             lint_groups: FnvHashMap::new(),
+            module_levels: vec!(),
+            cap: None,
         }
     }
 
@@ -133,7 +148,7 @@ impl LintStore {
             }
 
             if lint.default_level != Allow {
-                self.levels.insert(id, (lint.default_level, Default));
+                self.set_level(id, (lint.default_level, Default));
             }
         }
         self.passes.as_mut().unwrap().push(pass);
@@ -276,6 +291,69 @@ impl LintStore {
         }
     }
 
+    /// The lints `name` stands for: the lint itself, or the members of the
+    /// group of that name.
+    fn lints_named(&self, name: &str, sess: &Session) -> Option<Vec<LintId>> {
+        match self.find_lint(name, sess, None) {
+            Some(lint_id) => Some(vec![lint_id]),
+            None => self.lint_groups.get(name).map(|&(ref v, _)| v.clone()),
+        }
+    }
+
+    /// Resolves the lint names of `levels`, applying groups before single
+    /// lints so that a lint can override the level of its group.
+    fn resolve_config_levels(&self, sess: &Session, levels: &[(String, Level)])
+                             -> Vec<(LintId, Level)> {
+        let mut groups = vec!();
+        let mut lints = vec!();
+        for &(ref name, level) in levels.iter() {
+            let is_group = self.lint_groups.contains_key(name.as_slice());
+            match self.lints_named(name.as_slice(), sess) {
+                Some(ids) => {
+                    let dest = if is_group { &mut groups } else { &mut lints };
+                    dest.extend(ids.into_iter().map(|id| (id, level)));
+                }
+                None => {
+                    let path = &sess.opts.lint_config.as_ref().unwrap().path;
+                    sess.err(format!("unknown lint `{}` in lint config `{}`",
+                                     name, path.display()).as_slice());
+                }
+            }
+        }
+        groups.extend(lints.into_iter());
+        groups
+    }
+
+    /// Applies the lint configuration file, if there is one, for the crate
+    /// `crate_name`. This must run before `process_command_line`, whose
+    /// levels take precedence.
+    pub fn process_config(&mut self, sess: &Session, crate_name: &str) {
+        let config = match sess.opts.lint_config {
+            Some(ref config) => config,
+            None => return,
+        };
+
+        self.cap = config.cap_for(crate_name);
+        if self.cap.is_some() {
+            // Lower the default levels that are already in place.
+            let ids: Vec<LintId> = self.levels.keys().map(|&id| id).collect();
+            for id in ids.into_iter() {
+                let lvlsrc = self.get_level_source(id);
+                self.set_level(id, lvlsrc);
+            }
+        }
+
+        for (lint_id, level) in self.resolve_config_levels(sess, config.lints.as_slice())
+                                    .into_iter() {
+            self.set_level(lint_id, (level, ConfigFile(None)));
+        }
+
+        let module_levels = config.modules.iter().map(|m| {
+            (m.path.clone(), self.resolve_config_levels(sess, m.lints.as_slice()))
+        }).collect();
+        self.module_levels = module_levels;
+    }
+
     pub fn process_command_line(&mut self, sess: &Session) {
         for &(ref lint_name, level) in sess.opts.lint_opts.iter() {
             match self.find_lint(lint_name.index(&FullRange), sess, None) {
@@ -322,6 +400,10 @@ pub struct Context<'a, 'tcx: 'a> {
     /// Level of lints for certain NodeIds, stored here because the body of
     /// the lint needs to run in trans.
     node_levels: RefCell<FnvHashMap<(ast::NodeId, LintId), LevelSource>>,
+
+    /// Path of the module being checked, from the crate root. Used to find
+    /// the levels the lint configuration file sets for it.
+    mod_path: Vec<String>,
 }
 
 /// Convenience macro for calling a `LintPass` method on every pass in the context.
@@ -397,6 +479,15 @@ pub fn raw_emit_lint(sess: &Session, lint: &'static Lint,
             note = Some(src);
             msg.to_string()
         }
+        ConfigFile(module) => {
+            let config = sess.opts.lint_config.as_ref().unwrap();
+            match module {
+                Some(i) => format!("{} [set for `{}` by {}]", msg,
+                                   config.modules[i].path.connect("::"),
+                                   config.path.display()),
+                None => format!("{} [set by {}]", msg, config.path.display()),
+            }
+        }
     };
 
     // For purposes of printing, we can treat forbid as deny.
@@ -430,6 +521,7 @@ impl<'a, 'tcx> Context<'a, 'tcx> {
             lints: lint_store,
             level_stack: vec![],
             node_levels: RefCell::new(FnvHashMap::new()),
+            mod_path: vec![],
         }
     }
 
@@ -529,7 +621,41 @@ impl<'a, 'tcx> Context<'a, 'tcx> {
         f(self);
         run_lints!(self, exit_lint_attrs, attrs);
 
-        // rollback
+        self.pop_levels(pushed);
+    }
+
+    /// Merge the lint levels the lint configuration file sets for the
+    /// module at `mod_path`, returning how many levels were changed. Levels
+    /// given on the command line are kept.
+    fn push_config_levels(&mut self) -> uint {
+        let mut changes = vec![];
+        for (i, &(ref path, ref levels)) in self.lints.module_levels.iter().enumerate() {
+            if *path == self.mod_path {
+                changes.extend(levels.iter().map(|&(lint_id, level)| (lint_id, level, i)));
+            }
+        }
+
+        let mut pushed = 0u;
+        for (lint_id, level, i) in changes.into_iter() {
+            let (now, src) = self.lints.get_level_source(lint_id);
+            if now == Forbid && level != Forbid {
+                let lint_name = lint_id.as_str();
+                self.tcx.sess.err(format!("{}({}) for `{}` in lint config overruled by \
+                                           outer forbid({})",
+                                          level.as_str(), lint_name,
+                                          self.mod_path.connect("::"),
+                                          lint_name).as_slice());
+            } else if now != level && src != CommandLine {
+                self.level_stack.push((lint_id, (now, src)));
+                pushed += 1;
+                self.lints.set_level(lint_id, (level, ConfigFile(Some(i))));
+            }
+        }
+        pushed
+    }
+
+    /// Undo the last `pushed` changes to the lint levels.
+    fn pop_levels(&mut self, pushed: uint) {
         for _ in range(0, pushed) {
             let (lint, lvlsrc) = self.level_stack.pop().unwrap();
             self.lints.set_level(lint, lvlsrc);
@@ -550,11 +676,26 @@ impl<'a, 'tcx> Context<'a, 'tcx> {
 
 impl<'a, 'tcx, 'v> Visitor<'v> for Context<'a, 'tcx> {
     fn visit_item(&mut self, it: &ast::Item) {
+        let is_mod = match it.node {
+            ast::ItemMod(..) => true,
+            _ => false,
+        };
+        let mut pushed = 0;
+        if is_mod {
+            self.mod_path.push(token::get_ident(it.ident).get().to_string());
+            pushed = self.push_config_levels();
+        }
+
         self.with_lint_attrs(it.attrs.index(&FullRange), |cx| {
             run_lints!(cx, check_item, it);
             cx.visit_ids(|v| v.visit_item(it));
             visit::walk_item(cx, it);
-        })
+        });
+
+        if is_mod {
+            self.pop_levels(pushed);
+            self.mod_path.pop();
+        }
     }
 
     fn visit_foreign_item(&mut self, it: &ast::ForeignItem) {
@@ -208,7 +208,7 @@ impl LintId {
 }
 
 /// Setting for how to handle a lint.
-#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
+#[derive(Clone, Copy, PartialEq, PartialOrd, Eq, Ord, Show)]
 pub enum Level {
     Allow, Warn, Deny, Forbid
 }
@@ -248,10 +248,15 @@ pub enum LintSource {
 
     /// Lint level was set by a command-line flag.
     CommandLine,
+
+    /// Lint level was set by the lint configuration file, either for the
+    /// whole crate or for the module at the given index of its `modules`.
+    ConfigFile(Option<uint>),
 }
 
 pub type LevelSource = (Level, LintSource);
 
 pub mod builtin;
+pub mod config;
 
 mod context;
@@ -143,10 +143,14 @@ pub enum astencode_tag { // Reserves 0x40 -- 0x5f
     tag_table_upvar_borrow_map = 0x55,
     tag_table_capture_modes = 0x56,
     tag_table_object_cast_map = 0x57,
+    tag_table_free_regions = 0x58,
+    // The tables of one item, for incremental compilation
+    tag_item_tables = 0x59,
+    tag_item_tables_key = 0x5a,
 }
 
 static first_astencode_tag: uint = tag_ast as uint;
-static last_astencode_tag: uint = tag_table_object_cast_map as uint;
+static last_astencode_tag: uint = tag_item_tables_key as uint;
 impl astencode_tag {
     pub fn from_uint(value : uint) -> Option<astencode_tag> {
         let is_a_tag = first_astencode_tag <= value && value <= last_astencode_tag;
@@ -17,10 +17,12 @@ pub use self::InlinedItemRef::*;
 
 use back::svh::Svh;
 use session::config;
+use session::profile;
 use metadata::common::*;
 use metadata::cstore;
 use metadata::decoder;
 use metadata::tyencode;
+use middle::astencode;
 use middle::def;
 use middle::ty::{lookup_item_type};
 use middle::ty::{self, Ty};
@@ -2183,6 +2185,9 @@ fn encode_metadata_inner(wr: &mut SeekableMemWriter,
     encode_struct_field_attrs(&mut rbml_w, krate);
 
     stats.total_bytes = rbml_w.writer.tell().unwrap();
+    profile::counter("metadata: item bytes", stats.item_bytes);
+    profile::counter("metadata: index bytes", stats.index_bytes);
+    profile::counter("metadata: total bytes", stats.total_bytes);
 
     if tcx.sess.meta_stats() {
         for e in rbml_w.writer.get_ref().iter() {
@@ -2208,6 +2213,45 @@ fn encode_metadata_inner(wr: &mut SeekableMemWriter,
     }
 }
 
+/// Encodes what type checking recorded for each of `items`, given by a key
+/// and its node ids, into the file incremental compilation keeps next to its
+/// dependency graph. The types of all items share one buffer, as tydecode
+/// caches what it decodes by position.
+pub fn encode_item_tables(parms: EncodeParams,
+                          items: &[(String, ast_util::IdRange)]) -> Vec<u8> {
+    let EncodeParams {
+        item_symbols,
+        diag,
+        tcx,
+        reexports,
+        cstore,
+        encode_inlined_item,
+        link_meta,
+        reachable,
+        ..
+    } = parms;
+    let ecx = EncodeContext {
+        diag: diag,
+        tcx: tcx,
+        reexports: reexports,
+        item_symbols: item_symbols,
+        link_meta: link_meta,
+        cstore: cstore,
+        encode_inlined_item: RefCell::new(encode_inlined_item),
+        type_abbrevs: RefCell::new(FnvHashMap::new()),
+        reachable: reachable,
+    };
+
+    let mut wr = SeekableMemWriter::new();
+    {
+        let mut rbml_w = writer::Encoder::new(&mut wr);
+        for &(ref key, ids) in items.iter() {
+            astencode::encode_item_tables(&ecx, &mut rbml_w, key.as_slice(), ids);
+        }
+    }
+    wr.unwrap()
+}
+
 // Get the encoded string for a type
 pub fn encoded_ty<'tcx>(tcx: &ty::ctxt<'tcx>, t: Ty<'tcx>) -> String {
     let mut wr = SeekableMemWriter::new();
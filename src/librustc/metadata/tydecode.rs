@@ -378,7 +378,7 @@ fn parse_region_<F>(st: &mut PState, conv: &mut F) -> ty::Region where
         ty::ReStatic
       }
       'e' => {
-        ty::ReStatic
+        ty::ReEmpty
       }
       _ => panic!("parse_region: bad input")
     }
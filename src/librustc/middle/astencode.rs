@@ -12,6 +12,8 @@
 // FIXME: remove this after snapshot, and Results are handled
 #![allow(unused_must_use)]
 
+use self::DecodeSource::{Inlined, Previous};
+
 use metadata::common as c;
 use metadata::cstore as cstore;
 use session::Session;
@@ -27,6 +29,7 @@ use middle::mem_categorization::Typer;
 use middle::subst;
 use middle::subst::VecPerParamSpace;
 use middle::ty::{self, Ty, MethodCall, MethodCallee, MethodOrigin};
+use middle::ty_fold::{self, TypeFoldable};
 use util::ppaux::ty_to_string;
 
 use syntax::{ast, ast_map, ast_util, codemap, fold};
@@ -37,6 +40,7 @@ use syntax::parse::token;
 use syntax::ptr::P;
 use syntax;
 
+use std::collections::HashMap;
 use std::io::Seek;
 use std::rc::Rc;
 
@@ -52,9 +56,17 @@ use serialize::{EncoderHelpers};
 
 struct DecodeContext<'a, 'b, 'tcx: 'a> {
     tcx: &'a ty::ctxt<'tcx>,
-    cdata: &'b cstore::crate_metadata,
-    from_id_range: ast_util::IdRange,
-    to_id_range: ast_util::IdRange
+    source: DecodeSource<'b>,
+}
+
+/// Where decoded side tables come from, which decides how the node ids and
+/// def-ids in them are translated.
+enum DecodeSource<'b> {
+    /// An item inlined from another crate, whose node ids are moved from the
+    /// first range to the second.
+    Inlined(&'b cstore::crate_metadata, ast_util::IdRange, ast_util::IdRange),
+    /// An item of the local crate, type checked by a previous compilation.
+    Previous(&'b IdMap),
 }
 
 trait tr {
@@ -138,10 +150,8 @@ pub fn decode_inlined_item<'tcx>(cdata: &cstore::crate_metadata,
         let from_id_range = Decodable::decode(&mut ast_dsr).unwrap();
         let to_id_range = reserve_id_range(&tcx.sess, from_id_range);
         let dcx = &DecodeContext {
-            cdata: cdata,
             tcx: tcx,
-            from_id_range: from_id_range,
-            to_id_range: to_id_range
+            source: Inlined(cdata, from_id_range, to_id_range),
         };
         let raw_ii = decode_ast(ast_doc);
         let ii = ast_map::map_decoded_item(&dcx.tcx.map, path, raw_ii, dcx);
@@ -200,9 +210,20 @@ impl<'a, 'b, 'tcx> DecodeContext<'a, 'b, 'tcx> {
     /// that appear in types have this property, since if something might refer to an external item
     /// we would use a def-id to allow for the possibility that the item resides in another crate.
     pub fn tr_id(&self, id: ast::NodeId) -> ast::NodeId {
-        // from_id_range should be non-empty
-        assert!(!self.from_id_range.empty());
-        (id - self.from_id_range.min + self.to_id_range.min)
+        match self.source {
+            Inlined(_, from_id_range, to_id_range) => {
+                // from_id_range should be non-empty
+                assert!(!from_id_range.empty());
+                (id - from_id_range.min + to_id_range.min)
+            }
+            Previous(ids) => match ids.get(id) {
+                Some(id) => id,
+                None => {
+                    self.tcx.sess.bug(format!("node id {} of the previous compilation \
+                                               is not mapped", id).as_slice())
+                }
+            },
+        }
     }
 
     /// Translates an EXTERNAL def-id, converting the crate number from the one used in the encoded
@@ -217,9 +238,16 @@ impl<'a, 'b, 'tcx> DecodeContext<'a, 'b, 'tcx> {
     /// However, there are a *few* cases where def-ids are used but we know that the thing being
     /// referenced is in fact *internal* to the item being inlined.  In those cases, you should use
     /// `tr_intern_def_id()` below.
+    ///
+    /// Def-ids of a previous compilation keep their crate numbers, which are
+    /// the same as long as the upstream crates are: local ones are mapped
+    /// like node ids.
     pub fn tr_def_id(&self, did: ast::DefId) -> ast::DefId {
-
-        decoder::translate_def_id(self.cdata, did)
+        match self.source {
+            Inlined(cdata, _, _) => decoder::translate_def_id(cdata, did),
+            Previous(_) if did.krate == ast::LOCAL_CRATE => self.tr_intern_def_id(did),
+            Previous(_) => did,
+        }
     }
 
     /// Translates an INTERNAL def-id, meaning a def-id that is
@@ -233,6 +261,43 @@ impl<'a, 'b, 'tcx> DecodeContext<'a, 'b, 'tcx> {
     pub fn tr_span(&self, _span: Span) -> Span {
         codemap::DUMMY_SP // FIXME (#1972): handle span properly
     }
+
+    fn is_previous(&self) -> bool {
+        match self.source {
+            Inlined(..) => false,
+            Previous(_) => true,
+        }
+    }
+
+    /// The crate number decoded types are read with.
+    fn cnum(&self) -> ast::CrateNum {
+        match self.source {
+            Inlined(cdata, _, _) => cdata.cnum,
+            Previous(_) => ast::LOCAL_CRATE,
+        }
+    }
+
+    /// Translates the node ids in the regions of `value`, whose def-ids were
+    /// already translated by `convert_def_id`. Regions of inlined items are
+    /// left alone, since trans does not look at them, but the tables of a
+    /// previous compilation are used by borrowck and the other analysis
+    /// passes.
+    fn tr_regions<T: TypeFoldable<'tcx>>(&self, value: T) -> T {
+        match self.source {
+            Inlined(..) => value,
+            Previous(_) => ty_fold::fold_regions(self.tcx, &value, |r, _| match r {
+                ty::ReEarlyBound(id, space, index, name) => {
+                    ty::ReEarlyBound(self.tr_id(id), space, index, name)
+                }
+                ty::ReScope(scope) => ty::ReScope(scope.tr(self)),
+                ty::ReFree(fr) => ty::ReFree(ty::FreeRegion {
+                    scope: fr.scope.tr(self),
+                    bound_region: fr.bound_region,
+                }),
+                _ => r,
+            }),
+        }
+    }
 }
 
 impl tr_intern for ast::DefId {
@@ -1506,22 +1571,21 @@ impl<'a, 'tcx> rbml_decoder_decoder_helpers<'tcx> for reader::Decoder<'a> {
 
 
     fn read_ty<'b, 'c>(&mut self, dcx: &DecodeContext<'b, 'c, 'tcx>) -> Ty<'tcx> {
-        // Note: regions types embed local node ids.  In principle, we
-        // should translate these node ids into the new decode
-        // context.  However, we do not bother, because region types
-        // are not used during trans.
+        // Note: regions types embed local node ids, which `tr_regions`
+        // only translates for tables of a previous compilation: those of
+        // inlined items are only used by trans, which erases regions.
 
         return self.read_opaque(|this, doc| {
             debug!("read_ty({})", type_string(doc));
 
             let ty = tydecode::parse_ty_data(
                 doc.data,
-                dcx.cdata.cnum,
+                dcx.cnum(),
                 doc.start,
                 dcx.tcx,
                 |s, a| this.convert_def_id(dcx, s, a));
 
-            Ok(ty)
+            Ok(dcx.tr_regions(ty))
         }).unwrap();
 
         fn type_string(doc: rbml::Doc) -> String {
@@ -1543,11 +1607,11 @@ impl<'a, 'tcx> rbml_decoder_decoder_helpers<'tcx> for reader::Decoder<'a> {
         self.read_opaque(|this, doc| {
             let ty = tydecode::parse_trait_ref_data(
                 doc.data,
-                dcx.cdata.cnum,
+                dcx.cnum(),
                 doc.start,
                 dcx.tcx,
                 |s, a| this.convert_def_id(dcx, s, a));
-            Ok(ty)
+            Ok(dcx.tr_regions(ty))
         }).unwrap()
     }
 
@@ -1556,11 +1620,11 @@ impl<'a, 'tcx> rbml_decoder_decoder_helpers<'tcx> for reader::Decoder<'a> {
         ty::Binder(self.read_opaque(|this, doc| {
             let ty = tydecode::parse_trait_ref_data(
                 doc.data,
-                dcx.cdata.cnum,
+                dcx.cnum(),
                 doc.start,
                 dcx.tcx,
                 |s, a| this.convert_def_id(dcx, s, a));
-            Ok(ty)
+            Ok(dcx.tr_regions(ty))
         }).unwrap())
     }
 
@@ -1570,7 +1634,7 @@ impl<'a, 'tcx> rbml_decoder_decoder_helpers<'tcx> for reader::Decoder<'a> {
             Ok(tydecode::parse_type_param_def_data(
                 doc.data,
                 doc.start,
-                dcx.cdata.cnum,
+                dcx.cnum(),
                 dcx.tcx,
                 |s, a| this.convert_def_id(dcx, s, a)))
         }).unwrap()
@@ -1580,7 +1644,7 @@ impl<'a, 'tcx> rbml_decoder_decoder_helpers<'tcx> for reader::Decoder<'a> {
                               -> ty::Predicate<'tcx>
     {
         self.read_opaque(|this, doc| {
-            Ok(tydecode::parse_predicate_data(doc.data, doc.start, dcx.cdata.cnum, dcx.tcx,
+            Ok(tydecode::parse_predicate_data(doc.data, doc.start, dcx.cnum(), dcx.tcx,
                                               |s, a| this.convert_def_id(dcx, s, a)))
         }).unwrap()
     }
@@ -1623,22 +1687,25 @@ impl<'a, 'tcx> rbml_decoder_decoder_helpers<'tcx> for reader::Decoder<'a> {
                                        -> ty::ExistentialBounds<'tcx>
     {
         self.read_opaque(|this, doc| {
-            Ok(tydecode::parse_existential_bounds_data(doc.data,
-                                                       dcx.cdata.cnum,
-                                                       doc.start,
-                                                       dcx.tcx,
-                                                       |s, a| this.convert_def_id(dcx, s, a)))
+            let bounds = tydecode::parse_existential_bounds_data(
+                doc.data,
+                dcx.cnum(),
+                doc.start,
+                dcx.tcx,
+                |s, a| this.convert_def_id(dcx, s, a));
+            Ok(dcx.tr_regions(bounds))
         }).unwrap()
     }
 
     fn read_substs<'b, 'c>(&mut self, dcx: &DecodeContext<'b, 'c, 'tcx>)
                            -> subst::Substs<'tcx> {
         self.read_opaque(|this, doc| {
-            Ok(tydecode::parse_substs_data(doc.data,
-                                        dcx.cdata.cnum,
-                                        doc.start,
-                                        dcx.tcx,
-                                        |s, a| this.convert_def_id(dcx, s, a)))
+            let substs = tydecode::parse_substs_data(doc.data,
+                                                     dcx.cnum(),
+                                                     doc.start,
+                                                     dcx.tcx,
+                                                     |s, a| this.convert_def_id(dcx, s, a));
+            Ok(dcx.tr_regions(substs))
         }).unwrap()
     }
 
@@ -1791,12 +1858,13 @@ impl<'a, 'tcx> rbml_decoder_decoder_helpers<'tcx> for reader::Decoder<'a> {
     fn read_unboxed_closure<'b, 'c>(&mut self, dcx: &DecodeContext<'b, 'c, 'tcx>)
                                     -> ty::UnboxedClosure<'tcx> {
         let closure_type = self.read_opaque(|this, doc| {
-            Ok(tydecode::parse_ty_closure_data(
+            let closure_type = tydecode::parse_ty_closure_data(
                 doc.data,
-                dcx.cdata.cnum,
+                dcx.cnum(),
                 doc.start,
                 dcx.tcx,
-                |s, a| this.convert_def_id(dcx, s, a)))
+                |s, a| this.convert_def_id(dcx, s, a));
+            Ok(dcx.tr_regions(closure_type))
         }).unwrap();
         let variants = &[
             "FnUnboxedClosureKind",
@@ -1885,6 +1953,14 @@ fn decode_side_tables(dcx: &DecodeContext,
                 let val_dsr = &mut val_dsr;
 
                 match value {
+                    // A previous compilation only provides what type
+                    // checking computed: this one already has the tables
+                    // of resolve and of the collection of item types.
+                    c::tag_table_def |
+                    c::tag_table_freevars |
+                    c::tag_table_capture_modes |
+                    c::tag_table_tcache |
+                    c::tag_table_param_defs if dcx.is_previous() => {}
                     c::tag_table_def => {
                         let def = decode_def(dcx, val_doc);
                         dcx.tcx.def_map.borrow_mut().insert(id, def);
@@ -1959,6 +2035,13 @@ fn decode_side_tables(dcx: &DecodeContext,
                            .insert(ast_util::local_def(id),
                                    unboxed_closure);
                     }
+                    c::tag_table_free_regions => {
+                        let sub: ty::FreeRegion = Decodable::decode(val_dsr).unwrap();
+                        let sups: Vec<ty::FreeRegion> = Decodable::decode(val_dsr).unwrap();
+                        for sup in sups.iter() {
+                            dcx.tcx.region_maps.relate_free_regions(sub.tr(dcx), sup.tr(dcx));
+                        }
+                    }
                     _ => {
                         dcx.tcx.sess.bug(
                             format!("unknown tag found in side tables: {:x}",
@@ -1973,6 +2056,94 @@ fn decode_side_tables(dcx: &DecodeContext,
     });
 }
 
+// ______________________________________________________________________
+// Type-check results of the local crate, for incremental compilation
+
+/// Maps the node ids of a previous compilation of the crate to the ones they
+/// have now, one item at a time.
+pub struct IdMap {
+    // The ids each item had then and the first of its ids now, sorted by
+    // the former.
+    ranges: Vec<(ast_util::IdRange, ast::NodeId)>,
+}
+
+impl IdMap {
+    /// Builds the map from the ids each item had in the previous compilation
+    /// and the first of its ids now. The ranges must not overlap.
+    pub fn new(mut ranges: Vec<(ast_util::IdRange, ast::NodeId)>) -> IdMap {
+        ranges.sort_by(|a, b| a.0.min.cmp(&b.0.min));
+        IdMap { ranges: ranges }
+    }
+
+    pub fn get(&self, id: ast::NodeId) -> Option<ast::NodeId> {
+        let (mut lo, mut hi) = (0u, self.ranges.len());
+        while lo < hi {
+            let mid = (lo + hi) / 2;
+            let (range, to) = self.ranges[mid];
+            if id < range.min {
+                hi = mid;
+            } else if id >= range.max {
+                lo = mid + 1;
+            } else {
+                return Some(id - range.min + to);
+            }
+        }
+        None
+    }
+}
+
+/// Encodes under `key` what type checking recorded for the node ids `ids` of
+/// an item, for a later compilation to load with `decode_item_tables`
+/// instead of checking the item again.
+pub fn encode_item_tables(ecx: &e::EncodeContext,
+                          rbml_w: &mut Encoder,
+                          key: &str,
+                          ids: ast_util::IdRange) {
+    rbml_w.start_tag(c::tag_item_tables as uint);
+    rbml_w.wr_tagged_str(c::tag_item_tables_key as uint, key);
+    rbml_w.start_tag(c::tag_table as uint);
+    for id in range(ids.min, ids.max) {
+        encode_side_tables_for_id(ecx, rbml_w, id);
+    }
+    ecx.tcx.region_maps.each_free_region_relation(|sub, sups| {
+        let id = sub.scope.node_id();
+        if ids.min <= id && id < ids.max {
+            rbml_w.tag(c::tag_table_free_regions, |rbml_w| {
+                rbml_w.id(id);
+                rbml_w.tag(c::tag_table_val, |rbml_w| {
+                    sub.encode(rbml_w).unwrap();
+                    sups.encode(rbml_w).unwrap();
+                })
+            })
+        }
+    });
+    rbml_w.end_tag();
+    rbml_w.end_tag();
+}
+
+/// The tables written by `encode_item_tables` in `data`, by key.
+pub fn item_tables<'a>(data: &'a [u8]) -> HashMap<String, rbml::Doc<'a>> {
+    let mut tables = HashMap::new();
+    reader::tagged_docs(rbml::Doc::new(data), c::tag_item_tables as uint, |doc| {
+        let key = reader::get_doc(doc, c::tag_item_tables_key as uint).as_str();
+        tables.insert(key, doc);
+        true
+    });
+    tables
+}
+
+/// Loads the tables of an item of a previous compilation, whose node ids are
+/// translated with `ids`.
+pub fn decode_item_tables<'tcx>(tcx: &ty::ctxt<'tcx>,
+                                doc: rbml::Doc,
+                                ids: &IdMap) {
+    let dcx = &DecodeContext {
+        tcx: tcx,
+        source: Previous(ids),
+    };
+    decode_side_tables(dcx, doc);
+}
+
 // ______________________________________________________________________
 // Testing of astencode_gen
 
@@ -2084,3 +2255,14 @@ fn test_simplification() {
       _ => panic!()
     }
 }
+
+#[test]
+fn test_id_map() {
+    let ids = IdMap::new(vec![(ast_util::IdRange { min: 10, max: 20 }, 40),
+                              (ast_util::IdRange { min: 0, max: 5 }, 7)]);
+    assert_eq!(ids.get(0), Some(7));
+    assert_eq!(ids.get(4), Some(11));
+    assert_eq!(ids.get(5), None);
+    assert_eq!(ids.get(15), Some(45));
+    assert_eq!(ids.get(20), None);
+}
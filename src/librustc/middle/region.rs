@@ -150,6 +150,16 @@ impl RegionMaps {
         self.free_region_map.borrow_mut().insert(sub, vec!(sup));
     }
 
+    /// Calls `f` with each free region related by `relate_free_regions` and
+    /// the regions it is known to be a subregion of.
+    pub fn each_free_region_relation<F>(&self, mut f: F) where
+        F: FnMut(&FreeRegion, &[FreeRegion]),
+    {
+        for (sub, sups) in self.free_region_map.borrow().iter() {
+            f(sub, sups.as_slice());
+        }
+    }
+
     pub fn record_encl_scope(&self, sub: CodeExtent, sup: CodeExtent) {
         debug!("record_encl_scope(sub={:?}, sup={:?})", sub, sup);
         assert!(sub != sup);
@@ -42,6 +42,7 @@ pub use self::CopyImplementationError::*;
 
 use back::svh::Svh;
 use session::Session;
+use session::profile;
 use lint;
 use metadata::csearch;
 use middle;
@@ -105,6 +106,9 @@ pub struct CrateAnalysis<'tcx> {
     pub reachable: NodeSet,
     pub name: String,
     pub glob_map: Option<GlobMap>,
+    /// The traits in scope at each method call, kept for the dependency
+    /// graph when compiling incrementally.
+    pub trait_map: Option<TraitMap>,
 }
 
 #[derive(Copy, PartialEq, Eq, Hash)]
@@ -922,6 +926,21 @@ impl<'tcx> ctxt<'tcx> {
         println!("BareFnTy interner: #{}", self.bare_fn_interner.borrow().len());
         println!("Region interner: #{}", self.region_interner.borrow().len());
     }
+
+    /// Records the sizes of the type interners and caches with the
+    /// self-profiler, if one is running.
+    pub fn record_profile_counters(&self) {
+        if !profile::enabled() { return; }
+        profile::counter("ty: type interner", self.interner.borrow().len() as u64);
+        profile::counter("ty: substs interner", self.substs_interner.borrow().len() as u64);
+        profile::counter("ty: bare fn interner", self.bare_fn_interner.borrow().len() as u64);
+        profile::counter("ty: region interner", self.region_interner.borrow().len() as u64);
+        profile::counter("ty: node types", self.node_types.borrow().len() as u64);
+        profile::counter("ty: type cache", self.tcache.borrow().len() as u64);
+        profile::counter("ty: method map", self.method_map.borrow().len() as u64);
+        profile::counter("ty: impl or trait items",
+                         self.impl_or_trait_items.borrow().len() as u64);
+    }
 }
 
 #[derive(Show)]
@@ -19,6 +19,7 @@ pub use self::OutputType::*;
 pub use self::DebugInfoLevel::*;
 
 use session::{early_error, early_warn, Session};
+use session::profile;
 use session::search_paths::SearchPaths;
 
 use rustc_back::target::Target;
@@ -46,7 +47,7 @@ pub struct Config {
     pub uint_type: UintTy,
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Show)]
 pub enum OptLevel {
     No, // -O0
     Less, // -O1
@@ -54,7 +55,7 @@ pub enum OptLevel {
     Aggressive // -O3
 }
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Show)]
 pub enum DebugInfoLevel {
     NoDebugInfo,
     LimitedDebugInfo,
@@ -81,6 +82,8 @@ pub struct Options {
     pub optimize: OptLevel,
     pub debuginfo: DebugInfoLevel,
     pub lint_opts: Vec<(String, lint::Level)>,
+    /// Lint levels read from `--lint-config`.
+    pub lint_config: Option<lint::config::LintConfig>,
     pub describe_lints: bool,
     pub output_types: Vec<OutputType> ,
     // This was mutable for rustpkg, which updates search paths based on the
@@ -105,7 +108,15 @@ pub struct Options {
     pub prints: Vec<PrintRequest>,
     pub cg: CodegenOptions,
     pub color: ColorConfig,
+    pub error_format: ErrorFormat,
     pub show_span: Option<String>,
+    /// Where to write a self-profile of the compilation, and in which format.
+    pub self_profile: Option<(Path, profile::ProfileFormat)>,
+    /// A `FILE:LINE:COL` position to answer a query about, after analysis.
+    pub query: Option<String>,
+    /// The directory to keep the dependency graph and object files of
+    /// incremental compilation in, set with `-Z incremental=DIR`.
+    pub incremental: Option<Path>,
     pub externs: HashMap<String, Vec<String>>,
     pub crate_name: Option<String>,
     /// An optional name to use as the crate for std during std injection,
@@ -114,6 +125,15 @@ pub struct Options {
     pub alt_std_name: Option<String>
 }
 
+/// How diagnostics are written out.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Human readable messages with source snippets, the default.
+    HumanReadable,
+    /// One JSON object per diagnostic, see `syntax::json`.
+    Json,
+}
+
 #[derive(Clone, PartialEq, Eq)]
 #[allow(missing_copy_implementations)]
 pub enum PrintRequest {
@@ -197,6 +217,7 @@ pub fn basic_options() -> Options {
         optimize: No,
         debuginfo: NoDebugInfo,
         lint_opts: Vec::new(),
+        lint_config: None,
         describe_lints: false,
         output_types: Vec::new(),
         search_paths: SearchPaths::new(),
@@ -212,7 +233,11 @@ pub fn basic_options() -> Options {
         prints: Vec::new(),
         cg: basic_codegen_options(),
         color: Auto,
+        error_format: ErrorFormat::HumanReadable,
         show_span: None,
+        self_profile: None,
+        query: None,
+        incremental: None,
         externs: HashMap::new(),
         crate_name: None,
         alt_std_name: None,
@@ -280,7 +305,10 @@ debugging_opts! {
         NO_TRANS,
         NO_ANALYSIS,
         UNSTABLE_OPTIONS,
-        PRINT_ENUM_SIZES
+        PRINT_ENUM_SIZES,
+        APPLY_SUGGESTIONS,
+        SAVE_ANALYSIS_CSV,
+        INCREMENTAL_INFO
     ]
     0
 }
@@ -314,6 +342,8 @@ pub fn debugging_opts_map() -> Vec<(&'static str, &'static str, u64)> {
      ("ls", "List the symbols defined by a library crate", LS),
      ("save-analysis", "Write syntax and type analysis information \
                         in addition to normal output", SAVE_ANALYSIS),
+     ("save-analysis-csv", "Like save-analysis, but write the deprecated \
+                            CSV format instead of JSON", SAVE_ANALYSIS_CSV),
      ("print-move-fragments", "Print out move-fragment data for every fn",
       PRINT_MOVE_FRAGMENTS),
      ("flowgraph-print-loans", "Include loan analysis data in \
@@ -334,10 +364,14 @@ pub fn debugging_opts_map() -> Vec<(&'static str, &'static str, u64)> {
      ("unstable-options", "Adds unstable command line options to rustc interface",
       UNSTABLE_OPTIONS),
      ("print-enum-sizes", "Print the size of enums and their variants", PRINT_ENUM_SIZES),
+     ("apply-suggestions", "Apply machine-applicable suggestions to the source files",
+      APPLY_SUGGESTIONS),
+     ("incremental-info", "Print what -Z incremental reused",
+      INCREMENTAL_INFO),
     ]
 }
 
-#[derive(Clone)]
+#[derive(Clone, Show)]
 pub enum Passes {
     SomePasses(Vec<String>),
     AllPasses,
@@ -365,7 +399,7 @@ impl Passes {
 macro_rules! cgoptions {
     ($($opt:ident : $t:ty = ($init:expr, $parse:ident, $desc:expr)),* ,) =>
 (
-    #[derive(Clone)]
+    #[derive(Clone, Show)]
     pub struct CodegenOptions { $(pub $opt: $t),* }
 
     pub fn basic_codegen_options() -> CodegenOptions {
@@ -542,6 +576,13 @@ cgoptions! {
          2 = full debug info with variable and type information"),
     opt_level: Option<uint> = (None, parse_opt_uint,
         "Optimize with possible levels 0-3"),
+    instrument_coverage: bool = (false, parse_bool,
+        "count how often each block and branch runs, for coverage reports"),
+    profile_generate: Option<String> = (None, parse_opt_string,
+        "instrument the code to write a profile to this directory when run, \
+         for profile-guided optimization"),
+    profile_use: Option<String> = (None, parse_opt_string,
+        "optimize with the profile in this file, merged by llvm-profdata"),
 }
 
 pub fn build_codegen_options(matches: &getopts::Matches) -> CodegenOptions
@@ -794,6 +835,9 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
             auto   = colorize, if output goes to a tty (default);
             always = always colorize output;
             never  = never colorize output", "auto|always|never"),
+        opt::opt("", "error-format", "How errors and other messages are produced:
+            human = human readable output (default);
+            json  = one JSON object per diagnostic, for use by tools", "human|json"),
 
         // DEPRECATED
         opt::flag("", "print-crate-name", "Output the crate name and exit"),
@@ -824,6 +868,18 @@ pub fn rustc_optgroups() -> Vec<RustcOptGroup> {
                       `everybody_loops` (all function bodies replaced with `loop {}`).",
                      "TYPE"),
         opt::opt_u("", "show-span", "Show spans for compiler debugging", "expr|pat|ty"),
+        opt::opt_u("", "lint-config", "Read lint levels for the crate and its modules \
+                                       from a JSON file", "FILE"),
+        opt::opt_u("", "self-profile", "Write the time and memory used by each pass, \
+                                        and other compiler statistics, to FILE", "FILE"),
+        opt::opt_u("", "self-profile-format", "Format of the --self-profile output",
+                   "json|chrome"),
+        opt::opt_u("", "query", "Print the definition, type and references of the name \
+                                 at a position as JSON, and stop after analysis",
+                   "FILE:LINE:COL"),
+        opt::opt_u("", "coverage-report", "Print the coverage of the crate whose coverage \
+                                           map is MAP, adding up the profiles given as \
+                                           inputs, instead of compiling", "MAP"),
         opt::flagopt("", "dep-info",
                  "Output dependency info to <filename> after compiling, \
                   in a format suitable for use by Makefiles", "FILENAME"),
@@ -860,10 +916,25 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         }
     }
 
+    let lint_config = matches.opt_str("lint-config").map(|path| {
+        lint::config::LintConfig::load(&Path::new(path)).unwrap_or_else(|e| {
+            early_error(e.as_slice())
+        })
+    });
+
     let mut debugging_opts = 0;
+    let mut incremental = None;
     let debug_flags = matches.opt_strs("Z");
     let debug_map = debugging_opts_map();
     for debug_flag in debug_flags.iter() {
+        if debug_flag.starts_with("incremental=") {
+            let dir = debug_flag.slice_from("incremental=".len());
+            if dir.is_empty() {
+                early_error("-Z incremental requires a directory, e.g. -Z incremental=DIR");
+            }
+            incremental = Some(Path::new(dir));
+            continue;
+        }
         let mut this_bit = 0;
         for &(name, _, bit) in debug_map.iter() {
             if name == *debug_flag {
@@ -932,6 +1003,9 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
     }
 
     let cg = build_codegen_options(matches);
+    if cg.profile_generate.is_some() && cg.profile_use.is_some() {
+        early_error("-C profile-generate and -C profile-use both provided");
+    }
 
     let sysroot_opt = matches.opt_str("sysroot").map(|m| Path::new(m));
     let target = matches.opt_str("target").unwrap_or(
@@ -1103,6 +1177,19 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         }
     };
 
+    let error_format = match matches.opt_str("error-format").as_ref().map(|s| s.index(&FullRange)) {
+        Some("human") => ErrorFormat::HumanReadable,
+        Some("json")  => ErrorFormat::Json,
+
+        None => ErrorFormat::HumanReadable,
+
+        Some(arg) => {
+            early_error(format!("argument for --error-format must be human or json \
+                                 (instead was `{}`)",
+                                arg).index(&FullRange))
+        }
+    };
+
     let mut externs = HashMap::new();
     for arg in matches.opt_strs("extern").iter() {
         let mut parts = arg.splitn(1, '=');
@@ -1129,6 +1216,7 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         optimize: opt_level,
         debuginfo: debuginfo,
         lint_opts: lint_opts,
+        lint_config: lint_config,
         describe_lints: describe_lints,
         output_types: output_types,
         search_paths: search_paths,
@@ -1144,7 +1232,11 @@ pub fn build_session_options(matches: &getopts::Matches) -> Options {
         prints: prints,
         cg: cg,
         color: color,
+        error_format: error_format,
         show_span: None,
+        self_profile: None,
+        query: None,
+        incremental: incremental,
         externs: externs,
         crate_name: crate_name,
         alt_std_name: None,
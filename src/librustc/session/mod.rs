@@ -20,6 +20,7 @@ use syntax::codemap::Span;
 use syntax::diagnostic::{self, Emitter};
 use syntax::diagnostics;
 use syntax::feature_gate;
+use syntax::json;
 use syntax::parse;
 use syntax::parse::token;
 use syntax::parse::ParseSess;
@@ -29,6 +30,7 @@ use std::os;
 use std::cell::{Cell, RefCell};
 
 pub mod config;
+pub mod profile;
 pub mod search_paths;
 
 // Represents the data associated with a compilation
@@ -73,6 +75,13 @@ impl Session {
     pub fn span_err_with_code(&self, sp: Span, msg: &str, code: &str) {
         self.diagnostic().span_err_with_code(sp, msg, code)
     }
+    pub fn span_err_labeled(&self, msp: &diagnostic::MultiSpan, msg: &str) {
+        self.diagnostic().span_err_labeled(msp, msg)
+    }
+    pub fn span_err_labeled_with_code(&self, msp: &diagnostic::MultiSpan,
+                                      msg: &str, code: &str) {
+        self.diagnostic().span_err_labeled_with_code(msp, msg, code)
+    }
     pub fn err(&self, msg: &str) {
         self.diagnostic().handler().err(msg)
     }
@@ -115,6 +124,10 @@ impl Session {
     pub fn span_help(&self, sp: Span, msg: &str) {
         self.diagnostic().span_help(sp, msg)
     }
+    pub fn span_suggestion(&self, sp: Span, msg: &str, replacement: String,
+                           applicability: diagnostic::Applicability) {
+        self.diagnostic().span_suggestion(sp, msg, replacement, applicability)
+    }
     pub fn fileline_note(&self, sp: Span, msg: &str) {
         self.diagnostic().fileline_note(sp, msg)
     }
@@ -234,8 +247,20 @@ pub fn build_session(sopts: config::Options,
                      registry: diagnostics::registry::Registry)
                      -> Session {
     let codemap = codemap::CodeMap::new();
-    let diagnostic_handler =
-        diagnostic::default_handler(sopts.color, Some(registry));
+    let emitter: Box<Emitter + Send> = match sopts.error_format {
+        config::ErrorFormat::HumanReadable => {
+            box diagnostic::EmitterWriter::stderr(sopts.color, Some(registry))
+        }
+        config::ErrorFormat::Json => {
+            box json::JsonEmitter::stderr(Some(registry))
+        }
+    };
+    let emitter: Box<Emitter + Send> = if sopts.debugging_opts & config::APPLY_SUGGESTIONS != 0 {
+        box diagnostic::SuggestionApplier::new(emitter)
+    } else {
+        emitter
+    };
+    let diagnostic_handler = diagnostic::mk_handler(emitter);
     let span_diagnostic_handler =
         diagnostic::mk_span_handler(diagnostic_handler, codemap);
 
@@ -0,0 +1,599 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Self-profiling of the compiler.
+//!
+//! `-Z unstable-options --self-profile FILE` installs a `Profiler` for the
+//! duration of the compilation. Every pass wrapped in `util::common::time`
+//! is then recorded with its nesting depth, the thread it ran on, its start
+//! time and duration, and the resident set size of the process before and
+//! after it ran and at its peak while it ran.
+//!
+//! The peak is the kernel's high water mark, which is read and reset through
+//! `/proc/self/clear_refs` whenever a pass starts or finishes. What was read
+//! counts towards the peak of every pass running at the time, so nested
+//! passes and passes on other threads each get the peak of the process while
+//! they ran. Peaks are only recorded on Linux, and only since 4.0, which
+//! added the reset.
+//!
+//! Codegen worker threads share the profiler of the main thread, so their
+//! passes show up alongside the front-end passes. LLVM is only timed as a
+//! whole, as the function passes, module passes and codegen passes of each
+//! codegen unit: the pass managers of LLVM don't report to the profiler, and
+//! the time of each LLVM pass is only printed by `-Z time-llvm-passes`.
+//!
+//! Besides passes the profiler keeps named counters, such as the size of the
+//! type interners or the number of functions translated, set through
+//! `counter`. The peak resident set size of the whole compilation is
+//! recorded as the `peak_rss` counter once it is over.
+//!
+//! The data is written either as a plain JSON document (`json`, the default)
+//! or in the trace event format understood by `chrome://tracing` (`chrome`),
+//! selected with `--self-profile-format`.
+
+use session::early_warn;
+
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::BTreeMap;
+use std::io::{File, IoResult};
+use std::sync::{Arc, Mutex};
+use std::thread::Thread;
+
+use serialize::json::{self, Json};
+
+/// The format a profile is written in.
+#[derive(Clone, Copy, PartialEq, Eq, Show)]
+pub enum ProfileFormat {
+    /// A single JSON object with a `passes` and a `counters` array.
+    Json,
+    /// The Chrome trace event format.
+    ChromeTrace,
+}
+
+impl ProfileFormat {
+    pub fn from_str(s: &str) -> Option<ProfileFormat> {
+        match s {
+            "json" => Some(ProfileFormat::Json),
+            "chrome" => Some(ProfileFormat::ChromeTrace),
+            _ => None,
+        }
+    }
+}
+
+/// One run of a pass. Times are in nanoseconds since the profiler was
+/// created, memory sizes in bytes.
+#[derive(Clone, RustcEncodable, Show)]
+pub struct PassRecord {
+    pub name: String,
+    pub thread: String,
+    pub depth: uint,
+    pub start_ns: u64,
+    pub duration_ns: u64,
+    pub rss_start: Option<u64>,
+    pub rss_end: Option<u64>,
+    pub peak_rss: Option<u64>,
+}
+
+#[derive(Clone, RustcEncodable, Show)]
+pub struct Counter {
+    pub name: String,
+    pub value: u64,
+    /// When the counter was last set, in nanoseconds since the profiler was
+    /// created.
+    pub at_ns: u64,
+}
+
+#[derive(RustcEncodable)]
+struct ProfileData {
+    passes: Vec<PassRecord>,
+    counters: Vec<Counter>,
+}
+
+/// The peaks of the resident set size of the passes that are running, see
+/// the module documentation.
+struct Peaks {
+    next_id: uint,
+    running: Vec<(uint, Option<u64>)>,
+    /// The peak of the whole compilation so far.
+    overall: Option<u64>,
+}
+
+impl Peaks {
+    /// Reads and resets the high water mark, and counts it towards the
+    /// passes that are running.
+    fn sample(&mut self) {
+        if let Some(peak) = take_peak_rss() {
+            for pass in self.running.iter_mut() {
+                pass.1 = Some(cmp::max(pass.1.unwrap_or(0), peak));
+            }
+            self.overall = Some(cmp::max(self.overall.unwrap_or(0), peak));
+        }
+    }
+
+    fn start(&mut self) -> uint {
+        self.sample();
+        let id = self.next_id;
+        self.next_id += 1;
+        self.running.push((id, None));
+        id
+    }
+
+    fn finish(&mut self, id: uint) -> Option<u64> {
+        self.sample();
+        match self.running.iter().position(|&(i, _)| i == id) {
+            Some(i) => self.running.remove(i).1,
+            None => None,
+        }
+    }
+}
+
+/// Collects pass timings and counters. Clones share the same data, so a
+/// profiler can be handed to other threads.
+#[derive(Clone)]
+pub struct Profiler {
+    data: Arc<Mutex<ProfileData>>,
+    peaks: Arc<Mutex<Peaks>>,
+    epoch_ns: u64,
+}
+
+thread_local!(static CURRENT: RefCell<Option<Profiler>> = RefCell::new(None));
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler {
+            data: Arc::new(Mutex::new(ProfileData {
+                passes: Vec::new(),
+                counters: Vec::new(),
+            })),
+            peaks: Arc::new(Mutex::new(Peaks {
+                next_id: 0,
+                running: Vec::new(),
+                overall: None,
+            })),
+            epoch_ns: precise_time_ns(),
+        }
+    }
+
+    /// The profiler installed on the current thread, if any.
+    pub fn current() -> Option<Profiler> {
+        CURRENT.with(|c| c.borrow().clone())
+    }
+
+    /// Makes this the profiler of the current thread.
+    pub fn install(self) {
+        CURRENT.with(|c| *c.borrow_mut() = Some(self));
+    }
+
+    /// Removes the profiler of the current thread, if there is one.
+    pub fn uninstall() {
+        CURRENT.with(|c| *c.borrow_mut() = None);
+    }
+
+    /// Starts timing a pass at the given nesting depth. The pass is recorded
+    /// once `PassTimer::finish` is called.
+    pub fn start_pass(&self, name: &str, depth: uint) -> PassTimer {
+        PassTimer {
+            profiler: self.clone(),
+            name: name.to_string(),
+            depth: depth,
+            peak_id: self.peaks.lock().unwrap().start(),
+            rss_start: memory_usage().map(|m| m.rss),
+            start_ns: self.now_ns(),
+        }
+    }
+
+    /// Sets the counter `name`, replacing any previous value.
+    pub fn set_counter(&self, name: &str, value: u64) {
+        let at_ns = self.now_ns();
+        let mut data = self.data.lock().unwrap();
+        match data.counters.iter_mut().find(|c| c.name == name) {
+            Some(c) => {
+                c.value = value;
+                c.at_ns = at_ns;
+                return;
+            }
+            None => {}
+        }
+        data.counters.push(Counter {
+            name: name.to_string(),
+            value: value,
+            at_ns: at_ns,
+        });
+    }
+
+    pub fn passes(&self) -> Vec<PassRecord> {
+        self.data.lock().unwrap().passes.clone()
+    }
+
+    pub fn counters(&self) -> Vec<Counter> {
+        self.data.lock().unwrap().counters.clone()
+    }
+
+    pub fn to_json(&self) -> String {
+        json::encode(&*self.data.lock().unwrap())
+    }
+
+    /// Renders the profile as Chrome trace events: one complete (`X`) event
+    /// per pass and one counter (`C`) event per counter. Each thread that
+    /// ran a pass gets its own track.
+    pub fn to_chrome_trace(&self) -> String {
+        let data = self.data.lock().unwrap();
+        let mut threads: Vec<String> = Vec::new();
+        let mut events = Vec::new();
+
+        for pass in data.passes.iter() {
+            let tid = match threads.iter().position(|t| *t == pass.thread) {
+                Some(i) => i,
+                None => {
+                    threads.push(pass.thread.clone());
+                    threads.len() - 1
+                }
+            };
+
+            let mut args = BTreeMap::new();
+            args.insert("depth".to_string(), Json::U64(pass.depth as u64));
+            for &(key, value) in [("rss_start", pass.rss_start),
+                                  ("rss_end", pass.rss_end),
+                                  ("peak_rss", pass.peak_rss)].iter() {
+                if let Some(v) = value {
+                    args.insert(key.to_string(), Json::U64(v));
+                }
+            }
+
+            let mut event = trace_event(pass.name.as_slice(), "pass", "X",
+                                        pass.start_ns, tid, args);
+            if let Json::Object(ref mut obj) = event {
+                obj.insert("dur".to_string(), Json::F64(micros(pass.duration_ns)));
+            }
+            events.push(event);
+        }
+
+        for (tid, thread) in threads.iter().enumerate() {
+            let mut args = BTreeMap::new();
+            args.insert("name".to_string(), Json::String(thread.clone()));
+            events.push(trace_event("thread_name", "__metadata", "M", 0, tid, args));
+        }
+
+        for counter in data.counters.iter() {
+            let mut args = BTreeMap::new();
+            args.insert("value".to_string(), Json::U64(counter.value));
+            events.push(trace_event(counter.name.as_slice(), "counter", "C",
+                                    counter.at_ns, 0, args));
+        }
+
+        let mut trace = BTreeMap::new();
+        trace.insert("traceEvents".to_string(), Json::Array(events));
+        trace.insert("displayTimeUnit".to_string(), Json::String("ms".to_string()));
+        json::encode(&Json::Object(trace))
+    }
+
+    pub fn write(&self, path: &Path, format: ProfileFormat) -> IoResult<()> {
+        let out = match format {
+            ProfileFormat::Json => self.to_json(),
+            ProfileFormat::ChromeTrace => self.to_chrome_trace(),
+        };
+        let mut file = try!(File::create(path));
+        file.write_str(out.as_slice())
+    }
+
+    fn now_ns(&self) -> u64 {
+        precise_time_ns() - self.epoch_ns
+    }
+}
+
+/// A pass that is being timed, see `Profiler::start_pass`.
+pub struct PassTimer {
+    profiler: Profiler,
+    name: String,
+    depth: uint,
+    peak_id: uint,
+    rss_start: Option<u64>,
+    start_ns: u64,
+}
+
+impl PassTimer {
+    pub fn finish(self) {
+        let end_ns = self.profiler.now_ns();
+        let mem = memory_usage();
+        let peak_rss = self.profiler.peaks.lock().unwrap().finish(self.peak_id);
+        let thread = Thread::current();
+        let record = PassRecord {
+            name: self.name,
+            thread: thread.name().unwrap_or("<unnamed>").to_string(),
+            depth: self.depth,
+            start_ns: self.start_ns,
+            duration_ns: end_ns - self.start_ns,
+            rss_start: self.rss_start,
+            rss_end: mem.map(|m| m.rss),
+            peak_rss: peak_rss,
+        };
+        self.profiler.data.lock().unwrap().passes.push(record);
+    }
+}
+
+/// Installs a fresh profiler on the current thread and writes what it
+/// collected to `path` when dropped, including when compilation is aborted.
+pub struct ProfileWriter {
+    profiler: Profiler,
+    path: Path,
+    format: ProfileFormat,
+}
+
+impl ProfileWriter {
+    pub fn new(path: Path, format: ProfileFormat) -> ProfileWriter {
+        let profiler = Profiler::new();
+        profiler.clone().install();
+        ProfileWriter {
+            profiler: profiler,
+            path: path,
+            format: format,
+        }
+    }
+}
+
+impl Drop for ProfileWriter {
+    fn drop(&mut self) {
+        Profiler::uninstall();
+        let overall = {
+            let mut peaks = self.profiler.peaks.lock().unwrap();
+            peaks.sample();
+            peaks.overall
+        };
+        // Without the reset the high water mark is the overall peak.
+        if let Some(peak) = overall.or(memory_usage().map(|m| m.peak_rss)) {
+            self.profiler.set_counter("peak_rss", peak);
+        }
+        if let Err(e) = self.profiler.write(&self.path, self.format) {
+            early_warn(format!("failed to write self-profile to `{}`: {}",
+                               self.path.display(), e).as_slice());
+        }
+    }
+}
+
+/// Sets the counter `name` of the profiler installed on the current thread.
+/// Does nothing if no profiler is installed.
+pub fn counter(name: &str, value: u64) {
+    if let Some(p) = Profiler::current() {
+        p.set_counter(name, value);
+    }
+}
+
+/// Whether a profiler is installed on the current thread. Useful to avoid
+/// computing expensive counters nobody will look at.
+pub fn enabled() -> bool {
+    CURRENT.with(|c| c.borrow().is_some())
+}
+
+fn trace_event(name: &str, cat: &str, ph: &str, ts_ns: u64, tid: uint,
+               args: json::Object) -> Json {
+    let mut obj = BTreeMap::new();
+    obj.insert("name".to_string(), Json::String(name.to_string()));
+    obj.insert("cat".to_string(), Json::String(cat.to_string()));
+    obj.insert("ph".to_string(), Json::String(ph.to_string()));
+    obj.insert("ts".to_string(), Json::F64(micros(ts_ns)));
+    obj.insert("pid".to_string(), Json::U64(1));
+    obj.insert("tid".to_string(), Json::U64(tid as u64));
+    obj.insert("args".to_string(), Json::Object(args));
+    Json::Object(obj)
+}
+
+fn micros(ns: u64) -> f64 {
+    ns as f64 / 1000.0
+}
+
+#[derive(Copy)]
+struct MemoryUsage {
+    rss: u64,
+    peak_rss: u64,
+}
+
+/// The current and peak resident set size of the process, in bytes.
+#[cfg(target_os = "linux")]
+fn memory_usage() -> Option<MemoryUsage> {
+    let status = match File::open(&Path::new("/proc/self/status")).read_to_string() {
+        Ok(s) => s,
+        Err(..) => return None,
+    };
+    let field = |&: name: &str| -> Option<u64> {
+        status.lines()
+              .find(|l| l.starts_with(name))
+              .and_then(|l| l.index(&(name.len()..)).trim().split(' ').next())
+              .and_then(|kb| kb.parse::<u64>())
+              .map(|kb| kb * 1024)
+    };
+    match (field("VmRSS:"), field("VmHWM:")) {
+        (Some(rss), Some(peak)) => Some(MemoryUsage { rss: rss, peak_rss: peak }),
+        _ => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn memory_usage() -> Option<MemoryUsage> {
+    None
+}
+
+/// The peak resident set size of the process since the last call, in bytes,
+/// or `None` if the kernel can't reset it.
+#[cfg(target_os = "linux")]
+fn take_peak_rss() -> Option<u64> {
+    use std::io::{Open, Write};
+
+    let peak = match memory_usage() {
+        Some(mem) => mem.peak_rss,
+        None => return None,
+    };
+    // Writing 5 resets the high water mark to the current size.
+    let path = Path::new("/proc/self/clear_refs");
+    match File::open_mode(&path, Open, Write).and_then(|mut f| f.write_str("5")) {
+        Ok(()) => Some(peak),
+        Err(..) => None,
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn take_peak_rss() -> Option<u64> {
+    None
+}
+
+/// A monotonic clock in nanoseconds since an unspecified epoch.
+fn precise_time_ns() -> u64 {
+    imp::precise_time_ns()
+}
+
+#[cfg(windows)]
+mod imp {
+    use libc;
+
+    pub fn precise_time_ns() -> u64 {
+        let mut ticks_per_s = 0;
+        let mut ticks = 0;
+        unsafe {
+            libc::QueryPerformanceFrequency(&mut ticks_per_s);
+            libc::QueryPerformanceCounter(&mut ticks);
+        }
+        let ticks_per_s = if ticks_per_s == 0 {1} else {ticks_per_s as u64};
+        let ticks = ticks as u64;
+        // Multiplying the ticks first overflows after a few weeks of uptime
+        // at the usual frequencies, so split them into seconds first.
+        (ticks / ticks_per_s) * 1000000000 +
+            (ticks % ticks_per_s) * 1000000000 / ticks_per_s
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios"))]
+mod imp {
+    use libc;
+
+    extern {
+        fn mach_absolute_time() -> u64;
+        fn mach_timebase_info(info: *mut libc::mach_timebase_info) -> libc::c_int;
+    }
+
+    pub fn precise_time_ns() -> u64 {
+        let mut timebase = libc::mach_timebase_info { numer: 0, denom: 0 };
+        unsafe {
+            mach_timebase_info(&mut timebase);
+            mach_absolute_time() * timebase.numer as u64 / timebase.denom as u64
+        }
+    }
+}
+
+#[cfg(not(any(windows, target_os = "macos", target_os = "ios")))]
+mod imp {
+    use libc;
+
+    extern {
+        fn clock_gettime(clk_id: libc::c_int, tp: *mut libc::timespec) -> libc::c_int;
+    }
+
+    pub fn precise_time_ns() -> u64 {
+        let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+        unsafe {
+            clock_gettime(libc::CLOCK_MONOTONIC, &mut ts);
+        }
+        (ts.tv_sec as u64) * 1000000000 + (ts.tv_nsec as u64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Profiler, counter};
+    use util::common::time;
+
+    use serialize::json::{self, Json};
+
+    #[test]
+    fn nested_passes() {
+        let profiler = Profiler::new();
+        profiler.clone().install();
+
+        time(false, "outer", (), |_| {
+            time(false, "inner", (), |_| ());
+        });
+        Profiler::uninstall();
+        // Nothing is recorded once the profiler is gone.
+        time(false, "ignored", (), |_| ());
+
+        let passes = profiler.passes();
+        assert_eq!(passes.len(), 2);
+        // Passes are recorded as they finish.
+        let (inner, outer) = (&passes[0], &passes[1]);
+        assert_eq!(inner.name, "inner");
+        assert_eq!(inner.depth, 1);
+        assert_eq!(outer.name, "outer");
+        assert_eq!(outer.depth, 0);
+        assert!(outer.start_ns <= inner.start_ns);
+        assert!(inner.start_ns + inner.duration_ns <= outer.start_ns + outer.duration_ns);
+        // The peak of the outer pass covers the inner one.
+        if let (Some(inner_peak), Some(outer_peak)) = (inner.peak_rss, outer.peak_rss) {
+            assert!(inner_peak <= outer_peak);
+        }
+    }
+
+    #[test]
+    fn counters_are_replaced() {
+        let profiler = Profiler::new();
+        profiler.clone().install();
+        counter("items", 1);
+        counter("types", 7);
+        counter("items", 3);
+        Profiler::uninstall();
+        counter("items", 5);
+
+        let counters = profiler.counters();
+        assert_eq!(counters.len(), 2);
+        assert_eq!(counters[0].name, "items");
+        assert_eq!(counters[0].value, 3);
+        assert_eq!(counters[1].name, "types");
+        assert_eq!(counters[1].value, 7);
+    }
+
+    #[test]
+    fn json_output() {
+        let profiler = Profiler::new();
+        profiler.clone().install();
+        time(false, "parsing", (), |_| ());
+        counter("n_fns", 12);
+        Profiler::uninstall();
+
+        let json = json::from_str(profiler.to_json().as_slice()).unwrap();
+        let passes = json.find("passes").unwrap().as_array().unwrap();
+        assert_eq!(passes.len(), 1);
+        assert_eq!(passes[0].find("name").unwrap().as_string(), Some("parsing"));
+        assert!(passes[0].find("duration_ns").unwrap().is_u64());
+        let counters = json.find("counters").unwrap().as_array().unwrap();
+        assert_eq!(counters[0].find("value").unwrap().as_u64(), Some(12));
+    }
+
+    #[test]
+    fn chrome_trace_output() {
+        let profiler = Profiler::new();
+        profiler.clone().install();
+        time(false, "translation", (), |_| ());
+        counter("n_fns", 12);
+        Profiler::uninstall();
+
+        let json = json::from_str(profiler.to_chrome_trace().as_slice()).unwrap();
+        let events = json.find("traceEvents").unwrap().as_array().unwrap();
+        let phase = |&: e: &Json| e.find("ph").unwrap().as_string().unwrap().to_string();
+
+        let pass = events.iter().find(|e| phase(*e) == "X").unwrap();
+        assert_eq!(pass.find("name").unwrap().as_string(), Some("translation"));
+        assert!(pass.find("dur").unwrap().is_f64());
+        assert_eq!(pass.find("args").unwrap().find("depth").unwrap().as_u64(), Some(0));
+
+        assert!(events.iter().any(|e| phase(e) == "M"));
+
+        let counter = events.iter().find(|e| phase(*e) == "C").unwrap();
+        assert_eq!(counter.find("name").unwrap().as_string(), Some("n_fns"));
+        assert_eq!(counter.find("args").unwrap().find("value").unwrap().as_u64(), Some(12));
+    }
+}
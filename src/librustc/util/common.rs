@@ -17,6 +17,8 @@ use std::hash::{Hash, Hasher};
 use std::iter::repeat;
 use std::time::Duration;
 
+use session::profile::{self, Profiler};
+
 use syntax::ast;
 use syntax::visit;
 use syntax::visit::Visitor;
@@ -26,11 +28,14 @@ use syntax::visit::Visitor;
 #[derive(Clone, Copy, Show)]
 pub struct ErrorReported;
 
+/// Runs `f`, printing how long it took when `do_it` is set. The pass is
+/// also recorded by the self-profiler of the current thread, if there is one.
 pub fn time<T, U, F>(do_it: bool, what: &str, u: U, f: F) -> T where
     F: FnOnce(U) -> T,
 {
     thread_local!(static DEPTH: Cell<uint> = Cell::new(0));
-    if !do_it { return f(u); }
+    if !do_it && !profile::enabled() { return f(u); }
+    let profiler = Profiler::current();
 
     let old = DEPTH.with(|slot| {
         let r = slot.get();
@@ -38,6 +43,7 @@ pub fn time<T, U, F>(do_it: bool, what: &str, u: U, f: F) -> T where
         r
     });
 
+    let pass = profiler.map(|p| p.start_pass(what, old));
     let mut u = Some(u);
     let mut rv = None;
     let dur = {
@@ -48,9 +54,14 @@ pub fn time<T, U, F>(do_it: bool, what: &str, u: U, f: F) -> T where
         })
     };
     let rv = rv.unwrap();
+    if let Some(pass) = pass {
+        pass.finish();
+    }
 
-    println!("{}time: {}.{:03} \t{}", repeat("  ").take(old).collect::<String>(),
-             dur.num_seconds(), dur.num_milliseconds() % 1000, what);
+    if do_it {
+        println!("{}time: {}.{:03} \t{}", repeat("  ").take(old).collect::<String>(),
+                 dur.num_seconds(), dur.num_milliseconds() % 1000, what);
+    }
     DEPTH.with(|slot| slot.set(old));
 
     rv
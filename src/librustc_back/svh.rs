@@ -52,6 +52,7 @@ use std::hash::sip::SipState;
 use std::iter::range_step;
 use syntax::ast;
 use syntax::visit;
+use syntax::visit::Visitor;
 
 #[derive(Clone, PartialEq)]
 pub struct Svh {
@@ -118,6 +119,19 @@ impl Svh {
     }
 }
 
+/// Hashes a single item into `state` the way `Svh::calculate` hashes a
+/// whole crate, including the item's attributes. Used by incremental
+/// compilation to tell which items changed between two compilations.
+pub fn hash_item(item: &ast::Item, state: &mut SipState) {
+    {
+        let mut visit = svh_visitor::make(state);
+        visit.visit_item(item);
+    }
+    for attr in item.attrs.iter() {
+        attr.node.value.hash(state);
+    }
+}
+
 impl fmt::Show for Svh {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         //NOTE(stage0): uncomment after snapshot
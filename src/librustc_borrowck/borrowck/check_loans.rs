@@ -25,7 +25,8 @@ use rustc::middle::region;
 use rustc::middle::ty;
 use rustc::util::ppaux::Repr;
 use syntax::ast;
-use syntax::codemap::Span;
+use syntax::codemap::{Span, BytePos};
+use syntax::diagnostic::MultiSpan;
 
 use std::rc::Rc;
 
@@ -459,48 +460,44 @@ impl<'a, 'tcx> CheckLoanCtxt<'a, 'tcx> {
                 format!("`{}`", ol)
             };
 
-            match (new_loan.kind, old_loan.kind) {
+            let msg = match (new_loan.kind, old_loan.kind) {
                 (ty::MutBorrow, ty::MutBorrow) => {
-                    self.bccx.span_err(
-                        new_loan.span,
-                        format!("cannot borrow `{}`{} as mutable \
-                                more than once at a time",
-                                nl, new_loan_msg).index(&FullRange))
+                    format!("cannot borrow `{}`{} as mutable \
+                            more than once at a time",
+                            nl, new_loan_msg)
                 }
 
                 (ty::UniqueImmBorrow, _) => {
-                    self.bccx.span_err(
-                        new_loan.span,
-                        format!("closure requires unique access to `{}` \
-                                but {} is already borrowed{}",
-                                nl, ol_pronoun, old_loan_msg).index(&FullRange));
+                    format!("closure requires unique access to `{}` \
+                            but {} is already borrowed{}",
+                            nl, ol_pronoun, old_loan_msg)
                 }
 
                 (_, ty::UniqueImmBorrow) => {
-                    self.bccx.span_err(
-                        new_loan.span,
-                        format!("cannot borrow `{}`{} as {} because \
-                                previous closure requires unique access",
-                                nl, new_loan_msg, new_loan.kind.to_user_str()).index(&FullRange));
+                    format!("cannot borrow `{}`{} as {} because \
+                            previous closure requires unique access",
+                            nl, new_loan_msg, new_loan.kind.to_user_str())
                 }
 
                 (_, _) => {
-                    self.bccx.span_err(
-                        new_loan.span,
-                        format!("cannot borrow `{}`{} as {} because \
-                                {} is also borrowed as {}{}",
-                                nl,
-                                new_loan_msg,
-                                new_loan.kind.to_user_str(),
-                                ol_pronoun,
-                                old_loan.kind.to_user_str(),
-                                old_loan_msg).index(&FullRange));
+                    format!("cannot borrow `{}`{} as {} because \
+                            {} is also borrowed as {}{}",
+                            nl,
+                            new_loan_msg,
+                            new_loan.kind.to_user_str(),
+                            ol_pronoun,
+                            old_loan.kind.to_user_str(),
+                            old_loan_msg)
                 }
-            }
+            };
+
+            let mut msp = MultiSpan::new(new_loan.span);
+            msp.label_primary(format!("{} borrow occurs here",
+                                      new_loan.kind.to_user_str()).index(&FullRange));
 
             match new_loan.cause {
                 euv::ClosureCapture(span) => {
-                    self.bccx.span_note(
+                    msp.push_label(
                         span,
                         format!("borrow occurs due to use of `{}` in closure",
                                 nl).index(&FullRange));
@@ -549,14 +546,15 @@ impl<'a, 'tcx> CheckLoanCtxt<'a, 'tcx> {
                             ol, old_loan_msg)
                 }
             };
+            msp.push_label(old_loan.span, borrow_summary.index(&FullRange));
 
-            self.bccx.span_note(
-                old_loan.span,
-                format!("{}; {}", borrow_summary, rule_summary).index(&FullRange));
-
+            // Point at the end of the scope, like `span_end_note` does.
             let old_loan_span = self.tcx().map.span(old_loan.kill_scope.node_id());
-            self.bccx.span_end_note(old_loan_span,
-                                    "previous borrow ends here");
+            let scope_end = Span { lo: old_loan_span.hi - BytePos(1), ..old_loan_span };
+            msp.push_label(scope_end, "previous borrow ends here");
+
+            self.bccx.span_err_labeled(&msp, msg.index(&FullRange));
+            self.bccx.note(rule_summary.index(&FullRange));
 
             return false;
         }
@@ -619,16 +617,18 @@ impl<'a, 'tcx> CheckLoanCtxt<'a, 'tcx> {
         match self.analyze_restrictions_on_use(id, copy_path, ty::ImmBorrow) {
             UseOk => { }
             UseWhileBorrowed(loan_path, loan_span) => {
-                self.bccx.span_err(
-                    span,
-                    format!("cannot use `{}` because it was mutably borrowed",
-                            self.bccx.loan_path_to_string(copy_path).index(&FullRange))
-                    .index(&FullRange));
-                self.bccx.span_note(
+                let mut msp = MultiSpan::new(span);
+                msp.label_primary("use of borrowed value occurs here");
+                msp.push_label(
                     loan_span,
                     format!("borrow of `{}` occurs here",
                             self.bccx.loan_path_to_string(&*loan_path).index(&FullRange))
                     .index(&FullRange));
+                self.bccx.span_err_labeled(
+                    &msp,
+                    format!("cannot use `{}` because it was mutably borrowed",
+                            self.bccx.loan_path_to_string(copy_path).index(&FullRange))
+                    .index(&FullRange));
             }
         }
     }
@@ -655,12 +655,14 @@ impl<'a, 'tcx> CheckLoanCtxt<'a, 'tcx> {
                                 self.bccx.loan_path_to_string(move_path).index(&FullRange))
                 };
 
-                self.bccx.span_err(span, err_message.index(&FullRange));
-                self.bccx.span_note(
+                let mut msp = MultiSpan::new(span);
+                msp.label_primary("move out of borrowed value occurs here");
+                msp.push_label(
                     loan_span,
                     format!("borrow of `{}` occurs here",
                             self.bccx.loan_path_to_string(&*loan_path).index(&FullRange))
                     .index(&FullRange));
+                self.bccx.span_err_labeled(&msp, err_message.index(&FullRange));
             }
         }
     }
@@ -34,6 +34,7 @@ use syntax::ast_map;
 use syntax::ast_map::blocks::{FnLikeNode, FnParts};
 use syntax::ast_util;
 use syntax::codemap::Span;
+use syntax::diagnostic;
 use syntax::parse::token;
 use syntax::visit;
 use syntax::visit::{Visitor, FnKind};
@@ -675,12 +676,42 @@ impl<'a, 'tcx> BorrowckCtxt<'a, 'tcx> {
             format!("re-assignment of immutable variable `{}`",
                     self.loan_path_to_string(lp)).index(&FullRange));
         self.tcx.sess.span_note(assign.span, "prior assignment occurs here");
+        if let LpVar(id) = lp.kind {
+            self.suggest_mut_binding(id);
+        }
+    }
+
+    /// Suggests declaring the local variable `id` as `mut`, if it is bound by
+    /// a plain identifier pattern.
+    fn suggest_mut_binding(&self, id: ast::NodeId) {
+        let pat = match self.tcx.map.find(id) {
+            Some(ast_map::NodeLocal(pat)) | Some(ast_map::NodeArg(pat)) => pat,
+            _ => return,
+        };
+        match pat.node {
+            ast::PatIdent(ast::BindByValue(ast::MutImmutable), ref ident, None) => {
+                self.tcx.sess.span_suggestion(
+                    pat.span,
+                    "make this binding mutable",
+                    format!("mut {}", token::get_ident(ident.node)),
+                    diagnostic::MachineApplicable);
+            }
+            _ => {}
+        }
     }
 
     pub fn span_err(&self, s: Span, m: &str) {
         self.tcx.sess.span_err(s, m);
     }
 
+    pub fn span_err_labeled(&self, msp: &diagnostic::MultiSpan, m: &str) {
+        self.tcx.sess.span_err_labeled(msp, m);
+    }
+
+    pub fn note(&self, m: &str) {
+        self.tcx.sess.note(m);
+    }
+
     pub fn span_note(&self, s: Span, m: &str) {
         self.tcx.sess.span_note(s, m);
     }
@@ -848,7 +879,11 @@ impl<'a, 'tcx> BorrowckCtxt<'a, 'tcx> {
                                  self by mutable reference");
                         }
                     }
-                    _ => {}
+                    _ => {
+                        if let mc::cat_local(id) = err.cmt.cat {
+                            self.suggest_mut_binding(id);
+                        }
+                    }
                 }
             }
 
@@ -10,6 +10,7 @@
 
 use rustc::session::Session;
 use rustc::session::config::{self, Input, OutputFilenames};
+use rustc::session::profile;
 use rustc::session::search_paths::PathKind;
 use rustc::lint;
 use rustc::metadata::creader::CrateReader;
@@ -20,8 +21,10 @@ use rustc::plugin::load::Plugins;
 use rustc::plugin::registry::Registry;
 use rustc::plugin;
 use rustc::util::common::time;
+use rustc::util::nodemap::NodeSet;
 use rustc_borrowck as borrowck;
 use rustc_resolve as resolve;
+use rustc_trans::back::incremental;
 use rustc_trans::back::link;
 use rustc_trans::back::write;
 use rustc_trans::save;
@@ -48,6 +51,11 @@ pub fn compile_input(sess: Session,
                      outdir: &Option<Path>,
                      output: &Option<Path>,
                      addl_plugins: Option<Plugins>) {
+    // Written out when dropped, also when compilation stops early.
+    let _profile = sess.opts.self_profile.as_ref().map(|&(ref path, format)| {
+        profile::ProfileWriter::new(path.clone(), format)
+    });
+
     // We need nested scopes here, because the intermediate results can keep
     // large chunks of memory alive and we want to free them as soon as
     // possible to keep the peak memory usage low
@@ -82,11 +90,16 @@ pub fn compile_input(sess: Session,
         let arenas = ty::CtxtArenas::new();
         let analysis = phase_3_run_analysis_passes(sess, ast_map, &arenas, id);
         phase_save_analysis(&analysis.ty_cx.sess, analysis.ty_cx.map.krate(), &analysis, outdir);
+        if analysis.ty_cx.sess.opts.query.is_some() {
+            phase_answer_query(&analysis.ty_cx.sess, analysis.ty_cx.map.krate(), &analysis);
+            return;
+        }
 
         if log_enabled!(::log::INFO) {
             println!("Pre-trans");
             analysis.ty_cx.print_debug_stats();
         }
+        analysis.ty_cx.record_profile_counters();
 
         if stop_after_phase_3(&analysis.ty_cx.sess) { return; }
         let (tcx, trans) = phase_4_translate_to_llvm(analysis);
@@ -95,12 +108,14 @@ pub fn compile_input(sess: Session,
             println!("Post-trans");
             tcx.print_debug_stats();
         }
+        tcx.record_profile_counters();
 
         // Discard interned strings as they are no longer required.
         token::get_ident_interner().clear();
 
         (outputs, trans, tcx.sess)
     };
+    write_coverage_map(&sess, &trans, &outputs);
     phase_5_run_llvm_passes(&sess, &trans, &outputs);
     if stop_after_phase_5(&sess) { return; }
     phase_6_link_output(&sess, &trans, &outputs);
@@ -247,11 +262,13 @@ pub fn phase_2_configure_and_expand(sess: &Session,
         }
     }
 
-    // Lint plugins are registered; now we can process command line flags.
+    // Lint plugins are registered; now we can process the lint config file
+    // and command line flags.
     if sess.opts.describe_lints {
         super::describe_lints(&*sess.lint_store.borrow(), true);
         return None;
     }
+    sess.lint_store.borrow_mut().process_config(sess, crate_name);
     sess.lint_store.borrow_mut().process_command_line(sess);
 
     // Abort if there are errors from lint processing or a plugin registrar.
@@ -357,7 +374,7 @@ pub fn phase_3_run_analysis_passes<'tcx>(sess: Session,
     let lang_items = time(time_passes, "language item collection", (), |_|
                           middle::lang_items::collect_language_items(krate, &sess));
 
-    let make_glob_map = if save_analysis(&sess) {
+    let make_glob_map = if save_analysis(&sess) || sess.opts.query.is_some() {
         resolve::MakeGlobMap::Yes
     } else {
         resolve::MakeGlobMap::No
@@ -416,8 +433,19 @@ pub fn phase_3_run_analysis_passes<'tcx>(sess: Session,
                             lang_items,
                             stability_index);
 
+    // Type checking takes the trait map, which the dependency graph of an
+    // incremental compilation also needs.
+    let kept_trait_map = ty_cx.sess.opts.incremental.as_ref().map(|_| trait_map.clone());
+    let reused = match kept_trait_map {
+        Some(ref trait_map) => {
+            time(time_passes, "loading type-check results", (), |_|
+                 incremental::load_typeck_tables(&ty_cx, trait_map, name.as_slice()))
+        }
+        None => NodeSet::new(),
+    };
+
     // passes are timed inside typeck
-    typeck::check_crate(&ty_cx, trait_map);
+    typeck::check_crate(&ty_cx, trait_map, reused);
 
     time(time_passes, "check static items", (), |_|
          middle::check_static::check_crate(&ty_cx));
@@ -480,11 +508,12 @@ pub fn phase_3_run_analysis_passes<'tcx>(sess: Session,
         reachable: reachable_map,
         name: name,
         glob_map: glob_map,
+        trait_map: kept_trait_map,
     }
 }
 
 fn save_analysis(sess: &Session) -> bool {
-    (sess.opts.debugging_opts & config::SAVE_ANALYSIS) != 0
+    (sess.opts.debugging_opts & (config::SAVE_ANALYSIS | config::SAVE_ANALYSIS_CSV)) != 0
 }
 
 pub fn phase_save_analysis(sess: &Session,
@@ -498,6 +527,19 @@ pub fn phase_save_analysis(sess: &Session,
          save::process_crate(sess, krate, analysis, odir));
 }
 
+/// Answer the `--query` given on the command line, printing the answer to
+/// stdout.
+pub fn phase_answer_query(sess: &Session,
+                          krate: &ast::Crate,
+                          analysis: &ty::CrateAnalysis) {
+    let query = match sess.opts.query {
+        Some(ref q) => save::query::Query::parse(q.as_slice()).unwrap(),
+        None => return,
+    };
+    time(sess.time_passes(), "answering query", krate, |krate|
+         save::query::print_answer(sess, krate, analysis, &query));
+}
+
 /// Run the translation phase to LLVM, after which the AST and analysis can
 /// be discarded.
 pub fn phase_4_translate_to_llvm<'tcx>(analysis: ty::CrateAnalysis<'tcx>)
@@ -600,6 +642,25 @@ fn escape_dep_filename(filename: &str) -> String {
     filename.replace(" ", "\\ ")
 }
 
+/// Writes the coverage map of `-C instrument-coverage` next to the outputs,
+/// as `<crate>.covmap`.
+fn write_coverage_map(sess: &Session,
+                      trans: &trans::CrateTranslation,
+                      outputs: &OutputFilenames) {
+    let map = match trans.coverage_map {
+        Some(ref map) => map,
+        None => return,
+    };
+    let path = outputs.with_extension("covmap");
+    match io::File::create(&path).write_str(json::encode(map).as_slice()) {
+        Ok(()) => {}
+        Err(e) => {
+            sess.fatal(format!("error writing coverage map to `{}`: {}",
+                               path.display(), e).as_slice());
+        }
+    }
+}
+
 fn write_out_deps(sess: &Session,
                   input: &Input,
                   outputs: &OutputFilenames,
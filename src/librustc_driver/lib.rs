@@ -45,7 +45,9 @@ extern crate "rustc_llvm" as llvm;
 pub use syntax::diagnostic;
 
 use rustc_trans::back::link;
-use rustc::session::{config, Session, build_session};
+use rustc_trans::save;
+use rustc_trans::trans::coverage;
+use rustc::session::{config, profile, Session, build_session};
 use rustc::session::config::{Input, PrintRequest};
 use rustc::lint::Lint;
 use rustc::lint;
@@ -103,6 +105,18 @@ fn run_compiler(args: &[String]) {
     }
 
     let sopts = config::build_session_options(&matches);
+    if sopts.debugging_opts & config::UNSTABLE_OPTIONS != 0 {
+        if let Some(map) = matches.opt_str("coverage-report") {
+            let profiles: Vec<Path> = matches.free.iter()
+                                              .map(|p| Path::new(p.as_slice()))
+                                              .collect();
+            match coverage::report(&Path::new(map), profiles.as_slice()) {
+                Ok(report) => print!("{}", report),
+                Err(e) => early_error(e.as_slice()),
+            }
+            return;
+        }
+    }
     let odir = matches.opt_str("out-dir").map(|o| Path::new(o));
     let ofile = matches.opt_str("o").map(|o| Path::new(o));
     let (input, input_file_path) = match matches.free.len() {
@@ -162,6 +176,25 @@ fn run_compiler(args: &[String]) {
 
     if sess.unstable_options() {
         sess.opts.show_span = matches.opt_str("show-span");
+        sess.opts.self_profile = matches.opt_str("self-profile").map(|path| {
+            let format = match matches.opt_str("self-profile-format") {
+                None => profile::ProfileFormat::Json,
+                Some(f) => match profile::ProfileFormat::from_str(f.as_slice()) {
+                    Some(format) => format,
+                    None => early_error(format!("argument for --self-profile-format \
+                                                 must be json or chrome (instead was `{}`)",
+                                                f).as_slice()),
+                },
+            };
+            (Path::new(path), format)
+        });
+        sess.opts.query = matches.opt_str("query");
+        if let Some(ref q) = sess.opts.query {
+            if save::query::Query::parse(q.as_slice()).is_none() {
+                early_error(format!("argument for --query must be FILE:LINE:COL \
+                                     (instead was `{}`)", q).as_slice());
+            }
+        }
     }
 
     let r = matches.opt_strs("Z");
@@ -364,6 +397,8 @@ fn describe_debug_flags() {
             }
         }
     }
+    println!("    -Z {:>20} -- {}", "incremental=DIR",
+             "Reuse type-check results and object files of unchanged items, kept in DIR");
 }
 
 fn describe_codegen_flags() {
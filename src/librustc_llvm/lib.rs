@@ -473,6 +473,9 @@ pub type TargetMachineRef = *mut TargetMachine_opaque;
 pub enum Archive_opaque {}
 pub type ArchiveRef = *mut Archive_opaque;
 #[allow(missing_copy_implementations)]
+pub enum ProfileData_opaque {}
+pub type ProfileDataRef = *mut ProfileData_opaque;
+#[allow(missing_copy_implementations)]
 pub enum Twine_opaque {}
 pub type TwineRef = *mut Twine_opaque;
 #[allow(missing_copy_implementations)]
@@ -2020,6 +2023,14 @@ extern {
                                       out_len: *mut size_t) -> *const c_char;
     pub fn LLVMRustDestroyArchive(AR: ArchiveRef);
 
+    pub fn LLVMRustOpenProfileData(path: *const c_char) -> ProfileDataRef;
+    pub fn LLVMRustGetFunctionCounts(PD: ProfileDataRef,
+                                     name: *const c_char,
+                                     hash: u64,
+                                     counts: *mut u64,
+                                     len: size_t) -> bool;
+    pub fn LLVMRustDestroyProfileData(PD: ProfileDataRef);
+
     pub fn LLVMRustSetDLLExportStorageClass(V: ValueRef);
     pub fn LLVMVersionMajor() -> c_int;
     pub fn LLVMVersionMinor() -> c_int;
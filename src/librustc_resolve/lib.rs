@@ -74,21 +74,24 @@ use syntax::ast::{Ty, TyBool, TyChar, TyF32};
 use syntax::ast::{TyF64, TyFloat, TyIs, TyI8, TyI16, TyI32, TyI64, TyInt, TyObjectSum};
 use syntax::ast::{TyParam, TyParamBound, TyPath, TyPtr, TyPolyTraitRef, TyQPath};
 use syntax::ast::{TyRptr, TyStr, TyUs, TyU8, TyU16, TyU32, TyU64, TyUint};
-use syntax::ast::{TypeImplItem};
+use syntax::ast::{TypeImplItem, ViewItemUse};
 use syntax::ast;
 use syntax::ast_map;
 use syntax::ast_util::{PostExpansionMethod, local_def, walk_pat};
 use syntax::attr::AttrMetaMethods;
 use syntax::ext::mtwt;
 use syntax::parse::token::{self, special_names, special_idents};
-use syntax::codemap::{Span, Pos};
+use syntax::codemap::{Span, Pos, DUMMY_SP, NO_EXPANSION};
+use syntax::diagnostic::{Applicability, MachineApplicable, MaybeIncorrect};
 use syntax::owned_slice::OwnedSlice;
 use syntax::visit::{self, Visitor};
 
 use std::collections::{HashMap, HashSet};
 use std::collections::hash_map::Entry::{Occupied, Vacant};
 use std::cell::{Cell, RefCell};
+use std::cmp;
 use std::fmt;
+use std::iter;
 use std::mem::replace;
 use std::rc::{Rc, Weak};
 use std::uint;
@@ -161,6 +164,9 @@ impl<'a, 'v, 'tcx> Visitor<'v> for Resolver<'a, 'tcx> {
     fn visit_item(&mut self, item: &Item) {
         self.resolve_item(item);
     }
+    fn visit_mod(&mut self, module: &Mod, span: Span, id: NodeId) {
+        self.resolve_module(module, span, id);
+    }
     fn visit_arm(&mut self, arm: &Arm) {
         self.resolve_arm(arm);
     }
@@ -896,6 +902,15 @@ struct Resolver<'a, 'tcx:'a> {
     // so as to avoid printing duplicate errors
     emit_errors: bool,
 
+    // The path prefix which would make the last failed module path resolve,
+    // along with the (empty) span it should be inserted at. Reported as a
+    // suggestion along with the error for that path.
+    missing_path_prefix: Option<(Span, String)>,
+
+    // For each module being resolved, from the crate root in, the (empty)
+    // span at which a `use` declaration would be inserted into it.
+    use_insertion_points: Vec<Option<Span>>,
+
     make_glob_map: bool,
     // Maps imports to the names of items actually imported (this actually maps
     // all imports, but only glob imports are actually interesting).
@@ -968,6 +983,8 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
             last_private: NodeMap::new(),
 
             emit_errors: true,
+            missing_path_prefix: None,
+            use_insertion_points: Vec::new(),
             make_glob_map: make_glob_map == MakeGlobMap::Yes,
             glob_map: HashMap::new(),
         }
@@ -1062,6 +1079,7 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
                                           import_directive.subclass),
                                       help);
                     self.resolve_error(span, msg.index(&FullRange));
+                    self.suggest_missing_path_prefix(span);
                 }
                 Indeterminate => break, // Bail out. We'll come around next time.
                 Success(()) => () // Good. Continue.
@@ -1977,6 +1995,13 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
                                     format!("{}::", target_mod_str)
                                 };
 
+                                // The crate root is printed as `???`, and can't
+                                // be named by a relative prefix.
+                                if target_mod_str != "???" {
+                                    let at = Span { hi: span.lo, ..span };
+                                    self.missing_path_prefix = Some((at, prefix.clone()));
+                                }
+
                                 format!("Did you mean `{}{}`?", prefix, path_str)
                             },
                             None => format!("Maybe a missing `extern crate {}`?",
@@ -2253,9 +2278,11 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
                                               namespace,
                                               PathSearch,
                                               true) {
-                Failed(Some((span, msg))) =>
+                Failed(Some((span, msg))) => {
                     self.resolve_error(span, format!("failed to resolve. {}",
-                                                     msg).index(&FullRange)),
+                                                     msg).index(&FullRange));
+                    self.suggest_missing_path_prefix(span);
+                }
                 Failed(None) => (), // Continue up the search chain.
                 Indeterminate => {
                     // We couldn't see through the higher scope because of an
@@ -2929,8 +2956,7 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
 
             ItemMod(ref module_) => {
                 self.with_scope(Some(name), |this| {
-                    this.resolve_module(module_, item.span, name,
-                                        item.id);
+                    this.resolve_module(module_, item.span, item.id);
                 });
             }
 
@@ -3390,11 +3416,12 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
         }
     }
 
-    fn resolve_module(&mut self, module: &Mod, _span: Span,
-                      _name: Name, id: NodeId) {
+    fn resolve_module(&mut self, module: &Mod, _span: Span, id: NodeId) {
         // Write the implementations in scope into the module metadata.
         debug!("(resolving module) resolving module ID {}", id);
+        self.use_insertion_points.push(use_insertion_point(module));
         visit::walk_mod(self, module);
+        self.use_insertion_points.pop();
     }
 
     fn resolve_local(&mut self, local: &Local) {
@@ -3592,6 +3619,10 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
                         let msg = format!("use of undeclared type name `{}`",
                                           self.path_names_to_string(path));
                         self.resolve_error(ty.span, msg.index(&FullRange));
+                        if path.segments.len() == 1 && !path.global {
+                            let name = path.segments[0].identifier.name;
+                            self.suggest_imports(name, TypeNS);
+                        }
                     }
                 }
             }
@@ -4240,6 +4271,102 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
         }
     }
 
+    fn resolve_suggestion(&self, span: Span, msg: &str, replacement: String,
+                          applicability: Applicability) {
+        if self.emit_errors {
+            self.session.span_suggestion(span, msg, replacement, applicability);
+        }
+    }
+
+    // Suggests the prefix recorded by the failed resolution of a module path
+    // whose error was just reported at `span`.
+    fn suggest_missing_path_prefix(&mut self, span: Span) {
+        match self.missing_path_prefix.take() {
+            Some((at, prefix)) if at.lo == span.lo => {
+                // The prefix is relative to the crate root, which is only
+                // right for imports and for paths in the crate root.
+                self.resolve_suggestion(at, "add the missing path prefix",
+                                        prefix, MaybeIncorrect);
+            }
+            _ => {}
+        }
+    }
+
+    // Suggests importing the items named `name` in `namespace` that the
+    // current module could import, for the error just reported about `name`
+    // not being in scope. The import is machine-applicable when there is only
+    // one such item.
+    fn suggest_imports(&mut self, name: Name, namespace: Namespace) {
+        let at = match self.use_insertion_points.last() {
+            Some(&Some(at)) => at,
+            _ => return,
+        };
+        let root = self.graph_root.get_module();
+        let mut paths = Vec::new();
+        self.find_importable(&root, &mut Vec::new(), name, namespace, &mut paths);
+        if paths.is_empty() {
+            return
+        }
+
+        // Keep the indentation of the line the import is inserted on.
+        let col = self.session.codemap().lookup_char_pos(at.lo).col.to_uint();
+        let indent = iter::repeat(' ').take(col).collect::<String>();
+        let applicability = if paths.len() == 1 { MachineApplicable } else { MaybeIncorrect };
+        for path in paths.iter() {
+            self.resolve_suggestion(at, "consider importing it",
+                                    format!("use {};\n{}", path, indent), applicability);
+        }
+    }
+
+    // Collects into `paths` the paths from the crate root of the items named
+    // `name` in `namespace` under the local module `module`, at `prefix`, which
+    // are visible from the current module.
+    fn find_importable(&mut self, module: &Rc<Module>, prefix: &mut Vec<Name>,
+                       name: Name, namespace: Namespace, paths: &mut Vec<String>) {
+        let visible_inside = self.is_ancestor_of_current(&**module);
+        let children = module.children.borrow().iter().map(|(&child_name, bindings)| {
+            (child_name, bindings.clone())
+        }).collect::<Vec<(Name, Rc<NameBindings>)>>();
+        for &(child_name, ref bindings) in children.iter() {
+            if child_name == name && bindings.defined_in_namespace(namespace) &&
+               (visible_inside || bindings.defined_in_public_namespace(namespace)) {
+                prefix.push(child_name);
+                paths.push(self.names_to_string(prefix.as_slice()));
+                prefix.pop();
+            }
+            let child = match bindings.get_module_if_available() {
+                Some(ref child) if child.kind.get() == NormalModuleKind &&
+                                   child.def_id.get().map_or(false, |d| d.krate == LOCAL_CRATE) &&
+                                   (visible_inside ||
+                                    bindings.defined_in_public_namespace(TypeNS)) => {
+                    child.clone()
+                }
+                _ => continue,
+            };
+            prefix.push(child_name);
+            self.find_importable(&child, prefix, name, namespace, paths);
+            prefix.pop();
+        }
+    }
+
+    // Whether `module` is the current module or one of the modules it is
+    // nested in, whose private items it can see.
+    fn is_ancestor_of_current(&self, module: &Module) -> bool {
+        let mut current = self.current_module.clone();
+        loop {
+            if &*current as *const Module == module as *const Module {
+                return true
+            }
+            let parent = match current.parent_link {
+                NoParentLink => return false,
+                ModuleParentLink(ref parent, _) | BlockParentLink(ref parent, _) => {
+                    parent.upgrade().unwrap()
+                }
+            };
+            current = parent;
+        }
+    }
+
     fn find_fallback_in_self_type(&mut self, name: Name) -> FallbackSuggestion {
         fn extract_path_and_node_id(t: &Ty, allow: FallbackChecks)
                                                     -> Option<(Path, NodeId, FallbackChecks)> {
@@ -4466,33 +4593,50 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
                                              `self` argument is missing?");
                                 } else {
                                     let last_name = path.segments.last().unwrap().identifier.name;
-                                    let mut msg = match self.find_fallback_in_self_type(last_name) {
+                                    let fallback = self.find_fallback_in_self_type(last_name);
+                                    let suggestion = match fallback {
                                         NoSuggestion => {
                                             // limit search to 5 to reduce the number
                                             // of stupid suggestions
                                             self.find_best_match_for_name(path_name.as_slice(), 5)
-                                                                .map_or("".to_string(),
-                                                                        |x| format!("`{}`", x))
+                                                .map(|x| (format!("`{}`", x), x, MaybeIncorrect))
+                                        }
+                                        Field => {
+                                            let x = format!("self.{}", path_name);
+                                            Some((format!("`{}`", x), x, MachineApplicable))
                                         }
-                                        Field =>
-                                            format!("`self.{}`", path_name),
                                         Method
-                                        | TraitItem =>
-                                            format!("to call `self.{}`", path_name),
+                                        | TraitItem => {
+                                            let x = format!("self.{}", path_name);
+                                            Some((format!("to call `{}`", x), x, MaybeIncorrect))
+                                        }
                                         TraitMethod(path_str)
-                                        | StaticMethod(path_str) =>
-                                            format!("to call `{}::{}`", path_str, path_name)
+                                        | StaticMethod(path_str) => {
+                                            let x = format!("{}::{}", path_str, path_name);
+                                            Some((format!("to call `{}`", x), x, MaybeIncorrect))
+                                        }
                                     };
 
-                                    if msg.len() > 0 {
-                                        msg = format!(". Did you mean {}?", msg)
-                                    }
+                                    let msg = match suggestion {
+                                        Some((ref msg, _, _)) => format!(". Did you mean {}?", msg),
+                                        None => "".to_string(),
+                                    };
 
                                     self.resolve_error(
                                         expr.span,
                                         format!("unresolved name `{}`{}",
                                                 path_name,
                                                 msg).as_slice());
+                                    match suggestion {
+                                        Some((_, replacement, applicability)) => {
+                                            self.resolve_suggestion(expr.span, "try",
+                                                                    replacement, applicability);
+                                        }
+                                        None if path.segments.len() == 1 && !path.global => {
+                                            self.suggest_imports(last_name, ValueNS);
+                                        }
+                                        None => {}
+                                    }
                                 }
                             }
                         }
@@ -4811,6 +4955,27 @@ impl<'a, 'tcx> Resolver<'a, 'tcx> {
     }
 }
 
+/// Where a `use` declaration would be inserted into `module`: before its
+/// first import, or before its first item if it has no imports, so that it
+/// comes after any `extern crate`. `None` when everything in the module was
+/// generated by macros or injected.
+fn use_insertion_point(module: &Mod) -> Option<Span> {
+    let imports = module.view_items.iter().filter(|view_item| {
+        match view_item.node {
+            ViewItemUse(..) => true,
+            _ => false,
+        }
+    }).map(|view_item| (view_item.span, view_item.attrs.as_slice()));
+    let items = module.items.iter().map(|item| (item.span, item.attrs.as_slice()));
+    imports.chain(items).find(|&(span, _)| {
+        span != DUMMY_SP && span.expn_id == NO_EXPANSION
+    }).map(|(span, attrs)| {
+        // The span of an item leaves out its attributes.
+        let lo = attrs.iter().fold(span.lo, |lo, attr| cmp::min(lo, attr.span.lo));
+        Span { lo: lo, hi: lo, expn_id: NO_EXPANSION }
+    })
+}
+
 pub struct CrateMap {
     pub def_map: DefMap,
     pub freevars: RefCell<FreevarMap>,
@@ -0,0 +1,875 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Incremental recompilation, enabled with `-Z incremental=DIR`.
+//!
+//! # The dependency graph
+//!
+//! Every item directly inside a module is a node of the graph, together with
+//! everything nested in it: the methods of an impl, the items in a function
+//! body, and so on. A node's `hash` covers its AST, hashed like the crate's
+//! SVH, its attributes, its source text and where it starts in its file, so
+//! that moving an item also invalidates the line numbers baked into panic
+//! messages and debug info. A node's `deps` are the nodes its paths and
+//! method calls resolve to, and the traits in scope at its method calls.
+//! An impl is also a dependency of its trait and of its self type, since
+//! code using either may end up calling into it.
+//!
+//! The fingerprint of a node hashes its own hash together with those of all
+//! the nodes it depends on, directly or not. Using the whole closure rather
+//! than just the signatures of the direct dependencies is what makes
+//! inlining and monomorphization across items safe.
+//!
+//! # Codegen units
+//!
+//! In incremental mode, modules are assigned to codegen units by a hash of
+//! their path instead of by size, so a module stays in its unit as long as
+//! `-C codegen-units` does not change. Items of the crate root are always in
+//! unit 0. The fingerprint of a unit hashes the fingerprints of its nodes,
+//! the options that influence code generation and the hashes of the
+//! upstream crates. When it is the same as in the previous compilation, the
+//! unit's object file and bitcode are copied from DIR instead of being
+//! optimized and generated again by LLVM.
+//!
+//! Objects from different compilations can only be linked together if
+//! symbol names do not depend on unrelated parts of the crate. So, in
+//! incremental mode, symbol hashes leave out the crate hash (`-C metadata`
+//! still tells versions of a crate apart), exported symbols are
+//! disambiguated with `stable_id` instead of node ids, and every codegen
+//! unit has private copies of the monomorphizations and glue it uses.
+//!
+//! Reuse is per codegen unit, so a crate compiled with the default single
+//! codegen unit is only reused when nothing changed at all. Functions of a
+//! reused unit are declared, for their symbols to be known to the metadata
+//! and to the other units, but not translated.
+//!
+//! # Type-check results
+//!
+//! What type checking records for each node, the types of its expressions,
+//! its method calls, adjustments and closures, is kept keyed by the node's
+//! path. Before type checking, a first graph is built from the resolved
+//! paths, the traits in scope at method calls and the impls. A node whose
+//! hash did not change also keeps its previous dependencies, which include
+//! the methods type checking resolved for it then. A node whose fingerprint
+//! in that graph is the same as in the previous compilation is not checked
+//! again: its tables are loaded instead, with the node ids of the previous
+//! compilation translated through the nodes whose hash did not change.
+//!
+//! Parsing, expansion, resolution, the collection of item types and the
+//! analysis passes after type checking still run on the whole crate.
+//!
+//! The graph of the last successful compilation is kept in
+//! `DIR/<crate>.dep-graph.json`, the type-check results in
+//! `DIR/<crate>.typeck` and the object files in `DIR/<crate>.N.o`.
+
+use session::Session;
+use session::config::{self, OutputFilenames};
+use middle::astencode;
+use middle::def;
+use middle::ty;
+use back::svh;
+use util::nodemap::{NodeMap, NodeSet};
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::Hash;
+use std::hash::sip::SipState;
+use std::io::{self, fs, File, IoResult};
+use std::io::fs::PathExtensions;
+use std::mem;
+use std::num;
+
+use serialize::json::{self, Json};
+
+use syntax::ast;
+use syntax::ast_map;
+use syntax::ast_util::{IdRange, IdVisitor, IdVisitingOperation};
+use syntax::parse::token;
+use syntax::print::pprust;
+use syntax::visit::Visitor;
+
+macro_rules! try_opt {
+    ($e:expr) => (match $e { Some(v) => v, None => return None })
+}
+
+/// Bumped whenever the stored graph would be misread by a newer compiler.
+pub const FORMAT_VERSION: u64 = 2;
+
+/// A node of the dependency graph, see the module documentation.
+#[derive(Clone, Show)]
+pub struct ItemNode {
+    /// Path of the item from the crate root. Impls and extern blocks are
+    /// named after what they implement or contain, and a `#N` suffix tells
+    /// apart items that would otherwise get the same path.
+    pub path: String,
+    /// The codegen unit the item is translated in.
+    pub unit: uint,
+    pub hash: u64,
+    pub fingerprint: u64,
+    /// Paths of the nodes this one depends on directly.
+    pub deps: Vec<String>,
+    /// The node ids of the item and of everything nested in it, or an empty
+    /// range if they are not consecutive.
+    pub ids: IdRange,
+}
+
+/// The dependency graph of a crate, and the fingerprints of its codegen
+/// units.
+#[derive(Clone, Show)]
+pub struct DepGraph {
+    /// Hash of the options and upstream crates, included in every unit's
+    /// fingerprint.
+    pub session: u64,
+    pub items: Vec<ItemNode>,
+    pub units: Vec<u64>,
+}
+
+/// The codegen unit the module `id`, directly inside another module, is
+/// translated in.
+pub fn unit_of_module(tcx: &ty::ctxt, id: ast::NodeId) -> uint {
+    let units = tcx.sess.opts.cg.codegen_units;
+    let mut state = SipState::new();
+    tcx.map.path_to_string(id).hash(&mut state);
+    (state.result() % units as u64) as uint
+}
+
+/// Whether `id` is the crate root or a module.
+fn is_module(tcx: &ty::ctxt, id: ast::NodeId) -> bool {
+    if id == ast::CRATE_NODE_ID {
+        return true;
+    }
+    match tcx.map.find(id) {
+        Some(ast_map::NodeItem(&ast::Item { node: ast::ItemMod(_), .. })) => true,
+        _ => false,
+    }
+}
+
+/// Whether the item `id` is directly inside a module, rather than in a
+/// block.
+pub fn in_module(tcx: &ty::ctxt, id: ast::NodeId) -> bool {
+    is_module(tcx, tcx.map.get_parent(id))
+}
+
+/// A number identifying the item `id` which, unlike its node id, does not
+/// change when other items are added or removed: a hash of its path and of
+/// its offset in the outermost item containing it, to tell apart items with
+/// the same path in different blocks.
+pub fn stable_id(tcx: &ty::ctxt, id: ast::NodeId) -> uint {
+    let mut outermost = id;
+    loop {
+        let parent = tcx.map.get_parent(outermost);
+        if parent == outermost || is_module(tcx, parent) {
+            break;
+        }
+        outermost = parent;
+    }
+    let offset = tcx.map.span(id).lo.to_uint() - tcx.map.span(outermost).lo.to_uint();
+
+    let mut state = SipState::new();
+    tcx.map.path_to_string(id).hash(&mut state);
+    offset.hash(&mut state);
+    state.result() as uint
+}
+
+// Records the node each node id belongs to, and the range of those ids.
+struct OwnerCollector<'a> {
+    owners: &'a mut NodeMap<uint>,
+    node: uint,
+    ids: IdRange,
+    count: uint,
+}
+
+impl<'a> IdVisitingOperation for OwnerCollector<'a> {
+    fn visit_id(&mut self, id: ast::NodeId) {
+        if self.owners.insert(id, self.node).is_none() {
+            self.ids.add(id);
+            self.count += 1;
+        }
+    }
+}
+
+struct GraphBuilder<'a, 'tcx: 'a> {
+    tcx: &'a ty::ctxt<'tcx>,
+    items: Vec<ItemNode>,
+    deps: Vec<BTreeSet<uint>>,
+    owners: NodeMap<uint>,
+    seen: HashMap<String, uint>,
+    // For each impl, the node ids whose resolutions name its trait and its
+    // self type.
+    impls: Vec<(uint, Vec<ast::NodeId>)>,
+}
+
+impl<'a, 'tcx> GraphBuilder<'a, 'tcx> {
+    /// Collects the nodes of `krate` and the edges known before type
+    /// checking: resolved paths, the traits in scope at method calls, and
+    /// impls.
+    fn new(tcx: &'a ty::ctxt<'tcx>, krate: &ast::Crate, trait_map: &ty::TraitMap)
+           -> GraphBuilder<'a, 'tcx> {
+        let mut builder = GraphBuilder {
+            tcx: tcx,
+            items: vec![],
+            deps: vec![],
+            owners: NodeMap::new(),
+            seen: HashMap::new(),
+            impls: vec![],
+        };
+        builder.collect_mod(&krate.module, "", 0);
+
+        for (id, d) in tcx.def_map.borrow().iter() {
+            if let def::DefPrimTy(..) = *d {
+                continue;
+            }
+            let from = builder.owners.get(id).map(|n| *n);
+            let to = builder.owner_of(d.def_id());
+            builder.add_dep(from, to);
+        }
+        for (id, traits) in trait_map.iter() {
+            let from = builder.owners.get(id).map(|n| *n);
+            for did in traits.iter() {
+                let to = builder.owner_of(*did);
+                builder.add_dep(from, to);
+            }
+        }
+        let impls = mem::replace(&mut builder.impls, vec![]);
+        for &(node, ref ids) in impls.iter() {
+            for id in ids.iter() {
+                if let Some(d) = tcx.def_map.borrow().get(id) {
+                    let target = builder.owner_of(d.def_id());
+                    builder.add_dep(Some(node), target);
+                    builder.add_dep(target, Some(node));
+                }
+            }
+        }
+        builder
+    }
+
+    fn collect_mod(&mut self, module: &ast::Mod, prefix: &str, unit: uint) {
+        for item in module.items.iter() {
+            match item.node {
+                ast::ItemMod(ref m) => {
+                    let prefix = format!("{}{}::", prefix, token::get_ident(item.ident));
+                    let unit = unit_of_module(self.tcx, item.id);
+                    self.collect_mod(m, prefix.as_slice(), unit);
+                }
+                _ => self.add_item(&**item, prefix, unit),
+            }
+        }
+    }
+
+    fn add_item(&mut self, item: &ast::Item, prefix: &str, unit: uint) {
+        let node = self.items.len();
+
+        let name = match item.node {
+            ast::ItemImpl(_, _, _, ref trait_ref, ref self_ty, _) => {
+                let mut ids = vec![];
+                if let Some(ref trait_ref) = *trait_ref {
+                    ids.push(trait_ref.ref_id);
+                }
+                if let ast::TyPath(_, id) = self_ty.node {
+                    ids.push(id);
+                }
+                self.impls.push((node, ids));
+
+                match *trait_ref {
+                    Some(ref t) => format!("<impl {} for {}>",
+                                           pprust::path_to_string(&t.path),
+                                           pprust::ty_to_string(&**self_ty)),
+                    None => format!("<impl {}>", pprust::ty_to_string(&**self_ty)),
+                }
+            }
+            ast::ItemForeignMod(..) => "<extern>".to_string(),
+            _ => token::get_ident(item.ident).get().to_string(),
+        };
+        let mut path = format!("{}{}", prefix, name);
+        let n = self.seen.get(&path).map_or(0, |n| *n) + 1;
+        self.seen.insert(path.clone(), n);
+        if n > 1 {
+            path = format!("{}#{}", path, n);
+        }
+
+        let mut state = SipState::new();
+        svh::hash_item(item, &mut state);
+        let cm = self.tcx.sess.codemap();
+        let lo = cm.lookup_char_pos(item.span.lo);
+        lo.file.name.hash(&mut state);
+        lo.line.hash(&mut state);
+        lo.col.to_uint().hash(&mut state);
+        cm.span_to_snippet(item.span).hash(&mut state);
+
+        let ids = {
+            let mut op = OwnerCollector {
+                owners: &mut self.owners,
+                node: node,
+                ids: IdRange::max(),
+                count: 0,
+            };
+            {
+                let mut visitor = IdVisitor {
+                    operation: &mut op,
+                    pass_through_items: true,
+                    visited_outermost: false,
+                };
+                visitor.visit_item(item);
+            }
+            if !op.ids.empty() && op.count == (op.ids.max - op.ids.min) as uint {
+                op.ids
+            } else {
+                IdRange { min: 0, max: 0 }
+            }
+        };
+
+        self.items.push(ItemNode {
+            path: path,
+            unit: unit,
+            hash: state.result(),
+            fingerprint: 0,
+            deps: vec![],
+            ids: ids,
+        });
+        self.deps.push(BTreeSet::new());
+    }
+
+    // The node defining `did`, if it is in this crate.
+    fn owner_of(&self, did: ast::DefId) -> Option<uint> {
+        if did.krate != ast::LOCAL_CRATE {
+            return None;
+        }
+        self.owners.get(&did.node)
+            .or_else(|| self.owners.get(&self.tcx.map.get_parent(did.node)))
+            .map(|n| *n)
+    }
+
+    fn add_dep(&mut self, from: Option<uint>, to: Option<uint>) {
+        match (from, to) {
+            (Some(from), Some(to)) if from != to => { self.deps[from].insert(to); }
+            _ => {}
+        }
+    }
+
+    /// Computes the fingerprints of the nodes and of the codegen units.
+    fn finish(self, krate: &ast::Crate, crate_name: &str) -> DepGraph {
+        let GraphBuilder { tcx, mut items, deps, .. } = self;
+
+        for (node, deps) in deps.iter().enumerate() {
+            let paths = deps.iter().map(|&d| items[d].path.clone()).collect();
+            items[node].deps = paths;
+        }
+
+        // The fingerprint of a node hashes everything reachable from it, in
+        // an order that does not depend on the node numbering.
+        let mut fingerprints = Vec::with_capacity(items.len());
+        for node in range(0, items.len()) {
+            let mut reachable = BTreeSet::new();
+            let mut stack = vec![node];
+            while let Some(n) = stack.pop() {
+                if reachable.insert(n) {
+                    stack.extend(deps[n].iter().map(|d| *d));
+                }
+            }
+            let mut parts: Vec<(&str, u64)> = reachable.iter()
+                .map(|&n| (items[n].path.as_slice(), items[n].hash))
+                .collect();
+            parts.sort();
+            let mut state = SipState::new();
+            parts.hash(&mut state);
+            fingerprints.push(state.result());
+        }
+        for (item, fingerprint) in items.iter_mut().zip(fingerprints.into_iter()) {
+            item.fingerprint = fingerprint;
+        }
+
+        let session = hash_options(tcx, krate, crate_name);
+        let units = range(0, tcx.sess.opts.cg.codegen_units).map(|unit| {
+            let mut parts: Vec<(&str, u64)> = items.iter()
+                .filter(|item| item.unit == unit)
+                .map(|item| (item.path.as_slice(), item.fingerprint))
+                .collect();
+            parts.sort();
+            let mut state = SipState::new();
+            session.hash(&mut state);
+            parts.hash(&mut state);
+            state.result()
+        }).collect();
+
+        DepGraph {
+            session: session,
+            items: items,
+            units: units,
+        }
+    }
+}
+
+fn hash_options(tcx: &ty::ctxt, krate: &ast::Crate, crate_name: &str) -> u64 {
+    let sess = &tcx.sess;
+    let mut state = SipState::new();
+    FORMAT_VERSION.hash(&mut state);
+    crate_name.hash(&mut state);
+    sess.opts.target_triple.hash(&mut state);
+    format!("{:?} {:?} {:?}", sess.opts.optimize, sess.opts.debuginfo, sess.opts.cg)
+        .hash(&mut state);
+    (sess.opts.debugging_opts & !(config::INCREMENTAL_INFO | config::TIME_PASSES))
+        .hash(&mut state);
+    (*sess.crate_types.borrow()).hash(&mut state);
+    for attr in krate.attrs.iter() {
+        attr.node.value.hash(&mut state);
+    }
+
+    // Type-check results name the items of other crates by crate number,
+    // so the numbering is part of the hash.
+    let mut upstream = vec![];
+    sess.cstore.iter_crate_data(|cnum, data| {
+        upstream.push((cnum, data.name(), data.hash().as_str().to_string()));
+    });
+    upstream.sort();
+    upstream.hash(&mut state);
+    state.result()
+}
+
+impl DepGraph {
+    /// Builds the graph of the analysed crate `krate`.
+    pub fn build(tcx: &ty::ctxt, krate: &ast::Crate, crate_name: &str,
+                 trait_map: &ty::TraitMap) -> DepGraph {
+        let mut builder = GraphBuilder::new(tcx, krate, trait_map);
+        for (call, callee) in tcx.method_map.borrow().iter() {
+            let did = match callee.origin {
+                ty::MethodStatic(did) | ty::MethodStaticUnboxedClosure(did) => did,
+                ty::MethodTypeParam(ref p) => p.trait_ref.def_id,
+                ty::MethodTraitObject(ref o) => o.trait_ref.def_id,
+            };
+            let from = builder.owners.get(&call.expr_id).map(|n| *n);
+            let to = builder.owner_of(did);
+            builder.add_dep(from, to);
+        }
+        builder.finish(krate, crate_name)
+    }
+
+    /// Builds the graph of `krate` before type checking, when its method
+    /// calls are not resolved yet. Nodes whose hash did not change since
+    /// `previous` keep the dependencies they had then, so that their
+    /// fingerprints stay the same as long as nothing they use changed.
+    fn build_before_typeck(tcx: &ty::ctxt, krate: &ast::Crate, crate_name: &str,
+                           trait_map: &ty::TraitMap, previous: &DepGraph) -> DepGraph {
+        let mut builder = GraphBuilder::new(tcx, krate, trait_map);
+        let mut kept = vec![];
+        {
+            let nodes: HashMap<&str, uint> = builder.items.iter().enumerate()
+                .map(|(node, item)| (item.path.as_slice(), node))
+                .collect();
+            for old in previous.items.iter() {
+                match nodes.get(old.path.as_slice()) {
+                    Some(&node) if builder.items[node].hash == old.hash => {
+                        kept.extend(old.deps.iter()
+                                       .filter_map(|d| nodes.get(d.as_slice()))
+                                       .map(|&d| (node, d)));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for (from, to) in kept.into_iter() {
+            builder.add_dep(Some(from), Some(to));
+        }
+        builder.finish(krate, crate_name)
+    }
+
+    /// The items that are new or whose fingerprint changed since
+    /// `previous`.
+    pub fn changed_items<'a>(&'a self, previous: &DepGraph) -> Vec<&'a ItemNode> {
+        let old: HashMap<&str, u64> = previous.items.iter()
+            .map(|item| (item.path.as_slice(), item.fingerprint))
+            .collect();
+        self.items.iter().filter(|item| {
+            old.get(item.path.as_slice()) != Some(&item.fingerprint)
+        }).collect()
+    }
+
+    pub fn to_json(&self) -> Json {
+        let hex = |&: h: u64| Json::String(format!("{:016x}", h));
+
+        let items = self.items.iter().map(|item| {
+            let mut obj = BTreeMap::new();
+            obj.insert("path".to_string(), Json::String(item.path.clone()));
+            obj.insert("unit".to_string(), Json::U64(item.unit as u64));
+            obj.insert("hash".to_string(), hex(item.hash));
+            obj.insert("fingerprint".to_string(), hex(item.fingerprint));
+            obj.insert("deps".to_string(),
+                       Json::Array(item.deps.iter().map(|d| Json::String(d.clone())).collect()));
+            obj.insert("ids".to_string(),
+                       Json::Array(vec![Json::U64(item.ids.min as u64),
+                                        Json::U64(item.ids.max as u64)]));
+            Json::Object(obj)
+        }).collect();
+
+        let mut obj = BTreeMap::new();
+        obj.insert("format_version".to_string(), Json::U64(FORMAT_VERSION));
+        obj.insert("session".to_string(), hex(self.session));
+        obj.insert("items".to_string(), Json::Array(items));
+        obj.insert("units".to_string(),
+                   Json::Array(self.units.iter().map(|&u| hex(u)).collect()));
+        Json::Object(obj)
+    }
+
+    /// Reads a graph written by `to_json`. Returns `None` if it is malformed
+    /// or from another version of the format.
+    pub fn from_json(json: &Json) -> Option<DepGraph> {
+        fn hex(json: Option<&Json>) -> Option<u64> {
+            json.and_then(|j| j.as_string())
+                .and_then(|s| num::from_str_radix(s, 16))
+        }
+
+        if json.find("format_version").and_then(|v| v.as_u64()) != Some(FORMAT_VERSION) {
+            return None;
+        }
+
+        let mut items = vec![];
+        for item in try_opt!(json.find("items").and_then(|i| i.as_array())).iter() {
+            let deps = try_opt!(item.find("deps").and_then(|d| d.as_array()));
+            let ids = try_opt!(item.find("ids").and_then(|i| i.as_array()));
+            if ids.len() != 2 {
+                return None;
+            }
+            items.push(ItemNode {
+                path: try_opt!(item.find("path").and_then(|p| p.as_string())).to_string(),
+                unit: try_opt!(item.find("unit").and_then(|u| u.as_u64())) as uint,
+                hash: try_opt!(hex(item.find("hash"))),
+                fingerprint: try_opt!(hex(item.find("fingerprint"))),
+                deps: deps.iter().filter_map(|d| d.as_string().map(|d| d.to_string())).collect(),
+                ids: IdRange {
+                    min: try_opt!(ids[0].as_u64()) as ast::NodeId,
+                    max: try_opt!(ids[1].as_u64()) as ast::NodeId,
+                },
+            });
+        }
+
+        let mut units = vec![];
+        for unit in try_opt!(json.find("units").and_then(|u| u.as_array())).iter() {
+            units.push(try_opt!(hex(Some(unit))));
+        }
+
+        Some(DepGraph {
+            session: try_opt!(hex(json.find("session"))),
+            items: items,
+            units: units,
+        })
+    }
+}
+
+fn graph_path(dir: &Path, crate_name: &str) -> Path {
+    dir.join(format!("{}.dep-graph.json", crate_name))
+}
+
+fn typeck_path(dir: &Path, crate_name: &str) -> Path {
+    dir.join(format!("{}.typeck", crate_name))
+}
+
+fn read_graph(path: &Path) -> Option<DepGraph> {
+    File::open(path).read_to_string().ok()
+        .and_then(|s| json::from_str(s.as_slice()).ok())
+        .and_then(|j| DepGraph::from_json(&j))
+}
+
+/// Loads, before type checking, the type-check results of the nodes that
+/// did not change since the last compilation, and returns their node ids,
+/// which need not be checked again.
+pub fn load_typeck_tables(tcx: &ty::ctxt, trait_map: &ty::TraitMap, crate_name: &str)
+                          -> NodeSet {
+    let mut reused = NodeSet::new();
+    let dir = match tcx.sess.opts.incremental {
+        Some(ref dir) => dir,
+        None => return reused,
+    };
+    let previous = match read_graph(&graph_path(dir, crate_name)) {
+        Some(previous) => previous,
+        None => return reused,
+    };
+    let data = match File::open(&typeck_path(dir, crate_name)).read_to_end() {
+        Ok(data) => data,
+        Err(_) => return reused,
+    };
+
+    let graph = DepGraph::build_before_typeck(tcx, tcx.map.krate(), crate_name,
+                                              trait_map, &previous);
+    let mut count = 0u;
+    if graph.session == previous.session {
+        let old: HashMap<&str, &ItemNode> = previous.items.iter()
+            .map(|item| (item.path.as_slice(), item))
+            .collect();
+        let nodes: HashMap<&str, uint> = graph.items.iter().enumerate()
+            .map(|(node, item)| (item.path.as_slice(), node))
+            .collect();
+
+        // The node ids of the previous compilation can be translated for
+        // the nodes that did not change.
+        let mut ranges = vec![];
+        let mut mapped = Vec::with_capacity(graph.items.len());
+        for item in graph.items.iter() {
+            let same = match old.get(item.path.as_slice()) {
+                Some(old) if old.hash == item.hash && !old.ids.empty() && !item.ids.empty() &&
+                             old.ids.max - old.ids.min == item.ids.max - item.ids.min => {
+                    ranges.push((old.ids, item.ids.min));
+                    true
+                }
+                _ => false,
+            };
+            mapped.push(same);
+        }
+        let ids = astencode::IdMap::new(ranges);
+        let tables = astencode::item_tables(data.as_slice());
+
+        for (node, item) in graph.items.iter().enumerate() {
+            let unchanged = old.get(item.path.as_slice()).map_or(false, |old| {
+                old.fingerprint == item.fingerprint
+            });
+            let doc = match tables.get(&item.path) {
+                Some(doc) if unchanged => *doc,
+                _ => continue,
+            };
+
+            // The tables only mention the nodes the item depends on, whose
+            // ids must all be translated.
+            let mut reachable = BTreeSet::new();
+            let mut stack = vec![node];
+            while let Some(n) = stack.pop() {
+                if reachable.insert(n) {
+                    stack.extend(graph.items[n].deps.iter()
+                                      .filter_map(|d| nodes.get(d.as_slice()))
+                                      .map(|d| *d));
+                }
+            }
+            if !reachable.iter().all(|&n| mapped[n]) {
+                continue;
+            }
+
+            astencode::decode_item_tables(tcx, doc, &ids);
+            reused.extend(range(item.ids.min, item.ids.max));
+            count += 1;
+        }
+    }
+
+    if tcx.sess.debugging_opt(config::INCREMENTAL_INFO) {
+        println!("incremental: reused the type-check results of {} of {} items",
+                 count, graph.items.len());
+    }
+    reused
+}
+
+/// The cache directory of one crate, the graph of this compilation and the
+/// decisions made about its codegen units.
+pub struct Cache {
+    dir: Path,
+    crate_name: String,
+    graph: DepGraph,
+    previous: Option<DepGraph>,
+    // The extensions of the files kept for each codegen unit, or `None` if
+    // no unit can be reused with the options of this compilation.
+    exts: Option<Vec<&'static str>>,
+    reused: Vec<bool>,
+    typeck_tables: Vec<u8>,
+}
+
+impl Cache {
+    /// Opens the cache in `dir` for a compilation whose graph is `graph`,
+    /// and decides which codegen units are copied from it: those that did
+    /// not change and whose files with the extensions `exts` are all there.
+    pub fn open(sess: &Session, dir: &Path, crate_name: &str, graph: DepGraph,
+                exts: Option<Vec<&'static str>>) -> Cache {
+        if let Err(e) = fs::mkdir_recursive(dir, io::USER_RWX) {
+            sess.fatal(format!("couldn't create incremental compilation directory `{}`: {}",
+                               dir.display(), e).as_slice());
+        }
+        let mut cache = Cache {
+            dir: dir.clone(),
+            crate_name: crate_name.to_string(),
+            graph: graph,
+            previous: read_graph(&graph_path(dir, crate_name)),
+            exts: exts,
+            reused: vec![],
+            typeck_tables: vec![],
+        };
+        cache.reused = range(0, cache.graph.units.len()).map(|unit| {
+            match (&cache.previous, &cache.exts) {
+                (&Some(ref previous), &Some(ref exts)) => {
+                    previous.units.get(unit) == Some(&cache.graph.units[unit]) &&
+                        exts.iter().all(|ext| cache.cached_file(unit, *ext).exists())
+                }
+                _ => false,
+            }
+        }).collect();
+        cache
+    }
+
+    pub fn graph(&self) -> &DepGraph {
+        &self.graph
+    }
+
+    /// Whether each codegen unit is copied from the cache instead of being
+    /// generated.
+    pub fn reused_units(&self) -> &[bool] {
+        self.reused.as_slice()
+    }
+
+    fn cached_file(&self, unit: uint, ext: &str) -> Path {
+        self.dir.join(format!("{}.{}.{}", self.crate_name, unit, ext))
+    }
+
+    /// If codegen unit `unit` is reused, the files to copy, from the cache to
+    /// `outputs`, instead of generating them.
+    pub fn reused_files(&self, unit: uint, outputs: &OutputFilenames)
+                        -> Option<Vec<(Path, Path)>> {
+        if !self.reused[unit] {
+            return None;
+        }
+        let exts = try_opt!(self.exts.as_ref());
+        Some(exts.iter().map(|ext| {
+            (self.cached_file(unit, *ext),
+             outputs.with_extension(format!("{}.{}", unit, ext).as_slice()))
+        }).collect())
+    }
+
+    /// Sets the type-check results to keep, as encoded by
+    /// `encoder::encode_item_tables`.
+    pub fn set_typeck_tables(&mut self, tables: Vec<u8>) {
+        self.typeck_tables = tables;
+    }
+
+    /// Copies the files of the units that were generated again into the
+    /// cache, and saves the type-check results and the graph for the next
+    /// compilation.
+    pub fn store(&self, sess: &Session, outputs: &OutputFilenames) {
+        let result = (|| -> IoResult<()> {
+            // The graph is what makes the rest of the directory trusted, so
+            // it is removed until everything else is up to date.
+            let graph_path = graph_path(&self.dir, self.crate_name.as_slice());
+            if graph_path.exists() {
+                try!(fs::unlink(&graph_path));
+            }
+            for (unit, &reused) in self.reused.iter().enumerate() {
+                if reused {
+                    continue;
+                }
+                for ext in ["o", "bc"].iter() {
+                    let cached = self.cached_file(unit, *ext);
+                    if self.exts.as_ref().map_or(false, |exts| exts.contains(ext)) {
+                        let output = outputs.with_extension(format!("{}.{}", unit, ext)
+                                                                .as_slice());
+                        try!(fs::copy(&output, &cached));
+                    } else if cached.exists() {
+                        // Left from an older compilation of the unit.
+                        try!(fs::unlink(&cached));
+                    }
+                }
+            }
+            let mut tables = try!(File::create(&typeck_path(&self.dir,
+                                                            self.crate_name.as_slice())));
+            try!(tables.write(self.typeck_tables.as_slice()));
+            let mut file = try!(File::create(&graph_path));
+            file.write_str(json::encode(&self.graph.to_json()).as_slice())
+        })();
+        if let Err(e) = result {
+            sess.warn(format!("couldn't update incremental compilation directory `{}`: {}",
+                              self.dir.display(), e).as_slice());
+        }
+    }
+
+    /// Prints what was reused, for `-Z incremental-info`.
+    pub fn report(&self) {
+        match self.previous {
+            Some(ref previous) => {
+                let changed = self.graph.changed_items(previous);
+                println!("incremental: {} of {} items changed", changed.len(),
+                         self.graph.items.len());
+                for item in changed.iter() {
+                    println!("incremental: changed `{}` in codegen unit {}",
+                             item.path, item.unit);
+                }
+            }
+            None => println!("incremental: no previous compilation"),
+        }
+        for (unit, &reused) in self.reused.iter().enumerate() {
+            println!("incremental: codegen unit {} {}", unit,
+                     if reused { "reused" } else { "translated" });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DepGraph, ItemNode};
+    use syntax::ast_util::IdRange;
+
+    fn graph() -> DepGraph {
+        DepGraph {
+            session: 0xfeed,
+            items: vec![
+                ItemNode {
+                    path: "a::f".to_string(),
+                    unit: 1,
+                    hash: 1,
+                    fingerprint: 0xffff_ffff_ffff_ffff,
+                    deps: vec!["b".to_string()],
+                    ids: IdRange { min: 10, max: 20 },
+                },
+                ItemNode {
+                    path: "b".to_string(),
+                    unit: 0,
+                    hash: 2,
+                    fingerprint: 3,
+                    deps: vec![],
+                    ids: IdRange { min: 0, max: 0 },
+                },
+            ],
+            units: vec![4, 5],
+        }
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let graph = graph();
+        let read = DepGraph::from_json(&graph.to_json()).unwrap();
+        assert_eq!(read.session, graph.session);
+        assert_eq!(read.units, graph.units);
+        assert_eq!(read.items.len(), 2);
+        assert_eq!(read.items[0].path, "a::f");
+        assert_eq!(read.items[0].unit, 1);
+        assert_eq!(read.items[0].fingerprint, 0xffff_ffff_ffff_ffff);
+        assert_eq!(read.items[0].deps, vec!["b".to_string()]);
+        assert_eq!(read.items[0].ids, IdRange { min: 10, max: 20 });
+        assert!(read.items[1].ids.empty());
+    }
+
+    #[test]
+    fn changed_items() {
+        let previous = graph();
+        let mut graph = graph();
+        assert!(graph.changed_items(&previous).is_empty());
+
+        graph.items[1].fingerprint = 7;
+        graph.items.push(ItemNode {
+            path: "c".to_string(),
+            unit: 0,
+            hash: 8,
+            fingerprint: 9,
+            deps: vec![],
+            ids: IdRange { min: 20, max: 21 },
+        });
+        let changed: Vec<&str> = graph.changed_items(&previous).iter()
+                                      .map(|item| item.path.as_slice())
+                                      .collect();
+        assert_eq!(changed, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn other_versions_are_ignored() {
+        let mut json = graph().to_json();
+        if let ::serialize::json::Json::Object(ref mut obj) = json {
+            obj.insert("format_version".to_string(), ::serialize::json::Json::U64(0));
+        }
+        assert!(DepGraph::from_json(&json).is_none());
+    }
+}
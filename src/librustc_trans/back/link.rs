@@ -12,6 +12,7 @@ use super::archive::{Archive, ArchiveBuilder, ArchiveConfig, METADATA_FILENAME};
 use super::archive;
 use super::rpath;
 use super::rpath::RPathConfig;
+use super::incremental;
 use super::svh::Svh;
 use session::config;
 use session::config::NoDebugInfo;
@@ -194,7 +195,11 @@ fn symbol_hash<'tcx>(tcx: &ty::ctxt<'tcx>,
     symbol_hasher.reset();
     symbol_hasher.input_str(link_meta.crate_name.index(&FullRange));
     symbol_hasher.input_str("-");
-    symbol_hasher.input_str(link_meta.crate_hash.as_str());
+    // The crate hash changes with every edit, which would keep incremental
+    // compilation from linking against objects it cached.
+    if tcx.sess.opts.incremental.is_none() {
+        symbol_hasher.input_str(link_meta.crate_hash.as_str());
+    }
     for meta in tcx.sess.crate_metadata.borrow().iter() {
         symbol_hasher.input_str(meta.index(&FullRange));
     }
@@ -319,7 +324,11 @@ pub fn mangle_exported_name<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>, path: PathEl
         "abcdefghijklmnopqrstuvwxyz\
          ABCDEFGHIJKLMNOPQRSTUVWXYZ\
          0123456789";
-    let id = id as uint;
+    let id = if ccx.sess().opts.incremental.is_some() {
+        incremental::stable_id(ccx.tcx(), id)
+    } else {
+        id as uint
+    };
     let extra1 = id % EXTRA_CHARS.len();
     let id = id / EXTRA_CHARS.len();
     let extra2 = id % EXTRA_CHARS.len();
@@ -774,6 +783,9 @@ fn link_natively(sess: &Session, trans: &CrateTranslation, dylib: bool,
     link_args(&mut cmd, sess, dylib, tmpdir.path(),
               trans, obj_filename, out_filename);
     cmd.args(sess.target.target.options.post_link_args.index(&FullRange));
+    if sess.opts.cg.profile_generate.is_some() {
+        cmd.arg("-lprofiler-rt");
+    }
     if !sess.target.target.options.no_compiler_rt {
         cmd.arg("-lcompiler-rt");
     }
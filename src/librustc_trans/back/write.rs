@@ -13,6 +13,7 @@ use back::link::{get_cc_prog, remove};
 use session::config::{OutputFilenames, NoDebugInfo, Passes, SomePasses, AllPasses};
 use session::Session;
 use session::config;
+use session::profile::Profiler;
 use llvm;
 use llvm::{ModuleRef, TargetMachineRef, PassManagerRef, DiagnosticInfoRef, ContextRef};
 use llvm::SMDiagnosticRef;
@@ -537,6 +538,37 @@ unsafe fn optimize_and_codegen(cgcx: &CodegenContext,
     llvm::LLVMRustDisposeTargetMachine(tm);
 }
 
+/// The extensions of the files `run_passes` generates for each codegen unit,
+/// which incremental compilation can copy from its cache instead, or `None`
+/// if units can't be reused with these options. Units are only reused if
+/// nothing but object files and bitcode is wanted from them, and not with
+/// `-C instrument-coverage`, whose counters are named after the crate's SVH,
+/// or with `-C profile-use`, whose profile isn't part of the fingerprint of
+/// a unit.
+pub fn reusable_unit_files(sess: &Session) -> Option<Vec<&'static str>> {
+    if sess.opts.cg.no_integrated_as || sess.opts.cg.save_temps ||
+       sess.opts.cg.instrument_coverage || sess.opts.cg.profile_use.is_some() {
+        return None;
+    }
+    let mut exts = vec![];
+    for output_type in sess.opts.output_types.iter() {
+        match *output_type {
+            config::OutputTypeAssembly | config::OutputTypeLlvmAssembly => return None,
+            config::OutputTypeObject | config::OutputTypeExe => exts.push("o"),
+            config::OutputTypeBitcode => exts.push("bc"),
+            config::OutputTypeDepInfo => {}
+        }
+    }
+    // Matches `needs_crate_bitcode` in `run_passes`.
+    if sess.crate_types.borrow().contains(&config::CrateTypeRlib) &&
+       sess.opts.output_types.contains(&config::OutputTypeExe) {
+        exts.push("bc");
+    }
+    exts.sort();
+    exts.dedup();
+    Some(exts)
+}
+
 pub fn run_passes(sess: &Session,
                   trans: &CrateTranslation,
                   output_types: &[config::OutputType],
@@ -570,6 +602,12 @@ pub fn run_passes(sess: &Session,
 
     modules_config.opt_level = Some(get_llvm_opt_level(sess.opts.optimize));
 
+    // Lower the counters of `-C profile-generate` once the module is
+    // otherwise optimized.
+    if sess.opts.cg.profile_generate.is_some() {
+        modules_config.passes.push("instrprof".to_string());
+    }
+
     // Save all versions of the bytecode if we're saving our temporaries.
     if sess.opts.cg.save_temps {
         modules_config.emit_no_opt_bc = true;
@@ -628,12 +666,17 @@ pub fn run_passes(sess: &Session,
         work_items.push(work);
     }
 
+    // When compiling incrementally, the files of codegen units that did not
+    // change are copied from the cache instead.
     for (index, mtrans) in trans.modules.iter().enumerate() {
-        let work = build_work_item(sess,
-                                   *mtrans,
-                                   modules_config.clone(),
-                                   crate_output.clone(),
-                                   format!("{}", index));
+        let mut work = build_work_item(sess,
+                                       *mtrans,
+                                       modules_config.clone(),
+                                       crate_output.clone(),
+                                       format!("{}", index));
+        work.cached = trans.incremental.as_ref().and_then(|cache| {
+            cache.reused_files(index, crate_output)
+        });
         work_items.push(work);
     }
 
@@ -649,6 +692,14 @@ pub fn run_passes(sess: &Session,
         llvm::LLVMRustDisposeTargetMachine(tm);
     }
 
+    if let Some(ref cache) = trans.incremental {
+        sess.abort_if_errors();
+        if sess.debugging_opt(config::INCREMENTAL_INFO) {
+            cache.report();
+        }
+        cache.store(sess, crate_output);
+    }
+
     // Produce final compile outputs.
 
     let copy_if_one_unit = |&: ext: &str, output_type: config::OutputType, keep_numbered: bool| {
@@ -847,7 +898,10 @@ struct WorkItem {
     mtrans: ModuleTranslation,
     config: ModuleConfig,
     output_names: OutputFilenames,
-    name_extra: String
+    name_extra: String,
+    /// Files to copy, from the incremental compilation cache to the outputs,
+    /// instead of running LLVM.
+    cached: Option<Vec<(Path, Path)>>,
 }
 
 fn build_work_item(sess: &Session,
@@ -860,14 +914,30 @@ fn build_work_item(sess: &Session,
     let mut config = config;
     config.tm = create_target_machine(sess);
     WorkItem { mtrans: mtrans, config: config, output_names: output_names,
-               name_extra: name_extra }
+               name_extra: name_extra, cached: None }
 }
 
 fn execute_work_item(cgcx: &CodegenContext,
                      work_item: WorkItem) {
-    unsafe {
-        optimize_and_codegen(cgcx, work_item.mtrans, work_item.config,
-                             work_item.name_extra, work_item.output_names);
+    match work_item.cached {
+        Some(ref files) => {
+            for &(ref from, ref to) in files.iter() {
+                if let Err(e) = fs::copy(from, to) {
+                    cgcx.handler.err(format!("couldn't copy `{}` from the incremental \
+                                              compilation cache: {}",
+                                             from.display(), e).as_slice());
+                }
+            }
+            unsafe {
+                llvm::LLVMDisposeModule(work_item.mtrans.llmod);
+                llvm::LLVMContextDispose(work_item.mtrans.llcx);
+                llvm::LLVMRustDisposeTargetMachine(work_item.config.tm);
+            }
+        }
+        None => unsafe {
+            optimize_and_codegen(cgcx, work_item.mtrans, work_item.config,
+                                 work_item.name_extra, work_item.output_names);
+        },
     }
 }
 
@@ -896,6 +966,7 @@ fn run_work_multithreaded(sess: &Session,
         let work_items_arc = work_items_arc.clone();
         let diag_emitter = diag_emitter.clone();
         let remark = sess.opts.cg.remark.clone();
+        let profiler = Profiler::current();
 
         let (tx, rx) = channel();
         let mut tx = Some(tx);
@@ -903,6 +974,10 @@ fn run_work_multithreaded(sess: &Session,
 
         thread::Builder::new().name(format!("codegen-{}", i)).spawn(move |:| {
             let diag_handler = mk_handler(box diag_emitter);
+            // Record the LLVM passes of this worker in the session's profile.
+            if let Some(profiler) = profiler {
+                profiler.install();
+            }
 
             // Must construct cgcx inside the proc because it has non-Send
             // fields.
@@ -8,10 +8,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-//! Output a CSV file containing the output from rustc's analysis. The data is
+//! Output a JSON file containing the output from rustc's analysis. The data is
 //! primarily designed to be used as input to the DXR tool, specifically its
 //! Rust plugin. It could also be used by IDEs or other code browsing, search, or
-//! cross-referencing tools.
+//! cross-referencing tools. The format is versioned and documented in
+//! `src/doc/save-analysis.md`; `-Z save-analysis-csv` writes the older CSV
+//! format instead.
 //!
 //! Dumping the analysis is implemented by walking the AST and getting a bunch of
 //! info out from all over the place. We use Def IDs to identify objects. The
@@ -23,11 +25,12 @@
 //!
 //! SpanUtils is used to manipulate spans. In particular, to extract sub-spans
 //! from spans (e.g., the span for `bar` from the above example path).
-//! Recorder is used for recording the output in csv format. FmtStrs separates
-//! the format of the output away from extracting it from the compiler.
+//! Recorder is used for recording the output in JSON or csv format. FmtStrs
+//! separates the format of the output away from extracting it from the compiler.
 //! DxrVisitor walks the AST and processes it.
 
 use session::Session;
+use session::config;
 
 use middle::def;
 use middle::ty::{self, Ty};
@@ -38,8 +41,8 @@ use std::os;
 
 use syntax::ast_util::{self, PostExpansionMethod};
 use syntax::ast::{self, NodeId, DefId};
-use syntax::ast_map::NodeItem;
-use syntax::attr;
+use syntax::ast_map::{self, NodeItem};
+use syntax::attr::{self, AttrMetaMethods, AttributeMethods};
 use syntax::codemap::*;
 use syntax::parse::token::{self, get_ident, keywords};
 use syntax::owned_slice::OwnedSlice;
@@ -48,12 +51,15 @@ use syntax::print::pprust::{path_to_string,ty_to_string};
 use syntax::ptr::P;
 
 use self::span_utils::SpanUtils;
-use self::recorder::{Recorder, FmtStrs};
+use self::recorder::{Recorder, FmtStrs, Format};
 
 use util::ppaux;
 
+use serialize::json::Json;
+
 mod span_utils;
 mod recorder;
+pub mod query;
 
 // Helper function to escape quotes in a string
 fn escape(s: String) -> String {
@@ -96,7 +102,7 @@ impl <'l, 'tcx> DxrVisitor<'l, 'tcx> {
         self.sess.cstore.iter_crate_data(|n, cmd| {
             self.fmt.external_crate_str(krate.span, cmd.name.index(&FullRange), n);
         });
-        self.fmt.recorder.record("end_external_crates\n");
+        self.fmt.end_external_crates();
     }
 
     // Return all non-empty prefixes of a path.
@@ -1502,6 +1508,83 @@ impl<'l, 'tcx, 'v> Visitor<'v> for DxrVisitor<'l, 'tcx> {
     }
 }
 
+fn crate_name(krate: &ast::Crate) -> String {
+    match attr::find_crate_name(krate.attrs.index(&FullRange)) {
+        Some(name) => name.get().to_string(),
+        None => {
+            info!("Could not find crate name, using 'unknown_crate'");
+            String::from_str("unknown_crate")
+        },
+    }
+}
+
+// The doc comments of the item `id`, joined by newlines.
+fn doc_comments(map: &ast_map::Map, id: NodeId) -> Option<String> {
+    if map.find(id).is_none() {
+        return None;
+    }
+    map.with_attrs(id, |attrs| {
+        let docs: Vec<String> = attrs.unwrap_or(&[]).iter().filter_map(|attr| {
+            attr.with_desugared_doc(|attr| {
+                if attr.check_name("doc") {
+                    attr.value_str().map(|s| s.get().to_string())
+                } else {
+                    None
+                }
+            })
+        }).collect();
+        if docs.is_empty() { None } else { Some(docs.connect("\n")) }
+    })
+}
+
+// Walk the crate, recording everything in `format` with `recorder`.
+fn index_crate<'l, 'tcx>(sess: &'l Session,
+                         krate: &ast::Crate,
+                         analysis: &'l ty::CrateAnalysis<'tcx>,
+                         cratename: &str,
+                         recorder: Box<Recorder>,
+                         format: Format) -> FmtStrs<'l> {
+    let mut visitor = DxrVisitor {
+        sess: sess,
+        analysis: analysis,
+        collected_paths: vec!(),
+        collecting: false,
+        fmt: FmtStrs::new(recorder,
+                          SpanUtils {
+                              sess: sess,
+                              err_count: Cell::new(0)
+                          },
+                          cratename.to_string(),
+                          format),
+        span: SpanUtils {
+            sess: sess,
+            err_count: Cell::new(0)
+        },
+        cur_scope: 0
+    };
+
+    visitor.dump_crate_info(cratename, krate);
+
+    visit::walk_crate(&mut visitor, krate);
+
+    visitor.fmt
+}
+
+/// The analysis of the crate as a JSON document, as `-Z save-analysis`
+/// would write it, without writing any files.
+pub fn crate_index(sess: &Session,
+                   krate: &ast::Crate,
+                   analysis: &ty::CrateAnalysis) -> Json {
+    assert!(analysis.glob_map.is_some());
+    let recorder = box Recorder {
+        out: box io::util::NullWriter as Box<Writer+'static>,
+        dump_spans: false,
+    };
+    let mut fmt = index_crate(sess, krate, analysis, crate_name(krate).as_slice(),
+                              recorder, Format::Json);
+    fmt.into_json(|id| doc_comments(&analysis.ty_cx.map, id)).unwrap()
+}
+
 pub fn process_crate(sess: &Session,
                      krate: &ast::Crate,
                      analysis: &ty::CrateAnalysis,
@@ -1511,13 +1594,7 @@ pub fn process_crate(sess: &Session,
     }
 
     assert!(analysis.glob_map.is_some());
-    let cratename = match attr::find_crate_name(krate.attrs.index(&FullRange)) {
-        Some(name) => name.get().to_string(),
-        None => {
-            info!("Could not find crate name, using 'unknown_crate'");
-            String::from_str("unknown_crate")
-        },
-    };
+    let cratename = crate_name(krate);
 
     info!("Dumping crate {}", cratename);
 
@@ -1541,9 +1618,18 @@ pub fn process_crate(sess: &Session,
         info!("Writing output to {}", disp);
     }
 
+    let format = if (sess.opts.debugging_opts & config::SAVE_ANALYSIS_CSV) != 0 {
+        Format::Csv
+    } else {
+        Format::Json
+    };
+
     // Create output file.
     let mut out_name = cratename.clone();
-    out_name.push_str(".csv");
+    out_name.push_str(match format {
+        Format::Json => ".json",
+        Format::Csv => ".csv",
+    });
     root_path.push(out_name);
     let output_file = match File::create(&root_path) {
         Ok(f) => box f,
@@ -1554,28 +1640,10 @@ pub fn process_crate(sess: &Session,
     };
     root_path.pop();
 
-    let mut visitor = DxrVisitor {
-        sess: sess,
-        analysis: analysis,
-        collected_paths: vec!(),
-        collecting: false,
-        fmt: FmtStrs::new(box Recorder {
-                            out: output_file as Box<Writer+'static>,
-                            dump_spans: false,
-                        },
-                        SpanUtils {
-                            sess: sess,
-                            err_count: Cell::new(0)
-                        },
-                        cratename.clone()),
-        span: SpanUtils {
-            sess: sess,
-            err_count: Cell::new(0)
-        },
-        cur_scope: 0
+    let recorder = box Recorder {
+        out: output_file as Box<Writer+'static>,
+        dump_spans: false,
     };
-
-    visitor.dump_crate_info(cratename.index(&FullRange), krate);
-
-    visit::walk_crate(&mut visitor, krate);
+    let mut fmt = index_crate(sess, krate, analysis, cratename.as_slice(), recorder, format);
+    fmt.finish(|id| doc_comments(&analysis.ty_cx.map, id));
 }
@@ -0,0 +1,267 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Point queries for editors, answered by `rustc --query FILE:LINE:COL`.
+//!
+//! The crate is indexed as for `-Z save-analysis`, but in memory. The name
+//! under the cursor is the innermost definition or reference of the index
+//! whose span contains the position. The answer is a JSON object on stdout:
+//!
+//! * `definition`: the `kind`, `qualname`, `span` and `docs` of the
+//!   definition the name refers to, or `null`. Definitions in other crates
+//!   only have a `qualname`.
+//! * `type`: the type of the innermost expression or pattern containing the
+//!   position, or `null`.
+//! * `references`: the spans of all references to the definition in the
+//!   crate.
+//!
+//! Spans have the same shape as in the save-analysis format.
+
+use session::Session;
+use middle::ty;
+use util::ppaux;
+
+use std::collections::BTreeMap;
+
+use serialize::json::{self, Json};
+
+use syntax::ast;
+use syntax::codemap::Span;
+use syntax::visit::{self, Visitor};
+
+use super::{crate_index, generated_code};
+
+/// A position in a source file. Lines and columns are 1-based, columns are
+/// counted in characters.
+#[derive(Clone, PartialEq, Show)]
+pub struct Query {
+    pub file: String,
+    pub line: uint,
+    pub col: uint,
+}
+
+impl Query {
+    /// Parses `FILE:LINE:COL`.
+    pub fn parse(s: &str) -> Option<Query> {
+        let mut parts = s.rsplitn(2, ':');
+        let col = parts.next().and_then(|c| c.parse::<uint>());
+        let line = parts.next().and_then(|l| l.parse::<uint>());
+        match (parts.next(), line, col) {
+            (Some(file), Some(line), Some(col)) if !file.is_empty() && line > 0 && col > 0 => {
+                Some(Query { file: file.to_string(), line: line, col: col })
+            }
+            _ => None,
+        }
+    }
+
+    // Whether the position is within the given range of (line, column)
+    // pairs, both inclusive so that a cursor just after a name still finds
+    // it.
+    fn within(&self, file: &str, start: (uint, uint), end: (uint, uint)) -> bool {
+        let pos = (self.line, self.col);
+        file == self.file.as_slice() && start <= pos && pos <= end
+    }
+
+    // Whether a span of the save-analysis index contains the position.
+    fn within_json(&self, span: &Json) -> bool {
+        let field = |&: name: &str| span.find(name).and_then(|v| v.as_u64()).unwrap_or(0) as uint;
+        match span.find("file_name").and_then(|f| f.as_string()) {
+            Some(file) => self.within(file,
+                                      (field("line_start"), field("column_start")),
+                                      (field("line_end"), field("column_end"))),
+            None => false,
+        }
+    }
+}
+
+// The length in bytes of a span of the save-analysis index, to pick the
+// innermost of several.
+fn json_span_len(span: &Json) -> u64 {
+    let field = |&: name: &str| span.find(name).and_then(|v| v.as_u64()).unwrap_or(0);
+    field("byte_end") - field("byte_start")
+}
+
+// Finds the innermost expression or pattern containing the query position.
+struct TypeFinder<'a, 'tcx: 'a> {
+    tcx: &'a ty::ctxt<'tcx>,
+    query: &'a Query,
+    best: Option<(ast::NodeId, Span)>,
+}
+
+impl<'a, 'tcx> TypeFinder<'a, 'tcx> {
+    fn consider(&mut self, id: ast::NodeId, span: Span) {
+        if generated_code(span) {
+            return;
+        }
+        let cm = self.tcx.sess.codemap();
+        let lo = cm.lookup_char_pos(span.lo);
+        let hi = cm.lookup_char_pos(span.hi);
+        if !self.query.within(lo.file.name.as_slice(),
+                              (lo.line, lo.col.to_uint() + 1),
+                              (hi.line, hi.col.to_uint() + 1)) {
+            return;
+        }
+        if !self.tcx.node_types.borrow().contains_key(&id) {
+            return;
+        }
+        let inner = match self.best {
+            Some((_, best)) => span.hi - span.lo <= best.hi - best.lo,
+            None => true,
+        };
+        if inner {
+            self.best = Some((id, span));
+        }
+    }
+}
+
+impl<'a, 'tcx, 'v> Visitor<'v> for TypeFinder<'a, 'tcx> {
+    fn visit_expr(&mut self, e: &ast::Expr) {
+        self.consider(e.id, e.span);
+        visit::walk_expr(self, e);
+    }
+
+    fn visit_pat(&mut self, p: &ast::Pat) {
+        self.consider(p.id, p.span);
+        visit::walk_pat(self, p);
+    }
+}
+
+fn same_def_id(a: Option<&Json>, b: &Json) -> bool {
+    match a {
+        Some(a) => *a == *b,
+        None => false,
+    }
+}
+
+fn def_id(krate: u64, index: u64) -> Json {
+    let mut obj = BTreeMap::new();
+    obj.insert("krate".to_string(), Json::U64(krate));
+    obj.insert("index".to_string(), Json::U64(index));
+    Json::Object(obj)
+}
+
+/// Answers `query` about the crate, see the module documentation.
+pub fn run(sess: &Session,
+           krate: &ast::Crate,
+           analysis: &ty::CrateAnalysis,
+           query: &Query) -> Json {
+    let tcx = &analysis.ty_cx;
+    let index = crate_index(sess, krate, analysis);
+    let empty = vec![];
+    let section = |&: name: &str| index.find(name).and_then(|s| s.as_array()).unwrap_or(&empty);
+    let defs = section("defs");
+    let refs = section("refs");
+
+    // The definition or reference under the cursor, as the def id of what
+    // it names.
+    let mut target: Option<(Json, u64)> = None;
+    for def in defs.iter() {
+        let span = match def.find("span") {
+            Some(span) if query.within_json(span) => span,
+            _ => continue,
+        };
+        if let Some(id) = def.find("id").and_then(|id| id.as_u64()) {
+            let len = json_span_len(span);
+            if target.as_ref().map_or(true, |&(_, best)| len <= best) {
+                target = Some((def_id(0, id), len));
+            }
+        }
+    }
+    for r in refs.iter() {
+        let span = match r.find("span") {
+            Some(span) if query.within_json(span) => span,
+            _ => continue,
+        };
+        match r.find("ref_id") {
+            Some(&Json::Null) | None => {}
+            Some(ref_id) => {
+                let len = json_span_len(span);
+                if target.as_ref().map_or(true, |&(_, best)| len <= best) {
+                    target = Some((ref_id.clone(), len));
+                }
+            }
+        }
+    }
+
+    let mut definition = Json::Null;
+    let mut references = vec![];
+    if let Some((target, _)) = target {
+        let krate = target.find("krate").and_then(|k| k.as_u64()).unwrap();
+        let node = target.find("index").and_then(|i| i.as_u64()).unwrap();
+
+        let mut obj = BTreeMap::new();
+        if krate == 0 {
+            let def = defs.iter().find(|d| {
+                d.find("id").and_then(|id| id.as_u64()) == Some(node)
+            });
+            if let Some(def) = def {
+                for key in ["kind", "qualname", "span", "docs"].iter() {
+                    if let Some(v) = def.find(*key) {
+                        obj.insert(key.to_string(), v.clone());
+                    }
+                }
+            }
+        } else {
+            let did = ast::DefId { krate: krate as ast::CrateNum, node: node as ast::NodeId };
+            obj.insert("qualname".to_string(), Json::String(ty::item_path_str(tcx, did)));
+        }
+        if !obj.is_empty() {
+            definition = Json::Object(obj);
+        }
+
+        for r in refs.iter() {
+            if same_def_id(r.find("ref_id"), &target) {
+                if let Some(span) = r.find("span") {
+                    references.push(span.clone());
+                }
+            }
+        }
+    }
+
+    let mut finder = TypeFinder { tcx: tcx, query: query, best: None };
+    visit::walk_crate(&mut finder, krate);
+    let typ = match finder.best {
+        Some((id, _)) => {
+            Json::String(ppaux::ty_to_string(tcx, ty::node_id_to_type(tcx, id)))
+        }
+        None => Json::Null,
+    };
+
+    let mut answer = BTreeMap::new();
+    answer.insert("definition".to_string(), definition);
+    answer.insert("type".to_string(), typ);
+    answer.insert("references".to_string(), Json::Array(references));
+    Json::Object(answer)
+}
+
+/// Runs `query` and prints the answer on stdout.
+pub fn print_answer(sess: &Session,
+                    krate: &ast::Crate,
+                    analysis: &ty::CrateAnalysis,
+                    query: &Query) {
+    println!("{}", json::encode(&run(sess, krate, analysis, query)));
+}
+
+#[cfg(test)]
+mod test {
+    use super::Query;
+
+    #[test]
+    fn parse() {
+        assert_eq!(Query::parse("src/lib.rs:12:5"),
+                   Some(Query { file: "src/lib.rs".to_string(), line: 12, col: 5 }));
+        assert_eq!(Query::parse("C:\\src\\lib.rs:1:1").map(|q| q.file),
+                   Some("C:\\src\\lib.rs".to_string()));
+        assert_eq!(Query::parse("lib.rs:12"), None);
+        assert_eq!(Query::parse("lib.rs:0:1"), None);
+        assert_eq!(Query::parse(":1:1"), None);
+        assert_eq!(Query::parse("lib.rs:a:1"), None);
+    }
+}
@@ -13,12 +13,29 @@ pub use self::Row::*;
 use super::escape;
 use super::span_utils::SpanUtils;
 
+use std::collections::BTreeMap;
+use std::collections::btree_map::Entry::{Occupied, Vacant};
 use std::vec::Vec;
 
+use serialize::json::{self, Json};
+
 use syntax::ast;
 use syntax::ast::{NodeId,DefId};
 use syntax::codemap::*;
 
+/// Version of the JSON format, bumped whenever a change to it could break
+/// a consumer. See `src/doc/save-analysis.md` for the schema.
+pub const JSON_FORMAT_VERSION: u64 = 1;
+
+/// The format the analysis is written in.
+#[derive(Copy, PartialEq)]
+pub enum Format {
+    /// One JSON document for the whole crate, the default.
+    Json,
+    /// The original CSV rows, kept for existing consumers.
+    Csv,
+}
+
 pub struct Recorder {
     // output file
     pub out: Box<Writer+'static>,
@@ -49,6 +66,16 @@ pub struct FmtStrs<'a> {
     pub recorder: Box<Recorder>,
     span: SpanUtils<'a>,
     krate: String,
+    /// The JSON document being built, `None` when writing CSV.
+    json: Option<JsonIndex>,
+}
+
+/// The records of the JSON format, collected while walking the crate and
+/// written out by `FmtStrs::finish`.
+struct JsonIndex {
+    krate: Option<Json>,
+    /// Records by section, e.g. "defs" or "refs".
+    sections: BTreeMap<String, Vec<Json>>,
 }
 
 macro_rules! s { ($e:expr) => { format!("{}", $e) }}
@@ -90,11 +117,36 @@ pub enum Row {
 }
 
 impl<'a> FmtStrs<'a> {
-    pub fn new(rec: Box<Recorder>, span: SpanUtils<'a>, krate: String) -> FmtStrs<'a> {
+    pub fn new(rec: Box<Recorder>,
+               span: SpanUtils<'a>,
+               krate: String,
+               format: Format) -> FmtStrs<'a> {
+        let json = match format {
+            Format::Json => Some(JsonIndex {
+                krate: None,
+                sections: BTreeMap::new(),
+            }),
+            Format::Csv => None,
+        };
         FmtStrs {
             recorder: rec,
             span: span,
             krate: krate,
+            json: json,
+        }
+    }
+
+    // The section of the JSON document rows of each kind go to.
+    fn json_section(r: Row) -> &'static str {
+        match r {
+            Variable | Enum | Variant | VariantStruct | Function | MethodDecl |
+            Struct | Trait | Module | Typedef => "defs",
+            Impl => "impls",
+            UseAlias | UseGlob | ExternCrate => "imports",
+            Inheritance => "relations",
+            ExternalCrate => "external_crates",
+            Crate => "crate",
+            MethodCall | FnCall | ModRef | VarRef | TypeRef | StructRef | FnRef => "refs",
         }
     }
 
@@ -152,16 +204,24 @@ impl<'a> FmtStrs<'a> {
         }
     }
 
-    pub fn make_values_str(&self,
-                           kind: &'static str,
-                           fields: &Vec<&'static str>,
-                           values: Vec<String>,
-                           span: Span) -> Option<String> {
+    fn check_values(&self,
+                    kind: &'static str,
+                    fields: &Vec<&'static str>,
+                    values: &Vec<String>,
+                    span: Span) {
         if values.len() != fields.len() {
             self.span.sess.span_bug(span, format!(
                 "Mismatch between length of fields for '{}', expected '{}', found '{}'",
                 kind, fields.len(), values.len()).index(&FullRange));
         }
+    }
+
+    pub fn make_values_str(&self,
+                           kind: &'static str,
+                           fields: &Vec<&'static str>,
+                           values: Vec<String>,
+                           span: Span) -> Option<String> {
+        self.check_values(kind, fields, &values, span);
 
         let values = values.iter().map(|s| {
             // Never take more than 1020 chars
@@ -206,6 +266,13 @@ impl<'a> FmtStrs<'a> {
             return;
         }
 
+        if self.json.is_some() {
+            self.check_values(label, fields, &values, span);
+            let record = self.json_record(label, fields, values, None);
+            self.push_json(kind, record);
+            return;
+        }
+
         let values_str = match self.make_values_str(label, fields, values, span) {
             Some(vs) => vs,
             None => return,
@@ -240,6 +307,13 @@ impl<'a> FmtStrs<'a> {
                                              which does not require a span", label).as_slice());
         }
 
+        if self.json.is_some() {
+            self.check_values(label, fields, &values, span);
+            let record = self.json_record(label, fields, values, Some((span, sub_span)));
+            self.push_json(kind, record);
+            return;
+        }
+
         let values_str = match self.make_values_str(label, fields, values, span) {
             Some(vs) => vs,
             None => return,
@@ -617,4 +691,169 @@ impl<'a> FmtStrs<'a> {
                               sub_span,
                               svec!(id.node, id.krate, "", scope_id));
     }
+
+    /// Marks the end of the external crates, which the CSV format needs.
+    pub fn end_external_crates(&mut self) {
+        if self.json.is_none() {
+            self.recorder.record("end_external_crates\n");
+        }
+    }
+
+    fn json_span(&self, span: Span) -> Json {
+        let cm = self.span.sess.codemap();
+        let lo = cm.lookup_char_pos(span.lo);
+        let hi = cm.lookup_char_pos(span.hi);
+        let mut obj = BTreeMap::new();
+        obj.insert("file_name".to_string(), Json::String(lo.file.name.clone()));
+        obj.insert("byte_start".to_string(),
+                   Json::U64(cm.lookup_byte_offset(span.lo).pos.to_uint() as u64));
+        obj.insert("byte_end".to_string(),
+                   Json::U64(cm.lookup_byte_offset(span.hi).pos.to_uint() as u64));
+        obj.insert("line_start".to_string(), Json::U64(lo.line as u64));
+        obj.insert("line_end".to_string(), Json::U64(hi.line as u64));
+        obj.insert("column_start".to_string(), Json::U64(lo.col.to_uint() as u64 + 1));
+        obj.insert("column_end".to_string(), Json::U64(hi.col.to_uint() as u64 + 1));
+        Json::Object(obj)
+    }
+
+    // Turns the fields of a row into a JSON object. Ids become numbers, a
+    // def id and its crate become one `{"krate", "index"}` object, and the
+    // span of the name and the extent of the whole item are added when known.
+    fn json_record(&self,
+                   kind: &'static str,
+                   fields: &Vec<&'static str>,
+                   values: Vec<String>,
+                   spans: Option<(Span, Span)>) -> Json {
+        let mut obj = BTreeMap::new();
+        obj.insert("kind".to_string(), Json::String(kind.to_string()));
+        if let Some((span, sub_span)) = spans {
+            obj.insert("span".to_string(), self.json_span(sub_span));
+            obj.insert("extent".to_string(), self.json_span(span));
+        }
+
+        let value_of = |&: name: &str| -> Option<&str> {
+            fields.iter().position(|f| *f == name).map(|i| values[i].as_slice())
+        };
+        let number = |&: v: &str| -> Json {
+            match v.parse::<u64>() {
+                Some(n) => Json::U64(n),
+                None => Json::Null,
+            }
+        };
+
+        for (field, value) in fields.iter().zip(values.iter()) {
+            let value = value.as_slice();
+            let (key, json) = match *field {
+                "id" | "ctor_id" => (*field, number(value)),
+                "scopeid" => ("scope", number(value)),
+                "crate" => ("crate_num", number(value)),
+                "refid" | "declid" | "base" | "derived" => {
+                    let krate = value_of(format!("{}crate", field).as_slice()).unwrap_or("");
+                    let def_id = match (value.parse::<u64>(), krate.parse::<u64>()) {
+                        (Some(index), Some(krate)) => {
+                            let mut def_id = BTreeMap::new();
+                            def_id.insert("krate".to_string(), Json::U64(krate));
+                            def_id.insert("index".to_string(), Json::U64(index));
+                            Json::Object(def_id)
+                        }
+                        _ => Json::Null,
+                    };
+                    let key = match *field {
+                        "refid" => "ref_id",
+                        "declid" => "decl_id",
+                        f => f,
+                    };
+                    (key, def_id)
+                }
+                "refidcrate" | "declidcrate" | "basecrate" | "derivedcrate" => continue,
+                "qualname" if value.len() > 0 => {
+                    ("qualname", Json::String(format!("{}::{}", self.krate, value)))
+                }
+                f => (f, Json::String(value.to_string())),
+            };
+            obj.insert(key.to_string(), json);
+        }
+        Json::Object(obj)
+    }
+
+    fn push_json(&mut self, kind: Row, record: Json) {
+        let index = self.json.as_mut().unwrap();
+        match FmtStrs::json_section(kind) {
+            "crate" => index.krate = Some(record),
+            section => {
+                let records = match index.sections.entry(section.to_string()) {
+                    Vacant(entry) => entry.insert(Vec::new()),
+                    Occupied(entry) => entry.into_mut(),
+                };
+                records.push(record);
+            }
+        }
+    }
+
+    /// Writes out the JSON document, if that is the format in use. See
+    /// `into_json`.
+    pub fn finish<F>(&mut self, docs: F) where
+        F: Fn(NodeId) -> Option<String>,
+    {
+        if let Some(doc) = self.into_json(docs) {
+            self.recorder.record(json::encode(&doc).as_slice());
+        }
+    }
+
+    /// Builds the JSON document, if that is the format in use, adding the
+    /// doc comments `docs` finds for definitions and the macro expansions
+    /// of the crate.
+    pub fn into_json<F>(&mut self, docs: F) -> Option<Json> where
+        F: Fn(NodeId) -> Option<String>,
+    {
+        let mut index = match self.json.take() {
+            Some(index) => index,
+            None => return None,
+        };
+
+        if let Some(defs) = index.sections.get_mut("defs") {
+            for def in defs.iter_mut() {
+                if let Json::Object(ref mut obj) = *def {
+                    let doc = match obj.get("id").and_then(|id| id.as_u64()) {
+                        Some(id) => docs(id as NodeId),
+                        None => None,
+                    };
+                    if let Some(doc) = doc {
+                        obj.insert("docs".to_string(), Json::String(doc));
+                    }
+                }
+            }
+        }
+
+        let mut expansions = Vec::new();
+        self.span.sess.codemap().each_expansion(|id, info| {
+            let mut obj = BTreeMap::new();
+            obj.insert("id".to_string(), Json::U64(id.to_index().unwrap() as u64));
+            obj.insert("name".to_string(), Json::String(info.callee.name.clone()));
+            obj.insert("format".to_string(), Json::String(match info.callee.format {
+                MacroBang => "bang",
+                MacroAttribute => "attribute",
+            }.to_string()));
+            obj.insert("call_site".to_string(), self.json_span(info.call_site));
+            obj.insert("parent".to_string(), match info.call_site.expn_id.to_index() {
+                Some(i) => Json::U64(i as u64),
+                None => Json::Null,
+            });
+            obj.insert("callee_span".to_string(), match info.callee.span {
+                Some(sp) => self.json_span(sp),
+                None => Json::Null,
+            });
+            expansions.push(Json::Object(obj));
+        });
+
+        let mut doc = BTreeMap::new();
+        doc.insert("format_version".to_string(), Json::U64(JSON_FORMAT_VERSION));
+        doc.insert("crate".to_string(), index.krate.unwrap_or(Json::Null));
+        for section in ["external_crates", "defs", "refs", "imports", "impls", "relations"].iter() {
+            let records = index.sections.remove(*section).unwrap_or(Vec::new());
+            doc.insert(section.to_string(), Json::Array(records));
+        }
+        doc.insert("macro_expansions".to_string(), Json::Array(expansions));
+        Some(Json::Object(doc))
+    }
 }
@@ -207,6 +207,7 @@ use trans::callee;
 use trans::cleanup::{self, CleanupMethods};
 use trans::common::*;
 use trans::consts;
+use trans::coverage;
 use trans::datum::*;
 use trans::expr::{self, Dest};
 use trans::tvec;
@@ -1420,6 +1421,7 @@ fn trans_match_inner<'blk, 'tcx>(scope_cx: Block<'blk, 'tcx>,
     let mut arm_cxs = Vec::new();
     for arm_data in arm_datas.iter() {
         let mut bcx = arm_data.bodycx;
+        coverage::count(bcx, arm_data.arm.body.span, coverage::RegionKind::Branch);
 
         // insert bindings into the lllocals map and add cleanups
         let cs = fcx.push_custom_cleanup_scope();
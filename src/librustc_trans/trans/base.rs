@@ -32,7 +32,7 @@ use super::CrateTranslation;
 use super::ModuleTranslation;
 
 use back::link::{mangle_exported_name};
-use back::{link, abi};
+use back::{link, abi, incremental, write};
 use lint;
 use llvm::{BasicBlockRef, Linkage, ValueRef, Vector, get_param};
 use llvm;
@@ -45,6 +45,7 @@ use middle::weak_lang_items;
 use middle::subst::{Subst, Substs};
 use middle::ty::{self, Ty, UnboxedClosureTyper};
 use session::config::{self, NoDebugInfo, FullDebugInfo};
+use session::profile;
 use session::Session;
 use trans::_match;
 use trans::adt;
@@ -55,7 +56,7 @@ use trans::cleanup::CleanupMethods;
 use trans::cleanup;
 use trans::closure;
 use trans::common::{Block, C_bool, C_bytes_in_context, C_i32, C_integral};
-use trans::common::{C_null, C_struct_in_context, C_u64, C_u8, C_undef};
+use trans::common::{C_array, C_null, C_struct, C_struct_in_context, C_u64, C_u8, C_undef};
 use trans::common::{CrateContext, ExternMap, FunctionContext};
 use trans::common::{NodeInfo, Result};
 use trans::common::{node_id_type, return_type_is_void};
@@ -65,6 +66,7 @@ use trans::common;
 use trans::consts;
 use trans::context::SharedCrateContext;
 use trans::controlflow;
+use trans::coverage;
 use trans::datum;
 use trans::debuginfo;
 use trans::expr;
@@ -76,12 +78,13 @@ use trans::machine;
 use trans::machine::{llsize_of, llsize_of_real};
 use trans::meth;
 use trans::monomorphize;
+use trans::pgo;
 use trans::tvec;
 use trans::type_::Type;
 use trans::type_of;
 use trans::type_of::*;
 use trans::value::Value;
-use util::common::indenter;
+use util::common::{indenter, time};
 use util::ppaux::{Repr, ty_to_string};
 use util::sha2::Sha256;
 use util::nodemap::NodeMap;
@@ -1465,7 +1468,8 @@ pub fn new_fn_ctxt<'a, 'tcx>(ccx: &'a CrateContext<'a, 'tcx>,
           ccx: ccx,
           debug_context: debug_context,
           scopes: RefCell::new(Vec::new()),
-          cfg: cfg
+          cfg: cfg,
+          pgo_counters: RefCell::new(pgo::FunctionCounters::new()),
     };
 
     if has_env {
@@ -1717,6 +1721,7 @@ pub fn finish_fn<'blk, 'tcx>(fcx: &'blk FunctionContext<'blk, 'tcx>,
     build_return_block(fcx, ret_cx, substd_retty);
 
     debuginfo::clear_source_location(fcx);
+    pgo::finish_fn(fcx);
     fcx.cleanup();
 }
 
@@ -1819,6 +1824,7 @@ pub fn trans_closure<'a, 'b, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
                           Some(body.span),
                           &arena);
     let mut bcx = init_function(&fcx, false, output_type);
+    pgo::count(bcx);
 
     // cleanup scope for the incoming arguments
     let fn_cleanup_debug_loc =
@@ -1930,6 +1936,49 @@ pub fn trans_closure<'a, 'b, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
     finish_fn(&fcx, bcx, output_type);
 }
 
+/// Emits an internal function `name` that calls the C function `callee`
+/// with `arg`, an `i8*`, and runs it before `main`. Constructors run in
+/// order of `priority`, lowest first; 65535 is the default of C compilers.
+pub fn add_global_ctor(ccx: &CrateContext,
+                       name: &str,
+                       callee: &str,
+                       arg: ValueRef,
+                       priority: i32) {
+    let i8p = Type::i8p(ccx);
+    let void = Type::void(ccx);
+    let llcallee = decl_cdecl_fn(ccx, callee, Type::func(&[i8p], &void), ty::mk_nil(ccx.tcx()));
+    let llfn = decl_cdecl_fn(ccx, name, Type::func(&[], &void), ty::mk_nil(ccx.tcx()));
+    llvm::SetLinkage(llfn, llvm::InternalLinkage);
+
+    let ctors_name = "llvm.global_ctors\0".as_ptr() as *const _;
+    unsafe {
+        let llbb = llvm::LLVMAppendBasicBlockInContext(ccx.llcx(), llfn,
+                                                       "top\0".as_ptr() as *const _);
+        let bld = ccx.raw_builder();
+        llvm::LLVMPositionBuilderAtEnd(bld, llbb);
+        let args = [arg];
+        llvm::LLVMBuildCall(bld, llcallee, args.as_ptr(), args.len() as c_uint, noname());
+        llvm::LLVMBuildRetVoid(bld);
+
+        // A module has a single list of constructors, so it is rebuilt with
+        // the new one appended.
+        let mut entries = Vec::new();
+        let old = llvm::LLVMGetNamedGlobal(ccx.llmod(), ctors_name);
+        if !old.is_null() {
+            let init = llvm::LLVMGetInitializer(old);
+            for i in range(0, llvm::LLVMGetNumOperands(init)) {
+                entries.push(llvm::LLVMGetOperand(init, i as c_uint));
+            }
+            llvm::LLVMDeleteGlobal(old);
+        }
+        entries.push(C_struct(ccx, &[C_i32(ccx, priority), llfn], false));
+        let ctors = C_array(val_ty(entries[0]), entries.as_slice());
+        let llctors = llvm::LLVMAddGlobal(ccx.llmod(), val_ty(ctors).to_ref(), ctors_name);
+        llvm::LLVMSetInitializer(llctors, ctors);
+        llvm::SetLinkage(llctors, llvm::AppendingLinkage);
+    }
+}
+
 // trans_fn: creates an LLVM function corresponding to a source language
 // function.
 pub fn trans_fn<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
@@ -2265,6 +2314,11 @@ pub fn update_linkage(ccx: &CrateContext,
         Some(id) if ccx.reachable().contains(&id) => {
             llvm::SetLinkage(llval, llvm::ExternalLinkage);
         },
+        None if ccx.sess().opts.incremental.is_some() => {
+            // Monomorphizations and glue are private to each codegen unit
+            // when compiling incrementally, see `back::incremental`.
+            llvm::SetLinkage(llval, llvm::InternalLinkage);
+        },
         _ => {
             // `id` does not refer to an item in `ccx.reachable`.
             if ccx.sess().opts.cg.codegen_units > 1 {
@@ -2291,6 +2345,12 @@ pub fn trans_item(ccx: &CrateContext, item: &ast::Item) {
             // translated everywhere it's needed.
             for (ref ccx, is_origin) in ccx.maybe_iter(!from_external && trans_everywhere) {
                 let llfn = get_item_val(ccx, item.id);
+                if ccx.is_reused() {
+                    // The declaration registers the symbol, which is all a
+                    // unit copied from the incremental compilation cache
+                    // needs.
+                    continue;
+                }
                 if abi != Rust {
                     foreign::trans_rust_fn_with_foreign_abi(ccx,
                                                             &**decl,
@@ -2329,7 +2389,14 @@ pub fn trans_item(ccx: &CrateContext, item: &ast::Item) {
                          item.id);
       }
       ast::ItemMod(ref m) => {
-        trans_mod(&ccx.rotate(), m);
+        if ccx.sess().opts.incremental.is_none() {
+            trans_mod(&ccx.rotate(), m);
+        } else if incremental::in_module(ccx.tcx(), item.id) {
+            // Keep modules in the same codegen unit between compilations.
+            trans_mod(&ccx.shared().get_ccx(incremental::unit_of_module(ccx.tcx(), item.id)), m);
+        } else {
+            trans_mod(ccx, m);
+        }
       }
       ast::ItemEnum(ref enum_definition, ref gens) => {
         if gens.ty_params.is_empty() {
@@ -3100,7 +3167,7 @@ fn internalize_symbols(cx: &SharedCrateContext, reachable: &HashSet<String>) {
 
 pub fn trans_crate<'tcx>(analysis: ty::CrateAnalysis<'tcx>)
                          -> (ty::ctxt<'tcx>, CrateTranslation) {
-    let ty::CrateAnalysis { ty_cx: tcx, export_map, reachable, name, .. } = analysis;
+    let ty::CrateAnalysis { ty_cx: tcx, export_map, reachable, name, trait_map, .. } = analysis;
     let krate = tcx.map.krate();
 
     // Before we touch LLVM, make sure that multithreading is enabled.
@@ -3123,6 +3190,20 @@ pub fn trans_crate<'tcx>(analysis: ty::CrateAnalysis<'tcx>)
 
     let link_meta = link::build_link_meta(&tcx.sess, krate, name);
 
+    let mut incremental = tcx.sess.opts.incremental.as_ref().map(|dir| {
+        let crate_name = link_meta.crate_name.as_slice();
+        let graph = time(tcx.sess.time_passes(), "building dependency graph", (), |_| {
+            let trait_map = trait_map.as_ref().expect("no trait map to build the graph from");
+            incremental::DepGraph::build(&tcx, krate, crate_name, trait_map)
+        });
+        incremental::Cache::open(&tcx.sess, dir, crate_name, graph,
+                                 write::reusable_unit_files(&tcx.sess))
+    });
+    let reused_units = match incremental {
+        Some(ref cache) => cache.reused_units().to_vec(),
+        None => vec![],
+    };
+
     let codegen_units = tcx.sess.opts.cg.codegen_units;
     let shared_ccx = SharedCrateContext::new(link_meta.crate_name.index(&FullRange),
                                              codegen_units,
@@ -3130,7 +3211,8 @@ pub fn trans_crate<'tcx>(analysis: ty::CrateAnalysis<'tcx>)
                                              export_map,
                                              Sha256::new(),
                                              link_meta.clone(),
-                                             reachable);
+                                             reachable,
+                                             reused_units);
 
     {
         let ccx = shared_ccx.get_ccx(0);
@@ -3145,16 +3227,50 @@ pub fn trans_crate<'tcx>(analysis: ty::CrateAnalysis<'tcx>)
         }
     }
 
-    for ccx in shared_ccx.iter() {
+    let mut coverage_units = Vec::new();
+    for (unit, ccx) in shared_ccx.iter().enumerate() {
         glue::emit_tydescs(&ccx);
         if ccx.sess().opts.debuginfo != NoDebugInfo {
             debuginfo::finalize(&ccx);
         }
+        coverage_units.extend(coverage::finalize(&ccx, unit).into_iter());
+        if unit == 0 {
+            pgo::finalize(&ccx);
+        }
     }
+    if let Some(ref profile) = *shared_ccx.profile_data() {
+        if profile.mismatched() > 0 {
+            let path = shared_ccx.sess().opts.cg.profile_use.as_ref().unwrap();
+            shared_ccx.sess().warn(format!("{} functions have no profile in `{}`, \
+                                            it may be out of date",
+                                           profile.mismatched(), path).as_slice());
+        }
+    }
+    let coverage_map = if shared_ccx.sess().opts.cg.instrument_coverage {
+        Some(coverage::coverage_map(link_meta.crate_name.as_slice(), coverage_units))
+    } else {
+        None
+    };
 
     // Translate the metadata.
     let metadata = write_metadata(&shared_ccx, krate);
 
+    // Keep the type-check results of every item for the next compilation.
+    if let Some(ref mut cache) = incremental {
+        let items: Vec<_> = cache.graph().items.iter()
+            .filter(|item| !item.ids.empty())
+            .map(|item| (item.path.clone(), item.ids))
+            .collect();
+        let tables = time(shared_ccx.sess().time_passes(), "encoding type-check results", (),
+                          |_| {
+            let encode_inlined_item: encoder::EncodeInlinedItem =
+                box |ecx, rbml_w, ii| astencode::encode_inlined_item(ecx, rbml_w, ii);
+            let parms = crate_ctxt_to_encode_parms(&shared_ccx, encode_inlined_item);
+            encoder::encode_item_tables(parms, items.as_slice())
+        });
+        cache.set_typeck_tables(tables);
+    }
+
     if shared_ccx.sess().trans_stats() {
         let stats = shared_ccx.stats();
         println!("--- trans stats ---");
@@ -3179,6 +3295,16 @@ pub fn trans_crate<'tcx>(analysis: ty::CrateAnalysis<'tcx>)
             }
         }
     }
+    if profile::enabled() {
+        let stats = shared_ccx.stats();
+        profile::counter("trans: static tydescs", stats.n_static_tydescs.get() as u64);
+        profile::counter("trans: glues created", stats.n_glues_created.get() as u64);
+        profile::counter("trans: fns", stats.n_fns.get() as u64);
+        profile::counter("trans: monomorphizations", stats.n_monos.get() as u64);
+        profile::counter("trans: inlined items", stats.n_inlines.get() as u64);
+        profile::counter("trans: closures", stats.n_closures.get() as u64);
+        profile::counter("trans: llvm insns", stats.n_llvm_insns.get() as u64);
+    }
     if shared_ccx.sess().count_llvm_insns() {
         for (k, v) in shared_ccx.stats().llvm_insns.borrow().iter() {
             println!("{:7} {}", *v, *k);
@@ -3217,7 +3343,9 @@ pub fn trans_crate<'tcx>(analysis: ty::CrateAnalysis<'tcx>)
     // referenced from rt/rust_try.ll
     reachable.push("rust_eh_personality_catch".to_string());
 
-    if codegen_units > 1 {
+    // Cached objects of other codegen units may refer to any symbol, so
+    // incremental compilation keeps them all.
+    if codegen_units > 1 && incremental.is_none() {
         internalize_symbols(&shared_ccx, &reachable.iter().map(|x| x.clone()).collect());
     }
 
@@ -3236,6 +3364,8 @@ pub fn trans_crate<'tcx>(analysis: ty::CrateAnalysis<'tcx>)
         reachable: reachable,
         crate_formats: formats,
         no_builtins: no_builtins,
+        incremental: incremental,
+        coverage_map: coverage_map,
     };
 
     (shared_ccx.take_tcx(), translation)
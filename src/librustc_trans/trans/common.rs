@@ -33,6 +33,7 @@ use trans::datum;
 use trans::debuginfo;
 use trans::machine;
 use trans::monomorphize;
+use trans::pgo;
 use trans::type_::Type;
 use trans::type_of;
 use middle::traits;
@@ -424,6 +425,9 @@ pub debug_context: debuginfo::FunctionDebugContext,
 pub scopes: RefCell<Vec<cleanup::CleanupScope<'a, 'tcx>>>,
 
 pub cfg: Option<cfg::CFG>,
+
+// Used and maintained by the pgo module.
+pub pgo_counters: RefCell<pgo::FunctionCounters>,
 }
 
 impl<'a, 'tcx> FunctionContext<'a, 'tcx> {
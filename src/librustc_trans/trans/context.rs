@@ -18,9 +18,11 @@ use middle::traits;
 use trans::adt;
 use trans::base;
 use trans::builder::Builder;
+use trans::coverage;
 use trans::common::{ExternMap,tydesc_info,BuilderRef_res};
 use trans::debuginfo;
 use trans::monomorphize::MonoId;
+use trans::pgo;
 use trans::type_::{Type, TypeNames};
 use middle::ty::{self, Ty};
 use session::config::NoDebugInfo;
@@ -34,6 +36,7 @@ use std::cell::{Cell, RefCell};
 use std::ptr;
 use std::rc::Rc;
 use syntax::ast;
+use syntax::codemap::Span;
 use syntax::parse::token::InternedString;
 
 pub struct Stats {
@@ -71,6 +74,13 @@ pub struct SharedCrateContext<'tcx> {
 
     available_monomorphizations: RefCell<FnvHashSet<String>>,
     available_drop_glues: RefCell<FnvHashMap<Ty<'tcx>, String>>,
+
+    /// The profile of `-C profile-use`.
+    profile_data: Option<pgo::ProfileData>,
+
+    /// Whether each codegen unit is copied from the incremental compilation
+    /// cache, in which case its functions are declared but not translated.
+    reused_units: Vec<bool>,
 }
 
 /// The local portion of a `CrateContext`.  There is one `LocalCrateContext`
@@ -153,6 +163,11 @@ pub struct LocalCrateContext<'tcx> {
 
     trait_cache: RefCell<FnvHashMap<ty::PolyTraitRef<'tcx>,
                                     traits::Vtable<'tcx, ()>>>,
+
+    /// The counters of `-C instrument-coverage`, until `coverage::finalize`
+    /// knows how many there are, and the regions they count.
+    coverage_counters: RefCell<Option<ValueRef>>,
+    coverage_regions: RefCell<Vec<(coverage::RegionKind, Span)>>,
 }
 
 pub struct CrateContext<'a, 'tcx: 'a> {
@@ -241,11 +256,13 @@ impl<'tcx> SharedCrateContext<'tcx> {
                export_map: ExportMap,
                symbol_hasher: Sha256,
                link_meta: LinkMeta,
-               reachable: NodeSet)
+               reachable: NodeSet,
+               reused_units: Vec<bool>)
                -> SharedCrateContext<'tcx> {
         let (metadata_llcx, metadata_llmod) = unsafe {
             create_context_and_module(&tcx.sess, "metadata")
         };
+        let profile_data = pgo::ProfileData::load(&tcx.sess);
 
         let mut shared_ccx = SharedCrateContext {
             local_ccxs: Vec::with_capacity(local_count),
@@ -272,6 +289,8 @@ impl<'tcx> SharedCrateContext<'tcx> {
             },
             available_monomorphizations: RefCell::new(FnvHashSet::new()),
             available_drop_glues: RefCell::new(FnvHashMap::new()),
+            profile_data: profile_data,
+            reused_units: reused_units,
         };
 
         for i in range(0, local_count) {
@@ -360,6 +379,10 @@ impl<'tcx> SharedCrateContext<'tcx> {
     pub fn stats<'a>(&'a self) -> &'a Stats {
         &self.stats
     }
+
+    pub fn profile_data(&self) -> &Option<pgo::ProfileData> {
+        &self.profile_data
+    }
 }
 
 impl<'tcx> LocalCrateContext<'tcx> {
@@ -420,6 +443,8 @@ impl<'tcx> LocalCrateContext<'tcx> {
                 intrinsics: RefCell::new(FnvHashMap::new()),
                 n_llvm_insns: Cell::new(0u),
                 trait_cache: RefCell::new(FnvHashMap::new()),
+                coverage_counters: RefCell::new(None),
+                coverage_regions: RefCell::new(Vec::new()),
             };
 
             local_ccx.int_type = Type::int(&local_ccx.dummy_ccx(shared));
@@ -494,6 +519,11 @@ impl<'b, 'tcx> CrateContext<'b, 'tcx> {
         }
     }
 
+    /// Whether this codegen unit is copied from the incremental compilation
+    /// cache, so that the bodies of its functions need not be translated.
+    pub fn is_reused(&self) -> bool {
+        self.shared.reused_units.get(self.index).map_or(false, |r| *r)
+    }
 
     pub fn tcx<'a>(&'a self) -> &'a ty::ctxt<'tcx> {
         &self.shared.tcx
@@ -676,6 +706,10 @@ impl<'b, 'tcx> CrateContext<'b, 'tcx> {
         &self.shared.available_drop_glues
     }
 
+    pub fn profile_data(&self) -> &Option<pgo::ProfileData> {
+        &self.shared.profile_data
+    }
+
     pub fn int_type(&self) -> Type {
         self.local.int_type
     }
@@ -709,6 +743,14 @@ impl<'b, 'tcx> CrateContext<'b, 'tcx> {
         &self.local.trait_cache
     }
 
+    pub fn coverage_counters(&self) -> &RefCell<Option<ValueRef>> {
+        &self.local.coverage_counters
+    }
+
+    pub fn coverage_regions(&self) -> &RefCell<Vec<(coverage::RegionKind, Span)>> {
+        &self.local.coverage_regions
+    }
+
     /// Return exclusive upper bound on object size.
     ///
     /// The theoretical maximum object size is defined as the maximum positive `int` value. This
@@ -874,6 +916,7 @@ fn declare_intrinsic(ccx: &CrateContext, key: & &'static str) -> Option<ValueRef
 
     ifn!("llvm.expect.i1", fn(i1, i1) -> i1);
     ifn!("llvm.assume", fn(i1) -> void);
+    ifn!("llvm.instrprof.increment", fn(i8p, t_i64, t_i32, t_i32) -> void);
 
     // Some intrinsics were introduced in later versions of LLVM, but they have
     // fallbacks in libc or libm and such. Currently, all of these intrinsics
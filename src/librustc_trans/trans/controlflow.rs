@@ -20,10 +20,12 @@ use trans::cleanup::CleanupMethods;
 use trans::cleanup;
 use trans::common::*;
 use trans::consts;
+use trans::coverage;
 use trans::datum;
 use trans::debuginfo;
 use trans::expr;
 use trans::meth;
+use trans::pgo;
 use trans::type_::Type;
 use trans;
 use middle::ty;
@@ -102,6 +104,8 @@ pub fn trans_block<'blk, 'tcx>(bcx: Block<'blk, 'tcx>,
     let fcx = bcx.fcx;
     let mut bcx = bcx;
 
+    coverage::count(bcx, b.span, coverage::RegionKind::Block);
+
     let cleanup_debug_loc =
         debuginfo::get_cleanup_debug_loc_for_ast_node(bcx.ccx(), b.id, b.span, true);
     fcx.push_ast_cleanup_scope(cleanup_debug_loc);
@@ -189,6 +193,8 @@ pub fn trans_if<'blk, 'tcx>(bcx: Block<'blk, 'tcx>,
 
     let name = format!("then-block-{}-", thn.id);
     let then_bcx_in = bcx.fcx.new_id_block(name.index(&FullRange), thn.id);
+    coverage::count(then_bcx_in, thn.span, coverage::RegionKind::Branch);
+    let then_counter = pgo::count(then_bcx_in);
     let then_bcx_out = trans_block(then_bcx_in, &*thn, dest);
     trans::debuginfo::clear_source_location(bcx.fcx);
 
@@ -196,10 +202,24 @@ pub fn trans_if<'blk, 'tcx>(bcx: Block<'blk, 'tcx>,
     match els {
         Some(elexpr) => {
             let else_bcx_in = bcx.fcx.new_id_block("else-block", elexpr.id);
+            coverage::count(else_bcx_in, elexpr.span, coverage::RegionKind::Branch);
+            let else_counter = pgo::count(else_bcx_in);
             let else_bcx_out = expr::trans_into(else_bcx_in, &*elexpr, dest);
             next_bcx = bcx.fcx.join_blocks(if_id,
                                            &[then_bcx_out, else_bcx_out]);
             CondBr(bcx, cond_val, then_bcx_in.llbb, else_bcx_in.llbb);
+            pgo::record_branch(bcx, then_counter, else_counter);
+        }
+
+        None if bcx.sess().opts.cg.instrument_coverage || pgo::enabled(bcx.ccx()) => {
+            // The missing `else` needs a block of its own to be counted.
+            let else_bcx = bcx.fcx.new_id_block("else-block", if_id);
+            coverage::count(else_bcx, bcx.tcx().map.span(if_id),
+                            coverage::RegionKind::ImplicitElse);
+            let else_counter = pgo::count(else_bcx);
+            next_bcx = bcx.fcx.join_blocks(if_id, &[then_bcx_out, else_bcx]);
+            CondBr(bcx, cond_val, then_bcx_in.llbb, else_bcx.llbb);
+            pgo::record_branch(bcx, then_counter, else_counter);
         }
 
         None => {
@@ -0,0 +1,461 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Source-based code coverage, enabled with `-C instrument-coverage`.
+//!
+//! Translation puts a 64-bit counter at the start of every block, of every
+//! arm of a `match` and of both branches of an `if`, and remembers the span
+//! of the region each counter counts. The counters of a codegen unit live in
+//! one array, which a constructor function registers with the runtime
+//! (`rust_coverage_register` in `rt/rust_coverage.c`). When the program
+//! exits, the runtime appends the counters of every registered unit to the
+//! file named by `RUST_COVERAGE_FILE`, or to `default.rustcov`, one line per
+//! unit:
+//!
+//! ```text
+//! <unit name> <number of counters> <count>...
+//! ```
+//!
+//! The compiler writes the regions next to the crate's outputs, as
+//! `<crate>.covmap`:
+//!
+//! ```json
+//! {
+//!     "format_version": 1,
+//!     "crate": "foo",
+//!     "units": [
+//!         { "name": "rust_coverage.foo.<svh>.0", "regions": [ ... ] }
+//!     ]
+//! }
+//! ```
+//!
+//! The `n`th region of a unit is counted by its `n`th counter. Regions have a
+//! `kind`, which is `block`, `branch` or `else` (the missing `else` of an
+//! `if`, which has the span of the whole `if`), and the `file_name`,
+//! `line_start`, `column_start`, `line_end` and `column_end` of their span,
+//! as in the save-analysis format.
+//!
+//! `rustc -Z unstable-options --coverage-report foo.covmap PROFILE...` adds
+//! up any number of profiles, e.g. of several test runs, and prints the
+//! coverage of each file: how often each line ran, taken from the innermost
+//! block or branch the line starts in, and the branches that were never
+//! taken.
+//! Profiles written by an older build of the crate are ignored, since the
+//! unit names contain the crate's SVH.
+
+use llvm::{self, ValueRef};
+use trans::base;
+use trans::build::{Add, GEPi, Load, Store};
+use trans::common::*;
+use trans::consts;
+use trans::context::CrateContext;
+use trans::type_::Type;
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::File;
+use std::iter::repeat;
+
+use serialize::json::{self, Json};
+
+use syntax::codemap::{Span, DUMMY_SP, NO_EXPANSION};
+use syntax::parse::token;
+
+pub const FORMAT_VERSION: u64 = 1;
+
+/// What a coverage counter counts.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Show)]
+pub enum RegionKind {
+    /// Entries into a block.
+    Block,
+    /// Entries into an arm of a `match` or a branch of an `if`.
+    Branch,
+    /// Times an `if` without an `else` did not run its block.
+    ImplicitElse,
+}
+
+impl RegionKind {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            RegionKind::Block => "block",
+            RegionKind::Branch => "branch",
+            RegionKind::ImplicitElse => "else",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<RegionKind> {
+        match name {
+            "block" => Some(RegionKind::Block),
+            "branch" => Some(RegionKind::Branch),
+            "else" => Some(RegionKind::ImplicitElse),
+            _ => None,
+        }
+    }
+}
+
+/// Counts how often `bcx` is entered, as a region of `kind` at `span`.
+/// Does nothing unless `-C instrument-coverage` is given, and for code
+/// that comes from macro expansions or other crates.
+pub fn count<'blk, 'tcx>(bcx: Block<'blk, 'tcx>, span: Span, kind: RegionKind) {
+    let ccx = bcx.ccx();
+    if !ccx.sess().opts.cg.instrument_coverage || bcx.unreachable.get() ||
+       span == DUMMY_SP || span.expn_id != NO_EXPANSION {
+        return;
+    }
+
+    let counters = counters(ccx);
+    let index = {
+        let mut regions = ccx.coverage_regions().borrow_mut();
+        regions.push((kind, span));
+        regions.len() - 1
+    };
+    let counter = GEPi(bcx, counters, &[0, index]);
+    let count = Load(bcx, counter);
+    Store(bcx, Add(bcx, count, C_u64(ccx, 1)), counter);
+}
+
+// The counter array of the codegen unit. Until `finalize` knows how many
+// counters there are, this is a placeholder of type `[0 x i64]`.
+fn counters(ccx: &CrateContext) -> ValueRef {
+    if let Some(counters) = *ccx.coverage_counters().borrow() {
+        return counters;
+    }
+    let ty = Type::array(&Type::i64(ccx), 0);
+    let counters = unsafe {
+        llvm::LLVMAddGlobal(ccx.llmod(), ty.to_ref(),
+                            "rust_coverage_counters\0".as_ptr() as *const _)
+    };
+    *ccx.coverage_counters().borrow_mut() = Some(counters);
+    counters
+}
+
+/// Allocates the counters of codegen unit number `unit`, emits the
+/// constructor registering them, and returns the unit's entry of the
+/// coverage map. Returns `None` if nothing in the unit is counted.
+pub fn finalize(ccx: &CrateContext, unit: uint) -> Option<Json> {
+    let placeholder = match *ccx.coverage_counters().borrow() {
+        Some(placeholder) => placeholder,
+        None => return None,
+    };
+    let regions = ccx.coverage_regions().borrow();
+    let name = format!("rust_coverage.{}.{}.{}",
+                       ccx.link_meta().crate_name,
+                       ccx.link_meta().crate_hash.as_str(),
+                       unit);
+
+    let llrecord = unsafe {
+        let i8p = Type::i8p(ccx);
+        let ty = Type::array(&Type::i64(ccx), regions.len() as u64);
+        let counters = llvm::LLVMAddGlobal(ccx.llmod(), ty.to_ref(),
+                                           "rust_coverage_counters\0".as_ptr() as *const _);
+        llvm::LLVMSetInitializer(counters, C_null(ty));
+        llvm::SetLinkage(counters, llvm::InternalLinkage);
+        llvm::LLVMReplaceAllUsesWith(placeholder,
+                                     llvm::LLVMConstBitCast(counters,
+                                                            val_ty(placeholder).to_ref()));
+        llvm::LLVMDeleteGlobal(placeholder);
+
+        // struct rust_coverage_unit { name, counters, len, next }
+        let llname = C_cstr(ccx, token::intern_and_get_ident(name.as_slice()), true);
+        let record = C_struct(ccx, &[consts::ptrcast(llname, i8p),
+                                     consts::ptrcast(counters, Type::i64(ccx).ptr_to()),
+                                     C_u64(ccx, regions.len() as u64),
+                                     C_null(i8p)], false);
+        let llrecord = llvm::LLVMAddGlobal(ccx.llmod(), val_ty(record).to_ref(),
+                                           "rust_coverage_unit\0".as_ptr() as *const _);
+        llvm::LLVMSetInitializer(llrecord, record);
+        llvm::SetLinkage(llrecord, llvm::InternalLinkage);
+        llrecord
+    };
+    base::add_global_ctor(ccx, "rust_coverage_ctor", "rust_coverage_register",
+                          consts::ptrcast(llrecord, Type::i8p(ccx)), 65535);
+
+    let cm = ccx.sess().codemap();
+    let regions = regions.iter().map(|&(kind, span)| {
+        let lo = cm.lookup_char_pos(span.lo);
+        let hi = cm.lookup_char_pos(span.hi);
+        let mut region = BTreeMap::new();
+        region.insert("kind".to_string(), Json::String(kind.name().to_string()));
+        region.insert("file_name".to_string(), Json::String(lo.file.name.clone()));
+        region.insert("line_start".to_string(), Json::U64(lo.line as u64));
+        region.insert("column_start".to_string(), Json::U64(lo.col.to_uint() as u64 + 1));
+        region.insert("line_end".to_string(), Json::U64(hi.line as u64));
+        region.insert("column_end".to_string(), Json::U64(hi.col.to_uint() as u64 + 1));
+        Json::Object(region)
+    }).collect();
+
+    let mut obj = BTreeMap::new();
+    obj.insert("name".to_string(), Json::String(name));
+    obj.insert("regions".to_string(), Json::Array(regions));
+    Some(Json::Object(obj))
+}
+
+/// The coverage map of a crate, from the entries `finalize` returned.
+pub fn coverage_map(crate_name: &str, units: Vec<Json>) -> Json {
+    let mut obj = BTreeMap::new();
+    obj.insert("format_version".to_string(), Json::U64(FORMAT_VERSION));
+    obj.insert("crate".to_string(), Json::String(crate_name.to_string()));
+    obj.insert("units".to_string(), Json::Array(units));
+    Json::Object(obj)
+}
+
+// A counted region of the source, ordered by file and position.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Show)]
+struct Region {
+    file: String,
+    start: (uint, uint),
+    end: (uint, uint),
+    kind: RegionKind,
+}
+
+impl Region {
+    fn from_json(json: &Json) -> Option<Region> {
+        let field = |&: name: &str| json.find(name).and_then(|v| v.as_u64()).map(|v| v as uint);
+        let kind = json.find("kind").and_then(|k| k.as_string()).and_then(RegionKind::from_name);
+        let file = json.find("file_name").and_then(|f| f.as_string());
+        match (kind, file, field("line_start"), field("column_start"),
+               field("line_end"), field("column_end")) {
+            (Some(kind), Some(file), Some(ls), Some(cs), Some(le), Some(ce)) => {
+                Some(Region { file: file.to_string(), start: (ls, cs), end: (le, ce), kind: kind })
+            }
+            _ => None,
+        }
+    }
+
+    fn contains_line_start(&self, line: uint) -> bool {
+        self.start <= (line, 1) && (line, 1) < self.end
+    }
+
+    fn contains(&self, other: &Region) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+}
+
+/// The execution counts of a crate's regions, added up over profiles.
+pub struct Coverage {
+    units: HashMap<String, Vec<Region>>,
+    // Several counters can count the same region, e.g. in different
+    // monomorphizations of a function, so counts are kept per region.
+    counts: BTreeMap<Region, u64>,
+}
+
+impl Coverage {
+    /// Starts from the coverage map `map`, with every region not run.
+    pub fn new(map: &Json) -> Result<Coverage, String> {
+        match map.find("format_version").and_then(|v| v.as_u64()) {
+            Some(FORMAT_VERSION) => {}
+            _ => return Err(format!("unsupported coverage map version, expected {}",
+                                    FORMAT_VERSION)),
+        }
+        let units = match map.find("units").and_then(|u| u.as_array()) {
+            Some(units) => units,
+            None => return Err("coverage map has no `units`".to_string()),
+        };
+
+        let mut coverage = Coverage { units: HashMap::new(), counts: BTreeMap::new() };
+        for unit in units.iter() {
+            let name = unit.find("name").and_then(|n| n.as_string());
+            let regions = unit.find("regions").and_then(|r| r.as_array());
+            let (name, regions) = match (name, regions) {
+                (Some(name), Some(regions)) => (name, regions),
+                _ => return Err("coverage map has an invalid unit".to_string()),
+            };
+            let mut parsed = Vec::new();
+            for region in regions.iter() {
+                match Region::from_json(region) {
+                    Some(region) => {
+                        coverage.counts.insert(region.clone(), 0);
+                        parsed.push(region);
+                    }
+                    None => return Err(format!("coverage map has an invalid region \
+                                                in unit `{}`", name)),
+                }
+            }
+            coverage.units.insert(name.to_string(), parsed);
+        }
+        Ok(coverage)
+    }
+
+    /// Adds the counts of the profile `src`. Units the map does not know
+    /// about, such as those of other crates or older builds, are skipped.
+    pub fn add_profile(&mut self, src: &str) -> Result<(), String> {
+        for (i, line) in src.lines().enumerate() {
+            let mut words = line.words();
+            let name = match words.next() {
+                Some(name) => name,
+                None => continue,
+            };
+            let regions = match self.units.get(name) {
+                Some(regions) => regions,
+                None => continue,
+            };
+            let counts: Vec<Option<u64>> = words.map(|w| w.parse::<u64>()).collect();
+            if counts.iter().any(|c| c.is_none()) || counts.len() == 0 ||
+               counts[0] != Some(regions.len() as u64) ||
+               counts.len() != regions.len() + 1 {
+                return Err(format!("line {}: counts of `{}` don't match the coverage map",
+                                   i + 1, name));
+            }
+            for (region, count) in regions.iter().zip(counts.iter().skip(1)) {
+                *self.counts.get_mut(region).unwrap() += count.unwrap();
+            }
+        }
+        Ok(())
+    }
+
+    /// Renders the report: for each file, how often each line ran and which
+    /// branches were never taken. `source` returns the text of a file, if
+    /// it can be found.
+    pub fn render<F>(&self, mut source: F) -> String where F: FnMut(&str) -> Option<String> {
+        let mut out = String::new();
+        let mut files: Vec<&str> = self.counts.keys().map(|r| r.file.as_slice()).collect();
+        files.dedup();
+
+        for &file in files.iter() {
+            let regions: Vec<(&Region, u64)> = self.counts.iter()
+                .filter(|&(r, _)| r.file.as_slice() == file)
+                .map(|(r, &n)| (r, n))
+                .collect();
+
+            let lines = regions.iter().map(|&(r, _)| r.end.0).max().unwrap_or(0);
+            let mut line_counts: Vec<Option<u64>> = repeat(None).take(lines + 1).collect();
+            for line in range(1, lines + 1) {
+                line_counts[line] = line_count(regions.as_slice(), line);
+            }
+
+            let code = line_counts.iter().filter(|c| c.is_some()).count();
+            let covered = line_counts.iter().filter(|c| c.map_or(false, |n| n > 0)).count();
+            let branches: Vec<&(&Region, u64)> = regions.iter()
+                .filter(|&&(r, _)| r.kind != RegionKind::Block)
+                .collect();
+            let taken = branches.iter().filter(|&&&(_, n)| n > 0).count();
+            out.push_str(format!("{}: {} of {} lines, {} of {} branches covered\n",
+                                 file, covered, code, taken, branches.len()).as_slice());
+
+            let text = source(file).unwrap_or(String::new());
+            let mut text_lines = text.lines();
+            for line in range(1, lines + 1) {
+                let count = match line_counts[line] {
+                    Some(count) => format!("{}", count),
+                    None => String::new(),
+                };
+                out.push_str(format!("{:>5} {:>8} | {}\n",
+                                     line, count, text_lines.next().unwrap_or("")).as_slice());
+            }
+            for &&(region, count) in branches.iter() {
+                if count > 0 {
+                    continue;
+                }
+                let what = match region.kind {
+                    RegionKind::ImplicitElse => "missing `else` of the `if`",
+                    _ => "branch",
+                };
+                out.push_str(format!("never taken: {} at {}:{}\n",
+                                     what, region.start.0, region.start.1).as_slice());
+            }
+        }
+        out
+    }
+}
+
+// How often a line ran: the count of the innermost region the line starts
+// in, or else of the outermost region starting on the line, like the body
+// of a function after its signature.
+fn line_count(regions: &[(&Region, u64)], line: uint) -> Option<u64> {
+    let mut inside: Option<(&Region, u64)> = None;
+    let mut starting: Option<(&Region, u64)> = None;
+    for &(region, count) in regions.iter() {
+        if region.kind == RegionKind::ImplicitElse {
+            continue;
+        }
+        if region.contains_line_start(line) {
+            match inside {
+                Some((best, _)) if !best.contains(region) => {}
+                _ => inside = Some((region, count)),
+            }
+        } else if region.start.0 == line {
+            match starting {
+                Some((best, _)) if best.contains(region) => {}
+                _ => starting = Some((region, count)),
+            }
+        }
+    }
+    inside.or(starting).map(|(_, count)| count)
+}
+
+/// Adds up the profiles at `profiles` and renders the coverage report of the
+/// crate whose coverage map is at `map`, reading sources relative to the
+/// current directory.
+pub fn report(map: &Path, profiles: &[Path]) -> Result<String, String> {
+    let map = match File::open(map).read_to_string() {
+        Ok(src) => match json::from_str(src.as_slice()) {
+            Ok(map) => map,
+            Err(e) => return Err(format!("invalid coverage map `{}`: {}", map.display(), e)),
+        },
+        Err(e) => return Err(format!("couldn't read coverage map `{}`: {}", map.display(), e)),
+    };
+    let mut coverage = try!(Coverage::new(&map));
+    for path in profiles.iter() {
+        let src = match File::open(path).read_to_string() {
+            Ok(src) => src,
+            Err(e) => return Err(format!("couldn't read profile `{}`: {}", path.display(), e)),
+        };
+        try!(coverage.add_profile(src.as_slice())
+                     .map_err(|e| format!("invalid profile `{}`: {}", path.display(), e)));
+    }
+    Ok(coverage.render(|file| File::open(&Path::new(file)).read_to_string().ok()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::Coverage;
+    use serialize::json;
+
+    static MAP: &'static str = r#"{
+        "format_version": 1,
+        "crate": "foo",
+        "units": [
+            { "name": "u0", "regions": [
+                { "kind": "block", "file_name": "foo.rs",
+                  "line_start": 1, "column_start": 10, "line_end": 5, "column_end": 2 },
+                { "kind": "block", "file_name": "foo.rs",
+                  "line_start": 2, "column_start": 10, "line_end": 4, "column_end": 6 },
+                { "kind": "else", "file_name": "foo.rs",
+                  "line_start": 2, "column_start": 5, "line_end": 4, "column_end": 6 }
+            ] },
+            { "name": "u1", "regions": [
+                { "kind": "block", "file_name": "foo.rs",
+                  "line_start": 1, "column_start": 10, "line_end": 5, "column_end": 2 }
+            ] }
+        ]
+    }"#;
+
+    static SOURCE: &'static str = "fn f() {\n    if x {\n        y\n    }\n}\n";
+
+    #[test]
+    fn merge_and_render() {
+        let mut coverage = Coverage::new(&json::from_str(MAP).unwrap()).unwrap();
+        coverage.add_profile("u0 3 2 0 2\nother 1 7\n").unwrap();
+        coverage.add_profile("u1 1 1\n").unwrap();
+        let report = coverage.render(|_| Some(SOURCE.to_string()));
+        assert_eq!(report, "foo.rs: 3 of 5 lines, 1 of 1 branches covered\n\
+                            \x20   1        3 | fn f() {\n\
+                            \x20   2        0 |     if x {\n\
+                            \x20   3        0 |         y\n\
+                            \x20   4        0 |     }\n\
+                            \x20   5        3 | }\n");
+    }
+
+    #[test]
+    fn mismatched_profile() {
+        let mut coverage = Coverage::new(&json::from_str(MAP).unwrap()).unwrap();
+        assert!(coverage.add_profile("u0 2 1 1\n").is_err());
+        assert!(coverage.add_profile("u0 3 1 x 1\n").is_err());
+    }
+}
@@ -159,7 +159,13 @@ pub fn get_drop_glue<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>, t: Ty<'tcx>) -> Val
 
     let llfnty = Type::glue_fn(ccx, llty);
 
-    let (glue, new_sym) = match ccx.available_drop_glues().borrow().get(&t) {
+    let old_sym = if ccx.sess().opts.incremental.is_some() {
+        // Every codegen unit has its own glue when compiling incrementally.
+        None
+    } else {
+        ccx.available_drop_glues().borrow().get(&t).cloned()
+    };
+    let (glue, new_sym) = match old_sym {
         Some(old_sym) => {
             let glue = decl_cdecl_fn(ccx, old_sym.index(&FullRange), llfnty, ty::mk_nil(ccx.tcx()));
             (glue, None)
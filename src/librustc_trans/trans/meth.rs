@@ -80,6 +80,9 @@ pub fn trans_impl(ccx: &CrateContext,
                     let trans_everywhere = attr::requests_inline(method.attrs.index(&FullRange));
                     for (ref ccx, is_origin) in ccx.maybe_iter(trans_everywhere) {
                         let llfn = get_item_val(ccx, method.id);
+                        if ccx.is_reused() {
+                            continue;
+                        }
                         trans_fn(ccx,
                                  method.pe_fn_decl(),
                                  method.pe_body(),
@@ -8,9 +8,11 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use back::incremental;
 use llvm::{ContextRef, ModuleRef};
 use metadata::common::LinkMeta;
 use middle::dependency_format;
+use serialize::json::Json;
 
 pub use self::base::trans_crate;
 pub use self::context::CrateContext;
@@ -56,6 +58,8 @@ mod value;
 mod basic_block;
 mod llrepr;
 mod cleanup;
+pub mod coverage;
+mod pgo;
 
 #[derive(Copy)]
 pub struct ModuleTranslation {
@@ -74,4 +78,9 @@ pub struct CrateTranslation {
     pub reachable: Vec<String>,
     pub crate_formats: dependency_format::Dependencies,
     pub no_builtins: bool,
+    /// What is kept of this compilation and reused from the previous one,
+    /// when compiling incrementally.
+    pub incremental: Option<incremental::Cache>,
+    /// The coverage map, with `-C instrument-coverage`.
+    pub coverage_map: Option<Json>,
 }
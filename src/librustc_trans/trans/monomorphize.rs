@@ -153,7 +153,9 @@ pub fn monomorphic_fn<'a, 'tcx>(ccx: &CrateContext<'a, 'tcx>,
         base::update_linkage(ccx, lldecl, None, base::OriginalTranslation);
         set_llvm_fn_attrs(ccx, attrs, lldecl);
 
-        let is_first = !ccx.available_monomorphizations().borrow().contains(&s);
+        // Every codegen unit has its own copy when compiling incrementally.
+        let is_first = ccx.sess().opts.incremental.is_some() ||
+                       !ccx.available_monomorphizations().borrow().contains(&s);
         if is_first {
             ccx.available_monomorphizations().borrow_mut().insert(s.clone());
         }
@@ -0,0 +1,266 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Profile-guided optimization, with `-C profile-generate=DIR` and
+//! `-C profile-use=FILE`.
+//!
+//! As in clang, functions are instrumented while they are translated: every
+//! function has a counter for its entry and one for each side of every
+//! `if`, numbered in the order translation reaches them. The hash of a
+//! function is its number of counters.
+//!
+//! With `profile-generate`, the counters are `llvm.instrprof.increment`
+//! calls, which LLVM's `instrprof` pass lowers to counters that the
+//! profiling runtime of compiler-rt writes to `DIR/default-<pid>.profraw`
+//! when the program exits, unless `LLVM_PROFILE_FILE` names another file.
+//! `DIR` is created by the compiler, and made absolute.
+//! The raw profiles are merged with `llvm-profdata merge -output=FILE`.
+//!
+//! With `profile-use`, the counters are numbered the same way, and the counts
+//! of each function are looked up by its symbol name and hash. The counts of
+//! the sides of an `if` become the weights of its branch, and functions that
+//! never ran are marked cold. Functions without a matching profile, because
+//! they changed since it was recorded, are optimized as usual and reported
+//! in a warning.
+
+use back::write::llvm_err;
+use llvm::{self, ValueRef, ProfileDataRef};
+use session::Session;
+use trans::base;
+use trans::build::Call;
+use trans::common::*;
+use trans::consts;
+use trans::context::CrateContext;
+use trans::type_::Type;
+
+use libc::{c_char, c_uint, size_t};
+use std::cell::Cell;
+use std::ffi::{self, CString};
+use std::io::{self, fs};
+use std::iter::repeat;
+use std::os;
+use std::u32;
+
+use syntax::parse::token;
+
+/// The profile read with `-C profile-use`.
+pub struct ProfileData {
+    reader: ProfileDataRef,
+    mismatched: Cell<uint>,
+}
+
+impl ProfileData {
+    /// Reads the profile of `-C profile-use`, if given.
+    pub fn load(sess: &Session) -> Option<ProfileData> {
+        let path = match sess.opts.cg.profile_use {
+            Some(ref path) => path,
+            None => return None,
+        };
+        let cpath = CString::from_slice(path.as_bytes());
+        let reader = unsafe { llvm::LLVMRustOpenProfileData(cpath.as_ptr()) };
+        if reader.is_null() {
+            llvm_err(sess.diagnostic().handler(),
+                     format!("failed to read profile `{}`", path));
+        }
+        Some(ProfileData { reader: reader, mismatched: Cell::new(0) })
+    }
+
+    /// The number of functions that had no matching profile.
+    pub fn mismatched(&self) -> uint {
+        self.mismatched.get()
+    }
+
+    fn counts(&self, name: &[u8], hash: u64, len: uint) -> Option<Vec<u64>> {
+        let name = CString::from_slice(name);
+        let mut counts: Vec<u64> = repeat(0).take(len).collect();
+        let found = unsafe {
+            llvm::LLVMRustGetFunctionCounts(self.reader, name.as_ptr(), hash,
+                                            counts.as_mut_ptr(), len as size_t)
+        };
+        if found {
+            Some(counts)
+        } else {
+            self.mismatched.set(self.mismatched.get() + 1);
+            None
+        }
+    }
+}
+
+impl Drop for ProfileData {
+    fn drop(&mut self) {
+        unsafe { llvm::LLVMRustDestroyProfileData(self.reader) }
+    }
+}
+
+/// The counters of the function being translated.
+pub struct FunctionCounters {
+    len: uint,
+    // The private global holding the name of the function, for the
+    // intrinsic.
+    llname: Option<ValueRef>,
+    // The `llvm.instrprof.increment` calls, whose hash and number of
+    // counters are only known when the function is finished.
+    increments: Vec<ValueRef>,
+    // The conditional branches of `if`s, and the counters of their sides.
+    branches: Vec<(ValueRef, uint, uint)>,
+}
+
+impl FunctionCounters {
+    pub fn new() -> FunctionCounters {
+        FunctionCounters { len: 0, llname: None, increments: Vec::new(), branches: Vec::new() }
+    }
+}
+
+/// Whether either PGO option is given.
+pub fn enabled(ccx: &CrateContext) -> bool {
+    let cg = &ccx.sess().opts.cg;
+    cg.profile_generate.is_some() || cg.profile_use.is_some()
+}
+
+/// Gives `bcx` the next counter of its function, and returns its number.
+pub fn count<'blk, 'tcx>(bcx: Block<'blk, 'tcx>) -> Option<uint> {
+    let ccx = bcx.ccx();
+    if !enabled(ccx) || bcx.unreachable.get() {
+        return None;
+    }
+
+    let mut counters = bcx.fcx.pgo_counters.borrow_mut();
+    let index = counters.len;
+    counters.len += 1;
+
+    if ccx.sess().opts.cg.profile_generate.is_some() {
+        let llname = match counters.llname {
+            Some(llname) => llname,
+            None => {
+                let llname = name_global(ccx, bcx.fcx.llfn);
+                counters.llname = Some(llname);
+                llname
+            }
+        };
+        let increment = ccx.get_intrinsic(&"llvm.instrprof.increment");
+        let call = Call(bcx, increment, &[consts::ptrcast(llname, Type::i8p(ccx)),
+                                          C_u64(ccx, 0),
+                                          C_i32(ccx, 0),
+                                          C_i32(ccx, index as i32)], None);
+        counters.increments.push(call);
+    }
+    Some(index)
+}
+
+/// Records that the conditional branch just built at the end of `bcx`
+/// leads to the sides counted by `then` and `els`.
+pub fn record_branch<'blk, 'tcx>(bcx: Block<'blk, 'tcx>, then: Option<uint>, els: Option<uint>) {
+    if bcx.ccx().sess().opts.cg.profile_use.is_none() || bcx.unreachable.get() {
+        return;
+    }
+    if let (Some(then), Some(els)) = (then, els) {
+        let llbranch = unsafe { llvm::LLVMGetLastInstruction(bcx.llbb) };
+        bcx.fcx.pgo_counters.borrow_mut().branches.push((llbranch, then, els));
+    }
+}
+
+/// Completes the counters of a function once it is translated, and applies
+/// its profile.
+pub fn finish_fn(fcx: &FunctionContext) {
+    let ccx = fcx.ccx;
+    let counters = fcx.pgo_counters.borrow();
+    if counters.len == 0 {
+        return;
+    }
+    let hash = counters.len as u64;
+
+    for &call in counters.increments.iter() {
+        unsafe {
+            llvm::LLVMSetOperand(call, 1, C_u64(ccx, hash));
+            llvm::LLVMSetOperand(call, 2, C_i32(ccx, counters.len as i32));
+        }
+    }
+
+    let profile = match *ccx.profile_data() {
+        Some(ref profile) => profile,
+        None => return,
+    };
+    let name = unsafe { ffi::c_str_to_bytes(&llvm::LLVMGetValueName(fcx.llfn)).to_vec() };
+    let counts = match profile.counts(name.as_slice(), hash, counters.len) {
+        Some(counts) => counts,
+        None => return,
+    };
+
+    // The first counter counts entries into the function.
+    if counts[0] == 0 {
+        llvm::SetFunctionAttribute(fcx.llfn, llvm::ColdAttribute);
+    }
+    for &(llbranch, then, els) in counters.branches.iter() {
+        set_branch_weights(ccx, llbranch, &[counts[then], counts[els]]);
+    }
+}
+
+/// Creates the directory of `-C profile-generate=DIR`, and runs the
+/// profiling runtime's setup before anything else, so that profiles go to
+/// `DIR` whatever directory the program is run from.
+pub fn finalize(ccx: &CrateContext) {
+    if let Some(ref dir) = ccx.sess().opts.cg.profile_generate {
+        let dir = match os::make_absolute(&Path::new(dir.as_slice())) {
+            Ok(dir) => dir,
+            Err(e) => ccx.sess().fatal(format!("failed to find the profile directory \
+                                                `{}`: {}", dir, e).as_slice()),
+        };
+        if let Err(e) = fs::mkdir_recursive(&dir, io::USER_RWX) {
+            ccx.sess().fatal(format!("failed to create the profile directory `{}`: {}",
+                                     dir.display(), e).as_slice());
+        }
+        let dir = match dir.as_str() {
+            Some(dir) => dir,
+            None => ccx.sess().fatal(format!("the profile directory `{}` is not valid \
+                                              unicode", dir.display()).as_slice()),
+        };
+        let lldir = C_cstr(ccx, token::intern_and_get_ident(dir), true);
+        base::add_global_ctor(ccx, "rust_pgo_ctor", "rust_pgo_set_default_dir",
+                              consts::ptrcast(lldir, Type::i8p(ccx)), 0);
+    }
+}
+
+// The private global holding the name of `llfn`, which `instrprof` names
+// the function's counters after.
+fn name_global(ccx: &CrateContext, llfn: ValueRef) -> ValueRef {
+    unsafe {
+        let name = ffi::c_str_to_bytes(&llvm::LLVMGetValueName(llfn)).to_vec();
+        let llname = llvm::LLVMConstStringInContext(ccx.llcx(),
+                                                    name.as_ptr() as *const c_char,
+                                                    name.len() as c_uint,
+                                                    llvm::True);
+        let mut global_name = b"__llvm_profile_name_".to_vec();
+        global_name.push_all(name.as_slice());
+        let global_name = CString::from_vec(global_name);
+        let g = llvm::LLVMAddGlobal(ccx.llmod(), val_ty(llname).to_ref(), global_name.as_ptr());
+        llvm::LLVMSetInitializer(g, llname);
+        llvm::LLVMSetGlobalConstant(g, llvm::True);
+        llvm::SetLinkage(g, llvm::PrivateLinkage);
+        g
+    }
+}
+
+// Branch weights are 32-bit, so large counts are scaled down. One is added
+// so that no branch gets a weight of zero, as clang does.
+fn set_branch_weights(ccx: &CrateContext, llbranch: ValueRef, counts: &[u64]) {
+    let max = counts.iter().map(|&c| c).max().unwrap_or(0);
+    let scale = if max < u32::MAX as u64 { 1 } else { max / (u32::MAX as u64) + 1 };
+    let kind = "branch_weights";
+    unsafe {
+        let mut md = vec![llvm::LLVMMDStringInContext(ccx.llcx(),
+                                                      kind.as_ptr() as *const c_char,
+                                                      kind.len() as c_uint)];
+        md.extend(counts.iter().map(|&c| C_integral(Type::i32(ccx), c / scale + 1, false)));
+        llvm::LLVMSetMetadata(llbranch, llvm::MD_prof as c_uint,
+                              llvm::LLVMMDNodeInContext(ccx.llcx(),
+                                                        md.as_ptr(),
+                                                        md.len() as c_uint));
+    }
+}
@@ -25,6 +25,7 @@ use std::collections::hash_map::Entry::{Occupied, Vacant};
 use syntax::ast;
 use syntax::ast_util;
 use syntax::codemap::{Span, Spanned};
+use syntax::diagnostic::MultiSpan;
 use syntax::parse::token;
 use syntax::print::pprust;
 use syntax::ptr::P;
@@ -606,12 +607,14 @@ pub fn check_struct_pat_fields<'a, 'tcx>(pcx: &pat_ctxt<'a, 'tcx>,
     for &Spanned { node: ref field, span } in fields.iter() {
         let field_type = match used_fields.entry(field.ident.name) {
             Occupied(occupied) => {
-                span_err!(tcx.sess, span, E0025,
+                let mut msp = MultiSpan::new(span);
+                msp.label_primary("multiple uses of field");
+                msp.push_label(*occupied.get(),
+                               format!("field `{}` previously bound here",
+                                       token::get_ident(field.ident)).as_slice());
+                span_err_labeled!(tcx.sess, &msp, E0025,
                     "field `{}` bound multiple times in the pattern",
                     token::get_ident(field.ident));
-                span_note!(tcx.sess, *occupied.get(),
-                    "field `{}` previously bound here",
-                    token::get_ident(field.ident));
                 tcx.types.err
             }
             Vacant(vacant) => {
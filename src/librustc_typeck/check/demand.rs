@@ -16,6 +16,7 @@ use middle::infer;
 use std::result::Result::{Err, Ok};
 use syntax::ast;
 use syntax::codemap::Span;
+use syntax::diagnostic;
 use util::ppaux::Repr;
 
 // Requires that the two types unify, and prints an error message if
@@ -65,6 +66,38 @@ pub fn coerce<'a, 'tcx>(fcx: &FnCtxt<'a, 'tcx>, sp: Span,
       Ok(()) => { /* ok */ }
       Err(ref err) => {
         fcx.report_mismatched_types(sp, expected, expr_ty, err);
+        suggest_borrow(fcx, expected, expr_ty, expr);
       }
     }
 }
+
+// Suggests borrowing `expr` if a reference to it would have the expected
+// type, e.g. `foo(x)` instead of `foo(&x)`.
+fn suggest_borrow<'a, 'tcx>(fcx: &FnCtxt<'a, 'tcx>, expected: Ty<'tcx>,
+                            expr_ty: Ty<'tcx>, expr: &ast::Expr) {
+    let (referent, mutbl) = match expected.sty {
+        ty::ty_rptr(_, ty::mt { ty, mutbl }) => (ty, mutbl),
+        _ => return,
+    };
+    if infer::can_mk_subty(fcx.infcx(), expr_ty, referent).is_err() {
+        return
+    }
+    let snippet = match fcx.tcx().sess.codemap().span_to_snippet(expr.span) {
+        Some(snippet) => snippet,
+        None => return,
+    };
+    let snippet = match expr.node {
+        ast::ExprPath(..) | ast::ExprField(..) | ast::ExprTupField(..) |
+        ast::ExprIndex(..) | ast::ExprCall(..) | ast::ExprMethodCall(..) |
+        ast::ExprLit(..) | ast::ExprParen(..) | ast::ExprVec(..) |
+        ast::ExprTup(..) | ast::ExprStruct(..) | ast::ExprMac(..) => snippet,
+        _ => format!("({})", snippet),
+    };
+    let (borrow, applicability) = match mutbl {
+        ast::MutImmutable => ("&", diagnostic::MachineApplicable),
+        // The borrowed value may also need to be declared `mut`.
+        ast::MutMutable => ("&mut ", diagnostic::MaybeIncorrect),
+    };
+    fcx.tcx().sess.span_suggestion(expr.span, "consider borrowing here",
+                                   format!("{}{}", borrow, snippet), applicability);
+}
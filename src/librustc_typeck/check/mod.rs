@@ -116,6 +116,7 @@ use syntax::{self, abi, attr};
 use syntax::ast::{self, ProvidedMethod, RequiredMethod, TypeTraitItem, DefId};
 use syntax::ast_util::{self, local_def, PostExpansionMethod};
 use syntax::codemap::{self, Span};
+use syntax::diagnostic;
 use syntax::owned_slice::OwnedSlice;
 use syntax::parse::token;
 use syntax::print::pprust;
@@ -430,6 +431,9 @@ struct CheckItemTypesVisitor<'a, 'tcx: 'a> { ccx: &'a CrateCtxt<'a, 'tcx> }
 
 impl<'a, 'tcx, 'v> Visitor<'v> for CheckItemTypesVisitor<'a, 'tcx> {
     fn visit_item(&mut self, i: &ast::Item) {
+        if self.ccx.reused.contains(&i.id) {
+            return;
+        }
         check_item(self.ccx, i);
         visit::walk_item(self, i);
     }
@@ -1477,7 +1481,9 @@ fn check_cast(fcx: &FnCtxt,
                     ast::MutImmutable => ""
                 };
                 if ty::type_is_trait(t_1) {
-                    span_help!(fcx.tcx().sess, t.span, "did you mean `&{}{}`?", mtstr, tstr);
+                    fcx.tcx().sess.span_suggestion(t.span, "try casting to a reference",
+                                                   format!("&{}{}", mtstr, tstr),
+                                                   diagnostic::MaybeIncorrect);
                 } else {
                     span_help!(fcx.tcx().sess, span,
                                "consider using an implicit coercion to `&{}{}` instead",
@@ -1485,7 +1491,9 @@ fn check_cast(fcx: &FnCtxt,
                 }
             }
             ty::ty_uniq(..) => {
-                span_help!(fcx.tcx().sess, t.span, "did you mean `Box<{}>`?", tstr);
+                fcx.tcx().sess.span_suggestion(t.span, "try casting to a box",
+                                               format!("Box<{}>", tstr),
+                                               diagnostic::MaybeIncorrect);
             }
             _ => {
                 span_help!(fcx.tcx().sess, e.span,
@@ -18,6 +18,7 @@ use syntax::ast::{DefId};
 use syntax::ast::{LOCAL_CRATE};
 use syntax::ast;
 use syntax::codemap::{Span};
+use syntax::diagnostic::MultiSpan;
 use util::ppaux::Repr;
 
 pub fn check(tcx: &ty::ctxt) {
@@ -92,14 +93,19 @@ impl<'cx, 'tcx> OverlapChecker<'cx, 'tcx> {
             return;
         }
 
-        span_err!(self.tcx.sess, self.span_of_impl(impl1_def_id), E0119,
-                  "conflicting implementations for trait `{}`",
-                  ty::item_path_str(self.tcx, trait_def_id));
-
         if impl2_def_id.krate == ast::LOCAL_CRATE {
-            span_note!(self.tcx.sess, self.span_of_impl(impl2_def_id),
-                       "note conflicting implementation here");
+            let mut msp = MultiSpan::new(self.span_of_impl(impl1_def_id));
+            msp.label_primary("conflicting implementation");
+            msp.push_label(self.span_of_impl(impl2_def_id),
+                           "first implementation here");
+            span_err_labeled!(self.tcx.sess, &msp, E0119,
+                              "conflicting implementations for trait `{}`",
+                              ty::item_path_str(self.tcx, trait_def_id));
         } else {
+            span_err!(self.tcx.sess, self.span_of_impl(impl1_def_id), E0119,
+                      "conflicting implementations for trait `{}`",
+                      ty::item_path_str(self.tcx, trait_def_id));
+
             let crate_store = &self.tcx.sess.cstore;
             let cdata = crate_store.get_crate_data(impl2_def_id.krate);
             span_note!(self.tcx.sess, self.span_of_impl(impl1_def_id),
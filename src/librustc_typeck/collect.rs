@@ -55,6 +55,7 @@ use syntax::ast;
 use syntax::ast_map;
 use syntax::ast_util::{local_def, PostExpansionMethod};
 use syntax::codemap::Span;
+use syntax::diagnostic::MultiSpan;
 use syntax::parse::token::{special_idents};
 use syntax::parse::token;
 use syntax::ptr::P;
@@ -739,10 +740,12 @@ fn convert_struct<'a, 'tcx>(ccx: &CollectCtxt<'a, 'tcx>,
         if result.name != special_idents::unnamed_field.name {
             let dup = match seen_fields.get(&result.name) {
                 Some(prev_span) => {
-                    span_err!(tcx.sess, f.span, E0124,
-                              "field `{}` is already declared",
-                              token::get_name(result.name));
-                    span_note!(tcx.sess, *prev_span, "previously declared here");
+                    let mut msp = MultiSpan::new(f.span);
+                    msp.label_primary("field already declared");
+                    msp.push_label(*prev_span, "previously declared here");
+                    span_err_labeled!(tcx.sess, &msp, E0124,
+                                      "field `{}` is already declared",
+                                      token::get_name(result.name));
                     true
                 },
                 None => false,
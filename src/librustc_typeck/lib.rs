@@ -95,6 +95,7 @@ use middle::subst::VecPerParamSpace;
 use middle::ty::{self, Ty};
 use session::config;
 use util::common::time;
+use util::nodemap::NodeSet;
 use util::ppaux::Repr;
 use util::ppaux;
 
@@ -119,6 +120,9 @@ struct CrateCtxt<'a, 'tcx: 'a> {
     // A mapping from method call sites to traits that have that method.
     trait_map: ty::TraitMap,
     tcx: &'a ty::ctxt<'tcx>,
+    // The items whose type-check results were loaded from a previous
+    // incremental compilation, and which are not checked again.
+    reused: NodeSet,
 }
 
 // Functions that write types into the node type table
@@ -311,11 +315,12 @@ fn check_for_entry_fn(ccx: &CrateCtxt) {
     }
 }
 
-pub fn check_crate(tcx: &ty::ctxt, trait_map: ty::TraitMap) {
+pub fn check_crate(tcx: &ty::ctxt, trait_map: ty::TraitMap, reused: NodeSet) {
     let time_passes = tcx.sess.time_passes();
     let ccx = CrateCtxt {
         trait_map: trait_map,
-        tcx: tcx
+        tcx: tcx,
+        reused: reused,
     };
 
     time(time_passes, "type collecting", (), |_|
@@ -0,0 +1,716 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module compares the public APIs of two versions of a crate, for
+//! `rustdoc --api-diff`, and classifies the changes as breaking or
+//! compatible.
+//!
+//! Both versions are described in the JSON format of `jsondoc`. Items are
+//! matched by their path without the crate name, the methods of inherent
+//! impls by the path of their type, and trait impls by the trait and type
+//! they are for. Types are compared by the path of the item they resolve to,
+//! not the path they were written with. The comparison is conservative: a
+//! change that could break some user of the crate, such as renaming a type
+//! parameter, is breaking.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+use serialize::json::Json;
+
+/// Whether a change can break users of the crate.
+#[derive(RustcEncodable, Copy, PartialEq, Eq, Show)]
+pub enum Severity {
+    Breaking,
+    Compatible,
+}
+
+/// A change to one item of the API.
+#[derive(RustcEncodable)]
+pub struct Change {
+    pub severity: Severity,
+    /// What changed, e.g. `fn foo::bar` or `impl Clone for foo::Bar`.
+    pub item: String,
+    pub description: String,
+}
+
+/// The changes between two versions of a crate.
+#[derive(RustcEncodable)]
+pub struct ApiDiff {
+    pub changes: Vec<Change>,
+}
+
+impl ApiDiff {
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(|c| c.severity == Severity::Breaking)
+    }
+}
+
+/// The items of one version of the API.
+struct Api<'a> {
+    krate: &'a str,
+    /// Items by namespace and path.
+    items: BTreeMap<(String, &'static str), &'a Json>,
+    /// Trait impls, by `Trait for Type`.
+    impls: BTreeMap<String, &'a Json>,
+    /// The paths of all items, to tell if the parent of an item exists.
+    paths: HashSet<String>,
+    /// All items, by id.
+    ids: HashMap<(u64, u64), &'a Json>,
+    /// The paths of the items types can refer to, by id. Items of this crate
+    /// are named without the crate name, external ones with it.
+    resolved: HashMap<(u64, u64), String>,
+}
+
+// The crate number and index of an item id.
+fn id(j: &Json) -> Option<(u64, u64)> {
+    match (j.find("krate").and_then(|k| k.as_u64()), j.find("index").and_then(|i| i.as_u64())) {
+        (Some(krate), Some(index)) => Some((krate, index)),
+        _ => None,
+    }
+}
+
+fn string<'a>(j: &'a Json, key: &str) -> &'a str {
+    j.find(key).and_then(|s| s.as_string()).unwrap_or("")
+}
+
+fn array<'a>(j: &'a Json, key: &str) -> &'a [Json] {
+    match j.find(key).and_then(|a| a.as_array()) {
+        Some(a) => a.as_slice(),
+        None => &[],
+    }
+}
+
+fn boolean(j: &Json, key: &str) -> bool {
+    j.find(key).and_then(|b| b.as_boolean()).unwrap_or(false)
+}
+
+fn inner<'a>(item: &'a Json) -> &'a Json {
+    item.find("inner").unwrap_or(item)
+}
+
+// A path given as an array of names, without the first `skip` of them.
+fn join(path: &[Json], skip: uint) -> String {
+    path.iter().skip(skip).filter_map(|s| s.as_string()).collect::<Vec<&str>>().connect("::")
+}
+
+// The path of the item or module containing the item at `path`.
+fn parent(path: &str) -> &str {
+    match path.rfind(':') {
+        Some(i) => path.slice_to(i - 1),
+        None => "",
+    }
+}
+
+// Items of different namespaces can share a path.
+fn namespace(kind: &str) -> &'static str {
+    match kind {
+        "mod" | "struct" | "enum" | "trait" | "type" | "primitive" => "type",
+        "fn" | "static" | "constant" => "value",
+        "method" | "tymethod" => "method",
+        "structfield" => "field",
+        "variant" => "variant",
+        "associatedtype" => "associatedtype",
+        _ => "macro",
+    }
+}
+
+impl<'a> Api<'a> {
+    fn new(doc: &'a Json) -> Result<Api<'a>, String> {
+        let index = match doc.find("index").and_then(|i| i.as_array()) {
+            Some(index) => index,
+            None => return Err("malformed json: expected an index of items".to_string()),
+        };
+        let krate = doc.find("crate").map_or("", |c| string(c, "name"));
+        let mut api = Api {
+            krate: krate,
+            items: BTreeMap::new(),
+            impls: BTreeMap::new(),
+            paths: HashSet::new(),
+            ids: HashMap::new(),
+            resolved: HashMap::new(),
+        };
+        for path in array(doc, "paths").iter() {
+            if let (Some(did), Some(p)) = (path.find("id").and_then(id),
+                                           path.find("path").and_then(|p| p.as_array())) {
+                api.resolved.insert(did, join(p.as_slice(), 0));
+            }
+        }
+        for item in index.iter() {
+            if let Some(did) = item.find("id").and_then(id) {
+                api.ids.insert(did, item);
+                // The path starts with the crate name, which may differ.
+                if let Some(path) = item.find("path").and_then(|p| p.as_array()) {
+                    api.resolved.insert(did, join(path.as_slice(), 1));
+                }
+            }
+        }
+
+        api.paths.insert(String::new());
+        for item in index.iter() {
+            if let Some(path) = item.find("path").and_then(|p| p.as_array()) {
+                let path = join(path.as_slice(), 1);
+                if !path.is_empty() {
+                    api.insert(path, item);
+                }
+                continue;
+            }
+            if string(item, "kind") != "impl" {
+                continue;
+            }
+            let imp = inner(item);
+            let for_ = imp.find("for").map_or(String::new(), |t| ty(&api, t));
+            match imp.find("trait") {
+                Some(trait_) if !trait_.is_null() => {
+                    let key = format!("{} for {}", ty(&api, trait_), for_);
+                    api.impls.insert(key, item);
+                }
+                _ => {
+                    // The methods of inherent impls are named after the type.
+                    let for_ = imp.find("for").and_then(|t| t.find("id")).and_then(id)
+                                  .and_then(|id| api.resolved.get(&id).cloned())
+                                  .unwrap_or(for_);
+                    for item in array(imp, "items").iter() {
+                        let item = id(item).and_then(|id| api.ids.get(&id).map(|&i| i));
+                        if let Some(item) = item {
+                            api.insert(format!("{}::{}", for_, string(item, "name")), item);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(api)
+    }
+
+    fn insert(&mut self, path: String, item: &'a Json) {
+        self.paths.insert(path.clone());
+        self.items.insert((path, namespace(string(item, "kind"))), item);
+    }
+
+    // Whether the item or module containing the item at `path` exists.
+    fn has_parent(&self, path: &str) -> bool {
+        self.paths.contains(parent(path))
+    }
+}
+
+/// Compares the API described by `old` to that described by `new`, both
+/// documents written by `rustdoc --output-format api-json`.
+pub fn diff(old: &Json, new: &Json) -> Result<ApiDiff, String> {
+    let old = try!(Api::new(old));
+    let new = try!(Api::new(new));
+    let mut changes = Vec::new();
+    {
+        let mut change = |&mut: severity: Severity, item: &Json, path: &str, description: String| {
+            changes.push(Change {
+                severity: severity,
+                item: format!("{} {}::{}", string(item, "kind"), new.krate, path),
+                description: description,
+            });
+        };
+
+        for (&(ref path, ns), old_item) in old.items.iter() {
+            match new.items.get(&(path.clone(), ns)) {
+                Some(new_item) => {
+                    let changes = compare(&old, *old_item, &new, *new_item);
+                    for (severity, description) in changes.into_iter() {
+                        change(severity, *new_item, path.as_slice(), description);
+                    }
+                }
+                // An item whose parent is gone is reported with it.
+                None if new.has_parent(path.as_slice()) => {
+                    change(Severity::Breaking, *old_item, path.as_slice(),
+                           "removed".to_string());
+                }
+                None => {}
+            }
+        }
+
+        for (&(ref path, ns), new_item) in new.items.iter() {
+            if old.items.contains_key(&(path.clone(), ns)) ||
+               !old.has_parent(path.as_slice()) {
+                continue;
+            }
+            let severity = match string(*new_item, "kind") {
+                // Implementors of the trait would have to implement it.
+                "tymethod" => Severity::Breaking,
+                "associatedtype" if inner(*new_item).find("default")
+                                                    .map_or(true, |d| d.is_null()) => {
+                    Severity::Breaking
+                }
+                // Matches of the enum would not be exhaustive anymore.
+                "variant" => Severity::Breaking,
+                // Struct literals would miss the field, unless some fields
+                // were private already.
+                "structfield" => {
+                    match old.items.get(&(parent(path.as_slice()).to_string(), "type")) {
+                        Some(s) if !boolean(inner(*s), "fields_stripped") => Severity::Breaking,
+                        _ => Severity::Compatible,
+                    }
+                }
+                _ => Severity::Compatible,
+            };
+            change(severity, *new_item, path.as_slice(), "added".to_string());
+        }
+    }
+
+    for (name, _) in old.impls.iter() {
+        if !new.impls.contains_key(name) {
+            changes.push(Change {
+                severity: Severity::Breaking,
+                item: format!("impl {}", name),
+                description: "removed".to_string(),
+            });
+        }
+    }
+    for (name, _) in new.impls.iter() {
+        if !old.impls.contains_key(name) {
+            changes.push(Change {
+                severity: Severity::Compatible,
+                item: format!("impl {}", name),
+                description: "added".to_string(),
+            });
+        }
+    }
+
+    Ok(ApiDiff { changes: changes })
+}
+
+/// The changes between two versions of the same item.
+fn compare(old_api: &Api, old: &Json, new_api: &Api, new: &Json) -> Vec<(Severity, String)> {
+    let mut changes = Vec::new();
+    let (old_kind, new_kind) = (string(old, "kind"), string(new, "kind"));
+    match (old_kind, new_kind) {
+        ("tymethod", "method") => {
+            changes.push((Severity::Compatible,
+                          "a default implementation was added".to_string()));
+        }
+        ("method", "tymethod") => {
+            changes.push((Severity::Breaking,
+                          "its default implementation was removed".to_string()));
+        }
+        (a, b) if a != b => {
+            changes.push((Severity::Breaking, format!("changed from a {} to a {}", a, b)));
+            return changes;
+        }
+        _ => {}
+    }
+
+    let (old_sig, new_sig) = (signature(old_api, old), signature(new_api, new));
+    if old_sig != new_sig {
+        changes.push((Severity::Breaking,
+                      format!("changed from `{}` to `{}`", old_sig, new_sig)));
+    }
+
+    let (old_reqs, new_reqs) = (requirements(old_api, old), requirements(new_api, new));
+    for req in new_reqs.iter().filter(|r| !old_reqs.contains(*r)) {
+        changes.push((Severity::Breaking, format!("added bound `{}`", req)));
+    }
+    for req in old_reqs.iter().filter(|r| !new_reqs.contains(*r)) {
+        // Users of a trait may rely on its supertraits.
+        let severity = if new_kind == "trait" && req.starts_with("Self: ") {
+            Severity::Breaking
+        } else {
+            Severity::Compatible
+        };
+        changes.push((severity, format!("removed bound `{}`", req)));
+    }
+
+    if new_kind == "struct" && !boolean(inner(old), "fields_stripped") &&
+       boolean(inner(new), "fields_stripped") {
+        changes.push((Severity::Breaking,
+                      "private fields were added, so it can't be built by users".to_string()));
+    }
+
+    let level = |&: item: &Json| -> String {
+        item.find("stability").map_or(String::new(), |s| string(s, "level").to_string())
+    };
+    let (old_level, new_level) = (level(old), level(new));
+    if old_level != new_level && !new_level.is_empty() {
+        changes.push((Severity::Compatible, format!("became {}", new_level)));
+    }
+    changes
+}
+
+/// The signature of an item, without the bounds of its generics.
+fn signature(api: &Api, item: &Json) -> String {
+    let i = inner(item);
+    let name = string(item, "name");
+    let mut_ = |&: j: &Json| if boolean(j, "mutable") { "mut " } else { "" };
+    let of = |&: j: &Json, key: &str| j.find(key).map_or(String::new(), |t| ty(api, t));
+    match string(item, "kind") {
+        "fn" | "method" | "tymethod" => {
+            let self_ = i.find("self").map_or(String::new(), |s| self_ty(api, s));
+            format!("{}fn {}{}{}", if boolean(i, "unsafe") { "unsafe " } else { "" }, name,
+                    params(api, i.find("generics")), decl(api, i.find("decl"), self_))
+        }
+        "struct" => {
+            let g = params(api, i.find("generics"));
+            match string(i, "struct_type") {
+                "unit" => format!("struct {}{};", name, g),
+                "plain" => format!("struct {}{} {{ .. }}", name, g),
+                // The fields of tuple structs have no name, so they are part
+                // of the signature.
+                _ => format!("struct {}{}({});", name, g, tuple_fields(api, i)),
+            }
+        }
+        "variant" => {
+            match string(i, "kind") {
+                "tuple" => format!("{}({})", name, types(api, array(i, "types"))),
+                "struct" => format!("{} {{ .. }}", name),
+                _ => name.to_string(),
+            }
+        }
+        "enum" => format!("enum {}{}", name, params(api, i.find("generics"))),
+        "trait" => {
+            format!("{}trait {}{}", if boolean(i, "unsafe") { "unsafe " } else { "" }, name,
+                    params(api, i.find("generics")))
+        }
+        "type" => {
+            format!("type {}{} = {}", name, params(api, i.find("generics")), of(i, "type"))
+        }
+        "static" => format!("static {}{}: {}", mut_(i), name, of(i, "type")),
+        "constant" => format!("const {}: {}", name, of(i, "type")),
+        "structfield" => format!("{}: {}", name, of(i, "type")),
+        kind => format!("{} {}", kind, name),
+    }
+}
+
+// The types of the fields of a tuple struct, in order.
+fn tuple_fields(api: &Api, s: &Json) -> String {
+    let mut fields = array(s, "fields").iter().map(|f| {
+        match id(f).and_then(|id| api.ids.get(&id)) {
+            Some(f) => inner(*f).find("type").map_or("_".to_string(), |t| ty(api, t)),
+            None => "_".to_string(),
+        }
+    }).collect::<Vec<String>>();
+    if boolean(s, "fields_stripped") { fields.push("..".to_string()); }
+    fields.connect(", ")
+}
+
+/// The bounds an item puts on its generics, each as `T: Bound`.
+fn requirements(api: &Api, item: &Json) -> Vec<String> {
+    let i = inner(item);
+    let mut reqs = Vec::new();
+    if let Some(g) = i.find("generics") {
+        for p in array(g, "params").iter() {
+            for b in array(p, "bounds").iter() {
+                reqs.push(format!("{}: {}", string(p, "name"), bound(api, b)));
+            }
+        }
+        for p in array(g, "where_predicates").iter() {
+            match string(p, "kind") {
+                "bound" => {
+                    let t = p.find("type").map_or(String::new(), |t| ty(api, t));
+                    for b in array(p, "bounds").iter() {
+                        reqs.push(format!("{}: {}", t, bound(api, b)));
+                    }
+                }
+                "region" => {
+                    for b in array(p, "bounds").iter() {
+                        reqs.push(format!("{}: {}", string(p, "lifetime"),
+                                          b.as_string().unwrap_or("")));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    // Supertraits and the bounds of associated types.
+    let self_ = match string(item, "kind") {
+        "associatedtype" => format!("Self::{}", string(item, "name")),
+        _ => "Self".to_string(),
+    };
+    for b in array(i, "bounds").iter() {
+        reqs.push(format!("{}: {}", self_, bound(api, b)));
+    }
+    reqs
+}
+
+fn params(api: &Api, generics: Option<&Json>) -> String {
+    let g = match generics {
+        Some(g) => g,
+        None => return String::new(),
+    };
+    let mut names = array(g, "lifetimes").iter().filter_map(|l| l.as_string())
+                                          .map(|l| l.to_string()).collect::<Vec<String>>();
+    for p in array(g, "params").iter() {
+        names.push(match p.find("default") {
+            Some(d) if !d.is_null() => format!("{} = {}", string(p, "name"), ty(api, d)),
+            _ => string(p, "name").to_string(),
+        });
+    }
+    if names.is_empty() { String::new() } else { format!("<{}>", names.connect(", ")) }
+}
+
+fn self_ty(api: &Api, s: &Json) -> String {
+    match string(s, "kind") {
+        "value" => "self".to_string(),
+        "ref" => {
+            let lifetime = s.find("lifetime").and_then(|l| l.as_string())
+                            .map_or(String::new(), |l| format!("{} ", l));
+            format!("&{}{}self", lifetime, if boolean(s, "mutable") { "mut " } else { "" })
+        }
+        "explicit" => format!("self: {}", s.find("type").map_or(String::new(), |t| ty(api, t))),
+        _ => String::new(),
+    }
+}
+
+fn decl(api: &Api, d: Option<&Json>, self_: String) -> String {
+    let d = match d {
+        Some(d) => d,
+        None => return "()".to_string(),
+    };
+    let mut inputs = if self_.is_empty() { Vec::new() } else { vec![self_] };
+    for arg in array(d, "inputs").iter() {
+        let t = arg.find("type").map_or(String::new(), |t| ty(api, t));
+        inputs.push(match string(arg, "name") {
+            "" | "_" => t,
+            name => format!("{}: {}", name, t),
+        });
+    }
+    let output = match d.find("output") {
+        Some(o) if !(string(o, "kind") == "tuple" && array(o, "types").is_empty()) => {
+            format!(" -> {}", ty(api, o))
+        }
+        _ => String::new(),
+    };
+    format!("({}){}", inputs.connect(", "), output)
+}
+
+fn types(api: &Api, ts: &[Json]) -> String {
+    ts.iter().map(|t| ty(api, t)).collect::<Vec<String>>().connect(", ")
+}
+
+fn bound(api: &Api, b: &Json) -> String {
+    match string(b, "kind") {
+        "lifetime" => string(b, "lifetime").to_string(),
+        _ => {
+            let lifetimes = array(b, "lifetimes").iter().filter_map(|l| l.as_string())
+                                                 .collect::<Vec<&str>>();
+            format!("{}{}{}",
+                    if boolean(b, "maybe") { "?" } else { "" },
+                    if lifetimes.is_empty() {
+                        String::new()
+                    } else {
+                        format!("for<{}> ", lifetimes.connect(", "))
+                    },
+                    b.find("trait").map_or(String::new(), |t| ty(api, t)))
+        }
+    }
+}
+
+/// A type, as it would be written in Rust, but with the full path of the
+/// items it refers to.
+fn ty(api: &Api, t: &Json) -> String {
+    let of = |&: key: &str| t.find(key).map_or(String::new(), |t| ty(api, t));
+    let mut_ = if boolean(t, "mutable") { "mut " } else { "" };
+    match string(t, "kind") {
+        "resolved_path" => {
+            let args = t.find("args").map_or(String::new(), |a| {
+                match string(a, "kind") {
+                    "angle_bracketed" => {
+                        let mut args = array(a, "lifetimes").iter()
+                            .filter_map(|l| l.as_string()).map(|l| l.to_string())
+                            .collect::<Vec<String>>();
+                        args.extend(array(a, "types").iter().map(|t| ty(api, t)));
+                        if args.is_empty() {
+                            String::new()
+                        } else {
+                            format!("<{}>", args.connect(", "))
+                        }
+                    }
+                    "parenthesized" => {
+                        let output = a.find("output").and_then(|o| {
+                            if o.is_null() { None } else { Some(format!(" -> {}", ty(api, o))) }
+                        });
+                        format!("({}){}", types(api, array(a, "inputs")),
+                                output.unwrap_or(String::new()))
+                    }
+                    _ => String::new(),
+                }
+            });
+            // Items missing from the documentation keep the path they were
+            // written with.
+            let name = t.find("id").and_then(id).and_then(|id| api.resolved.get(&id))
+                        .map_or(string(t, "name"), |p| p.as_slice());
+            let bounds = array(t, "bounds").iter().map(|b| bound(api, b))
+                                           .collect::<Vec<String>>();
+            if bounds.is_empty() {
+                format!("{}{}", name, args)
+            } else {
+                format!("{}{} + {}", name, args, bounds.connect(" + "))
+            }
+        }
+        "generic" | "primitive" => string(t, "name").to_string(),
+        "tuple" => {
+            let ts = array(t, "types");
+            if ts.len() == 1 {
+                format!("({},)", ty(api, &ts[0]))
+            } else {
+                format!("({})", types(api, ts))
+            }
+        }
+        "slice" => format!("[{}]", of("type")),
+        "array" => format!("[{}; {}]", of("type"), string(t, "len")),
+        "ref" => {
+            let lifetime = t.find("lifetime").and_then(|l| l.as_string())
+                            .map_or(String::new(), |l| format!("{} ", l));
+            format!("&{}{}{}", lifetime, mut_, of("type"))
+        }
+        "raw_pointer" => {
+            format!("*{}{}", if boolean(t, "mutable") { "mut " } else { "const " }, of("type"))
+        }
+        "box" => format!("Box<{}>", of("type")),
+        "function" => {
+            let abi = match string(t, "abi") {
+                "" | "Rust" => String::new(),
+                abi => format!("extern \"{}\" ", abi),
+            };
+            format!("{}{}fn{}", if boolean(t, "unsafe") { "unsafe " } else { "" }, abi,
+                    decl(api, t.find("decl"), String::new()))
+        }
+        "closure" | "proc" => {
+            format!("{}{}", string(t, "kind"), decl(api, t.find("decl"), String::new()))
+        }
+        "qualified_path" => {
+            format!("<{} as {}>::{}", of("self_type"), of("trait"), string(t, "name"))
+        }
+        "trait_object" => array(t, "bounds").iter().map(|b| bound(api, b))
+                                            .collect::<Vec<String>>().connect(" + "),
+        "never" => "!".to_string(),
+        _ => "_".to_string(),
+    }
+}
+
+//NOTE(stage0): remove impl after snapshot
+#[cfg(stage0)]
+impl fmt::Show for ApiDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::String::fmt(self, f)
+    }
+}
+
+/// Lists the breaking changes, then the compatible ones, and a summary.
+impl fmt::String for ApiDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut counts = [0u, 0u];
+        for (i, &(severity, label)) in [(Severity::Breaking, "breaking"),
+                                        (Severity::Compatible, "compatible")].iter().enumerate() {
+            for c in self.changes.iter().filter(|c| c.severity == severity) {
+                try!(writeln!(f, "{}: {}: {}", label, c.item, c.description));
+                counts[i] += 1;
+            }
+        }
+        writeln!(f, "{} breaking changes, {} compatible changes", counts[0], counts[1])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serialize::json;
+    use super::{diff, ty, Api, Severity};
+
+    fn doc(items: &str) -> json::Json {
+        doc_with_paths(items, "")
+    }
+
+    fn doc_with_paths(items: &str, paths: &str) -> json::Json {
+        json::from_str(format!("{{\"format_version\":1,\"crate\":{{\"name\":\"foo\"}},\
+                                 \"index\":[{}],\"paths\":[{}]}}",
+                               items, paths).as_slice()).unwrap()
+    }
+
+    fn fun(index: uint, output: &str) -> String {
+        format!("{{\"id\":{{\"krate\":0,\"index\":{}}},\"name\":\"f\",\"path\":[\"foo\",\"f\"],\
+                 \"kind\":\"fn\",\"inner\":{{\"decl\":{{\"inputs\":[],\"output\":{}}},\
+                 \"generics\":{{\"lifetimes\":[],\"params\":[],\"where_predicates\":[]}},\
+                 \"unsafe\":false}}}}", index, output)
+    }
+
+    // A tuple struct `M` with one field of type `field`.
+    fn tuple_struct(field: &str) -> String {
+        format!("{{\"id\":{{\"krate\":0,\"index\":1}},\"name\":\"M\",\"path\":[\"foo\",\"M\"],\
+                 \"kind\":\"struct\",\"inner\":{{\"struct_type\":\"tuple\",\
+                 \"generics\":{{\"lifetimes\":[],\"params\":[],\"where_predicates\":[]}},\
+                 \"fields\":[{{\"krate\":0,\"index\":2}}],\"fields_stripped\":false}}}},\
+                 {{\"id\":{{\"krate\":0,\"index\":2}},\"name\":null,\"path\":null,\
+                 \"kind\":\"structfield\",\"inner\":{{\"type\":{}}}}}", field)
+    }
+
+    // A type written as `name`, that resolves to the item `krate:index`.
+    fn resolved(name: &str, krate: uint, index: uint) -> String {
+        format!("{{\"kind\":\"resolved_path\",\"name\":\"{}\",\
+                 \"id\":{{\"krate\":{},\"index\":{}}},\"args\":null,\"bounds\":null}}",
+                name, krate, index)
+    }
+
+    #[test]
+    fn types() {
+        let t = json::from_str("{\"kind\":\"ref\",\"lifetime\":\"'a\",\"mutable\":true,\
+                                 \"type\":{\"kind\":\"slice\",\"type\":{\"kind\":\"generic\",\
+                                 \"name\":\"T\"}}}").unwrap();
+        let doc = doc("");
+        assert_eq!(ty(&Api::new(&doc).unwrap(), &t).as_slice(), "&'a mut [T]");
+    }
+
+    #[test]
+    fn changed_tuple_field() {
+        let old = tuple_struct("{\"kind\":\"primitive\",\"name\":\"u32\"}");
+        let new = tuple_struct("{\"kind\":\"primitive\",\"name\":\"i64\"}");
+        let d = diff(&doc(old.as_slice()), &doc(new.as_slice())).unwrap();
+        assert_eq!(d.changes.len(), 1);
+        assert_eq!(d.changes[0].severity, Severity::Breaking);
+        assert_eq!(d.changes[0].description.as_slice(),
+                   "changed from `struct M(u32);` to `struct M(i64);`");
+    }
+
+    #[test]
+    fn resolved_paths() {
+        let paths = "{\"id\":{\"krate\":1,\"index\":5},\"path\":[\"bar\",\"Baz\"],\
+                     \"kind\":\"struct\"},\
+                     {\"id\":{\"krate\":1,\"index\":6},\"path\":[\"bar\",\"quux\",\"Baz\"],\
+                     \"kind\":\"struct\"}";
+        // The same type, written with another path.
+        let old = fun(1, resolved("Baz", 1, 5).as_slice());
+        let new = fun(1, resolved("bar::Baz", 1, 5).as_slice());
+        let d = diff(&doc_with_paths(old.as_slice(), paths),
+                     &doc_with_paths(new.as_slice(), paths)).unwrap();
+        assert_eq!(d.changes.len(), 0);
+        // Another type, written with the same path.
+        let new = fun(1, resolved("Baz", 1, 6).as_slice());
+        let d = diff(&doc_with_paths(old.as_slice(), paths),
+                     &doc_with_paths(new.as_slice(), paths)).unwrap();
+        assert_eq!(d.changes.len(), 1);
+        assert_eq!(d.changes[0].description.as_slice(),
+                   "changed from `fn f() -> bar::Baz` to `fn f() -> bar::quux::Baz`");
+    }
+
+    #[test]
+    fn changed_signature() {
+        let unit = fun(1, "{\"kind\":\"tuple\",\"types\":[]}");
+        let int = fun(1, "{\"kind\":\"primitive\",\"name\":\"isize\"}");
+        let d = diff(&doc(unit.as_slice()), &doc(int.as_slice())).unwrap();
+        assert_eq!(d.changes.len(), 1);
+        assert_eq!(d.changes[0].severity, Severity::Breaking);
+        assert_eq!(d.changes[0].item.as_slice(), "fn foo::f");
+        assert_eq!(d.changes[0].description.as_slice(),
+                   "changed from `fn f()` to `fn f() -> isize`");
+    }
+
+    #[test]
+    fn added_and_removed() {
+        let unit = fun(1, "{\"kind\":\"tuple\",\"types\":[]}");
+        let d = diff(&doc(""), &doc(unit.as_slice())).unwrap();
+        assert!(!d.is_breaking());
+        let d = diff(&doc(unit.as_slice()), &doc("")).unwrap();
+        assert!(d.is_breaking());
+        assert_eq!(d.changes[0].description.as_slice(), "removed");
+    }
+}
@@ -0,0 +1,342 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Resolution of intra-doc links, the links of documentation whose target
+//! is a Rust path rather than a URL.
+//!
+//! A path is resolved like a path in an expression of the module containing
+//! the documented item: its first segment names an item, import or extern
+//! crate of that module, or of the crate root failing that, unless it is
+//! `crate`, `self` or `super`. The last segment may name a variant, field,
+//! method or associated type of a type or trait.
+
+use syntax::ast;
+use syntax::ast_map;
+use syntax::ast_util;
+use syntax::attr::AttrMetaMethods;
+use syntax::codemap::Span;
+use syntax::parse::token;
+
+use rustc::metadata::csearch;
+use rustc::metadata::decoder;
+use rustc::middle::def;
+use rustc::middle::ty;
+
+use core::DocContext;
+use clean;
+use html::markdown;
+
+use super::register_def;
+
+// Glob imports are followed this deep, which also stops cycles of them.
+const MAX_GLOB_DEPTH: uint = 8;
+
+/// Resolves the intra-doc links of every item under `module`, and warns about
+/// those that name nothing.
+pub fn resolve_crate(cx: &DocContext, module: &clean::Item) -> Vec<clean::DocLink> {
+    let mut links = Vec::new();
+    if cx.tcx_opt().is_some() {
+        walk(cx, ast::CRATE_NODE_ID, module, &mut links);
+    }
+    links
+}
+
+fn walk(cx: &DocContext, scope: ast::NodeId, item: &clean::Item,
+        links: &mut Vec<clean::DocLink>) {
+    // A module's documentation, inner or outer, is resolved within it.
+    let scope = match item.inner {
+        clean::ModuleItem(..) if ast_util::is_local(item.def_id) => item.def_id.node,
+        _ => scope,
+    };
+
+    // The documentation is only collapsed into one attribute by a later pass.
+    let doc = item.attrs.iter().filter_map(|attr| {
+        match *attr {
+            clean::NameValue(ref name, ref value) if "doc" == *name => Some(value.as_slice()),
+            _ => None,
+        }
+    }).collect::<Vec<&str>>().connect("\n");
+
+    for link in markdown::collect_links(doc.as_slice()).into_iter() {
+        if links.iter().any(|l| l.item == item.def_id && l.target == link.target) {
+            continue;
+        }
+        match resolve(cx, scope, link.target.as_slice()) {
+            Some((def, fragment)) => {
+                links.push(clean::DocLink {
+                    item: item.def_id,
+                    target: link.target,
+                    did: register_def(cx, def),
+                    fragment: fragment,
+                });
+            }
+            None => {
+                let span = match cx.tcx_opt() {
+                    _ if item.def_id == ast_util::local_def(ast::CRATE_NODE_ID) => {
+                        Some(crate_doc_span(cx, link.target.as_slice()))
+                    }
+                    Some(tcx) if ast_util::is_local(item.def_id) => {
+                        tcx.map.opt_span(item.def_id.node)
+                    }
+                    _ => None,
+                };
+                cx.sess().opt_span_warn(span, format!("unresolved link to `{}`",
+                                                      link.target).as_slice());
+            }
+        }
+    }
+
+    let children: Vec<&clean::Item> = match item.inner {
+        clean::ModuleItem(ref m) => m.items.iter().collect(),
+        clean::StructItem(ref s) => s.fields.iter().collect(),
+        clean::EnumItem(ref e) => e.variants.iter().collect(),
+        clean::TraitItem(ref t) => t.items.iter().map(|m| m.item()).collect(),
+        clean::ImplItem(ref i) => i.items.iter().collect(),
+        clean::VariantItem(clean::Variant { kind: clean::StructVariant(ref s) }) => {
+            s.fields.iter().collect()
+        }
+        _ => Vec::new(),
+    };
+    for child in children.into_iter() {
+        walk(cx, scope, child, links);
+    }
+}
+
+/// The span of the crate's doc comment that mentions `target`, or else of the
+/// whole crate.
+fn crate_doc_span(cx: &DocContext, target: &str) -> Span {
+    cx.krate.attrs.iter().find(|attr| {
+        attr.check_name("doc") && attr.value_str().map_or(false, |doc| doc.contains(target))
+    }).map_or(cx.krate.span, |attr| attr.span)
+}
+
+/// Resolves `path` within the module `scope`, to the item it names and the
+/// anchor of the member it names on that item's page, if any.
+fn resolve(cx: &DocContext, scope: ast::NodeId, path: &str)
+           -> Option<(def::Def, Option<String>)> {
+    let tcx = cx.tcx();
+    let root = def::DefMod(ast_util::local_def(ast::CRATE_NODE_ID));
+    let segments: Vec<&str> = path.split_str("::").collect();
+
+    let (mut def, start) = match segments[0] {
+        "" | "crate" => (root, 1),
+        "self" => (def::DefMod(ast_util::local_def(scope)), 1),
+        "super" => {
+            let mut module = scope;
+            let mut n = 0;
+            while n < segments.len() && segments[n] == "super" {
+                if module == ast::CRATE_NODE_ID { return None }
+                module = tcx.map.get_parent(module);
+                n += 1;
+            }
+            (def::DefMod(ast_util::local_def(module)), n)
+        }
+        first => {
+            let found = lookup(cx, ast_util::local_def(scope), first, MAX_GLOB_DEPTH);
+            match found.or_else(|| lookup(cx, root.def_id(), first, MAX_GLOB_DEPTH)) {
+                Some(def) => (def, 1),
+                None => return None,
+            }
+        }
+    };
+
+    let rest = segments.slice_from(start);
+    for (i, &segment) in rest.iter().enumerate() {
+        def = match def {
+            def::DefMod(did) => match lookup(cx, did, segment, MAX_GLOB_DEPTH) {
+                Some(def) => def,
+                None => return None,
+            },
+            def::DefTy(..) | def::DefStruct(..) | def::DefTrait(..) if i == rest.len() - 1 => {
+                return member(tcx, def, segment).map(|fragment| (def, Some(fragment)));
+            }
+            _ => return None,
+        };
+    }
+    Some((def, None))
+}
+
+/// Finds the item or import named `name` in the module `did`.
+fn lookup(cx: &DocContext, did: ast::DefId, name: &str, depth: uint) -> Option<def::Def> {
+    let tcx = cx.tcx();
+    if !ast_util::is_local(did) {
+        // Items of the type namespace are favored, so that a path can go on.
+        let mut found = Vec::new();
+        {
+            let mut each = |&mut: def: decoder::DefLike, n: ast::Name, _| {
+                match def {
+                    decoder::DlDef(def) if token::get_name(n).get() == name => found.push(def),
+                    _ => {}
+                }
+            };
+            if did.node == ast::CRATE_NODE_ID {
+                csearch::each_top_level_item_of_crate(&tcx.sess.cstore, did.krate, each);
+            } else {
+                csearch::each_child_of_item(&tcx.sess.cstore, did, each);
+            }
+        }
+        let ty = found.iter().find(|def| is_type(**def)).map(|def| *def);
+        return ty.or(found.first().map(|def| *def));
+    }
+
+    let module = if did.node == ast::CRATE_NODE_ID {
+        &cx.krate.module
+    } else {
+        match tcx.map.find(did.node) {
+            Some(ast_map::NodeItem(item)) => match item.node {
+                ast::ItemMod(ref m) => m,
+                _ => return None,
+            },
+            _ => return None,
+        }
+    };
+
+    for item in module.items.iter() {
+        if let ast::ItemForeignMod(ref fm) = item.node {
+            for fi in fm.items.iter() {
+                if token::get_ident(fi.ident).get() == name {
+                    let fdid = ast_util::local_def(fi.id);
+                    return Some(match fi.node {
+                        ast::ForeignItemFn(..) => def::DefFn(fdid, false),
+                        ast::ForeignItemStatic(_, mutbl) => def::DefStatic(fdid, mutbl),
+                    });
+                }
+            }
+        } else if token::get_ident(item.ident).get() == name {
+            if let Some(def) = item_def(&**item) {
+                return Some(def);
+            }
+        }
+    }
+
+    let mut globs = Vec::new();
+    for vi in module.view_items.iter() {
+        let id = match vi.node {
+            ast::ViewItemExternCrate(ident, _, id) if token::get_ident(ident).get() == name => {
+                return tcx.sess.cstore.find_extern_mod_stmt_cnum(id).map(|cnum| {
+                    def::DefMod(ast::DefId { krate: cnum, node: ast::CRATE_NODE_ID })
+                });
+            }
+            ast::ViewItemExternCrate(..) => continue,
+            ast::ViewItemUse(ref vp) => match vp.node {
+                ast::ViewPathSimple(ident, _, id) if token::get_ident(ident).get() == name => id,
+                ast::ViewPathSimple(..) => continue,
+                ast::ViewPathList(_, ref list, _) => {
+                    let found = list.iter().find(|item| match item.node {
+                        ast::PathListIdent { name: ident, .. } => {
+                            token::get_ident(ident).get() == name
+                        }
+                        ast::PathListMod { .. } => false,
+                    });
+                    match found {
+                        Some(item) => item.node.id(),
+                        None => continue,
+                    }
+                }
+                ast::ViewPathGlob(_, id) => {
+                    globs.push(id);
+                    continue;
+                }
+            },
+        };
+        return tcx.def_map.borrow().get(&id).map(|def| *def);
+    }
+
+    if depth == 0 { return None }
+    for id in globs.into_iter() {
+        let def = tcx.def_map.borrow().get(&id).map(|def| *def);
+        if let Some(def::DefMod(gdid)) = def {
+            if let Some(def) = lookup(cx, gdid, name, depth - 1) {
+                return Some(def);
+            }
+        }
+    }
+    None
+}
+
+fn item_def(item: &ast::Item) -> Option<def::Def> {
+    let did = ast_util::local_def(item.id);
+    Some(match item.node {
+        ast::ItemFn(..) => def::DefFn(did, false),
+        ast::ItemStruct(..) => def::DefStruct(did),
+        ast::ItemEnum(..) => def::DefTy(did, true),
+        ast::ItemTy(..) => def::DefTy(did, false),
+        ast::ItemTrait(..) => def::DefTrait(did),
+        ast::ItemMod(..) => def::DefMod(did),
+        ast::ItemStatic(_, mutbl, _) => def::DefStatic(did, mutbl == ast::MutMutable),
+        ast::ItemConst(..) => def::DefConst(did),
+        ast::ItemForeignMod(..) | ast::ItemImpl(..) | ast::ItemMac(..) => return None,
+    })
+}
+
+fn is_type(def: def::Def) -> bool {
+    match def {
+        def::DefMod(..) | def::DefTy(..) | def::DefStruct(..) | def::DefTrait(..) => true,
+        _ => false,
+    }
+}
+
+/// The anchor of the member `name` of the type or trait `def`, as rendered on
+/// its page.
+fn member(tcx: &ty::ctxt, def: def::Def, name: &str) -> Option<String> {
+    let did = def.def_id();
+    let named = |&: n: ast::Name| token::get_name(n).get() == name;
+
+    match def {
+        def::DefTrait(..) => {
+            let provided = ty::provided_trait_methods(tcx, did);
+            return ty::trait_items(tcx, did).iter().find(|item| named(item.name())).map(|item| {
+                match *item {
+                    ty::MethodTraitItem(ref m) if provided.iter().any(|p| p.def_id == m.def_id) => {
+                        format!("method.{}", name)
+                    }
+                    ty::MethodTraitItem(..) => format!("tymethod.{}", name),
+                    ty::TypeTraitItem(..) => format!("assoc_type.{}", name),
+                }
+            });
+        }
+        def::DefTy(_, true) => {
+            if ty::enum_variants(tcx, did).iter().any(|v| named(v.name)) {
+                return Some(format!("variant.{}", name));
+            }
+        }
+        def::DefStruct(..) => {
+            if ty::lookup_struct_fields(tcx, did).iter().any(|f| named(f.name)) {
+                return Some(format!("structfield.{}", name));
+            }
+        }
+        _ => {}
+    }
+
+    // Methods and associated types of inherent impls.
+    ty::populate_implementations_for_type_if_necessary(tcx, did);
+    let impls = match tcx.inherent_impls.borrow().get(&did) {
+        Some(impls) => impls.clone(),
+        None => return None,
+    };
+    for impl_did in impls.iter() {
+        let items = match tcx.impl_items.borrow().get(impl_did) {
+            Some(items) => items.clone(),
+            None => continue,
+        };
+        for item in items.iter() {
+            match ty::impl_or_trait_item(tcx, item.def_id()) {
+                ty::MethodTraitItem(ref m) if named(m.name) => {
+                    return Some(format!("method.{}", name));
+                }
+                ty::TypeTraitItem(ref t) if named(t.name) => {
+                    return Some(format!("assoc_type.{}", name));
+                }
+                _ => {}
+            }
+        }
+    }
+    None
+}
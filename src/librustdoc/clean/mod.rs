@@ -58,9 +58,11 @@ use visit_ast;
 
 /// A stable identifier to the particular version of JSON output.
 /// Increment this when the `Crate` and related structures change.
-pub static SCHEMA_VERSION: &'static str = "0.8.3";
+pub static SCHEMA_VERSION: &'static str = "0.8.5";
 
 mod inline;
+mod links;
+mod xrefs;
 
 // extract the stability index for a node from tcx, if possible
 fn get_stability(cx: &DocContext, def_id: ast::DefId) -> Option<Stability> {
@@ -117,6 +119,8 @@ pub struct Crate {
     pub module: Option<Item>,
     pub externs: Vec<(ast::CrateNum, ExternalCrate)>,
     pub primitives: Vec<PrimitiveType>,
+    pub links: Vec<DocLink>,
+    pub references: Vec<SourceRef>,
 }
 
 impl<'a, 'tcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx> {
@@ -182,16 +186,52 @@ impl<'a, 'tcx> Clean<Crate> for visit_ast::RustdocVisitor<'a, 'tcx> {
             m.items.extend(tmp.into_iter());
         }
 
+        let links = links::resolve_crate(cx, &module);
+        let references = xrefs::collect_crate(cx);
+
         Crate {
             name: name.to_string(),
             src: cx.src.clone(),
             module: Some(module),
             externs: externs,
             primitives: primitives,
+            links: links,
+            references: references,
         }
     }
 }
 
+/// A link in the documentation of an item whose target is a Rust path, like
+/// ``[`Vec::push`]``, resolved to the item it names.
+#[derive(Clone, RustcEncodable, RustcDecodable)]
+pub struct DocLink {
+    /// The item whose documentation has the link.
+    pub item: ast::DefId,
+    /// The path, as found by `html::markdown::collect_links`.
+    pub target: String,
+    /// The item whose page the link goes to.
+    pub did: ast::DefId,
+    /// The anchor of the member named on that page, like `method.push`.
+    pub fragment: Option<String>,
+}
+
+/// A place in the crate's source where an item is named, found by
+/// `clean::xrefs`.
+#[derive(Clone, RustcEncodable, RustcDecodable)]
+pub struct SourceRef {
+    /// The file, as in `Span::filename`.
+    pub filename: String,
+    /// The byte offsets of the name in the file.
+    pub lo: uint,
+    pub hi: uint,
+    /// The line of the name, to list the usages of the item.
+    pub line: uint,
+    /// The item named.
+    pub did: ast::DefId,
+    /// Where the item is defined, if it is in this crate.
+    pub definition: Option<Span>,
+}
+
 #[derive(Clone, RustcEncodable, RustcDecodable)]
 pub struct ExternalCrate {
     pub name: String,
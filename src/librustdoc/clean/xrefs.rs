@@ -0,0 +1,196 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Cross-references of the crate's source: every place an item is named,
+//! found from the resolution and method tables of the type context, as
+//! save-analysis does. They link the identifiers of the rendered source to
+//! their definitions, and list the usages of each item.
+//!
+//! Only names written in the source are collected; those produced by macro
+//! expansion are left out, as there is no identifier to link.
+
+use syntax::ast;
+use syntax::ast_map;
+use syntax::ast_util;
+use syntax::codemap;
+use syntax::codemap::Pos;
+use syntax::parse::token;
+use syntax::visit;
+
+use rustc::middle::def;
+use rustc::middle::ty;
+
+use core::DocContext;
+use clean;
+use clean::Clean;
+
+/// Collects the cross-references of the crate being documented.
+pub fn collect_crate(cx: &DocContext) -> Vec<clean::SourceRef> {
+    let mut collector = Collector { cx: cx, refs: Vec::new() };
+    if cx.tcx_opt().is_some() {
+        visit::walk_crate(&mut collector, cx.krate);
+    }
+    collector.refs
+}
+
+struct Collector<'a, 'tcx: 'a> {
+    cx: &'a DocContext<'tcx>,
+    refs: Vec<clean::SourceRef>,
+}
+
+impl<'a, 'tcx> Collector<'a, 'tcx> {
+    /// Records that the identifier at `sp` names the item `did`.
+    fn add(&mut self, sp: codemap::Span, did: ast::DefId) {
+        if sp.expn_id != codemap::NO_EXPANSION { return }
+        let tcx = self.cx.tcx();
+        let cm = tcx.sess.codemap();
+        let lo = cm.lookup_byte_offset(sp.lo);
+        if lo.fm.name.starts_with("<") { return }
+
+        // Tuple structs and variants are named by their constructor in
+        // expressions, which are not items of their own.
+        let did = match tcx.map.find(did.node) {
+            Some(ast_map::NodeStructCtor(..)) if ast_util::is_local(did) => {
+                ast_util::local_def(tcx.map.get_parent(did.node))
+            }
+            _ => did,
+        };
+        let definition = if ast_util::is_local(did) {
+            tcx.map.opt_span(did.node).map(|sp| sp.clean(self.cx))
+        } else {
+            None
+        };
+        let hi = lo.pos.to_uint() + (sp.hi.to_uint() - sp.lo.to_uint());
+        self.refs.push(clean::SourceRef {
+            filename: lo.fm.name.clone(),
+            lo: lo.pos.to_uint(),
+            hi: hi,
+            line: cm.lookup_char_pos(sp.lo).line,
+            did: did,
+            definition: definition,
+        });
+    }
+
+    /// Records the item named by the node `id`, whose name is the last
+    /// segment of `path`.
+    fn add_path(&mut self, path: &ast::Path, id: ast::NodeId) {
+        let def = match self.cx.tcx().def_map.borrow().get(&id) {
+            Some(&def) => def,
+            None => return,
+        };
+        let did = match item_def_id(def) {
+            Some(did) => did,
+            None => return,
+        };
+        match path.segments.last() {
+            Some(segment) => match last_segment_span(self.cx, path.span, segment.identifier) {
+                Some(sp) => self.add(sp, did),
+                None => {}
+            },
+            None => {}
+        }
+    }
+}
+
+/// The id of the item `def` names, for the kinds of definitions that are
+/// items: not local variables, type parameters and the like.
+fn item_def_id(def: def::Def) -> Option<ast::DefId> {
+    match def {
+        def::DefFn(..) | def::DefStaticMethod(..) | def::DefMod(..) |
+        def::DefStatic(..) | def::DefConst(..) | def::DefVariant(..) |
+        def::DefTy(..) | def::DefAssociatedTy(..) | def::DefTrait(..) |
+        def::DefStruct(..) | def::DefMethod(..) => Some(def.def_id()),
+        _ => None,
+    }
+}
+
+/// The span of the identifier `ident` ending the path spanning `sp`, which
+/// may be followed by type parameters, like `Vec<T>`. Paths with type
+/// parameters before their last segment, like `Vec::<T>::new`, have no such
+/// span.
+fn last_segment_span(cx: &DocContext, sp: codemap::Span,
+                     ident: ast::Ident) -> Option<codemap::Span> {
+    let snippet = match cx.sess().codemap().span_to_snippet(sp) {
+        Some(snippet) => snippet,
+        None => return None,
+    };
+    let name = token::get_ident(ident);
+    let end = snippet.find('<').or_else(|| snippet.find('(')).unwrap_or(snippet.len());
+    let path = snippet.slice_to(end).trim_right();
+    if !path.ends_with(name.get()) { return None }
+    let lo = sp.lo + codemap::BytePos((path.len() - name.get().len()) as u32);
+    Some(codemap::mk_sp(lo, lo + codemap::BytePos(name.get().len() as u32)))
+}
+
+impl<'a, 'tcx, 'v> visit::Visitor<'v> for Collector<'a, 'tcx> {
+    fn visit_path(&mut self, path: &'v ast::Path, id: ast::NodeId) {
+        self.add_path(path, id);
+        visit::walk_path(self, path);
+    }
+
+    fn visit_view_item(&mut self, vi: &'v ast::ViewItem) {
+        if let ast::ViewItemUse(ref vp) = vi.node {
+            if let ast::ViewPathList(_, ref list, _) = vp.node {
+                for item in list.iter() {
+                    if let ast::PathListIdent { .. } = item.node {
+                        let def = self.cx.tcx().def_map.borrow().get(&item.node.id()).map(|d| *d);
+                        match def.and_then(item_def_id) {
+                            Some(did) => self.add(item.span, did),
+                            None => {}
+                        }
+                    }
+                }
+            }
+        }
+        visit::walk_view_item(self, vi);
+    }
+
+    fn visit_pat(&mut self, p: &'v ast::Pat) {
+        // Identifiers in patterns may name variants and constants.
+        if let ast::PatIdent(_, ref ident, None) = p.node {
+            let def = self.cx.tcx().def_map.borrow().get(&p.id).map(|d| *d);
+            match def.and_then(item_def_id) {
+                Some(did) => self.add(ident.span, did),
+                None => {}
+            }
+        }
+        visit::walk_pat(self, p);
+    }
+
+    fn visit_expr(&mut self, ex: &'v ast::Expr) {
+        if let ast::ExprMethodCall(ref ident, _, _) = ex.node {
+            let tcx = self.cx.tcx();
+            let origin = tcx.method_map.borrow()
+                            .get(&ty::MethodCall::expr(ex.id))
+                            .map(|callee| callee.origin.clone());
+            let did = match origin {
+                Some(ty::MethodStatic(did)) => {
+                    // Calls of provided methods go to the trait's method.
+                    match ty::impl_or_trait_item(tcx, did) {
+                        ty::MethodTraitItem(m) => Some(m.provided_source.unwrap_or(did)),
+                        ty::TypeTraitItem(_) => Some(did),
+                    }
+                }
+                Some(ty::MethodTypeParam(ref mp)) => {
+                    Some(ty::trait_item(tcx, mp.trait_ref.def_id, mp.method_num).def_id())
+                }
+                Some(ty::MethodTraitObject(ref mo)) => {
+                    Some(ty::trait_item(tcx, mo.trait_ref.def_id, mo.method_num).def_id())
+                }
+                Some(ty::MethodStaticUnboxedClosure(..)) | None => None,
+            };
+            match did {
+                Some(did) => self.add(ident.span, did),
+                None => {}
+            }
+        }
+        visit::walk_expr(self, ex);
+    }
+}
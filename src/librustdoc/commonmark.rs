@@ -0,0 +1,202 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The CommonMark documentation written by `rustdoc --output-format
+//! markdown`: a file per module, named after its path, e.g. `foo-bar.md`
+//! for `foo::bar`, so that the files can be put in a wiki as they are.
+//!
+//! The page of a module documents its items, with their signatures and the
+//! methods of their impls, and links to the pages of its submodules. The
+//! headings of the documentation are moved down to fit under those of the
+//! page.
+
+use std::cmp;
+use std::collections::{HashMap, HashSet};
+use std::io::{fs, File};
+use std::io;
+
+use clean;
+use html::item_type::ItemType;
+use html::render;
+use text;
+use text::Line;
+
+/// Writes the documentation of `krate` to the directory `dst`.
+pub fn run(krate: clean::Crate, dst: Path, passes: HashSet<String>) -> io::IoResult<()> {
+    render::unlinked_cache(&krate);
+    try!(fs::mkdir_recursive(&dst, io::USER_RWX));
+    let root = match krate.module {
+        Some(ref m) => m,
+        None => return Ok(()),
+    };
+    let mut cx = Context { dst: dst, passes: passes, impls: HashMap::new() };
+    text::collect_impls(root, &mut cx.impls);
+    cx.module(root, &[krate.name.clone()])
+}
+
+struct Context<'a> {
+    dst: Path,
+    passes: HashSet<String>,
+    impls: text::Impls<'a>,
+}
+
+/// The name of the file of the module at `path`.
+fn file_name(path: &[String]) -> String {
+    format!("{}.md", path.connect("-"))
+}
+
+impl<'a> Context<'a> {
+    /// Writes the page of the module `item` at `path`, and those of its
+    /// submodules.
+    fn module(&self, item: &clean::Item, path: &[String]) -> io::IoResult<()> {
+        let m = match item.inner {
+            clean::ModuleItem(ref m) => m,
+            _ => unreachable!(),
+        };
+        let mut w = format!("# {} `{}`\n\n", if m.is_crate { "Crate" } else { "Module" },
+                            path.connect("::"));
+        docs(&mut w, item, 1);
+
+        let sections = text::sections(m.items.as_slice(), &self.passes);
+        for &(ty, ref items) in sections.iter() {
+            w.push_str(format!("## {}\n\n", ty.section_name()).as_slice());
+            for &child in items.iter() {
+                if ty == ItemType::Module {
+                    let mut child_path = path.to_vec();
+                    child_path.push(child.name.clone().unwrap());
+                    w.push_str(format!("* [`{}`]({})", child.name.as_ref().unwrap(),
+                                       file_name(child_path.as_slice())).as_slice());
+                    summary(&mut w, child);
+                    w.push_str("\n");
+                } else {
+                    self.item(&mut w, child);
+                }
+            }
+            if ty == ItemType::Module {
+                w.push_str("\n");
+            }
+        }
+        let page = format!("{}\n", w.trim_right());
+        try!(File::create(&self.dst.join(file_name(path))).write_str(page.as_slice()));
+
+        for &(ty, ref items) in sections.iter() {
+            if ty != ItemType::Module { continue }
+            for &child in items.iter() {
+                let mut child_path = path.to_vec();
+                child_path.push(child.name.clone().unwrap());
+                try!(self.module(child, child_path.as_slice()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Documents an item of a module, other than a submodule.
+    fn item(&self, w: &mut String, item: &clean::Item) {
+        w.push_str(format!("### `{}`\n\n", item.name.as_ref().unwrap()).as_slice());
+        code(w, item);
+        docs(w, item, 3);
+        match item.inner {
+            clean::StructItem(ref s) => members(w, "Fields", s.fields.as_slice()),
+            clean::EnumItem(ref e) => members(w, "Variants", e.variants.as_slice()),
+            clean::TraitItem(ref t) => {
+                let (types, required, provided) = text::trait_items(t);
+                for &(title, ref items) in [("Associated Types", types),
+                                            ("Required Methods", required),
+                                            ("Provided Methods", provided)].iter() {
+                    if items.is_empty() { continue }
+                    w.push_str(format!("#### {}\n\n", title).as_slice());
+                    for &it in items.iter() {
+                        method(w, it);
+                    }
+                }
+            }
+            _ => {}
+        }
+        match self.impls.get(&item.def_id) {
+            Some(impls) => {
+                for i in impls.iter() {
+                    w.push_str(format!("#### `{}`\n\n", text::impl_header(*i)).as_slice());
+                    for it in i.items.iter() {
+                        method(w, it);
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// Writes the signature of `item` as a Rust code block.
+fn code(w: &mut String, item: &clean::Item) {
+    match text::signature(item) {
+        Some(sig) => w.push_str(format!("```rust\n{}\n```\n\n", sig).as_slice()),
+        None => {}
+    }
+}
+
+/// Writes the documentation of `item`, with its headings moved down by
+/// `level`.
+fn docs(w: &mut String, item: &clean::Item, level: uint) {
+    let doc = match item.doc_value() {
+        Some(doc) => doc,
+        None => return,
+    };
+    for line in text::lines(doc).into_iter() {
+        match line {
+            Line::Text(s) | Line::Code(s) => w.push_str(s),
+            Line::Heading(n, s) => {
+                let hashes = cmp::min(n + level, 6);
+                for _ in range(0, hashes) { w.push('#') }
+                w.push(' ');
+                w.push_str(s);
+            }
+            Line::StartCode(true) => w.push_str("```rust"),
+            Line::StartCode(false) | Line::EndCode => w.push_str("```"),
+        }
+        w.push('\n');
+    }
+    w.push('\n');
+}
+
+/// Writes the first paragraph of the documentation of `item` after a colon,
+/// on the current line.
+fn summary(w: &mut String, item: &clean::Item) {
+    let short = render::shorter(item.doc_value());
+    if short.is_empty() { return }
+    w.push_str(": ");
+    w.push_str(short.lines().map(|l| l.trim()).collect::<Vec<&str>>().connect(" ").as_slice());
+}
+
+/// Writes a list of the fields or variants `items`.
+fn members(w: &mut String, title: &str, items: &[clean::Item]) {
+    let mut list = String::new();
+    for (i, item) in items.iter().enumerate() {
+        match text::member_signature(item, i) {
+            Some(sig) => {
+                list.push_str(format!("* `{}`", sig.replace("\n", " ")).as_slice());
+                summary(&mut list, item);
+                list.push('\n');
+            }
+            None => {}
+        }
+    }
+    if !list.is_empty() {
+        w.push_str(format!("#### {}\n\n{}\n", title, list).as_slice());
+    }
+}
+
+/// Documents a method or an associated type.
+fn method(w: &mut String, item: &clean::Item) {
+    match text::signature(item) {
+        Some(sig) => w.push_str(format!("##### `{}`\n\n", sig).as_slice()),
+        None => return,
+    }
+    docs(w, item, 5);
+}
@@ -0,0 +1,185 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! This module crawls a `clean::Crate` and reports, for `rustdoc
+//! --show-coverage`, how many of its public items are documented and how many
+//! have examples, per module and for the whole crate. Unlike the stability
+//! summary, a module's counts only include its own items, not those of its
+//! children; a module counts as an item of itself.
+
+use std::cmp;
+use std::fmt;
+use std::ops::Add;
+
+use syntax::ast::Public;
+
+use clean::{Crate, Item, ModuleItem, Module, EnumItem, Enum, StructItem, Struct};
+use clean::{ImplItem, Impl, TraitItem, Trait, VariantItem, Variant, StructVariant};
+use clean::{ViewItemItem, PrimitiveItem};
+
+use html::markdown;
+
+/// The counts of public items.
+#[derive(RustcEncodable, RustcDecodable, PartialEq, Eq, Copy, Show)]
+pub struct Counts {
+    pub total: uint,
+    /// Items with documentation.
+    pub documented: uint,
+    /// Items whose documentation has at least one example that `rustdoc
+    /// --test` runs or compiles.
+    pub examples: uint,
+}
+
+impl Add for Counts {
+    type Output = Counts;
+
+    fn add(self, other: Counts) -> Counts {
+        Counts {
+            total:      self.total      + other.total,
+            documented: self.documented + other.documented,
+            examples:   self.examples   + other.examples,
+        }
+    }
+}
+
+impl Counts {
+    fn zero() -> Counts {
+        Counts { total: 0, documented: 0, examples: 0 }
+    }
+}
+
+/// The counts of the items of one module.
+#[derive(RustcEncodable, RustcDecodable, PartialEq, Eq)]
+pub struct ModuleCoverage {
+    /// The path of the module, starting with the crate name.
+    pub path: String,
+    pub counts: Counts,
+}
+
+/// The coverage of a crate, with the counts of all its items and of each of
+/// its modules.
+#[derive(RustcEncodable, RustcDecodable, PartialEq, Eq)]
+pub struct CrateCoverage {
+    pub name: String,
+    pub counts: Counts,
+    pub modules: Vec<ModuleCoverage>,
+}
+
+// is the item considered publically visible?
+fn visible(item: &Item) -> bool {
+    match item.inner {
+        ImplItem(_) => true,
+        _ => item.visibility == Some(Public)
+    }
+}
+
+fn count_item(item: &Item) -> Counts {
+    match item.doc_value() {
+        Some(doc) if !doc.trim().is_empty() => Counts {
+            total: 1,
+            documented: 1,
+            examples: if markdown::count_examples(doc) > 0 { 1 } else { 0 },
+        },
+        _ => Counts { total: 1, .. Counts::zero() },
+    }
+}
+
+fn sum<'a, I: Iterator<Item=&'a Item>>(items: I) -> Counts {
+    items.fold(Counts::zero(), |acc, i| acc + count_item(i))
+}
+
+// Counts the items of the module `item`, whose path is `path`, and collects
+// its coverage and that of the modules under it into `modules`.
+fn count_module(item: &Item, path: String, modules: &mut Vec<ModuleCoverage>) -> Counts {
+    let mut counts = count_item(item);
+    if let ModuleItem(Module { ref items, .. }) = item.inner {
+        for child in items.iter().filter(|i| visible(*i)) {
+            match child.inner {
+                ModuleItem(..) => {
+                    let name = child.name.as_ref().map_or("", |n| n.as_slice());
+                    count_module(child, format!("{}::{}", path, name), modules);
+                }
+                _ => counts = counts + count_nested(child),
+            }
+        }
+    }
+    modules.push(ModuleCoverage { path: path, counts: counts });
+    counts
+}
+
+// Counts an item and the items nested in it. Items of trait impls are not
+// counted, as they are documented by the trait.
+fn count_nested(item: &Item) -> Counts {
+    match item.inner {
+        ImplItem(Impl { ref items, trait_: None, .. }) => {
+            sum(items.iter().filter(|i| visible(*i)))
+        }
+        EnumItem(Enum { ref variants, .. }) => {
+            count_item(item) + variants.iter().fold(Counts::zero(), |acc, v| {
+                acc + count_nested(v)
+            })
+        }
+        VariantItem(Variant { kind: StructVariant(ref s) }) => {
+            count_item(item) + sum(s.fields.iter().filter(|i| visible(*i)))
+        }
+        StructItem(Struct { ref fields, .. }) => {
+            count_item(item) + sum(fields.iter().filter(|i| visible(*i)))
+        }
+        TraitItem(Trait { ref items, .. }) => {
+            count_item(item) + sum(items.iter().map(|m| m.item()))
+        }
+        ImplItem(..) | ViewItemItem(_) | PrimitiveItem(_) => Counts::zero(),
+        _ => count_item(item),
+    }
+}
+
+/// Measures the documentation coverage of a crate.
+pub fn build(krate: &Crate) -> CrateCoverage {
+    let mut modules = Vec::new();
+    if let Some(ref item) = krate.module {
+        count_module(item, krate.name.clone(), &mut modules);
+    }
+    modules.sort_by(|a, b| a.path.cmp(&b.path));
+    let counts = modules.iter().fold(Counts::zero(), |acc, m| acc + m.counts);
+    CrateCoverage { name: krate.name.clone(), counts: counts, modules: modules }
+}
+
+fn percent(n: uint, total: uint) -> f64 {
+    if total == 0 { 100.0 } else { n as f64 * 100.0 / total as f64 }
+}
+
+//NOTE(stage0): remove impl after snapshot
+#[cfg(stage0)]
+impl fmt::Show for CrateCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::String::fmt(self, f)
+    }
+}
+
+/// Formats the coverage as a table, with a row per module and one for the
+/// crate.
+impl fmt::String for CrateCoverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let width = cmp::max(self.modules.iter().map(|m| m.path.len()).max().unwrap_or(0), 6);
+        try!(writeln!(f, "{:<6$}  {:>6}  {:>10}  {:>7}  {:>8}  {:>7}",
+                      "Module", "Items", "Documented", "%", "Examples", "%", width));
+        let row = |&: f: &mut fmt::Formatter, name: &str, c: &Counts| {
+            writeln!(f, "{:<6$}  {:>6}  {:>10}  {:>6.1}%  {:>8}  {:>6.1}%",
+                     name, c.total,
+                     c.documented, percent(c.documented, c.total),
+                     c.examples, percent(c.examples, c.total),
+                     width)
+        };
+        for m in self.modules.iter() {
+            try!(row(f, m.path.as_slice(), &m.counts));
+        }
+        row(f, "Total", &self.counts)
+    }
+}
@@ -331,24 +331,65 @@ impl fmt::String for clean::Path {
     }
 }
 
+/// The root of the documentation of the crate of `did`, relative to the page
+/// at `loc`, if it is known.
+fn doc_root(cache: &render::Cache, did: ast::DefId, loc: &[String]) -> Option<String> {
+    if ast_util::is_local(did) || cache.inlined.contains(&did) {
+        Some(repeat("../").take(loc.len()).collect::<String>())
+    } else {
+        match cache.extern_locations[did.krate] {
+            render::Remote(ref s) => Some(s.to_string()),
+            render::Local => {
+                Some(repeat("../").take(loc.len()).collect::<String>())
+            }
+            render::Unknown => None,
+        }
+    }
+}
+
+/// The URL of the page of the item at `fqp` under `root`.
+fn item_url(root: &str, fqp: &[String], shortty: ItemType) -> String {
+    let mut url = String::from_str(root);
+    let to_link = &fqp[..(fqp.len() - 1)];
+    for component in to_link.iter() {
+        url.push_str(component.as_slice());
+        url.push_str("/");
+    }
+    match shortty {
+        ItemType::Module => {
+            url.push_str(fqp.last().unwrap().as_slice());
+            url.push_str("/index.html");
+        }
+        _ => {
+            url.push_str(shortty.to_static_str());
+            url.push_str(".");
+            url.push_str(fqp.last().unwrap().as_slice());
+            url.push_str(".html");
+        }
+    }
+    url
+}
+
+/// The URL of the page of `did`, relative to the page being rendered, if it
+/// is documented.
+pub fn href(did: ast::DefId) -> Option<String> {
+    let loc = CURRENT_LOCATION_KEY.with(|l| l.borrow().clone());
+    let cache = cache();
+    let root = match doc_root(&*cache, did, loc.as_slice()) {
+        Some(root) => root,
+        None => return None,
+    };
+    cache.paths.get(&did).map(|&(ref fqp, shortty)| {
+        item_url(root.as_slice(), fqp.as_slice(), shortty)
+    })
+}
+
 /// Used when rendering a `ResolvedPath` structure. This invokes the `path`
 /// rendering function with the necessary arguments for linking to a local path.
 fn resolved_path(w: &mut fmt::Formatter, did: ast::DefId, p: &clean::Path,
                  print_all: bool) -> fmt::Result {
     path(w, p, print_all,
-        |cache, loc| {
-            if ast_util::is_local(did) || cache.inlined.contains(&did) {
-                Some(repeat("../").take(loc.len()).collect::<String>())
-            } else {
-                match cache.extern_locations[did.krate] {
-                    render::Remote(ref s) => Some(s.to_string()),
-                    render::Local => {
-                        Some(repeat("../").take(loc.len()).collect::<String>())
-                    }
-                    render::Unknown => None,
-                }
-            }
-        },
+        |cache, loc| doc_root(cache, did, loc),
         |cache| {
             match cache.paths.get(&did) {
                 None => None,
@@ -408,25 +449,7 @@ fn path<F, G>(w: &mut fmt::Formatter,
     match info(&*cache) {
         // This is a documented path, link to it!
         Some((ref fqp, shortty)) if abs_root.is_some() => {
-            let mut url = String::from_str(abs_root.unwrap().as_slice());
-            let to_link = &fqp[..(fqp.len() - 1)];
-            for component in to_link.iter() {
-                url.push_str(component.as_slice());
-                url.push_str("/");
-            }
-            match shortty {
-                ItemType::Module => {
-                    url.push_str(fqp.last().unwrap().as_slice());
-                    url.push_str("/index.html");
-                }
-                _ => {
-                    url.push_str(shortty.to_static_str());
-                    url.push_str(".");
-                    url.push_str(fqp.last().unwrap().as_slice());
-                    url.push_str(".html");
-                }
-            }
-
+            let url = item_url(abs_root.unwrap().as_slice(), fqp.as_slice(), shortty);
             try!(write!(w, "<a class='{}' href='{}' title='{}'>{}</a>",
                           shortty, url, fqp.connect("::"), last.name));
         }
@@ -0,0 +1,218 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The full-text search index, written to `fulltext-index.js` with
+//! `--full-text-search` and searched by `static/main.js`.
+//!
+//! The documentation of each item of the search index is split into words,
+//! leaving out code blocks, link destinations and common English words, and
+//! the words are stemmed so that e.g. `parse`, `parses` and `parsing` are the
+//! same term.
+//! For each term, the index lists the items whose documentation has it and
+//! how many times. The JavaScript side must split and stem words exactly as
+//! `words` and `stem` do.
+//!
+//! To keep the index small, these postings are written as a string per term:
+//! the differences between successive item numbers and the counts, each as a
+//! variable-length number in base 64, with 5 bits per character and the sixth
+//! set on all characters but the last.
+
+use std::ascii::AsciiExt;
+use std::collections::BTreeMap;
+use serialize::json::ToJson;
+
+/// Words too common to be worth searching for.
+static STOP_WORDS: &'static [&'static str] = &[
+    "an", "and", "are", "as", "at", "be", "by", "for", "from", "has", "in",
+    "is", "it", "its", "of", "on", "or", "that", "the", "this", "to", "was",
+    "were", "will", "with",
+];
+
+/// Suffixes removed by `stem`, tried in order, with what replaces them.
+static SUFFIXES: &'static [(&'static str, &'static str)] = &[
+    ("ies", "y"), ("sses", "ss"), ("ches", "ch"), ("shes", "sh"), ("xes", "x"),
+    ("zes", "z"), ("ss", "ss"), ("us", "us"), ("is", "is"), ("s", ""),
+    ("ing", ""), ("ed", ""), ("ly", ""),
+];
+
+static BASE64: &'static [u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// The stem of a lowercase ASCII word: the word without the first of
+/// `SUFFIXES` it ends with, if at least three letters are left, and without
+/// a final `e`. A consonant doubled before `ing` or `ed` is undoubled.
+pub fn stem(word: &str) -> String {
+    let mut w = word.to_string();
+    for &(suffix, replacement) in SUFFIXES.iter() {
+        if w.ends_with(suffix) && w.len() - suffix.len() + replacement.len() >= 3 {
+            let len = w.len() - suffix.len();
+            w.truncate(len);
+            w.push_str(replacement);
+            let doubled = {
+                let b = w.as_bytes();
+                let (last, prev) = (b[b.len() - 1], b[b.len() - 2]);
+                last == prev && last != b'l' && last != b's' && last != b'z'
+            };
+            if doubled && (suffix == "ing" || suffix == "ed") {
+                w.pop();
+            }
+            break;
+        }
+    }
+    if w.len() > 3 && w.ends_with("e") {
+        w.pop();
+    }
+    w
+}
+
+/// The terms of some documentation: its stemmed words, in order, without
+/// those of fenced and indented code blocks, link destinations, reference
+/// definitions, words of a single character and `STOP_WORDS`. Words are runs
+/// of ASCII letters and digits, lowercased.
+pub fn words(doc: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut in_code = false;
+    // Whether the previous line was blank or part of an indented code block,
+    // so that an indented line starts or continues one.
+    let mut indented_code = true;
+    for line in doc.lines() {
+        let trimmed = line.trim_left();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code { continue }
+        if trimmed.is_empty() {
+            indented_code = true;
+            continue;
+        }
+        indented_code = indented_code && (line.starts_with("    ") || line.starts_with("\t"));
+        if indented_code || is_reference_definition(trimmed) { continue }
+        let lower = strip_link_destinations(line).to_ascii_lowercase();
+        for word in lower.split(|&: c: char| !(c.is_ascii() && c.is_alphanumeric())) {
+            if word.len() < 2 || STOP_WORDS.iter().any(|w| *w == word) { continue }
+            terms.push(stem(word));
+        }
+    }
+    terms
+}
+
+// A line like `[label]: destination`.
+fn is_reference_definition(line: &str) -> bool {
+    line.starts_with("[") && line.find_str("]:").is_some()
+}
+
+// The line without the destinations of its inline links, the `(...)` after
+// `[text]`.
+fn strip_link_destinations(line: &str) -> String {
+    let mut out = String::new();
+    let mut rest = line;
+    loop {
+        match rest.find_str("](") {
+            Some(i) => {
+                out.push_str(rest.slice_to(i + 1));
+                rest = rest.slice_from(i + 2);
+                match rest.find(')') {
+                    Some(j) => rest = rest.slice_from(j + 1),
+                    None => return out,
+                }
+            }
+            None => {
+                out.push_str(rest);
+                return out;
+            }
+        }
+    }
+}
+
+fn encode(mut n: uint, out: &mut String) {
+    loop {
+        let mut digit = n & 31;
+        n >>= 5;
+        if n > 0 { digit |= 32 }
+        out.push(BASE64[digit] as char);
+        if n == 0 { break }
+    }
+}
+
+/// Builds the index of the documentation `docs`, that of the items of the
+/// search index of `krate` in order, as a line of `fulltext-index.js`.
+pub fn build(krate: &str, docs: &[&str]) -> String {
+    let mut postings: BTreeMap<String, Vec<(uint, uint)>> = BTreeMap::new();
+    for (i, doc) in docs.iter().enumerate() {
+        for term in words(*doc).into_iter() {
+            let v = postings.entry(term).get().unwrap_or_else(
+                |vacant_entry| vacant_entry.insert(Vec::new()));
+            match v.last_mut() {
+                Some(&mut (item, ref mut count)) if item == i => { *count += 1; continue }
+                _ => {}
+            }
+            v.push((i, 1));
+        }
+    }
+
+    let terms = postings.into_iter().map(|(term, items)| {
+        let mut s = String::new();
+        let mut last = 0;
+        for &(item, count) in items.iter() {
+            encode(item - last, &mut s);
+            encode(count, &mut s);
+            last = item;
+        }
+        (term, s.to_json())
+    }).collect::<BTreeMap<_, _>>();
+    let documented = docs.iter().filter(|d| !d.is_empty()).count();
+    format!(r#"fullTextIndex['{}'] = {{"docs":{},"terms":{}}};"#,
+            krate, documented, terms.to_json())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{stem, words, encode, build};
+
+    #[test]
+    fn stems() {
+        let t = |&: word: &str, expected: &str| assert_eq!(stem(word).as_slice(), expected);
+        t("parse", "pars");
+        t("parses", "pars");
+        t("parsed", "pars");
+        t("parsing", "pars");
+        t("running", "run");
+        t("called", "call");
+        t("entries", "entry");
+        t("matches", "match");
+        t("boxes", "box");
+        t("class", "class");
+        t("quickly", "quick");
+        t("its", "its");
+        t("use", "use");
+    }
+
+    #[test]
+    fn splits_words() {
+        let doc = "Reads *all* the bytes.\n\n```\nlet x = read_to_end();\n```\n\
+                   Calls `read_to_end`, see [io](../io/index.html) and [fs].\n\n    \
+                   let y = fs::read();\n\n[fs]: ../fs/index.html";
+        assert_eq!(words(doc), vec!["read", "all", "byt", "call", "read", "end", "see",
+                                    "io", "fs"]);
+    }
+
+    #[test]
+    fn encodes() {
+        let mut s = String::new();
+        encode(0, &mut s);
+        encode(31, &mut s);
+        encode(32, &mut s);
+        encode(1000, &mut s);
+        assert_eq!(s, "AfgBof");
+        assert_eq!(build("foo", &["Parses it.", "", "parsing, parsed"]),
+                   r#"fullTextIndex['foo'] = {"docs":2,"terms":{"pars":"ABCC"}};"#);
+    }
+}
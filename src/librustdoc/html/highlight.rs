@@ -16,12 +16,20 @@
 use html::escape::Escape;
 
 use std::io;
+use syntax::codemap::Pos;
 use syntax::parse::lexer;
 use syntax::parse::token;
 use syntax::parse;
 
 /// Highlights some source code, returning the HTML output.
 pub fn highlight(src: &str, class: Option<&str>, id: Option<&str>) -> String {
+    highlight_with_links(src, class, id, &[])
+}
+
+/// Highlights some source code like `highlight`, making the identifiers at
+/// the byte offsets of `links`, sorted by offset, links to the given URLs.
+pub fn highlight_with_links(src: &str, class: Option<&str>, id: Option<&str>,
+                            links: &[(uint, uint, String)]) -> String {
     debug!("highlighting: ================\n{}\n==============", src);
     let sess = parse::new_parse_sess();
     let fm = parse::string_to_filemap(&sess,
@@ -33,6 +41,7 @@ pub fn highlight(src: &str, class: Option<&str>, id: Option<&str>) -> String {
          lexer::StringReader::new(&sess.span_diagnostic, fm),
          class,
          id,
+         links,
          &mut out).unwrap();
     String::from_utf8_lossy(out.index(&FullRange)).into_owned()
 }
@@ -45,10 +54,13 @@ pub fn highlight(src: &str, class: Option<&str>, id: Option<&str>) -> String {
 /// not from the tokens themselves, in order to stay true to the original
 /// source.
 fn doit(sess: &parse::ParseSess, mut lexer: lexer::StringReader,
-        class: Option<&str>, id: Option<&str>,
+        class: Option<&str>, id: Option<&str>, links: &[(uint, uint, String)],
         out: &mut Writer) -> io::IoResult<()> {
     use syntax::parse::lexer::Reader;
 
+    let start = lexer.filemap.start_pos.to_uint();
+    let mut links = links.iter().peekable();
+
     try!(write!(out, "<pre "));
     match id {
         Some(id) => try!(write!(out, "id='{}' ", id)),
@@ -178,7 +190,22 @@ fn doit(sess: &parse::ParseSess, mut lexer: lexer::StringReader,
         // as mentioned above, use the original source code instead of
         // stringifying this token
         let snip = sess.span_diagnostic.cm.span_to_snippet(next.sp).unwrap();
-        if klass == "" {
+
+        let lo = next.sp.lo.to_uint() - start;
+        while links.peek().map_or(false, |&&(link_lo, _, _)| link_lo < lo) {
+            links.next();
+        }
+        let href = match links.peek() {
+            Some(&&(link_lo, link_hi, ref href)) if link_lo == lo &&
+                                                   link_hi == next.sp.hi.to_uint() - start => {
+                Some(href)
+            }
+            _ => None,
+        };
+        if let Some(href) = href {
+            try!(write!(out, "<a class='{}' href='{}'>{}</a>", klass, href,
+                        Escape(snip.as_slice())));
+        } else if klass == "" {
             try!(write!(out, "{}", Escape(snip.as_slice())));
         } else {
             try!(write!(out, "<span class='{}'>{}</span>", klass,
@@ -101,6 +101,47 @@ impl ItemType {
             ItemType::Constant        => "constant",
         }
     }
+
+    /// Where the items of this type come in the listing of a module.
+    pub fn section_order(&self) -> u8 {
+        match *self {
+            ItemType::ViewItem        => 0,
+            ItemType::Primitive       => 1,
+            ItemType::Module          => 2,
+            ItemType::Macro           => 3,
+            ItemType::Struct          => 4,
+            ItemType::Enum            => 5,
+            ItemType::Constant        => 6,
+            ItemType::Static          => 7,
+            ItemType::Trait           => 8,
+            ItemType::Function        => 9,
+            ItemType::Typedef         => 10,
+            ty                        => 11 + ty as u8,
+        }
+    }
+
+    /// The heading of the section listing the items of this type.
+    pub fn section_name(&self) -> &'static str {
+        match *self {
+            ItemType::Module          => "Modules",
+            ItemType::Struct          => "Structs",
+            ItemType::Enum            => "Enums",
+            ItemType::Function        => "Functions",
+            ItemType::Typedef         => "Type Definitions",
+            ItemType::Static          => "Statics",
+            ItemType::Constant        => "Constants",
+            ItemType::Trait           => "Traits",
+            ItemType::Impl            => "Implementations",
+            ItemType::ViewItem        => "Reexports",
+            ItemType::TyMethod        => "Type Methods",
+            ItemType::Method          => "Methods",
+            ItemType::StructField     => "Struct Fields",
+            ItemType::Variant         => "Variants",
+            ItemType::Macro           => "Macros",
+            ItemType::Primitive       => "Primitive Types",
+            ItemType::AssociatedType  => "Associated Types",
+        }
+    }
 }
 
 //NOTE(stage0): remove impl after snapshot
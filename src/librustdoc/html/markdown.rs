@@ -48,6 +48,9 @@ pub struct Markdown<'a>(pub &'a str);
 /// A unit struct like `Markdown`, that renders the markdown with a
 /// table of contents.
 pub struct MarkdownWithToc<'a>(pub &'a str);
+/// A struct like `Markdown`, that also rewrites the links whose target is a
+/// Rust path to the given URLs, see `collect_links`.
+pub struct MarkdownWithLinks<'a>(pub &'a str, pub &'a [(String, String)]);
 
 const DEF_OUNIT: libc::size_t = 64;
 const HOEDOWN_EXT_NO_INTRA_EMPHASIS: libc::c_uint = 1 << 10;
@@ -72,6 +75,10 @@ type blockcodefn = extern "C" fn(*mut hoedown_buffer, *const hoedown_buffer,
 type headerfn = extern "C" fn(*mut hoedown_buffer, *const hoedown_buffer,
                               libc::c_int, *mut libc::c_void);
 
+type linkfn = extern "C" fn(*mut hoedown_buffer, *const hoedown_buffer,
+                            *const hoedown_buffer, *const hoedown_buffer,
+                            *mut libc::c_void) -> libc::c_int;
+
 #[repr(C)]
 struct hoedown_renderer {
     opaque: *mut hoedown_html_renderer_state,
@@ -81,7 +88,10 @@ struct hoedown_renderer {
     blockhtml: Option<extern "C" fn(*mut hoedown_buffer, *const hoedown_buffer,
                                     *mut libc::c_void)>,
     header: Option<headerfn>,
-    other: [libc::size_t; 28],
+    other_block_level_callbacks: [libc::size_t; 9],
+    other_span_level_callbacks_1: [libc::size_t; 9],
+    link: Option<linkfn>,
+    other: [libc::size_t; 9],
 }
 
 #[repr(C)]
@@ -102,10 +112,12 @@ struct html_toc_data {
     nesting_level: libc::c_int,
 }
 
-struct MyOpaque {
+struct MyOpaque<'a> {
     dfltblk: extern "C" fn(*mut hoedown_buffer, *const hoedown_buffer,
                            *const hoedown_buffer, *mut libc::c_void),
+    dfltlink: linkfn,
     toc_builder: Option<TocBuilder>,
+    links: &'a [(String, String)],
 }
 
 #[repr(C)]
@@ -143,7 +155,7 @@ extern {
 /// documentation but used in example code. `code` is the portion of
 /// `s` that should be used in tests. (None for lines that should be
 /// left as-is.)
-fn stripped_filtered_line<'a>(s: &'a str) -> Option<&'a str> {
+pub fn stripped_filtered_line<'a>(s: &'a str) -> Option<&'a str> {
     let trimmed = s.trim();
     if trimmed.starts_with("# ") {
         Some(trimmed.slice_from(2))
@@ -161,7 +173,8 @@ thread_local!(pub static PLAYGROUND_KRATE: RefCell<Option<Option<String>>> = {
     RefCell::new(None)
 });
 
-pub fn render(w: &mut fmt::Formatter, s: &str, print_toc: bool) -> fmt::Result {
+pub fn render(w: &mut fmt::Formatter, s: &str, links: &[(String, String)],
+              print_toc: bool) -> fmt::Result {
     extern fn block(ob: *mut hoedown_buffer, orig_text: *const hoedown_buffer,
                     lang: *const hoedown_buffer, opaque: *mut libc::c_void) {
         unsafe {
@@ -278,6 +291,48 @@ pub fn render(w: &mut fmt::Formatter, s: &str, print_toc: bool) -> fmt::Result {
         unsafe { hoedown_buffer_puts(ob, text.as_ptr()) }
     }
 
+    extern fn link(ob: *mut hoedown_buffer, link: *const hoedown_buffer,
+                   title: *const hoedown_buffer, content: *const hoedown_buffer,
+                   opaque: *mut libc::c_void) -> libc::c_int {
+        unsafe {
+            let state = opaque as *mut hoedown_html_renderer_state;
+            let my_opaque: &MyOpaque = &*((*state).opaque as *const MyOpaque);
+            let url = if link.is_null() {
+                None
+            } else {
+                let target = slice::from_raw_buf(&(*link).data, (*link).size as uint);
+                str::from_utf8(target).ok().and_then(|target| {
+                    my_opaque.links.iter().find(|&&(ref path, _)| path.as_slice() == target)
+                })
+            };
+            match url {
+                Some(&(_, ref url)) => {
+                    let buf = hoedown_buffer_new(DEF_OUNIT);
+                    let url = CString::from_slice(url.as_bytes());
+                    hoedown_buffer_puts(buf, url.as_ptr());
+                    let ret = (my_opaque.dfltlink)(ob, buf, title, content, opaque);
+                    hoedown_buffer_free(buf);
+                    ret
+                }
+                None => (my_opaque.dfltlink)(ob, link, title, content, opaque),
+            }
+        }
+    }
+
+    // Shortcut links like [`Vec::push`] are only links if the document
+    // defines their label, so the resolved ones get a definition whose
+    // target is their path, which `link` then rewrites.
+    let mut doc = String::from_str(s);
+    for l in collect_links(s).into_iter() {
+        match l.label {
+            Some(ref label) if links.iter().any(|&(ref path, _)| *path == l.target) => {
+                doc.push_str(format!("\n\n[{}]: {}", label, l.target).as_slice());
+            }
+            _ => {}
+        }
+    }
+    let s = doc.as_slice();
+
     reset_headers();
 
     unsafe {
@@ -285,11 +340,14 @@ pub fn render(w: &mut fmt::Formatter, s: &str, print_toc: bool) -> fmt::Result {
         let renderer = hoedown_html_renderer_new(0, 0);
         let mut opaque = MyOpaque {
             dfltblk: (*renderer).blockcode.unwrap(),
-            toc_builder: if print_toc {Some(TocBuilder::new())} else {None}
+            dfltlink: (*renderer).link.unwrap(),
+            toc_builder: if print_toc {Some(TocBuilder::new())} else {None},
+            links: links,
         };
         (*(*renderer).opaque).opaque = &mut opaque as *mut _ as *mut libc::c_void;
         (*renderer).blockcode = Some(block as blockcodefn);
         (*renderer).header = Some(header as headerfn);
+        (*renderer).link = Some(link as linkfn);
 
         let document = hoedown_document_new(renderer, HOEDOWN_EXTENSIONS, 16);
         hoedown_document_render(document, ob, s.as_ptr(),
@@ -336,9 +394,7 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector) {
                 stripped_filtered_line(l).unwrap_or(l)
             });
             let text = lines.collect::<Vec<&str>>().connect("\n");
-            tests.add_test(text.to_string(),
-                           block_info.should_fail, block_info.no_run,
-                           block_info.ignore, block_info.test_harness);
+            tests.add_test(text.to_string(), block_info);
         }
     }
 
@@ -375,13 +431,168 @@ pub fn find_testable_code(doc: &str, tests: &mut ::test::Collector) {
     }
 }
 
+/// Counts the code blocks of `doc` that `rustdoc --test` runs or compiles:
+/// those in Rust that are not marked `ignore`.
+pub fn count_examples(doc: &str) -> uint {
+    extern fn block(_ob: *mut hoedown_buffer,
+                    text: *const hoedown_buffer,
+                    lang: *const hoedown_buffer,
+                    opaque: *mut libc::c_void) {
+        unsafe {
+            if text.is_null() { return }
+            let block_info = if lang.is_null() {
+                LangString::all_false()
+            } else {
+                let lang = slice::from_raw_buf(&(*lang).data,
+                                               (*lang).size as uint);
+                let s = str::from_utf8(lang).unwrap();
+                LangString::parse(s)
+            };
+            if !block_info.rust || block_info.ignore { return }
+            let opaque = opaque as *mut hoedown_html_renderer_state;
+            let count = &mut *((*opaque).opaque as *mut uint);
+            *count += 1;
+        }
+    }
+
+    let mut count = 0u;
+    unsafe {
+        let ob = hoedown_buffer_new(DEF_OUNIT);
+        let renderer = hoedown_html_renderer_new(0, 0);
+        (*renderer).blockcode = Some(block as blockcodefn);
+        (*(*renderer).opaque).opaque = &mut count as *mut _ as *mut libc::c_void;
+
+        let document = hoedown_document_new(renderer, HOEDOWN_EXTENSIONS, 16);
+        hoedown_document_render(document, ob, doc.as_ptr(),
+                                doc.len() as libc::size_t);
+        hoedown_document_free(document);
+
+        hoedown_html_renderer_free(renderer);
+        hoedown_buffer_free(ob);
+    }
+    count
+}
+
+/// A Markdown link whose target is a Rust path.
+#[derive(Eq, PartialEq, Clone, Show)]
+pub struct RustLink {
+    /// The path, without backticks or a trailing `()`.
+    pub target: String,
+    /// The label of a shortcut link, which is only rendered as a link once
+    /// the document defines it.
+    pub label: Option<String>,
+}
+
+/// Finds the links of `doc` whose target is a Rust path, outside of code
+/// blocks. These are inline links and reference definitions whose URL is a
+/// path, like `[Foo](bar::Foo)`, and shortcut links without a definition
+/// whose label is a path in backticks or with several segments, like
+/// ``[`Vec::push`]`` or `[bar::Foo]`.
+pub fn collect_links(doc: &str) -> Vec<RustLink> {
+    let mut links = Vec::new();
+    let mut defined = Vec::new();
+    let mut in_code = false;
+    let mut lines = Vec::new();
+    for line in doc.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code { continue }
+
+        // Reference definitions, `[label]: target`
+        if trimmed.starts_with("[") {
+            match trimmed.find_str("]:") {
+                Some(end) => {
+                    defined.push(trimmed.slice(1, end));
+                    let target = trimmed.slice_from(end + 2).words().next().unwrap_or("");
+                    let target = target.trim_left_matches('<').trim_right_matches('>');
+                    if is_rust_path(target) {
+                        links.push(RustLink { target: target.to_string(), label: None });
+                    }
+                    continue;
+                }
+                None => {}
+            }
+        }
+        lines.push(line);
+    }
+
+    for line in lines.into_iter() {
+        let mut rest = line;
+        loop {
+            let open = match rest.find('[') { Some(open) => open, None => break };
+            let after = rest.slice_from(open + 1);
+            let close = match after.find(']') { Some(close) => close, None => break };
+            let label = after.slice_to(close);
+            rest = after.slice_from(close + 1);
+
+            if rest.starts_with("(") {
+                // An inline link, `[text](target)`
+                match rest.find(')') {
+                    Some(end) => {
+                        let target = rest.slice(1, end).trim();
+                        if is_rust_path(target) {
+                            links.push(RustLink { target: target.to_string(), label: None });
+                        }
+                        rest = rest.slice_from(end + 1);
+                    }
+                    None => break,
+                }
+            } else if rest.starts_with("[") {
+                // A full reference link, `[text][label]`
+                match rest.find(']') {
+                    Some(end) => rest = rest.slice_from(end + 1),
+                    None => break,
+                }
+            } else if !defined.contains(&label) {
+                let ticked = label.len() > 2 && label.starts_with("`") && label.ends_with("`");
+                let path = if ticked { label.slice(1, label.len() - 1) } else { label };
+                let path = if path.ends_with("()") { path.slice_to(path.len() - 2) } else { path };
+                if (ticked || path.contains("::")) && is_rust_path(path) {
+                    links.push(RustLink {
+                        target: path.to_string(),
+                        label: Some(label.to_string()),
+                    });
+                }
+            }
+        }
+    }
+    links
+}
+
+/// Whether `s` is a path, like `Foo`, `foo::Bar` or `::std::vec::Vec`.
+fn is_rust_path(s: &str) -> bool {
+    let s = if s.starts_with("::") { s.slice_from(2) } else { s };
+    !s.is_empty() && s.split_str("::").all(|segment| {
+        let mut chars = segment.chars();
+        match chars.next() {
+            Some(c) if c == '_' || c.is_alphabetic() => {
+                chars.all(|c| c == '_' || c.is_alphanumeric())
+            }
+            _ => false,
+        }
+    })
+}
+
+/// What the info string of a code block, e.g. the `rust,no_run` of
+/// "```rust,no_run", says about the block.
 #[derive(Eq, PartialEq, Clone, Show)]
-struct LangString {
-    should_fail: bool,
-    no_run: bool,
-    ignore: bool,
-    rust: bool,
-    test_harness: bool,
+pub struct LangString {
+    pub should_fail: bool,
+    pub no_run: bool,
+    pub ignore: bool,
+    pub rust: bool,
+    pub test_harness: bool,
+    /// The block must fail to compile.
+    pub compile_fail: bool,
+    /// With `compile_fail`, the codes of the errors compiling the block must
+    /// report, e.g. `E0308`.
+    pub error_codes: Vec<String>,
+    /// The names to pass to `--cfg` when compiling the block, given as
+    /// `cfg-NAME`.
+    pub cfgs: Vec<String>,
 }
 
 impl LangString {
@@ -392,10 +603,13 @@ impl LangString {
             ignore: false,
             rust: true,  // NB This used to be `notrust = false`
             test_harness: false,
+            compile_fail: false,
+            error_codes: Vec::new(),
+            cfgs: Vec::new(),
         }
     }
 
-    fn parse(string: &str) -> LangString {
+    pub fn parse(string: &str) -> LangString {
         let mut seen_rust_tags = false;
         let mut seen_other_tags = false;
         let mut data = LangString::all_false();
@@ -412,6 +626,15 @@ impl LangString {
                 "ignore" => { data.ignore = true; seen_rust_tags = true; },
                 "rust" => { data.rust = true; seen_rust_tags = true; },
                 "test_harness" => { data.test_harness = true; seen_rust_tags = true; }
+                "compile_fail" => { data.compile_fail = true; seen_rust_tags = true; }
+                x if x.starts_with("cfg-") && x.len() > 4 => {
+                    data.cfgs.push(x.slice_from(4).to_string());
+                    seen_rust_tags = true;
+                }
+                x if is_error_code(x) => {
+                    data.error_codes.push(x.to_string());
+                    seen_rust_tags = true;
+                }
                 _ => { seen_other_tags = true }
             }
         }
@@ -422,6 +645,11 @@ impl LangString {
     }
 }
 
+// Whether `s` is the code of a compiler error, like `E0308`.
+fn is_error_code(s: &str) -> bool {
+    s.len() == 5 && s.starts_with("E") && s.slice_from(1).chars().all(|c| c.is_digit(10))
+}
+
 /// By default this markdown renderer generates anchors for each header in the
 /// rendered document. The anchor name is the contents of the header separated
 /// by hyphens, and a task-local map is used to disambiguate among duplicate
@@ -448,7 +676,7 @@ impl<'a> fmt::String for Markdown<'a> {
         let Markdown(md) = *self;
         // This is actually common enough to special-case
         if md.len() == 0 { return Ok(()) }
-        render(fmt, md.as_slice(), false)
+        render(fmt, md.as_slice(), &[], false)
     }
 }
 
@@ -463,13 +691,21 @@ impl<'a> fmt::Show for MarkdownWithToc<'a> {
 impl<'a> fmt::String for MarkdownWithToc<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
         let MarkdownWithToc(md) = *self;
-        render(fmt, md.as_slice(), true)
+        render(fmt, md.as_slice(), &[], true)
+    }
+}
+
+impl<'a> fmt::String for MarkdownWithLinks<'a> {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        let MarkdownWithLinks(md, links) = *self;
+        if md.len() == 0 { return Ok(()) }
+        render(fmt, md.as_slice(), links, false)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{LangString, Markdown};
+    use super::{LangString, Markdown, RustLink, collect_links};
 
     #[test]
     fn test_lang_string_parse() {
@@ -481,6 +717,9 @@ mod tests {
                 ignore: ignore,
                 rust: rust,
                 test_harness: test_harness,
+                compile_fail: false,
+                error_codes: Vec::new(),
+                cfgs: Vec::new(),
             })
         }
 
@@ -498,6 +737,41 @@ mod tests {
         t("{.test_harness .rust}", false,        false,   false,   true,  true);
     }
 
+    #[test]
+    fn test_lang_string_compile_fail() {
+        let s = LangString::parse("compile_fail,E0308,cfg-unix");
+        assert!(s.compile_fail && s.rust);
+        assert_eq!(s.error_codes, vec!["E0308".to_string()]);
+        assert_eq!(s.cfgs, vec!["unix".to_string()]);
+
+        assert!(LangString::parse("{.compile_fail .sh}").rust);
+        assert!(!LangString::parse("E03").rust);
+        assert!(LangString::parse("E0308").error_codes.len() == 1);
+    }
+
+    #[test]
+    fn test_collect_links() {
+        fn link(target: &str, label: Option<&str>) -> RustLink {
+            RustLink { target: target.to_string(), label: label.map(|l| l.to_string()) }
+        }
+
+        assert_eq!(collect_links("See [`Vec::push`] and [Foo](crate::bar::Foo)."),
+                   vec![link("Vec::push", Some("`Vec::push`")),
+                        link("crate::bar::Foo", None)]);
+        assert_eq!(collect_links("[`foo()`], [`Bar`] and [a::b]"),
+                   vec![link("foo", Some("`foo()`")),
+                        link("Bar", Some("`Bar`")),
+                        link("a::b", Some("a::b"))]);
+        assert_eq!(collect_links("[foo]\n\n[foo]: ::bar::Foo"),
+                   vec![link("::bar::Foo", None)]);
+
+        // Not paths, or not links
+        assert_eq!(collect_links("[rust](http://rust-lang.org) [x] a[i] [1, 2]"), vec![]);
+        assert_eq!(collect_links("[Foo](../struct.Foo.html) [`a b`]"), vec![]);
+        assert_eq!(collect_links("[`Vec`][vec]\n\n[vec]: ../vec/struct.Vec.html"), vec![]);
+        assert_eq!(collect_links("```\nlet a = [`a::b`];\n```"), vec![]);
+    }
+
     #[test]
     fn issue_17736() {
         let markdown = "# title";
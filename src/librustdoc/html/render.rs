@@ -34,6 +34,7 @@
 //! both occur before the crate is rendered.
 pub use self::ExternalLocation::*;
 
+use std::ascii::AsciiExt;
 use std::cell::RefCell;
 use std::cmp::Ordering::{self, Less, Greater, Equal};
 use std::collections::{HashMap, HashSet};
@@ -49,7 +50,7 @@ use std::sync::Arc;
 use externalfiles::ExternalHtml;
 
 use serialize::json;
-use serialize::json::ToJson;
+use serialize::json::{Json, ToJson};
 use syntax::ast;
 use syntax::ast_util;
 use rustc::util::nodemap::NodeSet;
@@ -59,10 +60,12 @@ use doctree;
 use fold::DocFolder;
 use html::format::{VisSpace, Method, UnsafetySpace, MutableSpace, Stability};
 use html::format::{ConciseStability, TyParamBounds, WhereClause};
+use html::format;
+use html::fulltext;
 use html::highlight;
 use html::item_type::ItemType;
 use html::layout;
-use html::markdown::Markdown;
+use html::markdown::{Markdown, MarkdownWithLinks};
 use html::markdown;
 use stability_summary;
 
@@ -190,6 +193,16 @@ pub struct Cache {
     /// Set of definitions which have been inlined from external crates.
     pub inlined: HashSet<ast::DefId>,
 
+    /// The resolved intra-doc links of each item, see `clean::DocLink`.
+    pub links: HashMap<ast::DefId, Vec<clean::DocLink>>,
+
+    /// The cross-references of each source file, see `clean::SourceRef`,
+    /// sorted by their offset in the file.
+    pub references: HashMap<String, Vec<clean::SourceRef>>,
+
+    /// The places in the crate's source where each item is named.
+    pub usages: HashMap<ast::DefId, Vec<clean::SourceRef>>,
+
     // Private fields only used when initially crawling a crate to build a cache
 
     stack: Vec<String>,
@@ -218,8 +231,9 @@ struct SourceCollector<'a> {
 }
 
 /// Wrapper struct to render the source code of a file. This will do things like
-/// adding line numbers to the left-hand side.
-struct Source<'a>(&'a str);
+/// adding line numbers to the left-hand side. The identifiers at the byte
+/// offsets of the links, sorted by offset, link to the given URLs.
+struct Source<'a>(&'a str, &'a [(uint, uint, String)]);
 
 // Helper structs for rendering items/sidebars and carrying along contextual
 // information
@@ -239,7 +253,93 @@ struct IndexItem {
     name: String,
     path: String,
     desc: String,
+    /// The whole documentation, for the full-text index.
+    doc: String,
     parent: Option<ast::DefId>,
+    /// The types of the inputs and output of functions and methods, see
+    /// `signature`.
+    sig: Option<(Vec<Json>, Option<Json>)>,
+}
+
+/// The signature of a function or method, to search by type: the types of
+/// its inputs, including `self`, and of its output, if it returns something.
+/// `self_ty` is the type of `self`, see `self_type`.
+///
+/// Types are simplified to what a search query can name: a type is its
+/// lowercased name, or an array of its name and the types of its type
+/// parameters if it has any. Tuples are named `()` and slices and arrays
+/// `[]`. References and pointers are left out, leaving the type they point
+/// to, and type parameters are named `$` followed by their name. Types that
+/// can't be named this way, like closures, are `_`.
+fn signature(item: &clean::Item, self_ty: Json) -> Option<(Vec<Json>, Option<Json>)> {
+    let (decl, self_) = match item.inner {
+        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => (&f.decl, None),
+        clean::MethodItem(ref m) => (&m.decl, Some(&m.self_)),
+        clean::TyMethodItem(ref m) => (&m.decl, Some(&m.self_)),
+        _ => return None,
+    };
+    let mut inputs = Vec::new();
+    match self_ {
+        Some(&clean::SelfValue) | Some(&clean::SelfBorrowed(..)) => inputs.push(self_ty.clone()),
+        Some(&clean::SelfExplicit(ref t)) => inputs.push(sig_type(t, &self_ty)),
+        Some(&clean::SelfStatic) | None => {}
+    }
+    inputs.extend(decl.inputs.values.iter().map(|arg| sig_type(&arg.type_, &self_ty)));
+    let output = match decl.output {
+        clean::Return(clean::Tuple(ref types)) if types.is_empty() => None,
+        clean::Return(ref t) => Some(sig_type(t, &self_ty)),
+        clean::NoReturn => Some("!".to_json()),
+    };
+    Some((inputs, output))
+}
+
+/// The type of `self` in the signatures of the methods of `parent`: the
+/// type, or a type parameter for trait methods.
+fn self_type(paths: &HashMap<ast::DefId, (Vec<String>, ItemType)>,
+             parent: Option<ast::DefId>) -> Json {
+    match parent.and_then(|did| paths.get(&did)) {
+        Some(&(_, ItemType::Trait)) => "$self".to_json(),
+        Some(&(ref fqp, _)) => fqp.last().unwrap().to_ascii_lowercase().to_json(),
+        None => "_".to_json(),
+    }
+}
+
+/// Simplifies a type of a signature, see `signature`.
+fn sig_type(t: &clean::Type, self_ty: &Json) -> Json {
+    let named = |&: name: String, args: Vec<Json>| {
+        if args.is_empty() {
+            name.to_json()
+        } else {
+            let mut v = vec![name.to_json()];
+            v.extend(args.into_iter());
+            Json::Array(v)
+        }
+    };
+    match *t {
+        clean::ResolvedPath { ref path, .. } => {
+            let last = path.segments.last().unwrap();
+            let args = match last.params {
+                clean::PathParameters::AngleBracketed { ref types, .. } => {
+                    types.iter().map(|t| sig_type(t, self_ty)).collect()
+                }
+                clean::PathParameters::Parenthesized { .. } => Vec::new(),
+            };
+            named(last.name.to_ascii_lowercase(), args)
+        }
+        clean::Generic(ref name) if name.as_slice() == "Self" => self_ty.clone(),
+        clean::Generic(ref name) => format!("${}", name.to_ascii_lowercase()).to_json(),
+        clean::Primitive(p) => p.to_string().to_json(),
+        clean::Tuple(ref types) => {
+            named("()".to_string(), types.iter().map(|t| sig_type(t, self_ty)).collect())
+        }
+        clean::Vector(ref t) | clean::FixedVector(ref t, _) => {
+            named("[]".to_string(), vec![sig_type(&**t, self_ty)])
+        }
+        clean::BorrowedRef { ref type_, .. } => sig_type(&**type_, self_ty),
+        clean::RawPointer(_, ref t) | clean::Unique(ref t) => sig_type(&**t, self_ty),
+        clean::Bottom => "!".to_json(),
+        _ => "_".to_json(),
+    }
 }
 
 // TLS keys used to carry information around during rendering.
@@ -248,11 +348,13 @@ thread_local!(static CACHE_KEY: RefCell<Arc<Cache>> = Default::default());
 thread_local!(pub static CURRENT_LOCATION_KEY: RefCell<Vec<String>> =
                     RefCell::new(Vec::new()));
 
-/// Generates the documentation for `crate` into the directory `dst`
+/// Generates the documentation for `crate` into the directory `dst`, with a
+/// full-text index of the documentation if `full_text_search` is set.
 pub fn run(mut krate: clean::Crate,
            external_html: &ExternalHtml,
            dst: Path,
-           passes: HashSet<String>) -> io::IoResult<()> {
+           passes: HashSet<String>,
+           full_text_search: bool) -> io::IoResult<()> {
     let mut cx = Context {
         dst: dst,
         src_root: krate.src.dir_path(),
@@ -319,6 +421,28 @@ pub fn run(mut krate: clean::Crate,
         let paths = a.external_paths.borrow_mut().take().unwrap();
         paths.into_iter().map(|(k, (v, t))| (k, (v, ItemType::from_type_kind(t)))).collect()
       }).unwrap_or(HashMap::new());
+    let mut links: HashMap<ast::DefId, Vec<clean::DocLink>> = HashMap::new();
+    for link in krate.links.iter() {
+        let v = links.entry(link.item).get().unwrap_or_else(
+            |vacant_entry| vacant_entry.insert(Vec::with_capacity(1)));
+        v.push(link.clone());
+    }
+    let mut references: HashMap<String, Vec<clean::SourceRef>> = HashMap::new();
+    let mut usages: HashMap<ast::DefId, Vec<clean::SourceRef>> = HashMap::new();
+    for r in krate.references.iter() {
+        let v = references.entry(r.filename.clone()).get().unwrap_or_else(
+            |vacant_entry| vacant_entry.insert(Vec::new()));
+        v.push(r.clone());
+        let v = usages.entry(r.did).get().unwrap_or_else(
+            |vacant_entry| vacant_entry.insert(Vec::new()));
+        v.push(r.clone());
+    }
+    for v in references.values_mut() {
+        v.sort_by(|a, b| a.lo.cmp(&b.lo));
+    }
+    for v in usages.values_mut() {
+        v.sort_by(|a, b| (&a.filename, a.line).cmp(&(&b.filename, b.line)));
+    }
     let mut cache = Cache {
         impls: HashMap::new(),
         external_paths: paths.iter().map(|(&k, v)| (k, v.0.clone()))
@@ -343,6 +467,9 @@ pub fn run(mut krate: clean::Crate,
         inlined: analysis.as_ref().map(|a| {
             a.inlined.borrow_mut().take().unwrap()
         }).unwrap_or(HashSet::new()),
+        links: links,
+        references: references,
+        usages: usages,
     };
     cache.stack.push(krate.name.clone());
     krate = cache.fold_crate(krate);
@@ -369,6 +496,13 @@ pub fn run(mut krate: clean::Crate,
 
     // Build our search index
     let index = try!(build_index(&krate, &mut cache));
+    let full_text_index = if full_text_search {
+        let docs = cache.search_index.iter().map(|item| item.doc.as_slice())
+                                    .collect::<Vec<&str>>();
+        Some(fulltext::build(krate.name.as_slice(), docs.as_slice()))
+    } else {
+        None
+    };
 
     // Freeze the cache now that the index has been built. Put an Arc into TLS
     // for future parallelization opportunities
@@ -376,7 +510,7 @@ pub fn run(mut krate: clean::Crate,
     CACHE_KEY.with(|v| *v.borrow_mut() = cache.clone());
     CURRENT_LOCATION_KEY.with(|s| s.borrow_mut().clear());
 
-    try!(write_shared(&cx, &krate, &*cache, index));
+    try!(write_shared(&cx, &krate, &*cache, index, full_text_index));
     let krate = try!(render_sources(&mut cx, krate));
 
     // Crawl the crate, building a summary of the stability levels.
@@ -406,7 +540,9 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> io::IoResult<String>
                         name: item.name.clone().unwrap(),
                         path: fqp[..(fqp.len() - 1)].connect("::"),
                         desc: shorter(item.doc_value()).to_string(),
+                        doc: item.doc_value().unwrap_or("").to_string(),
                         parent: Some(did),
+                        sig: signature(item, self_type(&*paths, Some(did))),
                     });
                 },
                 None => {}
@@ -456,6 +592,13 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> io::IoResult<String>
                 let pathid = *nodeid_to_pathid.get(&nodeid).unwrap();
                 try!(write!(&mut w, ",{}", pathid));
             }
+            None if item.sig.is_some() => try!(write!(&mut w, ",null")),
+            None => {}
+        }
+        match item.sig {
+            Some((ref inputs, ref output)) => {
+                try!(write!(&mut w, ",[{},{}]", inputs.to_json(), output.to_json()));
+            }
             None => {}
         }
         try!(write!(&mut w, "]"));
@@ -480,7 +623,8 @@ fn build_index(krate: &clean::Crate, cache: &mut Cache) -> io::IoResult<String>
 fn write_shared(cx: &Context,
                 krate: &clean::Crate,
                 cache: &Cache,
-                search_index: String) -> io::IoResult<()> {
+                search_index: String,
+                full_text_index: Option<String>) -> io::IoResult<()> {
     // Write out the shared files. Note that these are shared among all rustdoc
     // docs placed in the output directory, so this needs to be a synchronized
     // operation with respect to all other rustdocs running around.
@@ -542,6 +686,22 @@ fn write_shared(cx: &Context,
     }
     try!(writeln!(&mut w, "initSearch(searchIndex);"));
 
+    // Update the full-text index, which crates documented without one are
+    // removed from
+    let dst = cx.dst.join("fulltext-index.js");
+    let all_indexes = try!(collect(&dst, krate.name.as_slice(),
+                                   "fullTextIndex"));
+    if full_text_index.is_some() || !all_indexes.is_empty() {
+        let mut w = try!(File::create(&dst));
+        try!(writeln!(&mut w, "var fullTextIndex = {{}};"));
+        for index in full_text_index.iter().chain(all_indexes.iter()) {
+            try!(writeln!(&mut w, "{}", *index));
+        }
+        try!(writeln!(&mut w, "initFullTextSearch(fullTextIndex);"));
+    } else if dst.exists() {
+        try!(fs::unlink(&dst));
+    }
+
     // Update the list of all implementors for traits
     let dst = cx.dst.join("implementors");
     try!(mkdir(&dst));
@@ -615,7 +775,29 @@ fn render_sources(cx: &mut Context,
     };
     // skip all invalid spans
     folder.seen.insert("".to_string());
-    Ok(folder.fold_crate(krate))
+    let krate = folder.fold_crate(krate);
+
+    // Files without documented items are rendered too if the rendered source
+    // links to them.
+    if folder.cx.include_sources {
+        let mut files = Vec::new();
+        for r in krate.references.iter() {
+            files.push(&r.filename);
+            if let Some(ref def) = r.definition {
+                files.push(&def.filename);
+            }
+        }
+        files.retain(|f| !f.starts_with("<"));
+        files.sort();
+        files.dedup();
+        for filename in files.into_iter() {
+            if !folder.seen.contains(filename) {
+                try!(folder.emit_source(filename.as_slice()));
+                folder.seen.insert(filename.clone());
+            }
+        }
+    }
+    Ok(krate)
 }
 
 /// Writes the entire contents of a string to a destination, not attempting to
@@ -663,6 +845,25 @@ fn clean_srcpath<F>(src_root: &Path, src: &[u8], mut f: F) where
     }
 }
 
+/// The path of `filename` in the rendered sources, as `clean_srcpath` makes
+/// it.
+fn source_path(src_root: &Path, filename: &str) -> String {
+    let mut path = Vec::new();
+    clean_srcpath(src_root, filename.as_bytes(), |component| {
+        path.push(component.to_string());
+    });
+    path.connect("/")
+}
+
+/// The URL of the rendered source of `filename`, where `root` is the path to
+/// the root of the documentation.
+fn source_url(root: &str, src_root: &Path, krate: &str, filename: &str) -> String {
+    format!("{root}src/{krate}/{path}.html",
+            root = root,
+            krate = krate,
+            path = source_path(src_root, filename))
+}
+
 /// Attempts to find where an external crate is located, given that we're
 /// rendering in to the specified source destination.
 fn extern_location(e: &clean::ExternalCrate, dst: &Path) -> ExternalLocation {
@@ -757,11 +958,16 @@ impl<'a> SourceCollector<'a> {
         // Create the intermediate directories
         let mut cur = self.dst.clone();
         let mut root_path = String::from_str("../../");
+        let mut location = vec!["src".to_string(), self.cx.layout.krate.clone()];
         clean_srcpath(&self.cx.src_root, p.dirname(), |component| {
             cur.push(component);
             mkdir(&cur).unwrap();
             root_path.push_str("../");
+            location.push(component.to_string());
         });
+        CURRENT_LOCATION_KEY.with(|slot| *slot.borrow_mut() = location);
+        let links = self.source_links(filename, root_path.as_slice());
+        CURRENT_LOCATION_KEY.with(|slot| slot.borrow_mut().clear());
 
         let mut fname = p.filename().expect("source has no filename").to_vec();
         fname.extend(".html".bytes());
@@ -778,10 +984,35 @@ impl<'a> SourceCollector<'a> {
             keywords: get_basic_keywords(),
         };
         try!(layout::render(&mut w as &mut Writer, &self.cx.layout,
-                            &page, &(""), &Source(contents)));
+                            &page, &(""), &Source(contents, links.as_slice())));
         try!(w.flush());
         return Ok(());
     }
+
+    /// The links of the identifiers of `filename` to the items they name: to
+    /// their definition for items of this crate, or else to their
+    /// documentation. `root_path` is the path from the rendered source to the
+    /// root of the documentation.
+    fn source_links(&self, filename: &str, root_path: &str) -> Vec<(uint, uint, String)> {
+        let cache = cache();
+        let refs = match cache.references.get(filename) {
+            Some(refs) => refs,
+            None => return Vec::new(),
+        };
+        refs.iter().filter_map(|r| {
+            let url = match r.definition {
+                Some(ref def) if def.filename.starts_with("<") => None,
+                Some(ref def) => {
+                    Some(format!("{}#{}", source_url(root_path, &self.cx.src_root,
+                                                     self.cx.layout.krate.as_slice(),
+                                                     def.filename.as_slice()),
+                                 def.loline))
+                }
+                None => format::href(r.did),
+            };
+            url.map(|url| (r.lo, r.hi, url))
+        }).collect()
+    }
 }
 
 impl DocFolder for Cache {
@@ -880,7 +1111,9 @@ impl DocFolder for Cache {
                         name: s.to_string(),
                         path: path.connect("::").to_string(),
                         desc: shorter(item.doc_value()).to_string(),
+                        doc: item.doc_value().unwrap_or("").to_string(),
                         parent: parent,
+                        sig: signature(&item, self_type(&self.paths, parent)),
                     });
                 }
                 (Some(parent), None) if is_method || (!self.privmod && !hidden_field)=> {
@@ -1304,11 +1537,6 @@ impl<'a> Item<'a> {
         // know the span, so we plow forward and generate a proper url. The url
         // has anchors for the line numbers that we're linking to.
         if ast_util::is_local(self.item.def_id) {
-            let mut path = Vec::new();
-            clean_srcpath(&cx.src_root, self.item.source.filename.as_bytes(),
-                          |component| {
-                path.push(component.to_string());
-            });
             let href = if self.item.source.loline == self.item.source.hiline {
                 format!("{}", self.item.source.loline)
             } else {
@@ -1316,11 +1544,11 @@ impl<'a> Item<'a> {
                         self.item.source.loline,
                         self.item.source.hiline)
             };
-            Some(format!("{root}src/{krate}/{path}.html#{href}",
-                         root = self.cx.root_path,
-                         krate = self.cx.layout.krate,
-                         path = path.connect("/"),
-                         href = href))
+            Some(format!("{}#{}",
+                         source_url(self.cx.root_path.as_slice(), &cx.src_root,
+                                    self.cx.layout.krate.as_slice(),
+                                    self.item.source.filename.as_slice()),
+                         href))
 
         // If this item is not part of the local crate, then things get a little
         // trickier. We don't actually know the span of the external item, but
@@ -1434,7 +1662,7 @@ impl<'a> fmt::String for Item<'a> {
 
         try!(write!(fmt, "</h1>\n"));
 
-        match self.item.inner {
+        try!(match self.item.inner {
             clean::ModuleItem(ref m) => {
                 item_module(fmt, self.cx, self.item, m.items.as_slice())
             }
@@ -1450,8 +1678,39 @@ impl<'a> fmt::String for Item<'a> {
                 item_static(fmt, self.item, i),
             clean::ConstantItem(ref c) => item_constant(fmt, self.item, c),
             _ => Ok(())
+        });
+
+        if self.cx.include_sources {
+            try!(item_usages(fmt, self.cx, self.item));
         }
+        Ok(())
+    }
+}
+
+/// Lists the lines of the crate's source where the item is named.
+fn item_usages(w: &mut fmt::Formatter, cx: &Context, it: &clean::Item) -> fmt::Result {
+    match it.inner {
+        clean::ModuleItem(..) | clean::PrimitiveItem(..) => return Ok(()),
+        _ => {}
+    }
+    let cache = cache();
+    let usages = match cache.usages.get(&it.def_id) {
+        Some(usages) => usages,
+        None => return Ok(()),
+    };
+    try!(write!(w, "<h2 id='usages' class='section-header'>\
+                    <a href=\"#usages\">Usages</a></h2>\n<ul class='usages'>\n"));
+    let mut last = None;
+    for u in usages.iter() {
+        if last == Some((&u.filename, u.line)) { continue }
+        last = Some((&u.filename, u.line));
+        try!(writeln!(w, "<li><a href='{url}#{line}'>{path}:{line}</a></li>",
+                      url = source_url(cx.root_path.as_slice(), &cx.src_root,
+                                       cx.layout.krate.as_slice(), u.filename.as_slice()),
+                      path = source_path(&cx.src_root, u.filename.as_slice()),
+                      line = u.line));
     }
+    write!(w, "</ul>\n")
 }
 
 fn item_path(item: &clean::Item) -> String {
@@ -1474,7 +1733,7 @@ fn full_path(cx: &Context, item: &clean::Item) -> String {
     return s
 }
 
-fn shorter<'a>(s: Option<&'a str>) -> &'a str {
+pub fn shorter<'a>(s: Option<&'a str>) -> &'a str {
     match s {
         Some(s) => match s.find_str("\n\n") {
             Some(pos) => s.slice_to(pos),
@@ -1487,13 +1746,33 @@ fn shorter<'a>(s: Option<&'a str>) -> &'a str {
 fn document(w: &mut fmt::Formatter, item: &clean::Item) -> fmt::Result {
     match item.doc_value() {
         Some(s) => {
-            try!(write!(w, "<div class='docblock'>{}</div>", Markdown(s)));
+            try!(write!(w, "<div class='docblock'>{}</div>",
+                        MarkdownWithLinks(s, doc_links(item).as_slice())));
         }
         None => {}
     }
     Ok(())
 }
 
+/// The URLs of the intra-doc links of `item` that go to documented items,
+/// relative to the page being rendered.
+fn doc_links(item: &clean::Item) -> Vec<(String, String)> {
+    let cache = cache();
+    let links = match cache.links.get(&item.def_id) {
+        Some(links) => links,
+        None => return Vec::new(),
+    };
+    links.iter().filter_map(|link| {
+        format::href(link.did).map(|url| {
+            let url = match link.fragment {
+                Some(ref fragment) => format!("{}#{}", url, fragment),
+                None => url,
+            };
+            (link.target.clone(), url)
+        })
+    }).collect()
+}
+
 fn item_module(w: &mut fmt::Formatter, cx: &Context,
                item: &clean::Item, items: &[clean::Item]) -> fmt::Result {
     try!(document(w, item));
@@ -1502,24 +1781,6 @@ fn item_module(w: &mut fmt::Formatter, cx: &Context,
         !cx.ignore_private_item(&items[*i])
     }).collect::<Vec<uint>>();
 
-    // the order of item types in the listing
-    fn reorder(ty: ItemType) -> u8 {
-        match ty {
-            ItemType::ViewItem        => 0,
-            ItemType::Primitive       => 1,
-            ItemType::Module          => 2,
-            ItemType::Macro           => 3,
-            ItemType::Struct          => 4,
-            ItemType::Enum            => 5,
-            ItemType::Constant        => 6,
-            ItemType::Static          => 7,
-            ItemType::Trait           => 8,
-            ItemType::Function        => 9,
-            ItemType::Typedef         => 10,
-            _                         => 11 + ty as u8,
-        }
-    }
-
     fn cmp(i1: &clean::Item, i2: &clean::Item, idx1: uint, idx2: uint) -> Ordering {
         let ty1 = shortty(i1);
         let ty2 = shortty(i2);
@@ -1527,7 +1788,7 @@ fn item_module(w: &mut fmt::Formatter, cx: &Context,
             return i1.name.cmp(&i2.name);
         }
 
-        let tycmp = reorder(ty1).cmp(&reorder(ty2));
+        let tycmp = ty1.section_order().cmp(&ty2.section_order());
         if let Equal = tycmp {
             // for reexports, `extern crate` takes precedence.
             match (&i1.inner, &i2.inner) {
@@ -1560,29 +1821,30 @@ fn item_module(w: &mut fmt::Formatter, cx: &Context,
                 try!(write!(w, "</table>"));
             }
             curty = myty;
-            let (short, name) = match myty.unwrap() {
-                ItemType::Module          => ("modules", "Modules"),
-                ItemType::Struct          => ("structs", "Structs"),
-                ItemType::Enum            => ("enums", "Enums"),
-                ItemType::Function        => ("functions", "Functions"),
-                ItemType::Typedef         => ("types", "Type Definitions"),
-                ItemType::Static          => ("statics", "Statics"),
-                ItemType::Constant        => ("constants", "Constants"),
-                ItemType::Trait           => ("traits", "Traits"),
-                ItemType::Impl            => ("impls", "Implementations"),
-                ItemType::ViewItem        => ("reexports", "Reexports"),
-                ItemType::TyMethod        => ("tymethods", "Type Methods"),
-                ItemType::Method          => ("methods", "Methods"),
-                ItemType::StructField     => ("fields", "Struct Fields"),
-                ItemType::Variant         => ("variants", "Variants"),
-                ItemType::Macro           => ("macros", "Macros"),
-                ItemType::Primitive       => ("primitives", "Primitive Types"),
-                ItemType::AssociatedType  => ("associated-types", "Associated Types"),
+            let ty = myty.unwrap();
+            let short = match ty {
+                ItemType::Module          => "modules",
+                ItemType::Struct          => "structs",
+                ItemType::Enum            => "enums",
+                ItemType::Function        => "functions",
+                ItemType::Typedef         => "types",
+                ItemType::Static          => "statics",
+                ItemType::Constant        => "constants",
+                ItemType::Trait           => "traits",
+                ItemType::Impl            => "impls",
+                ItemType::ViewItem        => "reexports",
+                ItemType::TyMethod        => "tymethods",
+                ItemType::Method          => "methods",
+                ItemType::StructField     => "fields",
+                ItemType::Variant         => "variants",
+                ItemType::Macro           => "macros",
+                ItemType::Primitive       => "primitives",
+                ItemType::AssociatedType  => "associated-types",
             };
             try!(write!(w,
                         "<h2 id='{id}' class='section-header'>\
                         <a href=\"#{id}\">{name}</a></h2>\n<table>",
-                        id = short, name = name));
+                        id = short, name = ty.section_name()));
         }
 
         match myitem.inner {
@@ -1620,7 +1882,8 @@ fn item_module(w: &mut fmt::Formatter, cx: &Context,
                     </tr>
                 ",
                 *myitem.name.as_ref().unwrap(),
-                Markdown(shorter(myitem.doc_value())),
+                MarkdownWithLinks(shorter(myitem.doc_value()),
+                                  doc_links(myitem).as_slice()),
                 class = shortty(myitem),
                 href = item_path(myitem),
                 title = full_path(cx, myitem),
@@ -2147,7 +2410,8 @@ fn render_impl(w: &mut fmt::Formatter, i: &Impl) -> fmt::Result {
         }
         match item.doc_value() {
             Some(s) if dox => {
-                try!(write!(w, "<div class='docblock'>{}</div>", Markdown(s)));
+                try!(write!(w, "<div class='docblock'>{}</div>",
+                            MarkdownWithLinks(s, doc_links(item).as_slice())));
                 Ok(())
             }
             Some(..) | None => Ok(())
@@ -2277,7 +2541,7 @@ impl<'a> fmt::Show for Source<'a> {
 
 impl<'a> fmt::String for Source<'a> {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        let Source(s) = *self;
+        let Source(s, links) = *self;
         let lines = s.lines().count();
         let mut cols = 0;
         let mut tmp = lines;
@@ -2290,7 +2554,8 @@ impl<'a> fmt::String for Source<'a> {
             try!(write!(fmt, "<span id=\"{0}\">{0:1$}</span>\n", i, cols));
         }
         try!(write!(fmt, "</pre>"));
-        try!(write!(fmt, "{}", highlight::highlight(s.as_slice(), None, None)));
+        try!(write!(fmt, "{}", highlight::highlight_with_links(s.as_slice(), None, None,
+                                                                links)));
         Ok(())
     }
 }
@@ -2321,3 +2586,15 @@ fn make_item_keywords(it: &clean::Item) -> String {
 pub fn cache() -> Arc<Cache> {
     CACHE_KEY.with(|c| c.borrow().clone())
 }
+
+/// Puts in place a cache in which nothing is documented, so that the types
+/// formatted by `html::format` link nowhere, for the backends that write text
+/// rather than HTML.
+pub fn unlinked_cache(krate: &clean::Crate) {
+    let mut cache: Cache = Default::default();
+    for &(n, _) in krate.externs.iter() {
+        cache.extern_locations.insert(n, Unknown);
+    }
+    CACHE_KEY.with(|v| *v.borrow_mut() = Arc::new(cache));
+    CURRENT_LOCATION_KEY.with(|s| s.borrow_mut().clear());
+}
@@ -155,6 +155,12 @@
     function initSearch(rawSearchIndex) {
         var currentResults, index, searchIndex;
         var MAX_LEV_DISTANCE = 3;
+        // the position in `searchIndex` of the first item of each crate
+        var crateOffsets = {};
+        // the index of `fulltext-index.js`, once loaded, and whether it was
+        // asked for
+        var fullTextIndex, fullTextRequested = false;
+        var MAX_DOC_RESULTS = 50;
         var params = getQueryStringParams();
 
         // Populate search bar with query string search term when provided,
@@ -368,6 +374,382 @@
             return true;
         }
 
+        // These must split and stem words exactly as `html::fulltext` does.
+        var stopWords = ["an", "and", "are", "as", "at", "be", "by", "for",
+                         "from", "has", "in", "is", "it", "its", "of", "on",
+                         "or", "that", "the", "this", "to", "was", "were",
+                         "will", "with"];
+        var suffixes = [["ies", "y"], ["sses", "ss"], ["ches", "ch"],
+                        ["shes", "sh"], ["xes", "x"], ["zes", "z"],
+                        ["ss", "ss"], ["us", "us"], ["is", "is"], ["s", ""],
+                        ["ing", ""], ["ed", ""], ["ly", ""]];
+        var base64 = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+        function stem(word) {
+            var w = word;
+            for (var i = 0; i < suffixes.length; ++i) {
+                var suffix = suffixes[i][0], replacement = suffixes[i][1];
+                var len = w.length - suffix.length;
+                if (len >= 0 && w.substr(len) === suffix &&
+                    len + replacement.length >= 3) {
+                    w = w.substr(0, len) + replacement;
+                    var last = w.charAt(w.length - 1);
+                    if ((suffix === "ing" || suffix === "ed") &&
+                        last === w.charAt(w.length - 2) && "lsz".indexOf(last) < 0) {
+                        w = w.substr(0, w.length - 1);
+                    }
+                    break;
+                }
+            }
+            if (w.length > 3 && w.charAt(w.length - 1) === "e") {
+                w = w.substr(0, w.length - 1);
+            }
+            return w;
+        }
+
+        // whether `term` is the stem of some word starting with `prefix`,
+        // i.e. whether `prefix` is `term` followed by the start of what
+        // `stem` removes from a word
+        function prefixOfStemmed(prefix, term) {
+            for (var i = 0; i < suffixes.length; ++i) {
+                var suffix = suffixes[i][0], replacement = suffixes[i][1];
+                var len = term.length - replacement.length;
+                if (len < 0 || term.substr(len) !== replacement) { continue }
+                var base = term.substr(0, len);
+                if (prefix.indexOf(base) !== 0) { continue }
+                var rest = prefix.substr(len),
+                    last = base.charAt(base.length - 1);
+                // the `e` that `stem` removes, or the doubled consonant
+                var ends = ["", "e", last];
+                for (var j = 0; j < ends.length; ++j) {
+                    if ((ends[j] + suffix).indexOf(rest) === 0) {
+                        return true;
+                    }
+                }
+            }
+            return false;
+        }
+
+        // the words of the query that are searched for in the documentation
+        function queryWords(query) {
+            var split = query.toLowerCase().split(/[^a-z0-9]+/), words = [];
+            for (var i = 0; i < split.length; ++i) {
+                if (split[i].length < 2 || stopWords.indexOf(split[i]) > -1) {
+                    continue;
+                }
+                words.push(split[i]);
+            }
+            return words;
+        }
+
+        // decodes the postings of a term into an array of
+        // [item number, count] pairs
+        function decodePostings(encoded) {
+            var numbers = [], n = 0, shift = 0;
+            for (var i = 0; i < encoded.length; ++i) {
+                var digit = base64.indexOf(encoded.charAt(i));
+                n += (digit & 31) << shift;
+                if (digit & 32) {
+                    shift += 5;
+                } else {
+                    numbers.push(n);
+                    n = 0;
+                    shift = 0;
+                }
+            }
+            var postings = [], item = 0;
+            for (var i = 0; i + 1 < numbers.length; i += 2) {
+                item += numbers[i];
+                postings.push([item, numbers[i + 1]]);
+            }
+            return postings;
+        }
+
+        /**
+         * Searches the text of the documentation for the words of the query.
+         * A word matches the terms equal to its stem, and the last word,
+         * which may not be fully typed, also those it or its stem is a
+         * prefix of, and those of the words it may be the start of.
+         * Results are ranked by the number of words they match, then by the
+         * tf-idf score of the matching terms.
+         * @param  {[Object]} query [The user query]
+         * @return {[Array]}        [The matching items of `searchIndex`]
+         */
+        function execFullTextQuery(query) {
+            if (!fullTextIndex) {
+                return [];
+            }
+            var words = queryWords(query.query),
+                typeFilter = itemTypeFromName(query.type),
+                partial = /[a-z0-9]$/i.test(query.raw),
+                scores = {};
+
+            for (var crate in fullTextIndex) {
+                if (!fullTextIndex.hasOwnProperty(crate) ||
+                    !crateOffsets.hasOwnProperty(crate)) { continue }
+                var terms = fullTextIndex[crate].terms,
+                    ndocs = fullTextIndex[crate].docs;
+                for (var i = 0; i < words.length; ++i) {
+                    var wordStem = stem(words[i]),
+                        prefix = partial && i === words.length - 1,
+                        matched = {};
+                    for (var term in terms) {
+                        if (!terms.hasOwnProperty(term)) { continue }
+                        if (term !== wordStem &&
+                            !(prefix && (term.indexOf(words[i]) === 0 ||
+                                         term.indexOf(wordStem) === 0 ||
+                                         prefixOfStemmed(words[i], term)))) {
+                            continue;
+                        }
+                        var postings = decodePostings(terms[term]),
+                            idf = Math.log(1 + ndocs / postings.length);
+                        for (var j = 0; j < postings.length; ++j) {
+                            var id = crateOffsets[crate] + postings[j][0];
+                            var score = scores[id] || {id: id, words: 0, score: 0};
+                            if (!matched[id]) {
+                                matched[id] = true;
+                                score.words += 1;
+                            }
+                            score.score += (1 + Math.log(postings[j][1])) * idf;
+                            scores[id] = score;
+                        }
+                    }
+                }
+            }
+
+            var results = [];
+            for (var id in scores) {
+                if (!scores.hasOwnProperty(id)) { continue }
+                var item = searchIndex[scores[id].id];
+                if (typeFilter < 0 || typeFilter === item.ty) {
+                    results.push(scores[id]);
+                }
+            }
+            results.sort(function(a, b) {
+                if (a.words !== b.words) return b.words - a.words;
+                if (a.score !== b.score) return b.score - a.score;
+                return a.id - b.id;
+            });
+            return results.slice(0, MAX_DOC_RESULTS).map(function(result) {
+                return searchIndex[result.id];
+            });
+        }
+
+        // Loads `fulltext-index.js`, if there is one, the first time a search
+        // is made. It calls `initFullTextSearch` once loaded.
+        function loadFullTextIndex() {
+            if (fullTextRequested) {
+                return;
+            }
+            fullTextRequested = true;
+            var script = document.createElement('script');
+            script.src = rootPath + 'fulltext-index.js';
+            script.async = true;
+            document.getElementsByTagName('head')[0].appendChild(script);
+        }
+
+        window.initFullTextSearch = function(rawFullTextIndex) {
+            fullTextIndex = rawFullTextIndex;
+            // show the matches in the documentation for the current query
+            currentResults = null;
+            search();
+        };
+
+        // splits `str` at the commas not nested in brackets
+        function splitTopLevel(str) {
+            var parts = [], depth = 0, start = 0;
+            for (var i = 0; i < str.length; ++i) {
+                var c = str.charAt(i);
+                if (c === '<' || c === '(' || c === '[') {
+                    depth += 1;
+                } else if (c === '>' || c === ')' || c === ']') {
+                    depth -= 1;
+                } else if (c === ',' && depth === 0) {
+                    parts.push(str.substring(start, i));
+                    start = i + 1;
+                }
+            }
+            parts.push(str.substring(start));
+            return parts.map(function(part) { return part.trim(); })
+                        .filter(function(part) { return part !== ''; });
+        }
+
+        /**
+         * Parses a type of a signature query into the form of the types of
+         * the signatures of the search index, see `html::render::signature`,
+         * except that single uppercase letters are variables, written as
+         * `{var: name}`. References and pointers are left out.
+         * @param  {[string]} str [The type, like `&Vec<T>`]
+         * @return {[Object]}     [The type, or null for `()`]
+         */
+        function parseSigType(str) {
+            str = str.trim().replace(/^(&(mut\s+)?|\*(const|mut)\s+)+/, '').trim();
+            // the closing bracket may not have been typed yet, and only
+            // strictly shorter types are parsed so that the recursion ends
+            function closer(c) {
+                var i = str.lastIndexOf(c);
+                return i < 0 ? str.length : i;
+            }
+            function parseInner(t) {
+                return t.length < str.length ? parseSigType(t) || '_' : '_';
+            }
+            if (str.charAt(0) === '[') {
+                return ['[]', parseInner(str.substring(1, closer(']')).split(';')[0])];
+            }
+            if (str.charAt(0) === '(') {
+                var types = splitTopLevel(str.substring(1, closer(')')));
+                if (types.length === 0) {
+                    return null;
+                }
+                return ['()'].concat(types.map(parseInner));
+            }
+            var lt = str.indexOf('<');
+            var name = (lt < 0 ? str : str.substring(0, lt)).split('::').pop().trim();
+            if (/^[A-Z]$/.test(name)) {
+                return {var: name};
+            }
+            name = name.toLowerCase();
+            name = {uint: 'usize', int: 'isize'}[name] || name;
+            if (lt < 0) {
+                return name;
+            }
+            var args = splitTopLevel(str.substring(lt + 1, closer('>')));
+            return [name].concat(args.map(parseInner));
+        }
+
+        // the inputs and output of a query like `Vec<T>, usize -> Option<T>`,
+        // either of which is undefined when left out
+        function parseSigQuery(query) {
+            var arrow = query.indexOf('->');
+            var inputs = query.substring(0, arrow).trim(),
+                output = query.substring(arrow + 2).trim();
+            return {
+                inputs: inputs === '' ? undefined : splitTopLevel(inputs).map(function(t) {
+                    return parseSigType(t) || '_';
+                }),
+                output: output === '' ? undefined : parseSigType(output),
+            };
+        }
+
+        /**
+         * Unifies the type `q` of a query with the type `t` of a signature.
+         * `_` matches any type, and the variables of the query and the type
+         * parameters of the signature match any type, as long as they match
+         * the same one everywhere. A type written without its parameters
+         * matches the type with any parameters.
+         * @param  {[Object]} q        [The type of the query]
+         * @param  {[Object]} t        [The type of the signature]
+         * @param  {[Object]} bindings [The types bound so far, updated]
+         * @return {[boolean]}         [Whether the types unify]
+         */
+        function unifyType(q, t, bindings) {
+            if (q === '_' || t === '_') {
+                return true;
+            }
+            if (typeof t === 'string' && t.charAt(0) === '$') {
+                return bind(bindings.generics, t, JSON.stringify(q));
+            }
+            if (q.var !== undefined) {
+                return bind(bindings.vars, q.var, JSON.stringify(t));
+            }
+            var qName = typeof q === 'string' ? q : q[0],
+                tName = typeof t === 'string' ? t : t[0];
+            if (qName !== tName) {
+                return false;
+            }
+            if (typeof q === 'string' || typeof t === 'string') {
+                return true;
+            }
+            if (q.length !== t.length) {
+                return false;
+            }
+            for (var i = 1; i < q.length; ++i) {
+                if (!unifyType(q[i], t[i], bindings)) {
+                    return false;
+                }
+            }
+            return true;
+        }
+
+        function bind(map, name, type) {
+            if (map.hasOwnProperty(name)) {
+                return map[name] === type;
+            }
+            map[name] = type;
+            return true;
+        }
+
+        function copyBindings(bindings) {
+            return {generics: $.extend({}, bindings.generics),
+                    vars: $.extend({}, bindings.vars)};
+        }
+
+        // matches the query inputs from `i` on with distinct inputs of the
+        // signature, returning the resulting bindings or null
+        function unifyInputs(qInputs, tInputs, i, used, bindings) {
+            if (i === qInputs.length) {
+                return bindings;
+            }
+            for (var j = 0; j < tInputs.length; ++j) {
+                if (used[j]) { continue }
+                var attempt = copyBindings(bindings);
+                if (!unifyType(qInputs[i], tInputs[j], attempt)) { continue }
+                used[j] = true;
+                var result = unifyInputs(qInputs, tInputs, i + 1, used, attempt);
+                used[j] = false;
+                if (result) {
+                    return result;
+                }
+            }
+            return null;
+        }
+
+        /**
+         * Searches the signatures of functions and methods for those that
+         * unify with a query like `Vec<T> -> Option<T>`. The inputs of the
+         * query may come in any order and a signature may have more of
+         * them. Results are ranked by how specific they are: the number of
+         * type parameters bound and of inputs not in the query.
+         * @param  {[Object]} query [The user query]
+         * @return {[Array]}        [The matching items of `searchIndex`]
+         */
+        function execSignatureQuery(query) {
+            var sigQuery = parseSigQuery(query.query),
+                typeFilter = itemTypeFromName(query.type),
+                results = [];
+
+            for (var i = 0; i < searchIndex.length; ++i) {
+                var item = searchIndex[i], sig = item.sig;
+                if (!sig || (typeFilter >= 0 && typeFilter !== item.ty)) { continue }
+                var bindings = {generics: {}, vars: {}};
+                if (sigQuery.output !== undefined) {
+                    if (sigQuery.output === null || sig[1] === null) {
+                        if (sigQuery.output !== sig[1]) { continue }
+                    } else if (!unifyType(sigQuery.output, sig[1], bindings)) {
+                        continue;
+                    }
+                }
+                var extra = 0;
+                if (sigQuery.inputs !== undefined) {
+                    bindings = unifyInputs(sigQuery.inputs, sig[0], 0, [], bindings);
+                    if (!bindings) { continue }
+                    extra = sig[0].length - sigQuery.inputs.length;
+                }
+                results.push({item: item,
+                              score: Object.keys(bindings.generics).length + extra});
+            }
+
+            results.sort(function(a, b) {
+                if (a.score !== b.score) return a.score - b.score;
+                var aCrate = (a.item.crate !== window.currentCrate),
+                    bCrate = (b.item.crate !== window.currentCrate);
+                if (aCrate !== bCrate) return aCrate - bCrate;
+                if (a.item.name !== b.item.name) return (a.item.name > b.item.name ? +1 : -1);
+                return 0;
+            });
+            return results.map(function(result) { return result.item; });
+        }
+
         function getQuery() {
             var matches, type, query, raw = $('.search-input').val();
             query = raw;
@@ -440,16 +822,12 @@
             return $('<h1/>').text(content).html();
         }
 
-        function showResults(results) {
-            var output, shown, query = getQuery();
-
-            currentResults = query.id;
-            output = '<h1>Results for ' + escape(query.query) +
-                (query.type ? ' (type: ' + escape(query.type) + ')' : '') + '</h1>';
-            output += '<table class="search-results">';
+        function showResults(results, docResults) {
+            var output, query = getQuery();
 
-            if (results.length > 0) {
-                shown = [];
+            // the rows of a table of results
+            function resultRows(results) {
+                var rows = '', shown = [];
 
                 results.forEach(function(item) {
                     var name, type, href, displayPath;
@@ -484,19 +862,36 @@
                                '/' + type + '.' + name + '.html';
                     }
 
-                    output += '<tr class="' + type + ' result"><td>' +
-                              '<a href="' + href + '">' +
-                              displayPath + '<span class="' + type + '">' +
-                              name + '</span></a></td><td>' +
-                              '<a href="' + href + '">' +
-                              '<span class="desc">' + item.desc +
-                              '&nbsp;</span></a></td></tr>';
+                    rows += '<tr class="' + type + ' result"><td>' +
+                            '<a href="' + href + '">' +
+                            displayPath + '<span class="' + type + '">' +
+                            name + '</span></a></td><td>' +
+                            '<a href="' + href + '">' +
+                            '<span class="desc">' + item.desc +
+                            '&nbsp;</span></a></td></tr>';
                 });
+                return rows;
+            }
+
+            currentResults = query.id;
+            output = '<h1>Results for ' + escape(query.query) +
+                (query.type ? ' (type: ' + escape(query.type) + ')' : '') + '</h1>';
+            output += '<table class="search-results">';
+
+            if (results.length > 0 || docResults.length > 0) {
+                output += resultRows(results);
             } else {
                 output += 'No results :( <a href="https://duckduckgo.com/?q=' +
                     encodeURIComponent('rust ' + query.query) +
                     '">Try on DuckDuckGo?</a>';
             }
+            output += '</table>';
+
+            if (docResults.length > 0) {
+                output += '<h1>In the documentation</h1>';
+                output += '<table class="search-results">' +
+                          resultRows(docResults) + '</table>';
+            }
 
             output += "</p>";
             $('#main.content').addClass('hidden');
@@ -536,6 +931,12 @@
                 }
             }
 
+            // queries with an arrow search by type signature
+            if (query.query.indexOf('->') > -1) {
+                showResults(execSignatureQuery(query).slice(0, maxResults), []);
+                return;
+            }
+
             resultIndex = execQuery(query, 20000, index);
             len = resultIndex.length;
             for (i = 0; i < len; ++i) {
@@ -549,7 +950,8 @@
                 }
             }
 
-            showResults(results);
+            loadFullTextIndex();
+            showResults(results, execFullTextQuery(query));
         }
 
         // This mapping table should match the discriminants of
@@ -591,7 +993,11 @@
                 //              (String) name,
                 //              (String) full path or empty string for previous path,
                 //              (String) description,
-                //              (optional Number) the parent path index to `paths`]
+                //              (optional Number) the parent path index to `paths`,
+                //              (optional Array) for functions and methods, the
+                //                  types of the inputs and of the output, or
+                //                  null if there is none,
+                //                  see `html::render::signature`]
                 var items = rawSearchIndex[crate].items;
                 // an array of [(Number) item type,
                 //              (String) name]
@@ -612,11 +1018,12 @@
                 // faster analysis operations
                 var len = items.length;
                 var lastPath = "";
+                crateOffsets[crate] = searchIndex.length;
                 for (var i = 0; i < len; ++i) {
                     var rawRow = items[i];
                     var row = {crate: crate, ty: rawRow[0], name: rawRow[1],
                                path: rawRow[2] || lastPath, desc: rawRow[3],
-                               parent: paths[rawRow[4]]};
+                               parent: paths[rawRow[4]], sig: rawRow[5]};
                     searchIndex.push(row);
                     if (typeof row.name === "string") {
                         var word = row.name.toLowerCase();
@@ -0,0 +1,660 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The documented JSON format written by `rustdoc --output-format api-json`.
+//!
+//! Unlike the dump of `clean::Crate` that `--output-format json`
+//! writes for `rustdoc` to read back, this format does not follow the
+//! `clean` types: it is versioned by `FORMAT_VERSION` and described in
+//! `src/doc/rustdoc-json.md`, so that other tools can rely on it. Any change
+//! to what this module writes must keep to that document, and bump the
+//! version if it could break an existing consumer.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serialize::json::{Json, ToJson};
+use syntax::ast;
+use syntax::attr;
+
+use clean;
+use plugins;
+
+/// Version of the format, bumped whenever a change to it could break a
+/// consumer.
+pub const FORMAT_VERSION: u64 = 1;
+
+macro_rules! object {
+    ($($key:expr => $value:expr,)*) => {{
+        let mut obj = BTreeMap::new();
+        $(obj.insert($key.to_string(), $value);)*
+        Json::Object(obj)
+    }}
+}
+
+/// Builds the document for `krate`, along with the output of the plugins
+/// that were run over it.
+pub fn build(krate: &clean::Crate, plugins: Vec<plugins::PluginJson>) -> Json {
+    let mut cx = Context {
+        krate: krate.name.clone(),
+        index: Vec::new(),
+        impls: HashMap::new(),
+        implementors: HashMap::new(),
+    };
+    if let Some(ref module) = krate.module {
+        cx.collect_impls(module);
+    }
+    let root = krate.module.as_ref().map_or(Json::Null, |m| cx.item(m, &[]));
+
+    let externs = krate.externs.iter().map(|&(num, ref ext)| {
+        object! {
+            "krate" => Json::U64(num as u64),
+            "name" => ext.name.to_json(),
+        }
+    }).collect::<Vec<Json>>();
+
+    let plugins = plugins.into_iter().filter_map(|opt| {
+        opt.map(|(name, json)| (name.to_string(), json))
+    }).collect::<BTreeMap<String, Json>>();
+
+    object! {
+        "format_version" => Json::U64(FORMAT_VERSION),
+        "crate" => object! { "name" => krate.name.to_json(), },
+        "root" => root,
+        "index" => Json::Array(cx.index),
+        "paths" => Json::Array(external_paths()),
+        "external_crates" => Json::Array(externs),
+        "plugins" => Json::Object(plugins),
+    }
+}
+
+struct Context {
+    /// The name of the crate.
+    krate: String,
+    /// Every item written so far.
+    index: Vec<Json>,
+    /// The impls of each type, by the id of the type.
+    impls: HashMap<ast::DefId, Vec<ast::DefId>>,
+    /// The impls of each trait, by the id of the trait.
+    implementors: HashMap<ast::DefId, Vec<ast::DefId>>,
+}
+
+impl Context {
+    fn collect_impls(&mut self, item: &clean::Item) {
+        match item.inner {
+            clean::ModuleItem(ref m) => {
+                for child in m.items.iter() {
+                    self.collect_impls(child);
+                }
+            }
+            clean::ImplItem(ref i) => {
+                if let clean::ResolvedPath { did, .. } = i.for_ {
+                    push(&mut self.impls, did, item.def_id);
+                }
+                if let Some(clean::ResolvedPath { did, .. }) = i.trait_ {
+                    push(&mut self.implementors, did, item.def_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Adds `item` and the items under it to the index, and returns its id.
+    /// `parent` is the path of the item containing it.
+    fn item(&mut self, item: &clean::Item, parent: &[String]) -> Json {
+        // Impls and their items can't be named by a path.
+        let path = match (&item.inner, &item.name) {
+            (&clean::ModuleItem(clean::Module { is_crate: true, .. }), _) => {
+                Some(vec![self.krate.clone()])
+            }
+            (&clean::ImplItem(..), _) => None,
+            (_, &Some(ref name)) if !parent.is_empty() => {
+                let mut path = parent.to_vec();
+                path.push(name.clone());
+                Some(path)
+            }
+            _ => None,
+        };
+        let child_path = path.clone().unwrap_or(Vec::new());
+
+        let inner = self.inner(item, child_path.as_slice());
+        let visibility = match item.visibility {
+            Some(ast::Public) => "public".to_json(),
+            Some(ast::Inherited) => "inherited".to_json(),
+            None => Json::Null,
+        };
+        let stability = item.stability.as_ref().map_or(Json::Null, |s| {
+            object! {
+                "level" => stability_level(s.level).to_json(),
+                "text" => s.text.to_json(),
+            }
+        });
+        self.index.push(object! {
+            "id" => def_id(item.def_id),
+            "name" => item.name.to_json(),
+            "path" => path.to_json(),
+            "kind" => item_kind(item).to_json(),
+            "visibility" => visibility,
+            "docs" => item.doc_value().map(|s| s.to_string()).to_json(),
+            "span" => span(&item.source),
+            "stability" => stability,
+            "inner" => inner,
+        });
+        def_id(item.def_id)
+    }
+
+    fn items(&mut self, items: &[clean::Item], parent: &[String]) -> Json {
+        Json::Array(items.iter().map(|i| self.item(i, parent)).collect())
+    }
+
+    fn inner(&mut self, item: &clean::Item, path: &[String]) -> Json {
+        match item.inner {
+            clean::ModuleItem(ref m) => {
+                let (imports, items): (Vec<&clean::Item>, Vec<&clean::Item>) =
+                    m.items.iter().partition(|i| match i.inner {
+                        clean::ViewItemItem(..) => true,
+                        _ => false,
+                    });
+                let items = items.into_iter().map(|i| self.item(i, path)).collect();
+                object! {
+                    "is_crate" => m.is_crate.to_json(),
+                    "items" => Json::Array(items),
+                    "imports" => Json::Array(imports.into_iter().map(import).collect()),
+                }
+            }
+            clean::StructItem(ref s) => {
+                object! {
+                    "struct_type" => struct_type(s.struct_type),
+                    "generics" => generics(&s.generics),
+                    "fields" => self.fields(s.fields.as_slice(), path),
+                    "fields_stripped" => s.fields_stripped.to_json(),
+                    "impls" => self.impls_of(item.def_id),
+                }
+            }
+            clean::EnumItem(ref e) => {
+                object! {
+                    "generics" => generics(&e.generics),
+                    "variants" => self.items(e.variants.as_slice(), path),
+                    "variants_stripped" => e.variants_stripped.to_json(),
+                    "impls" => self.impls_of(item.def_id),
+                }
+            }
+            clean::VariantItem(ref v) => {
+                match v.kind {
+                    clean::CLikeVariant => object! { "kind" => "plain".to_json(), },
+                    clean::TupleVariant(ref tys) => object! {
+                        "kind" => "tuple".to_json(),
+                        "types" => Json::Array(tys.iter().map(ty).collect()),
+                    },
+                    clean::StructVariant(ref s) => object! {
+                        "kind" => "struct".to_json(),
+                        "fields" => self.fields(s.fields.as_slice(), path),
+                        "fields_stripped" => s.fields_stripped.to_json(),
+                    },
+                }
+            }
+            clean::StructFieldItem(clean::TypedStructField(ref t)) => {
+                object! { "type" => ty(t), }
+            }
+            clean::StructFieldItem(clean::HiddenStructField) => Json::Object(BTreeMap::new()),
+            clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+                object! {
+                    "decl" => fn_decl(&f.decl),
+                    "generics" => generics(&f.generics),
+                    "unsafe" => unsafety(f.unsafety),
+                    "foreign" => match item.inner {
+                        clean::ForeignFunctionItem(..) => true,
+                        _ => false,
+                    }.to_json(),
+                }
+            }
+            clean::TyMethodItem(ref m) => {
+                method(&m.decl, &m.generics, m.unsafety, &m.self_)
+            }
+            clean::MethodItem(ref m) => {
+                method(&m.decl, &m.generics, m.unsafety, &m.self_)
+            }
+            clean::TypedefItem(ref t) => {
+                object! {
+                    "type" => ty(&t.type_),
+                    "generics" => generics(&t.generics),
+                }
+            }
+            clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => {
+                object! {
+                    "type" => ty(&s.type_),
+                    "mutable" => (s.mutability == clean::Mutable).to_json(),
+                    "expr" => s.expr.to_json(),
+                    "foreign" => match item.inner {
+                        clean::ForeignStaticItem(..) => true,
+                        _ => false,
+                    }.to_json(),
+                }
+            }
+            clean::ConstantItem(ref c) => {
+                object! {
+                    "type" => ty(&c.type_),
+                    "expr" => c.expr.to_json(),
+                }
+            }
+            clean::TraitItem(ref t) => {
+                let items = t.items.iter().map(|m| self.item(m.item(), path)).collect();
+                let implementors = self.implementors.get(&item.def_id).map_or(Vec::new(), |v| {
+                    v.iter().map(|did| def_id(*did)).collect()
+                });
+                object! {
+                    "unsafe" => unsafety(t.unsafety),
+                    "generics" => generics(&t.generics),
+                    "bounds" => bounds(t.bounds.as_slice()),
+                    "items" => Json::Array(items),
+                    "implementors" => Json::Array(implementors),
+                }
+            }
+            clean::ImplItem(ref i) => {
+                object! {
+                    "generics" => generics(&i.generics),
+                    "trait" => i.trait_.as_ref().map_or(Json::Null, ty),
+                    "for" => ty(&i.for_),
+                    "items" => self.items(i.items.as_slice(), &[]),
+                    "derived" => i.derived.to_json(),
+                }
+            }
+            clean::MacroItem(ref m) => object! { "source" => m.source.to_json(), },
+            clean::PrimitiveItem(p) => object! { "name" => p.to_string().to_json(), },
+            clean::AssociatedTypeItem(ref t) => {
+                object! {
+                    "bounds" => bounds(t.bounds.as_slice()),
+                    "default" => t.default.as_ref().map_or(Json::Null, ty),
+                }
+            }
+            clean::ViewItemItem(..) => import(item),
+        }
+    }
+
+    // Fields stripped from the documentation are left out.
+    fn fields(&mut self, fields: &[clean::Item], path: &[String]) -> Json {
+        Json::Array(fields.iter().filter(|f| match f.inner {
+            clean::StructFieldItem(clean::HiddenStructField) => false,
+            _ => true,
+        }).map(|f| self.item(f, path)).collect())
+    }
+
+    fn impls_of(&self, did: ast::DefId) -> Json {
+        Json::Array(self.impls.get(&did).map_or(Vec::new(), |v| {
+            v.iter().map(|did| def_id(*did)).collect()
+        }))
+    }
+}
+
+fn push(map: &mut HashMap<ast::DefId, Vec<ast::DefId>>, key: ast::DefId, value: ast::DefId) {
+    let v = map.entry(key).get().unwrap_or_else(
+        |vacant_entry| vacant_entry.insert(Vec::with_capacity(1)));
+    v.push(value);
+}
+
+/// The paths of the external items the crate refers to, known only when
+/// the crate was documented from source.
+fn external_paths() -> Vec<Json> {
+    let analysis = ::ANALYSISKEY.with(|a| a.clone());
+    let analysis = analysis.borrow();
+    let paths = match analysis.as_ref() {
+        Some(a) => a.external_paths.borrow().clone().unwrap_or(HashMap::new()),
+        None => return Vec::new(),
+    };
+    let mut paths = paths.into_iter().collect::<Vec<_>>();
+    paths.sort_by(|&(a, _), &(b, _)| (a.krate, a.node).cmp(&(b.krate, b.node)));
+    paths.into_iter().map(|(did, (path, kind))| {
+        object! {
+            "id" => def_id(did),
+            "path" => path.to_json(),
+            "kind" => type_kind(kind).to_json(),
+        }
+    }).collect()
+}
+
+// The kinds of items are spelled out here rather than taken from the names
+// of the HTML pages, so that the format doesn't change along with them.
+fn item_kind(item: &clean::Item) -> &'static str {
+    match item.inner {
+        clean::ModuleItem(..) => "mod",
+        clean::StructItem(..) => "struct",
+        clean::EnumItem(..) => "enum",
+        clean::FunctionItem(..) | clean::ForeignFunctionItem(..) => "fn",
+        clean::TypedefItem(..) => "type",
+        clean::StaticItem(..) | clean::ForeignStaticItem(..) => "static",
+        clean::ConstantItem(..) => "constant",
+        clean::TraitItem(..) => "trait",
+        clean::ImplItem(..) => "impl",
+        clean::ViewItemItem(..) => "import",
+        clean::TyMethodItem(..) => "tymethod",
+        clean::MethodItem(..) => "method",
+        clean::StructFieldItem(..) => "structfield",
+        clean::VariantItem(..) => "variant",
+        clean::MacroItem(..) => "macro",
+        clean::PrimitiveItem(..) => "primitive",
+        clean::AssociatedTypeItem(..) => "associatedtype",
+    }
+}
+
+fn type_kind(kind: clean::TypeKind) -> &'static str {
+    match kind {
+        clean::TypeStruct => "struct",
+        clean::TypeEnum => "enum",
+        clean::TypeFunction => "fn",
+        clean::TypeTrait => "trait",
+        clean::TypeModule => "mod",
+        clean::TypeStatic => "static",
+        clean::TypeConst => "constant",
+        clean::TypeVariant => "variant",
+        clean::TypeTypedef => "type",
+    }
+}
+
+fn def_id(did: ast::DefId) -> Json {
+    object! {
+        "krate" => Json::U64(did.krate as u64),
+        "index" => Json::U64(did.node as u64),
+    }
+}
+
+fn span(s: &clean::Span) -> Json {
+    // Items inlined from other crates have no span.
+    if s.filename.is_empty() {
+        return Json::Null;
+    }
+    object! {
+        "file_name" => s.filename.to_json(),
+        "line_start" => Json::U64(s.loline as u64),
+        "column_start" => Json::U64(s.locol as u64 + 1),
+        "line_end" => Json::U64(s.hiline as u64),
+        "column_end" => Json::U64(s.hicol as u64 + 1),
+    }
+}
+
+fn stability_level(level: attr::StabilityLevel) -> &'static str {
+    match level {
+        attr::Deprecated => "deprecated",
+        attr::Experimental => "experimental",
+        attr::Unstable => "unstable",
+        attr::Stable => "stable",
+        attr::Frozen => "frozen",
+        attr::Locked => "locked",
+    }
+}
+
+fn unsafety(u: ast::Unsafety) -> Json {
+    (u == ast::Unsafety::Unsafe).to_json()
+}
+
+fn struct_type(t: ::doctree::StructType) -> Json {
+    match t {
+        ::doctree::Plain => "plain",
+        ::doctree::Tuple => "tuple",
+        ::doctree::Newtype => "newtype",
+        ::doctree::Unit => "unit",
+    }.to_json()
+}
+
+fn lifetimes(ls: &[clean::Lifetime]) -> Json {
+    Json::Array(ls.iter().map(|l| l.get_ref().to_json()).collect())
+}
+
+fn path(p: &clean::Path) -> String {
+    let names = p.segments.iter().map(|s| s.name.as_slice()).collect::<Vec<&str>>();
+    let names = names.connect("::");
+    if p.global { format!("::{}", names) } else { names }
+}
+
+fn import(item: &clean::Item) -> Json {
+    let source = |&: s: &clean::ImportSource| -> (Json, Json) {
+        (path(&s.path).to_json(), s.did.map_or(Json::Null, def_id))
+    };
+    match item.inner {
+        clean::ViewItemItem(clean::ViewItem {
+            inner: clean::ExternCrate(ref name, ref src, _)
+        }) => {
+            object! {
+                "kind" => "extern_crate".to_json(),
+                "name" => name.to_json(),
+                "source" => src.to_json(),
+                "visibility" => (item.visibility == Some(ast::Public)).to_json(),
+            }
+        }
+        clean::ViewItemItem(clean::ViewItem { inner: clean::Import(ref vp) }) => {
+            let (kind, name, src, names) = match *vp {
+                clean::SimpleImport(ref name, ref src) => {
+                    ("simple", Some(name.clone()), src, None)
+                }
+                clean::GlobImport(ref src) => ("glob", None, src, None),
+                clean::ImportList(ref src, ref list) => ("list", None, src, Some(list)),
+            };
+            let (src, id) = source(src);
+            let names = names.map_or(Json::Null, |list| {
+                Json::Array(list.iter().map(|n| object! {
+                    "name" => n.name.to_json(),
+                    "id" => n.source.map_or(Json::Null, def_id),
+                }).collect())
+            });
+            object! {
+                "kind" => kind.to_json(),
+                "name" => name.to_json(),
+                "source" => src,
+                "id" => id,
+                "names" => names,
+                "visibility" => (item.visibility == Some(ast::Public)).to_json(),
+            }
+        }
+        _ => Json::Null,
+    }
+}
+
+fn method(decl: &clean::FnDecl, g: &clean::Generics, u: ast::Unsafety,
+          self_: &clean::SelfTy) -> Json {
+    let self_ = match *self_ {
+        clean::SelfStatic => Json::Null,
+        clean::SelfValue => object! { "kind" => "value".to_json(), },
+        clean::SelfBorrowed(ref l, m) => object! {
+            "kind" => "ref".to_json(),
+            "lifetime" => l.as_ref().map(|l| l.get_ref().to_string()).to_json(),
+            "mutable" => (m == clean::Mutable).to_json(),
+        },
+        clean::SelfExplicit(ref t) => object! {
+            "kind" => "explicit".to_json(),
+            "type" => ty(t),
+        },
+    };
+    object! {
+        "decl" => fn_decl(decl),
+        "generics" => generics(g),
+        "unsafe" => unsafety(u),
+        "self" => self_,
+    }
+}
+
+fn fn_decl(decl: &clean::FnDecl) -> Json {
+    let inputs = decl.inputs.values.iter().map(|arg| object! {
+        "name" => arg.name.to_json(),
+        "type" => ty(&arg.type_),
+    }).collect();
+    let output = match decl.output {
+        clean::Return(ref t) => ty(t),
+        clean::NoReturn => object! { "kind" => "never".to_json(), },
+    };
+    object! {
+        "inputs" => Json::Array(inputs),
+        "output" => output,
+    }
+}
+
+fn generics(g: &clean::Generics) -> Json {
+    let params = g.type_params.iter().map(|p| object! {
+        "name" => p.name.to_json(),
+        "id" => def_id(p.did),
+        "bounds" => bounds(p.bounds.as_slice()),
+        "default" => p.default.as_ref().map_or(Json::Null, ty),
+    }).collect();
+    let predicates = g.where_predicates.iter().map(|p| match *p {
+        clean::WherePredicate::BoundPredicate { ty: ref t, bounds: ref b } => object! {
+            "kind" => "bound".to_json(),
+            "type" => ty(t),
+            "bounds" => bounds(b.as_slice()),
+        },
+        clean::WherePredicate::RegionPredicate { ref lifetime, bounds: ref b } => object! {
+            "kind" => "region".to_json(),
+            "lifetime" => lifetime.get_ref().to_json(),
+            "bounds" => lifetimes(b.as_slice()),
+        },
+        clean::WherePredicate::EqPredicate => object! { "kind" => "eq".to_json(), },
+    }).collect();
+    object! {
+        "lifetimes" => lifetimes(g.lifetimes.as_slice()),
+        "params" => Json::Array(params),
+        "where_predicates" => Json::Array(predicates),
+    }
+}
+
+fn bounds(bs: &[clean::TyParamBound]) -> Json {
+    Json::Array(bs.iter().map(|b| match *b {
+        clean::RegionBound(ref l) => object! {
+            "kind" => "lifetime".to_json(),
+            "lifetime" => l.get_ref().to_json(),
+        },
+        clean::TraitBound(ref t, modifier) => object! {
+            "kind" => "trait".to_json(),
+            "trait" => ty(&t.trait_),
+            "lifetimes" => lifetimes(t.lifetimes.as_slice()),
+            "maybe" => (modifier == ast::TraitBoundModifier::Maybe).to_json(),
+        },
+    }).collect())
+}
+
+fn path_args(p: &clean::Path) -> Json {
+    match p.segments.last().map(|s| &s.params) {
+        Some(&clean::PathParameters::AngleBracketed { lifetimes: ref ls, ref types }) => {
+            object! {
+                "kind" => "angle_bracketed".to_json(),
+                "lifetimes" => lifetimes(ls.as_slice()),
+                "types" => Json::Array(types.iter().map(ty).collect()),
+            }
+        }
+        Some(&clean::PathParameters::Parenthesized { ref inputs, ref output }) => {
+            object! {
+                "kind" => "parenthesized".to_json(),
+                "inputs" => Json::Array(inputs.iter().map(ty).collect()),
+                "output" => output.as_ref().map_or(Json::Null, ty),
+            }
+        }
+        None => Json::Null,
+    }
+}
+
+fn closure(kind: &str, c: &clean::ClosureDecl) -> Json {
+    object! {
+        "kind" => kind.to_json(),
+        "lifetimes" => lifetimes(c.lifetimes.as_slice()),
+        "decl" => fn_decl(&c.decl),
+        "once" => (c.onceness == ast::Once).to_json(),
+        "unsafe" => unsafety(c.unsafety),
+        "bounds" => bounds(c.bounds.as_slice()),
+    }
+}
+
+fn ty(t: &clean::Type) -> Json {
+    match *t {
+        clean::ResolvedPath { path: ref p, ref typarams, did } => {
+            object! {
+                "kind" => "resolved_path".to_json(),
+                "name" => path(p).to_json(),
+                "id" => def_id(did),
+                "args" => path_args(p),
+                "bounds" => typarams.as_ref().map_or(Json::Null, |b| bounds(b.as_slice())),
+            }
+        }
+        clean::Generic(ref name) => object! {
+            "kind" => "generic".to_json(),
+            "name" => name.to_json(),
+        },
+        clean::Primitive(p) => object! {
+            "kind" => "primitive".to_json(),
+            "name" => p.to_string().to_json(),
+        },
+        clean::Closure(ref c) => closure("closure", &**c),
+        clean::Proc(ref c) => closure("proc", &**c),
+        clean::BareFunction(ref f) => object! {
+            "kind" => "function".to_json(),
+            // `clean` keeps the quotes of `extern "C"`.
+            "abi" => f.abi.trim_matches('"').to_json(),
+            "unsafe" => unsafety(f.unsafety),
+            "lifetimes" => lifetimes(f.generics.lifetimes.as_slice()),
+            "decl" => fn_decl(&f.decl),
+        },
+        clean::Tuple(ref tys) => object! {
+            "kind" => "tuple".to_json(),
+            "types" => Json::Array(tys.iter().map(ty).collect()),
+        },
+        clean::Vector(ref t) => object! {
+            "kind" => "slice".to_json(),
+            "type" => ty(&**t),
+        },
+        clean::FixedVector(ref t, ref len) => object! {
+            "kind" => "array".to_json(),
+            "type" => ty(&**t),
+            "len" => len.to_json(),
+        },
+        clean::Bottom => object! { "kind" => "never".to_json(), },
+        clean::Unique(ref t) => object! {
+            "kind" => "box".to_json(),
+            "type" => ty(&**t),
+        },
+        clean::RawPointer(m, ref t) => object! {
+            "kind" => "raw_pointer".to_json(),
+            "mutable" => (m == clean::Mutable).to_json(),
+            "type" => ty(&**t),
+        },
+        clean::BorrowedRef { ref lifetime, mutability, type_: ref t } => object! {
+            "kind" => "ref".to_json(),
+            "lifetime" => lifetime.as_ref().map(|l| l.get_ref().to_string()).to_json(),
+            "mutable" => (mutability == clean::Mutable).to_json(),
+            "type" => ty(&**t),
+        },
+        clean::QPath { ref name, ref self_type, ref trait_ } => object! {
+            "kind" => "qualified_path".to_json(),
+            "name" => name.to_json(),
+            "self_type" => ty(&**self_type),
+            "trait" => ty(&**trait_),
+        },
+        clean::PolyTraitRef(ref b) => object! {
+            "kind" => "trait_object".to_json(),
+            "bounds" => bounds(b.as_slice()),
+        },
+        // Binders are never produced by `clean`.
+        clean::Infer | clean::TyParamBinder(..) => object! { "kind" => "infer".to_json(), },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serialize::json;
+    use super::ty;
+    use clean;
+
+    #[test]
+    fn types() {
+        let t = clean::BorrowedRef {
+            lifetime: None,
+            mutability: clean::Mutable,
+            type_: box clean::Vector(box clean::Generic("T".to_string())),
+        };
+        assert_eq!(ty(&t).to_string(),
+                   "{\"kind\":\"ref\",\"lifetime\":null,\"mutable\":true,\
+                     \"type\":{\"kind\":\"slice\",\"type\":{\"kind\":\"generic\",\
+                     \"name\":\"T\"}}}");
+        assert_eq!(ty(&clean::Bottom), json::from_str("{\"kind\":\"never\"}").unwrap());
+    }
+}
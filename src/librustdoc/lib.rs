@@ -48,6 +48,7 @@ pub use clean::SCHEMA_VERSION;
 pub mod externalfiles;
 
 pub mod clean;
+pub mod commonmark;
 pub mod core;
 pub mod doctree;
 pub mod fold;
@@ -56,15 +57,21 @@ pub mod html {
     pub mod escape;
     pub mod item_type;
     pub mod format;
+    pub mod fulltext;
     pub mod layout;
     pub mod markdown;
     pub mod render;
     pub mod toc;
 }
+pub mod jsondoc;
+pub mod api_diff;
+pub mod man;
 pub mod markdown;
 pub mod passes;
 pub mod plugins;
+pub mod doc_coverage;
 pub mod stability_summary;
+pub mod text;
 pub mod visit_ast;
 pub mod test;
 mod flock;
@@ -118,7 +125,7 @@ pub fn opts() -> Vec<getopts::OptGroup> {
         optopt("r", "input-format", "the input type of the specified file",
                "[rust|json]"),
         optopt("w", "output-format", "the output type to write",
-               "[html|json]"),
+               "[html|json|api-json|markdown|man]"),
         optopt("o", "output", "where to place the output", "PATH"),
         optopt("", "crate-name", "specify the name of this crate", "NAME"),
         optmulti("L", "library-path", "directory to add to crate search path",
@@ -133,6 +140,13 @@ pub fn opts() -> Vec<getopts::OptGroup> {
                  "PLUGINS"),
         optflag("", "no-defaults", "don't run the default passes"),
         optflag("", "test", "run code examples as tests"),
+        optflag("", "show-coverage", "print how many public items are documented and \
+                                       have examples, as a table or, with \
+                                       `--output-format json`, as JSON"),
+        optopt("", "api-diff", "compare the public API of the input with that of an \
+                                 older version of the crate, and list the breaking and \
+                                 compatible changes",
+                "OLD"),
         optmulti("", "test-args", "arguments to pass to the test runner",
                  "ARGS"),
         optopt("", "target", "target triple to document", "TRIPLE"),
@@ -150,6 +164,8 @@ pub fn opts() -> Vec<getopts::OptGroup> {
                  "files to include inline between the content and </body> of a rendered \
                  Markdown file or generated documentation",
                  "FILES"),
+        optflag("", "full-text-search", "also index the text of the documentation, to \
+                                          search it from the generated documentation"),
         optopt("", "markdown-playground-url",
                "URL to send code snippets to", "URL"),
         optflag("", "markdown-no-toc", "don't include table of contents")
@@ -232,6 +248,28 @@ pub fn main_args(args: &[String]) -> int {
     };
     let crate_name = matches.opt_str("crate-name");
 
+    if let Some(old) = matches.opt_str("api-diff") {
+        let old = api_input(old.as_slice(), externs.clone(), &matches);
+        let new = api_input(input, externs, &matches);
+        let diff = match (old, new) {
+            (Ok(old), Ok(new)) => api_diff::diff(&old, &new),
+            (Err(s), _) | (_, Err(s)) => Err(s),
+        };
+        let diff = match diff {
+            Ok(diff) => diff,
+            // Distinct from the status of a breaking change.
+            Err(s) => {
+                println!("input error: {}", s);
+                return 2;
+            }
+        };
+        match matches.opt_str("w").as_ref().map(|s| s.as_slice()) {
+            Some("json") => println!("{}", json::as_pretty_json(&diff)),
+            _ => print!("{}", diff),
+        }
+        return if diff.is_breaking() { 1 } else { 0 };
+    }
+
     match (should_test, markdown_input) {
         (true, true) => {
             return markdown::test(input, libs, externs, test_args)
@@ -253,11 +291,22 @@ pub fn main_args(args: &[String]) -> int {
         }
     };
     let Output { krate, json_plugins, passes, } = out;
+
+    if matches.opt_present("show-coverage") {
+        let coverage = doc_coverage::build(&krate);
+        match matches.opt_str("w").as_ref().map(|s| s.as_slice()) {
+            Some("json") => println!("{}", json::as_pretty_json(&coverage)),
+            _ => print!("{}", coverage),
+        }
+        return 0;
+    }
+
     info!("going to format");
     match matches.opt_str("w").as_ref().map(|s| s.as_slice()) {
         Some("html") | None => {
             match html::render::run(krate, &external_html, output.unwrap_or(Path::new("doc")),
-                                    passes.into_iter().collect()) {
+                                    passes.into_iter().collect(),
+                                    matches.opt_present("full-text-search")) {
                 Ok(()) => {}
                 Err(e) => panic!("failed to generate documentation: {}", e),
             }
@@ -269,6 +318,28 @@ pub fn main_args(args: &[String]) -> int {
                 Err(e) => panic!("failed to write json: {}", e),
             }
         }
+        Some("api-json") => {
+            let json = jsondoc::build(&krate, json_plugins);
+            let dst = output.unwrap_or(Path::new("doc.json"));
+            match File::create(&dst).and_then(|mut f| write!(&mut f, "{}", json)) {
+                Ok(()) => {}
+                Err(e) => panic!("failed to write json: {}", e),
+            }
+        }
+        Some("markdown") => {
+            match commonmark::run(krate, output.unwrap_or(Path::new("doc")),
+                                  passes.into_iter().collect()) {
+                Ok(()) => {}
+                Err(e) => panic!("failed to write markdown: {}", e),
+            }
+        }
+        Some("man") => {
+            match man::run(krate, output.unwrap_or(Path::new("doc")),
+                           passes.into_iter().collect()) {
+                Ok(()) => {}
+                Err(e) => panic!("failed to write man pages: {}", e),
+            }
+        }
         Some(s) => {
             println!("unknown output format: {}", s);
             return 1;
@@ -297,6 +368,30 @@ fn acquire_input(input: &str,
     }
 }
 
+/// Describes the API of a version of the crate for `--api-diff`, either read
+/// from a file written by `--output-format api-json` or documented from
+/// source.
+fn api_input(input: &str,
+             externs: core::Externs,
+             matches: &getopts::Matches) -> Result<Json, String> {
+    if !input.ends_with(".json") {
+        let Output { krate, json_plugins, .. } = rust_input(input, externs, matches);
+        return Ok(jsondoc::build(&krate, json_plugins));
+    }
+    let json = match File::open(&Path::new(input)) {
+        Ok(mut f) => json::from_reader(&mut f).map_err(|e| format!("{:?}", e)),
+        Err(e) => Err(format!("couldn't open {}: {}", input, e)),
+    };
+    let json = try!(json);
+    match json.find("format_version").and_then(|v| v.as_u64()) {
+        Some(jsondoc::FORMAT_VERSION) => Ok(json),
+        Some(v) => Err(format!("{} is in version {} of the JSON format, but only \
+                                version {} is understood",
+                               input, v, jsondoc::FORMAT_VERSION)),
+        None => Err(format!("{} was not written by `--output-format api-json`", input)),
+    }
+}
+
 /// Extracts `--extern CRATE=PATH` arguments from `matches` and
 /// returns a `HashMap` mapping crate names to their paths or else an
 /// error message.
@@ -425,7 +520,8 @@ fn rust_input(cratefile: &str, externs: core::Externs, matches: &getopts::Matche
 /// This input format purely deserializes the json output file. No passes are
 /// run over the deserialized output.
 fn json_input(input: &str) -> Result<Output, String> {
-    let mut input = match File::open(&Path::new(input)) {
+    let input_path = Path::new(input);
+    let mut input = match File::open(&input_path) {
         Ok(f) => f,
         Err(e) => {
             return Err(format!("couldn't open {}: {}", input, e))
@@ -435,6 +531,12 @@ fn json_input(input: &str) -> Result<Output, String> {
         Err(s) => Err(format!("{:?}", s)),
         Ok(Json::Object(obj)) => {
             let mut obj = obj;
+            if obj.contains_key(&"format_version".to_string()) {
+                return Err(format!("{} is in the documented JSON format, which \
+                                    can't be read back; write it with \
+                                    `--output-format json` instead",
+                                   input_path.display()));
+            }
             // Make sure the schema is what we expect
             match obj.remove(&"schema".to_string()) {
                 Some(Json::String(version)) => {
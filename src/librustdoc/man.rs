@@ -0,0 +1,370 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The man pages written by `rustdoc --output-format man`: a page in
+//! section 3 for each documented item, named after its path with `.` in
+//! place of `::`, e.g. `foo.bar.Widget.3`, since `:` is not allowed in the
+//! names of files everywhere. Functions, statics, constants and macros can
+//! share the name of a module or type, so their pages have the keyword of
+//! their kind before their name, e.g. `foo.bar.fn.make.3`. Undocumented
+//! items have no page of their own, but are listed in the page of their
+//! module.
+//!
+//! The pages are written with the `man` macros of roff. The Markdown of the
+//! documentation is translated as far as roff can show it: paragraphs,
+//! headings, lists, code blocks and code spans, and emphasis. Links are left
+//! as their text.
+
+use std::ascii::AsciiExt;
+use std::collections::{HashMap, HashSet};
+use std::io::{fs, File};
+use std::io;
+
+use clean;
+use html::item_type::ItemType;
+use html::render;
+use text;
+use text::Line;
+
+/// Writes the man pages of `krate` to the directory `dst`.
+pub fn run(krate: clean::Crate, dst: Path, passes: HashSet<String>) -> io::IoResult<()> {
+    render::unlinked_cache(&krate);
+    try!(fs::mkdir_recursive(&dst, io::USER_RWX));
+    let root = match krate.module {
+        Some(ref m) => m,
+        None => return Ok(()),
+    };
+    let mut cx = Context {
+        dst: dst,
+        krate: krate.name.clone(),
+        passes: passes,
+        impls: HashMap::new(),
+    };
+    text::collect_impls(root, &mut cx.impls);
+    cx.module(root, &[krate.name.clone()])
+}
+
+struct Context<'a> {
+    dst: Path,
+    krate: String,
+    passes: HashSet<String>,
+    impls: text::Impls<'a>,
+}
+
+impl<'a> Context<'a> {
+    /// Writes the pages of the module `item` at `path` and of the items
+    /// under it.
+    fn module(&self, item: &clean::Item, path: &[String]) -> io::IoResult<()> {
+        let m = match item.inner {
+            clean::ModuleItem(ref m) => m,
+            _ => unreachable!(),
+        };
+        let sections = text::sections(m.items.as_slice(), &self.passes);
+        let documented = item.doc_value().is_some();
+        if documented {
+            let mut w = self.page(item, path);
+            for &(ty, ref items) in sections.iter() {
+                w.push_str(format!(".SH {}\n", ty.section_name().to_ascii_uppercase())
+                               .as_slice());
+                for &child in items.iter() {
+                    let name = child.name.as_ref().unwrap();
+                    w.push_str(".TP\n");
+                    if child.doc_value().is_some() {
+                        let mut child_path = path.to_vec();
+                        child_path.push(name.clone());
+                        let page = page_name(child, child_path.as_slice());
+                        w.push_str(format!(".BR {} (3)\n", escape(page.as_slice())).as_slice());
+                    } else {
+                        w.push_str(format!(".B {}\n", escape(name.as_slice())).as_slice());
+                    }
+                    let summary = summary(child);
+                    if !summary.is_empty() {
+                        w.push_str(format!("{}\n", summary).as_slice());
+                    }
+                }
+            }
+            try!(self.write(w, item, path));
+        }
+
+        for &(_, ref items) in sections.iter() {
+            for &child in items.iter() {
+                let mut child_path = path.to_vec();
+                child_path.push(child.name.clone().unwrap());
+                match child.inner {
+                    clean::ModuleItem(..) => try!(self.module(child, child_path.as_slice())),
+                    _ if child.doc_value().is_some() => {
+                        let mut w = self.page(child, child_path.as_slice());
+                        self.members(&mut w, child);
+                        try!(self.write(w, child, child_path.as_slice()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The start of the page of `item`, at `path`: its name, signature and
+    /// documentation.
+    fn page(&self, item: &clean::Item, path: &[String]) -> String {
+        let name = escape(path.connect("::").as_slice());
+        let mut w = format!(".TH \"{}\" 3 \"\" \"{}\" \"Rust Library Documentation\"\n",
+                            name, escape(self.krate.as_slice()));
+        w.push_str(format!(".SH NAME\n{} \\- {}\n", name, summary(item)).as_slice());
+        match text::signature(item) {
+            Some(sig) => {
+                w.push_str(".SH SYNOPSIS\n.nf\n");
+                for line in sig.lines() {
+                    w.push_str(escape(line).as_slice());
+                    w.push('\n');
+                }
+                w.push_str(".fi\n");
+            }
+            None => {}
+        }
+        w.push_str(".SH DESCRIPTION\n");
+        docs(&mut w, item, ".PP");
+        w
+    }
+
+    /// Writes the sections of the fields, variants, methods and associated
+    /// types of `item`.
+    fn members(&self, w: &mut String, item: &clean::Item) {
+        match item.inner {
+            clean::StructItem(ref s) => {
+                let fields = s.fields.iter().collect::<Vec<&clean::Item>>();
+                member_list(w, "FIELDS", fields.as_slice());
+            }
+            clean::EnumItem(ref e) => {
+                let variants = e.variants.iter().collect::<Vec<&clean::Item>>();
+                member_list(w, "VARIANTS", variants.as_slice());
+            }
+            clean::TraitItem(ref t) => {
+                let (types, required, provided) = text::trait_items(t);
+                member_list(w, "ASSOCIATED TYPES", types.as_slice());
+                member_list(w, "REQUIRED METHODS", required.as_slice());
+                member_list(w, "PROVIDED METHODS", provided.as_slice());
+            }
+            _ => {}
+        }
+        match self.impls.get(&item.def_id) {
+            Some(impls) => {
+                w.push_str(".SH METHODS\n");
+                for i in impls.iter() {
+                    w.push_str(format!(".SS {}\n", escape(text::impl_header(*i).as_slice()))
+                                   .as_slice());
+                    for it in i.items.iter() {
+                        member(w, it, text::signature(it));
+                    }
+                }
+            }
+            None => {}
+        }
+    }
+
+    fn write(&self, page: String, item: &clean::Item, path: &[String]) -> io::IoResult<()> {
+        let dst = self.dst.join(format!("{}.3", page_name(item, path)));
+        File::create(&dst).write_str(page.as_slice())
+    }
+}
+
+/// The name of the page of `item`, at `path`, which is also the name of its
+/// file without the section. The kinds are spelled as keywords, which no
+/// module can be named.
+fn page_name(item: &clean::Item, path: &[String]) -> String {
+    let kind = match ItemType::from_item(item) {
+        ItemType::Function => "fn",
+        ItemType::Static => "static",
+        ItemType::Constant => "const",
+        ItemType::Macro => "macro",
+        _ => return path.connect("."),
+    };
+    format!("{}.{}.{}", path.init().connect("."), kind, path.last().unwrap())
+}
+
+/// Writes a section listing the fields, variants or methods `items`.
+fn member_list(w: &mut String, title: &str, items: &[&clean::Item]) {
+    let sigs = items.iter().enumerate().map(|(i, it)| text::member_signature(*it, i))
+                    .collect::<Vec<Option<String>>>();
+    if sigs.iter().all(|sig| sig.is_none()) { return }
+    w.push_str(format!(".SH {}\n", title).as_slice());
+    for (&it, sig) in items.iter().zip(sigs.into_iter()) {
+        member(w, it, sig);
+    }
+}
+
+/// Writes the signature `sig` of `item`, followed by its documentation,
+/// indented.
+fn member(w: &mut String, item: &clean::Item, sig: Option<String>) {
+    let sig = match sig {
+        Some(sig) => sig,
+        None => return,
+    };
+    w.push_str(".TP\n");
+    w.push_str(format!(".B {}\n", escape(sig.replace("\n", " ").as_slice())).as_slice());
+    if item.doc_value().is_some() {
+        docs(w, item, ".IP");
+    }
+}
+
+/// The first paragraph of the documentation of `item`, on one line.
+fn summary(item: &clean::Item) -> String {
+    let short = render::shorter(item.doc_value());
+    let text = short.lines().map(|l| l.trim()).collect::<Vec<&str>>().connect(" ");
+    inline(text.as_slice())
+}
+
+/// Writes the documentation of `item`, starting paragraphs with `para`:
+/// `.PP`, or `.IP` to keep them indented.
+fn docs(w: &mut String, item: &clean::Item, para: &str) {
+    let doc = match item.doc_value() {
+        Some(doc) => doc,
+        None => return,
+    };
+    // Whether the text written last is the end of a paragraph, and the next
+    // must start a new one.
+    let mut new_para = false;
+    for line in text::lines(doc).into_iter() {
+        match line {
+            Line::Text(s) if s.trim().is_empty() => new_para = true,
+            // Link reference definitions show nothing.
+            Line::Text(s) if s.trim_left().starts_with("[") && s.contains("]:") => {}
+            Line::Text(s) => {
+                let s = s.trim();
+                if s.starts_with("* ") || s.starts_with("- ") || s.starts_with("+ ") {
+                    w.push_str(".IP \\(bu 2\n");
+                    w.push_str(inline(s.slice_from(2)).as_slice());
+                } else {
+                    if new_para {
+                        w.push_str(format!("{}\n", para).as_slice());
+                    }
+                    w.push_str(line_start(inline(s)).as_slice());
+                }
+                w.push('\n');
+                new_para = false;
+            }
+            Line::Heading(_, s) => {
+                if para == ".PP" {
+                    w.push_str(format!(".SS {}\n", escape(s)).as_slice());
+                } else {
+                    w.push_str(format!("{}\n.B {}\n", para, escape(s)).as_slice());
+                }
+                new_para = false;
+            }
+            Line::StartCode(..) => w.push_str(format!("{}\n.RS 4\n.nf\n", para).as_slice()),
+            Line::Code(s) => {
+                w.push_str(line_start(escape(s)).as_slice());
+                w.push('\n');
+            }
+            Line::EndCode => {
+                w.push_str(".fi\n.RE\n");
+                new_para = true;
+            }
+        }
+    }
+}
+
+/// `s` escaped for roff: backslashes and minus signs, which roff would
+/// otherwise show as hyphens.
+fn escape(s: &str) -> String {
+    s.replace("\\", "\\e").replace("-", "\\-")
+}
+
+/// Keeps a line of text that starts with a dot or an apostrophe from being
+/// read as a request.
+fn line_start(s: String) -> String {
+    if s.starts_with(".") || s.starts_with("'") {
+        format!("\\&{}", s)
+    } else {
+        s
+    }
+}
+
+/// Translates the Markdown of a line of text: code spans are shown in bold,
+/// as is strong emphasis, other emphasis in italics, and links as their
+/// text.
+fn inline(s: &str) -> String {
+    let mut out = String::new();
+    let mut bold = false;
+    let mut italic = false;
+    let mut rest = s;
+    while !rest.is_empty() {
+        if rest.starts_with("`") {
+            match rest.slice_from(1).find('`') {
+                Some(end) => {
+                    out.push_str("\\fB");
+                    out.push_str(escape(rest.slice(1, end + 1)).as_slice());
+                    out.push_str(if italic { "\\fI" } else { "\\fR" });
+                    rest = rest.slice_from(end + 2);
+                    continue;
+                }
+                None => {}
+            }
+        } else if rest.starts_with("**") {
+            bold = !bold;
+            out.push_str(if bold { "\\fB" } else if italic { "\\fI" } else { "\\fR" });
+            rest = rest.slice_from(2);
+            continue;
+        } else if rest.starts_with("*") && (italic || !rest.slice_from(1).starts_with(" ")) {
+            italic = !italic;
+            out.push_str(if italic { "\\fI" } else if bold { "\\fB" } else { "\\fR" });
+            rest = rest.slice_from(1);
+            continue;
+        } else if rest.starts_with("[") {
+            // A link, written `[text](url)`, `[text][name]` or `[text]`.
+            match rest.find(']') {
+                Some(end) => {
+                    out.push_str(inline(rest.slice(1, end)).as_slice());
+                    rest = rest.slice_from(end + 1);
+                    let close = if rest.starts_with("(") {
+                        rest.find(')')
+                    } else if rest.starts_with("[") {
+                        rest.find(']')
+                    } else {
+                        None
+                    };
+                    if let Some(close) = close {
+                        rest = rest.slice_from(close + 1);
+                    }
+                    continue;
+                }
+                None => {}
+            }
+        }
+        let c = rest.char_at(0);
+        out.push_str(escape(rest.slice_to(c.len_utf8())).as_slice());
+        rest = rest.slice_from(c.len_utf8());
+    }
+    if bold || italic {
+        out.push_str("\\fR");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, inline, line_start};
+
+    #[test]
+    fn escapes() {
+        assert_eq!(escape(r"a\b-c"), r"a\eb\-c");
+        assert_eq!(line_start(".foo".to_string()), r"\&.foo");
+        assert_eq!(line_start("foo.".to_string()), "foo.");
+    }
+
+    #[test]
+    fn translates_inline_markdown() {
+        assert_eq!(inline("Calls `foo-bar` *now*."), r"Calls \fBfoo\-bar\fR \fInow\fR.");
+        assert_eq!(inline("**Never** do [this](http://x.org/) or [that][1]."),
+                   r"\fBNever\fR do this or that.");
+        assert_eq!(inline("See [`Vec`] and *a `b` c*"), r"See \fBVec\fR and \fIa \fBb\fI c\fR");
+        assert_eq!(inline("a * b"), "a * b");
+    }
+}
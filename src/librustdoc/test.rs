@@ -15,6 +15,7 @@ use std::io::{Command, TempDir};
 use std::io;
 use std::os;
 use std::str;
+use std::sync::{Arc, Mutex};
 use std::thread::Thread;
 use std::thunk::Thunk;
 
@@ -34,6 +35,7 @@ use clean;
 use clean::Clean;
 use fold::DocFolder;
 use html::markdown;
+use html::markdown::LangString;
 use passes;
 use visit_ast::RustdocVisitor;
 
@@ -109,27 +111,12 @@ pub fn run(input: &str,
 }
 
 fn runtest(test: &str, cratename: &str, libs: SearchPaths,
-           externs: core::Externs,
-           should_fail: bool, no_run: bool, as_test_harness: bool) {
+           externs: core::Externs, info: LangString) {
+    let LangString { should_fail, no_run, test_harness: as_test_harness,
+                     compile_fail, error_codes, cfgs, .. } = info;
     // the test harness wants its own `main` & top level functions, so
     // never wrap the test in `fn main() { ... }`
     let test = maketest(test, Some(cratename), true, as_test_harness);
-    let input = config::Input::Str(test.to_string());
-
-    let sessopts = config::Options {
-        maybe_sysroot: Some(os::self_exe_path().unwrap().dir_path()),
-        search_paths: libs,
-        crate_types: vec!(config::CrateTypeExecutable),
-        output_types: vec!(config::OutputTypeExe),
-        no_trans: no_run,
-        externs: externs,
-        cg: config::CodegenOptions {
-            prefer_dynamic: true,
-            .. config::basic_codegen_options()
-        },
-        test: as_test_harness,
-        ..config::basic_options().clone()
-    };
 
     // Shuffle around a few input and output handles here. We're going to pass
     // an explicit handle into rustc to collect output messages, but we also
@@ -158,36 +145,102 @@ fn runtest(test: &str, cratename: &str, libs: SearchPaths,
         };
         io::util::copy(&mut p, &mut err).unwrap();
     });
-    let emitter = diagnostic::EmitterWriter::new(box w2, None);
 
-    // Compile the code
-    let codemap = CodeMap::new();
-    let diagnostic_handler = diagnostic::mk_handler(box emitter);
-    let span_diagnostic_handler =
-        diagnostic::mk_span_handler(diagnostic_handler, codemap);
-
-    let sess = session::build_session_(sessopts,
-                                      None,
-                                      span_diagnostic_handler);
+    // The messages of a test that must fail to compile are kept to look for
+    // the expected error codes, and only shown if they are not there.
+    let (msg_tx, msg_rx) = channel();
+    let emitter = if compile_fail {
+        diagnostic::EmitterWriter::new(box io::ChanWriter::new(msg_tx), None)
+    } else {
+        diagnostic::EmitterWriter::new(box w2, None)
+    };
 
     let outdir = TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir");
-    let out = Some(outdir.path().clone());
-    let cfg = config::build_configuration(&sess);
-    let libdir = sess.target_filesearch(PathKind::All).get_lib_path();
-    driver::compile_input(sess, cfg, &input, &out, &None, None);
+    let compiled = compile(test, libs, externs, cfgs, no_run, as_test_harness,
+                           emitter, outdir.path());
+
+    let libdir = match compiled {
+        Some(_) if compile_fail => {
+            panic!("test compiled successfully, but it's marked `compile_fail`");
+        }
+        Some(libdir) => libdir,
+        None if compile_fail => {
+            let messages = io::ChanReader::new(msg_rx).read_to_string().unwrap_or(String::new());
+            let missing = error_codes.iter().filter(|code| {
+                !messages.contains(format!("[{}]", code).as_slice())
+            }).map(|code| code.as_slice()).collect::<Vec<&str>>();
+            if !missing.is_empty() {
+                panic!("some expected error codes were not found: {}\n{}",
+                       missing.connect(", "), messages);
+            }
+            return;
+        }
+        None => panic!("couldn't compile the test"),
+    };
 
     if no_run { return }
 
-    // Run the code!
-    //
+    run_binary(Command::new(outdir.path().join("rust-out")), libdir, should_fail);
+}
+
+/// Compiles the program `source` into `outdir`, reporting its errors to
+/// `emitter`. Returns the directory of the target's libraries, or `None` if
+/// the program did not compile.
+fn compile(source: String, libs: SearchPaths, externs: core::Externs,
+           cfgs: Vec<String>, no_run: bool, as_test_harness: bool,
+           emitter: diagnostic::EmitterWriter, outdir: &Path) -> Option<Path> {
+    let input = config::Input::Str(source);
+    let sessopts = config::Options {
+        maybe_sysroot: Some(os::self_exe_path().unwrap().dir_path()),
+        search_paths: libs,
+        crate_types: vec!(config::CrateTypeExecutable),
+        output_types: vec!(config::OutputTypeExe),
+        no_trans: no_run,
+        externs: externs,
+        cg: config::CodegenOptions {
+            prefer_dynamic: true,
+            .. config::basic_codegen_options()
+        },
+        test: as_test_harness,
+        ..config::basic_options().clone()
+    };
+
+    // Errors abort the compilation by panicking, so it's done in its own
+    // task.
+    let out = Some(outdir.clone());
+    Thread::scoped(move |:| {
+        // The compiler's errors are reported already, not the panic.
+        io::stdio::set_stderr(box io::util::NullWriter);
+
+        let codemap = CodeMap::new();
+        let diagnostic_handler = diagnostic::mk_handler(box emitter);
+        let span_diagnostic_handler =
+            diagnostic::mk_span_handler(diagnostic_handler, codemap);
+
+        let sess = session::build_session_(sessopts,
+                                          None,
+                                          span_diagnostic_handler);
+
+        let mut cfg = config::build_configuration(&sess);
+        cfg.extend(cfgs.into_iter().map(|cfg_| {
+            let cfg_ = token::intern_and_get_ident(cfg_.as_slice());
+            P(dummy_spanned(ast::MetaWord(cfg_)))
+        }));
+        let libdir = sess.target_filesearch(PathKind::All).get_lib_path();
+        driver::compile_input(sess, cfg, &input, &out, &None, None);
+        libdir
+    }).join().ok()
+}
+
+/// Runs a compiled test, checking that it fails exactly when it should.
+fn run_binary(mut cmd: Command, libdir: Path, should_fail: bool) {
     // We're careful to prepend the *target* dylib search path to the child's
     // environment to ensure that the target loads the right libraries at
     // runtime. It would be a sad day if the *host* libraries were loaded as a
     // mistake.
-    let mut cmd = Command::new(outdir.path().join("rust-out"));
     let newpath = {
         let mut path = DynamicLibrary::search_path();
-        path.insert(0, libdir.clone());
+        path.insert(0, libdir);
         DynamicLibrary::create_path(path.as_slice())
     };
     cmd.env(DynamicLibrary::envvar(), newpath.as_slice());
@@ -208,6 +261,74 @@ fn runtest(test: &str, cratename: &str, libs: SearchPaths,
     }
 }
 
+/// Examples that are compiled together into a single program, rather than
+/// one program each. Each example is wrapped in a module of its own, and the
+/// program runs the example whose index it is given as its argument.
+struct Batch {
+    tests: Vec<String>,
+    /// The directory of the compiled program and that of the target's
+    /// libraries, once compiled, or `None` if the batch did not compile.
+    compiled: Option<Option<(TempDir, Path)>>,
+}
+
+/// Whether the example can be compiled as part of a `Batch`. Examples that
+/// need a crate of their own to behave as written are not: those with their
+/// own `main`, crate attributes or `extern crate`s, and those compiled in a
+/// special way.
+fn can_batch(test: &str, info: &LangString) -> bool {
+    !info.test_harness && !info.compile_fail && info.cfgs.is_empty() &&
+        !test.contains("fn main") && !test.contains("#![") && !test.contains("extern crate")
+}
+
+fn batch_source(tests: &[String], cratename: &str) -> String {
+    let mut prog = String::new();
+    // `use` paths start at the crate root, so the crate is linked there too.
+    if cratename != "std" && tests.iter().any(|t| t.contains(cratename)) {
+        prog.push_str(format!("extern crate {};\n", cratename).as_slice());
+    }
+    for (i, test) in tests.iter().enumerate() {
+        // Paths in expressions are relative to the module, unlike at the root
+        // of a crate of its own, so `std` is imported for `std::mem::swap(..)`
+        // and the like. It goes after the `extern crate` of the example, and
+        // before the `main` wrapping it, which batched examples never define.
+        let test = maketest(test.as_slice(), Some(cratename), true, false);
+        let main = test.find_str("fn main() {").unwrap_or(test.len());
+        let test = format!("{}#[allow(unused_imports)]\nuse std;\n{}",
+                           test.slice_to(main), test.slice_from(main));
+        prog.push_str(format!("mod __doctest_{} {{\n{}\npub fn __run() {{ main() }}\n}}\n",
+                              i, test).as_slice());
+    }
+    prog.push_str("fn main() {\n    match ::std::os::args()[1].as_slice() {\n");
+    for i in range(0, tests.len()) {
+        prog.push_str(format!("        \"{0}\" => __doctest_{0}::__run(),\n", i).as_slice());
+    }
+    prog.push_str("        _ => panic!(\"no such test\"),\n    }\n}\n");
+    prog
+}
+
+/// Compiles the batch, unless it was already, and returns the path of the
+/// program and the directory of the target's libraries. The lock is held
+/// while compiling, so the tests of the batch wait for the first of them to
+/// compile it.
+fn compile_batch(batch: &Mutex<Batch>, cratename: &str, libs: SearchPaths,
+                 externs: core::Externs) -> Option<(Path, Path)> {
+    let mut batch = batch.lock().unwrap();
+    if batch.compiled.is_none() {
+        let source = batch_source(batch.tests.as_slice(), cratename);
+        debug!("Compiling batch of {} tests", batch.tests.len());
+        // The examples are compiled on their own if the batch fails, so its
+        // errors are not reported.
+        let emitter = diagnostic::EmitterWriter::new(box io::util::NullWriter, None);
+        let outdir = TempDir::new("rustdoctest").ok().expect("rustdoc needs a tempdir");
+        let libdir = compile(source, libs, externs, Vec::new(), false, false,
+                             emitter, outdir.path());
+        batch.compiled = Some(libdir.map(|libdir| (outdir, libdir)));
+    }
+    batch.compiled.as_ref().unwrap().as_ref().map(|&(ref outdir, ref libdir)| {
+        (outdir.path().join("rust-out"), libdir.clone())
+    })
+}
+
 pub fn maketest(s: &str, cratename: Option<&str>, lints: bool, dont_insert_main: bool) -> String {
     let mut prog = String::new();
     if lints {
@@ -250,6 +371,7 @@ pub struct Collector {
     use_headers: bool,
     current_header: Option<String>,
     cratename: String,
+    batch: Arc<Mutex<Batch>>,
 }
 
 impl Collector {
@@ -264,11 +386,11 @@ impl Collector {
             use_headers: use_headers,
             current_header: None,
             cratename: cratename,
+            batch: Arc::new(Mutex::new(Batch { tests: Vec::new(), compiled: None })),
         }
     }
 
-    pub fn add_test(&mut self, test: String,
-                    should_fail: bool, no_run: bool, should_ignore: bool, as_test_harness: bool) {
+    pub fn add_test(&mut self, test: String, info: LangString) {
         let name = if self.use_headers {
             let s = self.current_header.as_ref().map(|s| s.as_slice()).unwrap_or("");
             format!("{}_{}", s, self.cnt)
@@ -279,21 +401,43 @@ impl Collector {
         let libs = self.libs.clone();
         let externs = self.externs.clone();
         let cratename = self.cratename.to_string();
+        let batch = if !info.ignore && can_batch(test.as_slice(), &info) {
+            let mut batch = self.batch.lock().unwrap();
+            batch.tests.push(test.clone());
+            Some((self.batch.clone(), batch.tests.len() - 1))
+        } else {
+            None
+        };
         debug!("Creating test {}: {}", name, test);
         self.tests.push(testing::TestDescAndFn {
             desc: testing::TestDesc {
                 name: testing::DynTestName(name),
-                ignore: should_ignore,
+                ignore: info.ignore,
                 should_fail: testing::ShouldFail::No, // compiler failures are test failures
             },
             testfn: testing::DynTestFn(Thunk::new(move|| {
-                runtest(test.as_slice(),
-                        cratename.as_slice(),
-                        libs,
-                        externs,
-                        should_fail,
-                        no_run,
-                        as_test_harness);
+                let compiled = match batch {
+                    Some((ref batch, _)) => {
+                        compile_batch(&**batch, cratename.as_slice(),
+                                      libs.clone(), externs.clone())
+                    }
+                    None => None,
+                };
+                match (compiled, batch) {
+                    (Some((exe, libdir)), Some((_, index))) => {
+                        if info.no_run { return }
+                        let mut cmd = Command::new(exe);
+                        cmd.arg(index.to_string());
+                        run_binary(cmd, libdir, info.should_fail);
+                    }
+                    // Examples that can't be batched, or whose batch did
+                    // not compile, are compiled on their own.
+                    _ => runtest(test.as_slice(),
+                                 cratename.as_slice(),
+                                 libs,
+                                 externs,
+                                 info),
+                }
             }))
         });
     }
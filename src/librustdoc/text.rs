@@ -0,0 +1,351 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! What the backends writing text rather than HTML share: the Markdown
+//! files of `--output-format markdown` and the man pages of
+//! `--output-format man`.
+//!
+//! They document the crate as it comes out of the passes, listing the same
+//! items as the HTML pages, in the same order, and write signatures as
+//! `html::format` does, without the markup.
+
+use std::collections::{HashMap, HashSet};
+
+use syntax::ast;
+
+use clean;
+use html::format::{VisSpace, UnsafetySpace, MutableSpace, Method};
+use html::format::{WhereClause, TyParamBounds};
+use html::escape::Escape;
+use html::item_type::ItemType;
+use html::markdown::{self, LangString};
+
+/// The impls of the crate, by the type they are for.
+pub type Impls<'a> = HashMap<ast::DefId, Vec<&'a clean::Impl>>;
+
+/// Collects the impls of the items under `item`.
+pub fn collect_impls<'a>(item: &'a clean::Item, impls: &mut Impls<'a>) {
+    match item.inner {
+        clean::ModuleItem(ref m) => {
+            for child in m.items.iter() {
+                collect_impls(child, impls);
+            }
+        }
+        clean::ImplItem(ref i) => {
+            if let clean::ResolvedPath { did, .. } = i.for_ {
+                let v = impls.entry(did).get().unwrap_or_else(
+                    |vacant_entry| vacant_entry.insert(Vec::new()));
+                v.push(i);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The items of a module that are documented, grouped by type in the order
+/// the HTML pages list them. Reexports, impls and primitives are left out,
+/// as are private modules when `strip-private` was run.
+pub fn sections<'a>(items: &'a [clean::Item],
+                    passes: &HashSet<String>) -> Vec<(ItemType, Vec<&'a clean::Item>)> {
+    let mut listed = items.iter().filter(|item| {
+        if item.name.is_none() { return false }
+        match item.inner {
+            clean::ModuleItem(ref m) => {
+                !((m.items.len() == 0 && item.doc_value().is_none()) ||
+                  (passes.contains("strip-private") && item.visibility != Some(ast::Public)))
+            }
+            clean::ViewItemItem(..) | clean::ImplItem(..) | clean::PrimitiveItem(..) => false,
+            _ => true,
+        }
+    }).collect::<Vec<&clean::Item>>();
+    listed.sort_by(|a, b| {
+        let (ta, tb) = (ItemType::from_item(*a), ItemType::from_item(*b));
+        (ta.section_order(), &a.name).cmp(&(tb.section_order(), &b.name))
+    });
+
+    let mut sections: Vec<(ItemType, Vec<&clean::Item>)> = Vec::new();
+    for item in listed.into_iter() {
+        let ty = ItemType::from_item(item);
+        match sections.last_mut() {
+            Some(&mut (last, ref mut v)) if last == ty => { v.push(item); continue }
+            _ => {}
+        }
+        sections.push((ty, vec![item]));
+    }
+    sections
+}
+
+/// The associated types, required methods and provided methods of the
+/// trait `t`, in the order they are declared.
+pub fn trait_items(t: &clean::Trait) -> (Vec<&clean::Item>, Vec<&clean::Item>,
+                                         Vec<&clean::Item>) {
+    let (mut types, mut required, mut provided) = (Vec::new(), Vec::new(), Vec::new());
+    for m in t.items.iter() {
+        match *m {
+            clean::TypeTraitItem(ref it) => types.push(it),
+            clean::RequiredMethod(ref it) => required.push(it),
+            clean::ProvidedMethod(ref it) => provided.push(it),
+        }
+    }
+    (types, required, provided)
+}
+
+/// The signature of `item` as it would be written in Rust, or `None` for
+/// items that have none, like impls. Structs and enums are written with
+/// their fields and variants.
+pub fn signature(item: &clean::Item) -> Option<String> {
+    markup(item).map(|sig| unhtml(sig.as_slice()))
+}
+
+/// The signature of the field, variant or method `item`, the `i`th member
+/// of its parent. The fields of tuple structs have no name, so they are
+/// written with their position instead, e.g. `pub 0: u32`.
+pub fn member_signature(item: &clean::Item, i: uint) -> Option<String> {
+    match (&item.inner, &item.name) {
+        (&clean::StructFieldItem(clean::TypedStructField(ref t)), &None) => {
+            Some(unhtml(format!("{}{}: {}", VisSpace(item.visibility), i, t).as_slice()))
+        }
+        _ => signature(item),
+    }
+}
+
+/// The signature of `item` as `html::format` writes it, with markup.
+fn markup(item: &clean::Item) -> Option<String> {
+    let name = match item.name {
+        Some(ref name) => name.as_slice(),
+        None => "",
+    };
+    let vis = VisSpace(item.visibility);
+    let sig = match item.inner {
+        clean::ModuleItem(ref m) if m.is_crate => format!("extern crate {};", name),
+        clean::ModuleItem(..) => format!("{}mod {}", vis, name),
+        clean::FunctionItem(ref f) | clean::ForeignFunctionItem(ref f) => {
+            format!("{}{}fn {}{}{}{}", vis, UnsafetySpace(f.unsafety), name,
+                    f.generics, f.decl, WhereClause(&f.generics))
+        }
+        clean::TyMethodItem(ref m) => {
+            format!("{}fn {}{}{}{}", UnsafetySpace(m.unsafety), name, m.generics,
+                    Method(&m.self_, &m.decl), WhereClause(&m.generics))
+        }
+        clean::MethodItem(ref m) => {
+            format!("{}{}fn {}{}{}{}", vis, UnsafetySpace(m.unsafety), name, m.generics,
+                    Method(&m.self_, &m.decl), WhereClause(&m.generics))
+        }
+        clean::StructItem(ref s) => {
+            let head = format!("{}struct {}{}{}", vis, name, s.generics,
+                               WhereClause(&s.generics));
+            struct_body(head, s.struct_type, s.fields.as_slice(), s.fields_stripped, ";")
+        }
+        clean::EnumItem(ref e) => {
+            let mut s = format!("{}enum {}{}{} {{\n", vis, name, e.generics,
+                                WhereClause(&e.generics));
+            for v in e.variants.iter() {
+                s.push_str(format!("    {},\n", markup(v).unwrap()).as_slice());
+            }
+            if e.variants_stripped {
+                s.push_str("    // some variants omitted\n");
+            }
+            s.push_str("}");
+            s
+        }
+        clean::VariantItem(ref v) => {
+            match v.kind {
+                clean::CLikeVariant => name.to_string(),
+                clean::TupleVariant(ref tys) => {
+                    let tys = tys.iter().map(|t| format!("{}", t)).collect::<Vec<String>>();
+                    format!("{}({})", name, tys.connect(", "))
+                }
+                clean::StructVariant(ref s) => {
+                    let body = struct_body(name.to_string(), s.struct_type,
+                                           s.fields.as_slice(), s.fields_stripped, "");
+                    body.replace("\n", "\n    ")
+                }
+            }
+        }
+        clean::StructFieldItem(clean::TypedStructField(ref t)) => {
+            format!("{}{}: {}", vis, name, t)
+        }
+        clean::StructFieldItem(clean::HiddenStructField) => return None,
+        clean::TraitItem(ref t) => {
+            let bounds = if t.bounds.is_empty() {
+                String::new()
+            } else {
+                format!(": {}", TyParamBounds(t.bounds.as_slice()))
+            };
+            format!("{}{}trait {}{}{}{}", vis, UnsafetySpace(t.unsafety), name,
+                    t.generics, bounds, WhereClause(&t.generics))
+        }
+        clean::AssociatedTypeItem(ref t) => {
+            let mut s = format!("type {}", name);
+            if !t.bounds.is_empty() {
+                s.push_str(format!(": {}", TyParamBounds(t.bounds.as_slice())).as_slice());
+            }
+            if let Some(ref default) = t.default {
+                s.push_str(format!(" = {}", default).as_slice());
+            }
+            s
+        }
+        clean::TypedefItem(ref t) => {
+            format!("type {}{} = {};", name, t.generics, t.type_)
+        }
+        clean::StaticItem(ref s) | clean::ForeignStaticItem(ref s) => {
+            let sig = format!("{}static {}{}: {}", vis, MutableSpace(s.mutability), name,
+                              s.type_);
+            initialized(sig, s.expr.as_slice())
+        }
+        clean::ConstantItem(ref c) => {
+            initialized(format!("{}const {}: {}", vis, name, c.type_), c.expr.as_slice())
+        }
+        clean::MacroItem(ref m) => format!("{}", Escape(m.source.as_slice())),
+        clean::ViewItemItem(..) | clean::ImplItem(..) | clean::PrimitiveItem(..) => {
+            return None
+        }
+    };
+    Some(sig)
+}
+
+/// The header of an impl, like `impl<T> Clone for Foo<T>`.
+pub fn impl_header(i: &clean::Impl) -> String {
+    let s = match i.trait_ {
+        Some(ref t) => format!("impl{} {} for {}{}", i.generics, t, i.for_,
+                               WhereClause(&i.generics)),
+        None => format!("impl{} {}{}", i.generics, i.for_, WhereClause(&i.generics)),
+    };
+    unhtml(s.as_slice())
+}
+
+fn struct_body(head: String, ty: ::doctree::StructType, fields: &[clean::Item],
+               stripped: bool, end: &str) -> String {
+    match ty {
+        ::doctree::Plain => {
+            let mut s = format!("{} {{\n", head);
+            for field in fields.iter() {
+                if let Some(sig) = markup(field) {
+                    s.push_str(format!("    {},\n", sig).as_slice());
+                }
+            }
+            if stripped || fields.iter().any(|f| markup(f).is_none()) {
+                s.push_str("    // some fields omitted\n");
+            }
+            s.push_str("}");
+            s
+        }
+        ::doctree::Tuple | ::doctree::Newtype => {
+            let fields = fields.iter().map(|field| {
+                match field.inner {
+                    clean::StructFieldItem(clean::TypedStructField(ref t)) => {
+                        format!("{}{}", VisSpace(field.visibility), t)
+                    }
+                    _ => "_".to_string(),
+                }
+            }).collect::<Vec<String>>();
+            format!("{}({}){}", head, fields.connect(", "), end)
+        }
+        ::doctree::Unit => format!("{}{}", head, end),
+    }
+}
+
+/// The markup of a static or constant, with its value if it has one. The
+/// value is source code, not markup.
+fn initialized(sig: String, expr: &str) -> String {
+    if expr.is_empty() { sig } else { format!("{} = {}", sig, Escape(expr)) }
+}
+
+/// `s` without its HTML tags, and with its entities replaced by the
+/// characters they stand for.
+pub fn unhtml(s: &str) -> String {
+    let mut out = String::new();
+    let mut in_tag = false;
+    for c in s.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if in_tag => {}
+            c => out.push(c),
+        }
+    }
+    out.replace("&lt;", "<").replace("&gt;", ">").replace("&#39;", "'")
+       .replace("&quot;", "\"").replace("&nbsp;", " ").replace("&amp;", "&")
+}
+
+/// A line of documentation, as the text backends see it.
+#[derive(PartialEq, Show)]
+pub enum Line<'a> {
+    /// A line of text.
+    Text(&'a str),
+    /// A heading, with its level and its text.
+    Heading(uint, &'a str),
+    /// The fence starting a code block, with whether it is Rust code.
+    StartCode(bool),
+    /// A line of a code block.
+    Code(&'a str),
+    /// The fence ending a code block.
+    EndCode,
+}
+
+/// The lines of the documentation `doc`. The lines of Rust code blocks that
+/// are hidden from the HTML pages are left out.
+pub fn lines(doc: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut code = None;
+    for line in doc.lines() {
+        let trimmed = line.trim_left();
+        match code {
+            Some(rust) => {
+                if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                    lines.push(Line::EndCode);
+                    code = None;
+                } else if !rust || markdown::stripped_filtered_line(line).is_none() {
+                    lines.push(Line::Code(line));
+                }
+            }
+            None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                let rust = LangString::parse(trimmed.slice_from(3)).rust;
+                lines.push(Line::StartCode(rust));
+                code = Some(rust);
+            }
+            None if trimmed.starts_with("#") &&
+                    trimmed.trim_left_matches('#').starts_with(" ") => {
+                let level = trimmed.chars().take_while(|&c| c == '#').count();
+                lines.push(Line::Heading(level, trimmed.slice_from(level)
+                                                       .trim_matches('#').trim()));
+            }
+            None => lines.push(Line::Text(line)),
+        }
+    }
+    if code.is_some() {
+        lines.push(Line::EndCode);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{unhtml, lines};
+    use super::Line::{Text, Heading, StartCode, Code, EndCode};
+
+    #[test]
+    fn strips_html() {
+        assert_eq!(unhtml("fn foo&lt;T&gt;(x: &amp;<a class='struct' href='x.html'>Foo</a>) \
+                           -&gt; T <span class='where'>where T: Clone</span>"),
+                   "fn foo<T>(x: &Foo) -> T where T: Clone");
+        assert_eq!(unhtml("&amp;lt;"), "&lt;");
+    }
+
+    #[test]
+    fn splits_lines() {
+        let doc = "Does things.\n\n# Examples\n\n```\n# let x = 1;\nfoo(x);\n```\n\n\
+                   ```text\n# not hidden\n```\n## Panics ##";
+        assert_eq!(lines(doc), vec![Text("Does things."), Text(""), Heading(1, "Examples"),
+                                    Text(""), StartCode(true), Code("foo(x);"), EndCode,
+                                    Text(""), StartCode(false), Code("# not hidden"), EndCode,
+                                    Heading(2, "Panics")]);
+    }
+}
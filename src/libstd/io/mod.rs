@@ -282,6 +282,7 @@ pub mod extensions;
 pub mod fs;
 pub mod net;
 pub mod pipe;
+pub mod poll;
 pub mod process;
 pub mod stdio;
 pub mod timer;
@@ -11,7 +11,8 @@
 //! Internet Protocol (IP) addresses.
 //!
 //! This module contains functions useful for parsing, formatting, and
-//! manipulating IP addresses.
+//! manipulating IP addresses, and for working with IP networks given in CIDR
+//! notation (`Ipv4Net` and `Ipv6Net`).
 
 #![allow(missing_docs)]
 
@@ -63,6 +64,67 @@ impl fmt::String for IpAddr {
     }
 }
 
+impl IpAddr {
+    /// Returns true for the unspecified address, `0.0.0.0` or `::`.
+    pub fn is_unspecified(&self) -> bool {
+        match *self {
+            Ipv4Addr(0, 0, 0, 0) | Ipv6Addr(0, 0, 0, 0, 0, 0, 0, 0) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true for loopback addresses, `127.0.0.0/8` and `::1`.
+    pub fn is_loopback(&self) -> bool {
+        match *self {
+            Ipv4Addr(127, _, _, _) | Ipv6Addr(0, 0, 0, 0, 0, 0, 0, 1) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns true for private addresses: `10.0.0.0/8`, `172.16.0.0/12` and
+    /// `192.168.0.0/16` (RFC 1918), and unique local IPv6 addresses in
+    /// `fc00::/7` (RFC 4193).
+    pub fn is_private(&self) -> bool {
+        match *self {
+            Ipv4Addr(10, _, _, _) | Ipv4Addr(192, 168, _, _) => true,
+            Ipv4Addr(172, b, _, _) => b >= 16 && b <= 31,
+            Ipv6Addr(a, _, _, _, _, _, _, _) => a & 0xfe00 == 0xfc00,
+            _ => false,
+        }
+    }
+
+    /// Returns true for link-local addresses, `169.254.0.0/16` and
+    /// `fe80::/10`.
+    pub fn is_link_local(&self) -> bool {
+        match *self {
+            Ipv4Addr(169, 254, _, _) => true,
+            Ipv6Addr(a, _, _, _, _, _, _, _) => a & 0xffc0 == 0xfe80,
+            _ => false,
+        }
+    }
+
+    /// Returns true for multicast addresses, `224.0.0.0/4` and `ff00::/8`.
+    pub fn is_multicast(&self) -> bool {
+        match *self {
+            Ipv4Addr(a, _, _, _) => a >= 224 && a <= 239,
+            Ipv6Addr(a, _, _, _, _, _, _, _) => a & 0xff00 == 0xff00,
+        }
+    }
+
+    /// Returns true for the ranges reserved for documentation:
+    /// `192.0.2.0/24`, `198.51.100.0/24` and `203.0.113.0/24` (RFC 5737), and
+    /// `2001:db8::/32` (RFC 3849).
+    pub fn is_documentation(&self) -> bool {
+        match *self {
+            Ipv4Addr(192, 0, 2, _) |
+            Ipv4Addr(198, 51, 100, _) |
+            Ipv4Addr(203, 0, 113, _) |
+            Ipv6Addr(0x2001, 0xdb8, _, _, _, _, _, _) => true,
+            _ => false,
+        }
+    }
+}
+
 #[derive(Copy, PartialEq, Eq, Clone, Hash, Show)]
 pub struct SocketAddr {
     pub ip: IpAddr,
@@ -78,6 +140,313 @@ impl fmt::String for SocketAddr {
     }
 }
 
+fn ipv4_to_u32(a: u8, b: u8, c: u8, d: u8) -> u32 {
+    ((a as u32) << 24) | ((b as u32) << 16) | ((c as u32) << 8) | (d as u32)
+}
+
+fn u32_to_ipv4(n: u32) -> IpAddr {
+    Ipv4Addr((n >> 24) as u8, (n >> 16) as u8, (n >> 8) as u8, n as u8)
+}
+
+// IPv6 addresses are handled as a pair of 64 bit halves, high half first.
+fn ipv6_to_u64s(a: u16, b: u16, c: u16, d: u16,
+                e: u16, f: u16, g: u16, h: u16) -> (u64, u64) {
+    let hi = ((a as u64) << 48) | ((b as u64) << 32) | ((c as u64) << 16) | (d as u64);
+    let lo = ((e as u64) << 48) | ((f as u64) << 32) | ((g as u64) << 16) | (h as u64);
+    (hi, lo)
+}
+
+fn u64s_to_ipv6((hi, lo): (u64, u64)) -> IpAddr {
+    Ipv6Addr((hi >> 48) as u16, (hi >> 32) as u16, (hi >> 16) as u16, hi as u16,
+             (lo >> 48) as u16, (lo >> 32) as u16, (lo >> 16) as u16, lo as u16)
+}
+
+fn ipv4_mask(prefix_len: u8) -> u32 {
+    if prefix_len == 0 { 0 } else { !0u32 << (32 - prefix_len as uint) }
+}
+
+fn ipv6_mask(prefix_len: u8) -> (u64, u64) {
+    match prefix_len {
+        0 => (0, 0),
+        1...64 => (!0u64 << (64 - prefix_len as uint), 0),
+        _ => (!0u64, !0u64 << (128 - prefix_len as uint)),
+    }
+}
+
+/// An IPv4 network, written as an address and a prefix length such as
+/// `10.0.0.0/8`.
+///
+/// The address is kept as given, so `10.1.2.3/8` is a valid network whose
+/// `network()` address is `10.0.0.0`; use `trunc` to clear the host bits.
+///
+/// # Example
+///
+/// ```rust
+/// use std::io::net::ip::{Ipv4Addr, Ipv4Net};
+///
+/// let net: Ipv4Net = "192.168.0.0/16".parse().unwrap();
+/// assert!(net.contains(Ipv4Addr(192, 168, 42, 1)));
+/// assert_eq!(net.netmask(), Ipv4Addr(255, 255, 0, 0));
+/// assert_eq!(net.broadcast(), Ipv4Addr(192, 168, 255, 255));
+///
+/// let subnets: Vec<Ipv4Net> = net.subnets(18).unwrap().collect();
+/// assert_eq!(subnets.len(), 4);
+/// assert_eq!(subnets[1].to_string(), "192.168.64.0/18");
+/// ```
+#[experimental = "recently added"]
+#[derive(Copy, PartialEq, Eq, Clone, Hash, Show)]
+pub struct Ipv4Net {
+    addr: u32,
+    prefix_len: u8,
+}
+
+impl Ipv4Net {
+    /// Creates a network from an address and a prefix length. Returns `None`
+    /// if `addr` is not an IPv4 address or `prefix_len` is greater than 32.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Option<Ipv4Net> {
+        match addr {
+            Ipv4Addr(a, b, c, d) if prefix_len <= 32 => {
+                Some(Ipv4Net { addr: ipv4_to_u32(a, b, c, d), prefix_len: prefix_len })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the address this network was created with.
+    pub fn addr(&self) -> IpAddr { u32_to_ipv4(self.addr) }
+
+    /// Returns the length of the network prefix, in bits.
+    pub fn prefix_len(&self) -> u8 { self.prefix_len }
+
+    /// Returns the netmask, e.g. `255.0.0.0` for a `/8` network.
+    pub fn netmask(&self) -> IpAddr { u32_to_ipv4(ipv4_mask(self.prefix_len)) }
+
+    /// Returns the hostmask, the inverse of the netmask.
+    pub fn hostmask(&self) -> IpAddr { u32_to_ipv4(!ipv4_mask(self.prefix_len)) }
+
+    /// Returns the first address of the network, with all host bits cleared.
+    pub fn network(&self) -> IpAddr {
+        u32_to_ipv4(self.addr & ipv4_mask(self.prefix_len))
+    }
+
+    /// Returns the broadcast address of the network, with all host bits set.
+    pub fn broadcast(&self) -> IpAddr {
+        u32_to_ipv4(self.addr | !ipv4_mask(self.prefix_len))
+    }
+
+    /// Returns this network with the host bits of its address cleared.
+    pub fn trunc(&self) -> Ipv4Net {
+        Ipv4Net { addr: self.addr & ipv4_mask(self.prefix_len), prefix_len: self.prefix_len }
+    }
+
+    /// Returns whether `addr` lies within this network. IPv6 addresses are
+    /// never contained in an IPv4 network.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match addr {
+            Ipv4Addr(a, b, c, d) => {
+                let mask = ipv4_mask(self.prefix_len);
+                ipv4_to_u32(a, b, c, d) & mask == self.addr & mask
+            }
+            Ipv6Addr(..) => false,
+        }
+    }
+
+    /// Returns whether `other` is the same network as, or a subnet of, this
+    /// network.
+    pub fn contains_net(&self, other: &Ipv4Net) -> bool {
+        other.prefix_len >= self.prefix_len && self.contains(other.addr())
+    }
+
+    /// Returns the network one bit shorter than this one which contains it,
+    /// or `None` for `0.0.0.0/0`.
+    pub fn supernet(&self) -> Option<Ipv4Net> {
+        if self.prefix_len == 0 { return None }
+        Some(Ipv4Net { addr: self.addr, prefix_len: self.prefix_len - 1 }.trunc())
+    }
+
+    /// Returns an iterator over the subnets of this network with the given
+    /// prefix length, in order. Returns `None` if `prefix_len` is shorter than
+    /// this network's prefix or longer than 32.
+    pub fn subnets(&self, prefix_len: u8) -> Option<Ipv4Subnets> {
+        if prefix_len < self.prefix_len || prefix_len > 32 { return None }
+        let first = self.addr & ipv4_mask(self.prefix_len);
+        Some(Ipv4Subnets {
+            next: Some(first),
+            last: first | (!ipv4_mask(self.prefix_len) & ipv4_mask(prefix_len)),
+            prefix_len: prefix_len,
+        })
+    }
+}
+
+impl fmt::String for Ipv4Net {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr(), self.prefix_len)
+    }
+}
+
+/// An iterator over the subnets of an `Ipv4Net`, returned by
+/// `Ipv4Net::subnets`.
+#[experimental = "recently added"]
+pub struct Ipv4Subnets {
+    next: Option<u32>,
+    last: u32,
+    prefix_len: u8,
+}
+
+impl Iterator for Ipv4Subnets {
+    type Item = Ipv4Net;
+
+    fn next(&mut self) -> Option<Ipv4Net> {
+        let addr = match self.next { Some(addr) => addr, None => return None };
+        self.next = if addr == self.last {
+            None
+        } else {
+            Some(addr + (1 << (32 - self.prefix_len as uint)))
+        };
+        Some(Ipv4Net { addr: addr, prefix_len: self.prefix_len })
+    }
+}
+
+/// An IPv6 network, written as an address and a prefix length such as
+/// `2001:db8::/32`.
+///
+/// As with `Ipv4Net`, the address is kept as given. IPv6 has no broadcast
+/// addresses, so there is no equivalent of `Ipv4Net::broadcast`.
+#[experimental = "recently added"]
+#[derive(Copy, PartialEq, Eq, Clone, Hash, Show)]
+pub struct Ipv6Net {
+    addr: (u64, u64),
+    prefix_len: u8,
+}
+
+impl Ipv6Net {
+    /// Creates a network from an address and a prefix length. Returns `None`
+    /// if `addr` is not an IPv6 address or `prefix_len` is greater than 128.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Option<Ipv6Net> {
+        match addr {
+            Ipv6Addr(a, b, c, d, e, f, g, h) if prefix_len <= 128 => {
+                Some(Ipv6Net {
+                    addr: ipv6_to_u64s(a, b, c, d, e, f, g, h),
+                    prefix_len: prefix_len,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the address this network was created with.
+    pub fn addr(&self) -> IpAddr { u64s_to_ipv6(self.addr) }
+
+    /// Returns the length of the network prefix, in bits.
+    pub fn prefix_len(&self) -> u8 { self.prefix_len }
+
+    /// Returns the netmask, e.g. `ffff:ffff::` for a `/32` network.
+    pub fn netmask(&self) -> IpAddr { u64s_to_ipv6(ipv6_mask(self.prefix_len)) }
+
+    /// Returns the hostmask, the inverse of the netmask.
+    pub fn hostmask(&self) -> IpAddr {
+        let (hi, lo) = ipv6_mask(self.prefix_len);
+        u64s_to_ipv6((!hi, !lo))
+    }
+
+    /// Returns the first address of the network, with all host bits cleared.
+    pub fn network(&self) -> IpAddr { u64s_to_ipv6(self.masked()) }
+
+    /// Returns the last address of the network, with all host bits set.
+    pub fn last(&self) -> IpAddr {
+        let (hi, lo) = self.addr;
+        let (mask_hi, mask_lo) = ipv6_mask(self.prefix_len);
+        u64s_to_ipv6((hi | !mask_hi, lo | !mask_lo))
+    }
+
+    /// Returns this network with the host bits of its address cleared.
+    pub fn trunc(&self) -> Ipv6Net {
+        Ipv6Net { addr: self.masked(), prefix_len: self.prefix_len }
+    }
+
+    /// Returns whether `addr` lies within this network. IPv4 addresses are
+    /// never contained in an IPv6 network, even if it contains their
+    /// IPv4-mapped form.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match addr {
+            Ipv6Addr(a, b, c, d, e, f, g, h) => {
+                let (hi, lo) = ipv6_to_u64s(a, b, c, d, e, f, g, h);
+                let (mask_hi, mask_lo) = ipv6_mask(self.prefix_len);
+                (hi & mask_hi, lo & mask_lo) == self.masked()
+            }
+            Ipv4Addr(..) => false,
+        }
+    }
+
+    /// Returns whether `other` is the same network as, or a subnet of, this
+    /// network.
+    pub fn contains_net(&self, other: &Ipv6Net) -> bool {
+        other.prefix_len >= self.prefix_len && self.contains(other.addr())
+    }
+
+    /// Returns the network one bit shorter than this one which contains it,
+    /// or `None` for `::/0`.
+    pub fn supernet(&self) -> Option<Ipv6Net> {
+        if self.prefix_len == 0 { return None }
+        Some(Ipv6Net { addr: self.addr, prefix_len: self.prefix_len - 1 }.trunc())
+    }
+
+    /// Returns an iterator over the subnets of this network with the given
+    /// prefix length, in order. Returns `None` if `prefix_len` is shorter than
+    /// this network's prefix or longer than 128.
+    pub fn subnets(&self, prefix_len: u8) -> Option<Ipv6Subnets> {
+        if prefix_len < self.prefix_len || prefix_len > 128 { return None }
+        let (first_hi, first_lo) = self.masked();
+        let (outer_hi, outer_lo) = ipv6_mask(self.prefix_len);
+        let (inner_hi, inner_lo) = ipv6_mask(prefix_len);
+        Some(Ipv6Subnets {
+            next: Some((first_hi, first_lo)),
+            last: (first_hi | (!outer_hi & inner_hi),
+                   first_lo | (!outer_lo & inner_lo)),
+            prefix_len: prefix_len,
+        })
+    }
+
+    fn masked(&self) -> (u64, u64) {
+        let (hi, lo) = self.addr;
+        let (mask_hi, mask_lo) = ipv6_mask(self.prefix_len);
+        (hi & mask_hi, lo & mask_lo)
+    }
+}
+
+impl fmt::String for Ipv6Net {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr(), self.prefix_len)
+    }
+}
+
+/// An iterator over the subnets of an `Ipv6Net`, returned by
+/// `Ipv6Net::subnets`.
+#[experimental = "recently added"]
+pub struct Ipv6Subnets {
+    next: Option<(u64, u64)>,
+    last: (u64, u64),
+    prefix_len: u8,
+}
+
+impl Iterator for Ipv6Subnets {
+    type Item = Ipv6Net;
+
+    fn next(&mut self) -> Option<Ipv6Net> {
+        let (hi, lo) = match self.next { Some(addr) => addr, None => return None };
+        self.next = if (hi, lo) == self.last {
+            None
+        } else if self.prefix_len <= 64 {
+            Some((hi + (1 << (64 - self.prefix_len as uint)), lo))
+        } else {
+            // add to the low half, carrying into the high half
+            let next_lo = lo + (1 << (128 - self.prefix_len as uint));
+            Some((if next_lo < lo { hi + 1 } else { hi }, next_lo))
+        };
+        Some(Ipv6Net { addr: (hi, lo), prefix_len: self.prefix_len })
+    }
+}
+
 struct Parser<'a> {
     // parsing as ASCII, so can use byte array
     s: &'a [u8],
@@ -345,6 +714,29 @@ impl<'a> Parser<'a> {
         self.read_seq_3::<IpAddr, char, u16, _, _, _>(ip_addr, colon, port)
                 .map(|t| match t { (ip, _, port) => SocketAddr { ip: ip, port: port } })
     }
+
+    // Read the `/len` suffix of a network, failing if len is greater than max
+    fn read_prefix_len(&mut self, max: u32) -> Option<u8> {
+        self.read_atomically(|p| {
+            p.read_given_char('/')
+             .and_then(|_| p.read_number(10, 3, max + 1))
+             .map(|n| n as u8)
+        })
+    }
+
+    fn read_ipv4_net(&mut self) -> Option<Ipv4Net> {
+        self.read_atomically(|p| {
+            let addr = match p.read_ipv4_addr() { Some(addr) => addr, None => return None };
+            p.read_prefix_len(32).and_then(|len| Ipv4Net::new(addr, len))
+        })
+    }
+
+    fn read_ipv6_net(&mut self) -> Option<Ipv6Net> {
+        self.read_atomically(|p| {
+            let addr = match p.read_ipv6_addr() { Some(addr) => addr, None => return None };
+            p.read_prefix_len(128).and_then(|len| Ipv6Net::new(addr, len))
+        })
+    }
 }
 
 impl FromStr for IpAddr {
@@ -359,6 +751,18 @@ impl FromStr for SocketAddr {
     }
 }
 
+impl FromStr for Ipv4Net {
+    fn from_str(s: &str) -> Option<Ipv4Net> {
+        Parser::new(s).read_till_eof(|p| p.read_ipv4_net())
+    }
+}
+
+impl FromStr for Ipv6Net {
+    fn from_str(s: &str) -> Option<Ipv6Net> {
+        Parser::new(s).read_till_eof(|p| p.read_ipv6_net())
+    }
+}
+
 /// A trait for objects which can be converted or resolved to one or more `SocketAddr` values.
 ///
 /// Implementing types minimally have to implement either `to_socket_addr` or `to_socket_addr_all`
@@ -695,4 +1099,175 @@ mod test {
         let a = SocketAddr { ip: Ipv4Addr(127, 0, 0, 1), port: 23924 };
         assert!("localhost:23924".to_socket_addr_all().unwrap().contains(&a));
     }
+
+    #[test]
+    fn ip_addr_classification() {
+        assert!(Ipv4Addr(0, 0, 0, 0).is_unspecified());
+        assert!(Ipv6Addr(0, 0, 0, 0, 0, 0, 0, 0).is_unspecified());
+        assert!(Ipv4Addr(127, 1, 2, 3).is_loopback());
+        assert!(Ipv6Addr(0, 0, 0, 0, 0, 0, 0, 1).is_loopback());
+        assert!(!Ipv4Addr(128, 0, 0, 1).is_loopback());
+
+        assert!(Ipv4Addr(10, 1, 2, 3).is_private());
+        assert!(Ipv4Addr(172, 16, 0, 1).is_private());
+        assert!(Ipv4Addr(172, 31, 255, 255).is_private());
+        assert!(!Ipv4Addr(172, 32, 0, 1).is_private());
+        assert!(Ipv4Addr(192, 168, 1, 1).is_private());
+        assert!(!Ipv4Addr(192, 169, 1, 1).is_private());
+        assert!(Ipv6Addr(0xfd12, 0x3456, 0, 0, 0, 0, 0, 1).is_private());
+        assert!(!Ipv6Addr(0xfe80, 0, 0, 0, 0, 0, 0, 1).is_private());
+
+        assert!(Ipv4Addr(169, 254, 0, 1).is_link_local());
+        assert!(Ipv6Addr(0xfe80, 0, 0, 0, 0, 0, 0, 1).is_link_local());
+        assert!(Ipv6Addr(0xfebf, 0, 0, 0, 0, 0, 0, 1).is_link_local());
+        assert!(!Ipv6Addr(0xfec0, 0, 0, 0, 0, 0, 0, 1).is_link_local());
+
+        assert!(Ipv4Addr(224, 0, 0, 1).is_multicast());
+        assert!(Ipv4Addr(239, 255, 255, 255).is_multicast());
+        assert!(!Ipv4Addr(240, 0, 0, 0).is_multicast());
+        assert!(Ipv6Addr(0xff02, 0, 0, 0, 0, 0, 0, 1).is_multicast());
+
+        assert!(Ipv4Addr(192, 0, 2, 1).is_documentation());
+        assert!(Ipv4Addr(198, 51, 100, 1).is_documentation());
+        assert!(Ipv4Addr(203, 0, 113, 1).is_documentation());
+        assert!(Ipv6Addr(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1).is_documentation());
+        assert!(!Ipv4Addr(8, 8, 8, 8).is_documentation());
+    }
+
+    #[test]
+    fn test_from_str_ipv4_net() {
+        let net: Ipv4Net = FromStr::from_str("10.0.0.0/8").unwrap();
+        assert_eq!(net.addr(), Ipv4Addr(10, 0, 0, 0));
+        assert_eq!(net.prefix_len(), 8);
+        assert_eq!(net, Ipv4Net::new(Ipv4Addr(10, 0, 0, 0), 8).unwrap());
+        assert_eq!(net.to_string(), "10.0.0.0/8");
+
+        let net: Option<Ipv4Net> = FromStr::from_str("0.0.0.0/0");
+        assert!(net.is_some());
+        let net: Option<Ipv4Net> = FromStr::from_str("1.2.3.4/32");
+        assert!(net.is_some());
+
+        // prefix too long
+        let none: Option<Ipv4Net> = FromStr::from_str("10.0.0.0/33");
+        assert_eq!(None, none);
+        // no prefix
+        let none: Option<Ipv4Net> = FromStr::from_str("10.0.0.0");
+        assert_eq!(None, none);
+        // empty prefix
+        let none: Option<Ipv4Net> = FromStr::from_str("10.0.0.0/");
+        assert_eq!(None, none);
+        // ipv6 address
+        let none: Option<Ipv4Net> = FromStr::from_str("::1/8");
+        assert_eq!(None, none);
+
+        assert_eq!(Ipv4Net::new(Ipv6Addr(0, 0, 0, 0, 0, 0, 0, 1), 8), None);
+        assert_eq!(Ipv4Net::new(Ipv4Addr(10, 0, 0, 0), 33), None);
+    }
+
+    #[test]
+    fn test_from_str_ipv6_net() {
+        let net: Ipv6Net = FromStr::from_str("2001:db8::/32").unwrap();
+        assert_eq!(net.addr(), Ipv6Addr(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0));
+        assert_eq!(net.prefix_len(), 32);
+        assert_eq!(net.to_string(), "2001:db8:0:0:0:0:0:0/32");
+
+        let net: Option<Ipv6Net> = FromStr::from_str("::1/128");
+        assert!(net.is_some());
+
+        // prefix too long
+        let none: Option<Ipv6Net> = FromStr::from_str("::/129");
+        assert_eq!(None, none);
+        // ipv4 address
+        let none: Option<Ipv6Net> = FromStr::from_str("10.0.0.0/8");
+        assert_eq!(None, none);
+    }
+
+    #[test]
+    fn ipv4_net_arithmetic() {
+        let net = Ipv4Net::new(Ipv4Addr(172, 16, 33, 7), 12).unwrap();
+        assert_eq!(net.netmask(), Ipv4Addr(255, 240, 0, 0));
+        assert_eq!(net.hostmask(), Ipv4Addr(0, 15, 255, 255));
+        assert_eq!(net.network(), Ipv4Addr(172, 16, 0, 0));
+        assert_eq!(net.broadcast(), Ipv4Addr(172, 31, 255, 255));
+        assert_eq!(net.trunc().addr(), Ipv4Addr(172, 16, 0, 0));
+
+        assert!(net.contains(Ipv4Addr(172, 20, 1, 1)));
+        assert!(!net.contains(Ipv4Addr(172, 32, 0, 0)));
+        assert!(!net.contains(Ipv6Addr(0, 0, 0, 0, 0, 0xffff, 0xac10, 1)));
+
+        let sub = Ipv4Net::new(Ipv4Addr(172, 18, 0, 0), 16).unwrap();
+        assert!(net.contains_net(&sub));
+        assert!(!sub.contains_net(&net));
+        assert_eq!(sub.supernet(), Ipv4Net::new(Ipv4Addr(172, 18, 0, 0), 15));
+
+        let all = Ipv4Net::new(Ipv4Addr(0, 0, 0, 0), 0).unwrap();
+        assert_eq!(all.netmask(), Ipv4Addr(0, 0, 0, 0));
+        assert_eq!(all.broadcast(), Ipv4Addr(255, 255, 255, 255));
+        assert!(all.contains(Ipv4Addr(8, 8, 8, 8)));
+        assert_eq!(all.supernet(), None);
+
+        let host = Ipv4Net::new(Ipv4Addr(1, 2, 3, 4), 32).unwrap();
+        assert_eq!(host.network(), host.broadcast());
+    }
+
+    #[test]
+    fn ipv4_net_subnets() {
+        let net = Ipv4Net::new(Ipv4Addr(10, 0, 0, 0), 24).unwrap();
+        let subnets: Vec<String> = net.subnets(26).unwrap().map(|n| n.to_string()).collect();
+        assert_eq!(subnets, vec!["10.0.0.0/26".to_string(), "10.0.0.64/26".to_string(),
+                                 "10.0.0.128/26".to_string(), "10.0.0.192/26".to_string()]);
+
+        assert_eq!(net.subnets(24).unwrap().collect::<Vec<_>>(), vec![net]);
+        assert_eq!(net.subnets(32).unwrap().count(), 256);
+        assert!(net.subnets(23).is_none());
+        assert!(net.subnets(33).is_none());
+
+        // the last subnet of the address space must not overflow
+        let top = Ipv4Net::new(Ipv4Addr(255, 255, 255, 0), 24).unwrap();
+        assert_eq!(top.subnets(25).unwrap().last().unwrap().addr(),
+                   Ipv4Addr(255, 255, 255, 128));
+        let all = Ipv4Net::new(Ipv4Addr(0, 0, 0, 0), 0).unwrap();
+        assert_eq!(all.subnets(1).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn ipv6_net_arithmetic() {
+        let net = Ipv6Net::new(Ipv6Addr(0x2001, 0xdb8, 0x1234, 0, 0, 0, 0, 1), 36).unwrap();
+        assert_eq!(net.netmask(), Ipv6Addr(0xffff, 0xffff, 0xf000, 0, 0, 0, 0, 0));
+        assert_eq!(net.hostmask(),
+                   Ipv6Addr(0, 0, 0x0fff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff));
+        assert_eq!(net.network(), Ipv6Addr(0x2001, 0xdb8, 0x1000, 0, 0, 0, 0, 0));
+        assert_eq!(net.last(),
+                   Ipv6Addr(0x2001, 0xdb8, 0x1fff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff));
+
+        assert!(net.contains(Ipv6Addr(0x2001, 0xdb8, 0x1fff, 0, 0, 0, 0, 0)));
+        assert!(!net.contains(Ipv6Addr(0x2001, 0xdb8, 0x2000, 0, 0, 0, 0, 0)));
+        assert!(!net.contains(Ipv4Addr(127, 0, 0, 1)));
+
+        let low = Ipv6Net::new(Ipv6Addr(0, 0, 0, 0, 0, 0, 0, 0x100), 120).unwrap();
+        assert_eq!(low.netmask(),
+                   Ipv6Addr(0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xffff, 0xff00));
+        assert!(low.contains(Ipv6Addr(0, 0, 0, 0, 0, 0, 0, 0x1ff)));
+        assert!(!low.contains(Ipv6Addr(0, 0, 0, 0, 0, 0, 0, 0x200)));
+
+        let doc: Ipv6Net = FromStr::from_str("2001:db8::/32").unwrap();
+        assert!(doc.contains_net(&net));
+        assert!(!net.contains_net(&doc));
+        assert_eq!(net.supernet().unwrap().prefix_len(), 35);
+    }
+
+    #[test]
+    fn ipv6_net_subnets() {
+        let net: Ipv6Net = FromStr::from_str("2001:db8::/32").unwrap();
+        let subnets: Vec<Ipv6Net> = net.subnets(34).unwrap().collect();
+        assert_eq!(subnets.len(), 4);
+        assert_eq!(subnets[3].addr(), Ipv6Addr(0x2001, 0xdb8, 0xc000, 0, 0, 0, 0, 0));
+
+        // subnets which cross from the low into the high half
+        let net: Ipv6Net = FromStr::from_str("::ffff:ffff:ffff:ff00/63").unwrap();
+        let subnets: Vec<Ipv6Net> = net.subnets(65).unwrap().collect();
+        assert_eq!(subnets.len(), 4);
+        assert_eq!(subnets[2].addr(), Ipv6Addr(0, 0, 0, 1, 0, 0, 0, 0));
+        assert_eq!(subnets[3].addr(), Ipv6Addr(0, 0, 0, 1, 0x8000, 0, 0, 0));
+    }
 }
@@ -17,21 +17,58 @@
 //! These pipes are similar to TCP in the sense that you can have both a stream to a
 //! server and a server itself. The server provided accepts other `UnixStream`
 //! instances as clients.
+//!
+//! On Unix, this module additionally provides connectionless `UnixDatagram`
+//! sockets, the ability to pass file descriptors between processes over a
+//! socket, and to query the credentials of the process on the other end. On
+//! Linux, sockets may also be named in the abstract namespace, which is
+//! independent of the filesystem.
 
 #![allow(missing_docs)]
 
 use prelude::v1::*;
 
 use ffi::CString;
+#[cfg(unix)] use libc;
 use path::BytesContainer;
 use io::{Listener, Acceptor, IoResult, TimedOut, standard_error};
 use sys::pipe::UnixAcceptor as UnixAcceptorImp;
+#[cfg(unix)] use sys::pipe::UnixDatagram as UnixDatagramImp;
 use sys::pipe::UnixListener as UnixListenerImp;
 use sys::pipe::UnixStream as UnixStreamImp;
 use time::Duration;
 
 use sys_common;
 
+/// The address of a Unix domain socket.
+#[cfg(unix)]
+#[experimental = "recently added"]
+#[derive(Clone, PartialEq, Eq, Show)]
+pub enum UnixAddr {
+    /// The socket has not been bound to an address.
+    Unnamed,
+    /// The socket is bound to a path in the filesystem.
+    Path(Path),
+    /// The socket is bound to a name in the abstract namespace (Linux only).
+    Abstract(Vec<u8>),
+}
+
+/// The credentials of the process on the other end of a Unix domain socket.
+///
+/// These are the credentials of the peer at the time it connected the socket
+/// (or created the socket pair), not necessarily its current credentials.
+#[cfg(unix)]
+#[experimental = "recently added"]
+#[derive(Copy, Clone, PartialEq, Eq, Show)]
+pub struct Credentials {
+    /// The peer's process id. This is only available on Linux and Android.
+    pub pid: Option<libc::pid_t>,
+    /// The peer's effective user id.
+    pub uid: libc::uid_t,
+    /// The peer's effective group id.
+    pub gid: libc::gid_t,
+}
+
 /// A stream which communicates over a named pipe.
 pub struct UnixStream {
     inner: UnixStreamImp,
@@ -81,6 +118,55 @@ impl UnixStream {
             .map(|inner| UnixStream { inner: inner })
     }
 
+    /// Connect to a socket bound to `name` in the abstract namespace.
+    ///
+    /// The name may contain arbitrary bytes, including nulls, and does not
+    /// correspond to any file on disk.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[experimental = "recently added"]
+    pub fn connect_abstract(name: &[u8]) -> IoResult<UnixStream> {
+        UnixStreamImp::connect_abstract(name, None)
+            .map(|inner| UnixStream { inner: inner })
+    }
+
+    /// Returns the credentials of the process which created the other end of
+    /// this stream.
+    #[cfg(unix)]
+    #[experimental = "recently added"]
+    pub fn peer_credentials(&self) -> IoResult<Credentials> {
+        self.inner.peer_credentials()
+    }
+
+    /// Sends the bytes in `buf` along with the file descriptors in `fds`,
+    /// returning how many bytes of `buf` were written.
+    ///
+    /// The descriptors are duplicated into the receiving process when it
+    /// reads the first byte of this message with `recv_fds`. They remain open
+    /// in this process, and at least one byte must be sent with them.
+    ///
+    /// Unlike `write`, this only makes a single attempt at writing `buf`, so
+    /// that the descriptors are attached to a single message.
+    #[cfg(unix)]
+    #[experimental = "recently added"]
+    pub fn send_fds(&mut self, buf: &[u8],
+                    fds: &[libc::c_int]) -> IoResult<uint> {
+        self.inner.send_fds(buf, fds)
+    }
+
+    /// Reads some bytes into `buf`, returning how many bytes were read along
+    /// with any file descriptors which were sent with them.
+    ///
+    /// The received descriptors are owned by the caller, which is responsible
+    /// for closing them. They are marked close-on-exec. If more descriptors
+    /// were sent than fit in a message, the ones received are closed and an
+    /// error is returned.
+    #[cfg(unix)]
+    #[experimental = "recently added"]
+    pub fn recv_fds(&mut self, buf: &mut [u8])
+                    -> IoResult<(uint, Vec<libc::c_int>)> {
+        self.inner.recv_fds(buf)
+    }
+
 
     /// Closes the reading half of this connection.
     ///
@@ -186,6 +272,17 @@ impl UnixListener {
         UnixListenerImp::bind(&path)
             .map(|inner| UnixListener { inner: inner })
     }
+
+    /// Creates a new listener bound to `name` in the abstract namespace.
+    ///
+    /// Unlike a listener bound to a path, no file is created, and the name is
+    /// released as soon as the listener is closed.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[experimental = "recently added"]
+    pub fn bind_abstract(name: &[u8]) -> IoResult<UnixListener> {
+        UnixListenerImp::bind_abstract(name)
+            .map(|inner| UnixListener { inner: inner })
+    }
 }
 
 impl Listener<UnixStream, UnixAcceptor> for UnixListener {
@@ -268,6 +365,186 @@ impl sys_common::AsInner<UnixAcceptorImp> for UnixAcceptor {
     }
 }
 
+/// A connectionless Unix domain socket, which sends and receives whole
+/// messages.
+///
+/// Datagrams sent between Unix sockets are reliable and are delivered in
+/// order, unlike UDP datagrams.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// # #![allow(unused_must_use)]
+/// use std::io::net::pipe::UnixDatagram;
+///
+/// let mut socket = UnixDatagram::bind(&Path::new("/tmp/server.sock")).unwrap();
+/// let mut buf = [0; 64];
+/// match socket.recv_from(&mut buf) {
+///     Ok((amt, src)) => println!("{} bytes from {:?}", amt, src),
+///     Err(e) => println!("couldn't receive a datagram: {}", e),
+/// }
+/// ```
+#[cfg(unix)]
+#[experimental = "recently added"]
+pub struct UnixDatagram {
+    inner: UnixDatagramImp,
+}
+
+#[cfg(unix)]
+impl UnixDatagram {
+    /// Creates a socket bound to `path`. The path is removed again once the
+    /// socket and all of its clones have been closed.
+    pub fn bind<P: BytesContainer>(path: P) -> IoResult<UnixDatagram> {
+        let path = CString::from_slice(path.container_as_bytes());
+        UnixDatagramImp::bind(&path).map(|inner| UnixDatagram { inner: inner })
+    }
+
+    /// Creates a socket bound to `name` in the abstract namespace.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn bind_abstract(name: &[u8]) -> IoResult<UnixDatagram> {
+        UnixDatagramImp::bind_abstract(name)
+            .map(|inner| UnixDatagram { inner: inner })
+    }
+
+    /// Creates a socket which is not bound to any address.
+    ///
+    /// Such a socket can send datagrams, but its peers cannot reply to it.
+    pub fn unbound() -> IoResult<UnixDatagram> {
+        UnixDatagramImp::unbound().map(|inner| UnixDatagram { inner: inner })
+    }
+
+    /// Creates a pair of unnamed sockets which are connected to each other.
+    pub fn pair() -> IoResult<(UnixDatagram, UnixDatagram)> {
+        UnixDatagramImp::pair().map(|(a, b)| {
+            (UnixDatagram { inner: a }, UnixDatagram { inner: b })
+        })
+    }
+
+    /// Connects this socket to the socket bound to `path`, which becomes the
+    /// destination of `send` and the only source `recv` will accept
+    /// datagrams from.
+    pub fn connect<P: BytesContainer>(&mut self, path: P) -> IoResult<()> {
+        let path = CString::from_slice(path.container_as_bytes());
+        self.inner.connect(&path)
+    }
+
+    /// Connects this socket to the socket bound to `name` in the abstract
+    /// namespace.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn connect_abstract(&mut self, name: &[u8]) -> IoResult<()> {
+        self.inner.connect_abstract(name)
+    }
+
+    /// Returns the address this socket is bound to.
+    pub fn socket_name(&mut self) -> IoResult<UnixAddr> {
+        self.inner.socket_name()
+    }
+
+    /// Receives a single datagram from the socket. On success, returns the
+    /// number of bytes read and the address of the sender.
+    ///
+    /// Any part of the datagram which does not fit into `buf` is discarded.
+    pub fn recv_from(&mut self, buf: &mut [u8]) -> IoResult<(uint, UnixAddr)> {
+        self.inner.recv_from(buf)
+    }
+
+    /// Receives a single datagram from the peer this socket is connected to.
+    pub fn recv(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        self.inner.recv(buf)
+    }
+
+    /// Sends `buf` as a single datagram to the socket bound to `path`.
+    pub fn send_to<P: BytesContainer>(&mut self, buf: &[u8],
+                                      path: P) -> IoResult<()> {
+        let path = CString::from_slice(path.container_as_bytes());
+        self.inner.send_to(buf, &path)
+    }
+
+    /// Sends `buf` as a single datagram to the socket bound to `name` in the
+    /// abstract namespace.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn send_to_abstract(&mut self, buf: &[u8], name: &[u8]) -> IoResult<()> {
+        self.inner.send_to_abstract(buf, name)
+    }
+
+    /// Sends `buf` as a single datagram to the peer this socket is connected
+    /// to.
+    pub fn send(&mut self, buf: &[u8]) -> IoResult<()> {
+        self.inner.send(buf)
+    }
+
+    /// Sends `buf` as a single datagram to the connected peer, along with the
+    /// file descriptors in `fds`.
+    ///
+    /// See `UnixStream::send_fds` for more information.
+    pub fn send_fds(&mut self, buf: &[u8], fds: &[libc::c_int]) -> IoResult<()> {
+        self.inner.send_fds(buf, fds)
+    }
+
+    /// Receives a single datagram along with any file descriptors sent with
+    /// it.
+    ///
+    /// See `UnixStream::recv_fds` for more information.
+    pub fn recv_fds(&mut self, buf: &mut [u8])
+                    -> IoResult<(uint, Vec<libc::c_int>)> {
+        self.inner.recv_fds(buf)
+    }
+
+    /// Returns the credentials of the process which created the peer of this
+    /// socket. This is only meaningful for sockets created by `pair`, or
+    /// connected to a socket created by such a process.
+    pub fn peer_credentials(&self) -> IoResult<Credentials> {
+        self.inner.peer_credentials()
+    }
+
+    /// Moves this socket into or out of nonblocking mode.
+    ///
+    /// For more information, see `TcpStream::set_nonblocking`
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.inner.set_nonblocking(nonblocking)
+    }
+
+    /// Sets the read/write timeout for this socket.
+    ///
+    /// For more information, see `TcpStream::set_timeout`
+    #[experimental = "the timeout argument may change in type and value"]
+    pub fn set_timeout(&mut self, timeout_ms: Option<u64>) {
+        self.inner.set_timeout(timeout_ms)
+    }
+
+    /// Sets the read timeout for this socket.
+    ///
+    /// For more information, see `TcpStream::set_timeout`
+    #[experimental = "the timeout argument may change in type and value"]
+    pub fn set_read_timeout(&mut self, timeout_ms: Option<u64>) {
+        self.inner.set_read_timeout(timeout_ms)
+    }
+
+    /// Sets the write timeout for this socket.
+    ///
+    /// For more information, see `TcpStream::set_timeout`
+    #[experimental = "the timeout argument may change in type and value"]
+    pub fn set_write_timeout(&mut self, timeout_ms: Option<u64>) {
+        self.inner.set_write_timeout(timeout_ms)
+    }
+}
+
+#[cfg(unix)]
+impl Clone for UnixDatagram {
+    /// Creates a new handle to this socket. The handle does not share the
+    /// timeouts or blocking mode of the original.
+    fn clone(&self) -> UnixDatagram {
+        UnixDatagram { inner: self.inner.clone() }
+    }
+}
+
+#[cfg(unix)]
+impl sys_common::AsInner<UnixDatagramImp> for UnixDatagram {
+    fn as_inner(&self) -> &UnixDatagramImp {
+        &self.inner
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use prelude::v1::*;
@@ -278,6 +555,10 @@ mod tests {
     use io::{PermissionDenied, Acceptor, Listener};
     use io::test::*;
     use super::*;
+    #[cfg(unix)] use io::ResourceUnavailable;
+    #[cfg(unix)] use libc;
+    #[cfg(unix)] use sys::fs::FileDesc;
+    #[cfg(unix)] use sys::os;
     use sync::mpsc::channel;
     use thread::Thread;
     use time::Duration;
@@ -863,4 +1144,150 @@ mod tests {
 
         assert_eq!(rx.recv().unwrap().err().unwrap().kind, EndOfFile);
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn datagram_smoke() {
+        let path1 = next_test_unix();
+        let path2 = next_test_unix();
+        let mut server = UnixDatagram::bind(&path1).unwrap();
+        let mut client = UnixDatagram::bind(&path2).unwrap();
+
+        client.send_to(&[1, 2, 3], &path1).unwrap();
+        let mut buf = [0; 8];
+        let (n, src) = server.recv_from(&mut buf).unwrap();
+        assert_eq!(buf.slice_to(n), [1, 2, 3].as_slice());
+        assert_eq!(src, UnixAddr::Path(path2.clone()));
+        assert_eq!(server.socket_name().unwrap(), UnixAddr::Path(path1.clone()));
+
+        drop(server);
+        drop(client);
+        assert!(!path1.exists());
+        assert!(!path2.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn datagram_connect() {
+        let path = next_test_unix();
+        let mut server = UnixDatagram::bind(&path).unwrap();
+        let mut client = UnixDatagram::unbound().unwrap();
+        client.connect(&path).unwrap();
+
+        client.send(&[99]).unwrap();
+        let mut buf = [0];
+        let (n, src) = server.recv_from(&mut buf).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(buf[0], 99);
+        assert_eq!(src, UnixAddr::Unnamed);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn datagram_preserves_boundaries() {
+        let (mut a, mut b) = UnixDatagram::pair().unwrap();
+        a.send(&[1, 2]).unwrap();
+        a.send(&[3]).unwrap();
+
+        let mut buf = [0; 4];
+        assert_eq!(b.recv(&mut buf), Ok(2));
+        assert_eq!(b.recv(&mut buf), Ok(1));
+        assert_eq!(buf[0], 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn datagram_timeouts() {
+        let (mut a, _b) = UnixDatagram::pair().unwrap();
+        a.set_read_timeout(Some(20));
+        assert_eq!(a.recv(&mut [0]).err().unwrap().kind, TimedOut);
+
+        a.set_nonblocking(true);
+        assert_eq!(a.recv(&mut [0]).err().unwrap().kind, ResourceUnavailable);
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[test]
+    fn abstract_namespace() {
+        let name = format!("rust-test-abstract-{}", next_test_unix().display());
+        let name = name.as_bytes();
+        let mut acceptor = UnixListener::bind_abstract(name).listen().unwrap();
+
+        let mut client = UnixStream::connect_abstract(name).unwrap();
+        let mut server = acceptor.accept().unwrap();
+        client.write(&[1]).unwrap();
+        let mut buf = [0];
+        assert_eq!(server.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], 1);
+
+        let mut a = UnixDatagram::bind_abstract(name).unwrap();
+        assert_eq!(a.socket_name().unwrap(), UnixAddr::Abstract(name.to_vec()));
+        let mut b = UnixDatagram::unbound().unwrap();
+        b.send_to_abstract(&[2], name).unwrap();
+        assert_eq!(a.recv(&mut buf), Ok(1));
+        assert_eq!(buf[0], 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn peer_credentials() {
+        smalltest(move |server| {
+            let cred = server.peer_credentials().unwrap();
+            assert_eq!(cred.uid, unsafe { libc::getuid() });
+            assert_eq!(cred.gid, unsafe { libc::getgid() });
+            if cfg!(any(target_os = "linux", target_os = "android")) {
+                assert_eq!(cred.pid, Some(unsafe { libc::getpid() }));
+            }
+        }, move |_client| {})
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn pass_fds() {
+        let (reader, writer) = unsafe { os::pipe().unwrap() };
+        smalltest(move |mut server| {
+            let mut buf = [0];
+            let (n, fds) = server.recv_fds(&mut buf).unwrap();
+            assert_eq!(n, 1);
+            assert_eq!(buf[0], 42);
+            assert_eq!(fds.len(), 1);
+            let writer = FileDesc::new(fds[0], true);
+            writer.write(&[7]).unwrap();
+        }, move |mut client| {
+            assert_eq!(client.send_fds(&[42], &[writer.fd()]), Ok(1));
+        });
+
+        let mut buf = [0];
+        assert_eq!(reader.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], 7);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn datagram_pass_fds() {
+        let (mut a, mut b) = UnixDatagram::pair().unwrap();
+        let (reader1, writer1) = unsafe { os::pipe().unwrap() };
+        let (reader2, writer2) = unsafe { os::pipe().unwrap() };
+        a.send_fds(&[0], &[writer1.fd(), writer2.fd()]).unwrap();
+        drop(writer1);
+        drop(writer2);
+
+        let mut buf = [0; 4];
+        let (n, fds) = b.recv_fds(&mut buf).unwrap();
+        assert_eq!(n, 1);
+        assert_eq!(fds.len(), 2);
+        let fds: Vec<FileDesc> = fds.into_iter().map(|fd| FileDesc::new(fd, true)).collect();
+        fds[0].write(&[1]).unwrap();
+        fds[1].write(&[2]).unwrap();
+        assert_eq!(reader1.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], 1);
+        assert_eq!(reader2.read(&mut buf), Ok(1));
+        assert_eq!(buf[0], 2);
+
+        // messages without descriptors can also be read with recv_fds
+        a.send(&[3]).unwrap();
+        let (n, fds) = b.recv_fds(&mut buf).unwrap();
+        assert_eq!(n, 1);
+        assert!(fds.is_empty());
+    }
 }
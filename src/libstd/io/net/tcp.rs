@@ -235,6 +235,23 @@ impl TcpStream {
     pub fn set_write_timeout(&mut self, timeout_ms: Option<u64>) {
         self.inner.set_write_timeout(timeout_ms)
     }
+
+    /// Moves this stream into or out of nonblocking mode.
+    ///
+    /// In nonblocking mode reads and writes never wait for the stream to
+    /// become ready. A read which would block returns an error of kind
+    /// `ResourceUnavailable`; a write which would block returns either that
+    /// error, if nothing could be written, or a `ShortWrite` error carrying the
+    /// number of bytes which were written. Any timeouts are ignored while in
+    /// nonblocking mode.
+    ///
+    /// Like timeouts, the mode is local to this handle: it is not shared with
+    /// cloned instances of this stream. It is typically combined with a
+    /// `std::io::poll::Poller` to learn when the stream is ready.
+    #[experimental = "recently added"]
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.inner.set_nonblocking(nonblocking)
+    }
 }
 
 impl Clone for TcpStream {
@@ -401,6 +418,17 @@ impl TcpAcceptor {
                       subject to change"]
     pub fn set_timeout(&mut self, ms: Option<u64>) { self.inner.set_timeout(ms); }
 
+    /// Moves this acceptor into or out of nonblocking mode.
+    ///
+    /// In nonblocking mode `accept` returns an error of kind
+    /// `ResourceUnavailable` instead of waiting when no connection is pending,
+    /// and any timeout is ignored. As with `set_timeout`, the mode only
+    /// affects this handle and not its clones.
+    #[experimental = "recently added"]
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.inner.set_nonblocking(nonblocking)
+    }
+
     /// Closes the accepting capabilities of this acceptor.
     ///
     /// This function is similar to `TcpStream`'s `close_{read,write}` methods
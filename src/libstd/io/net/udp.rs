@@ -152,6 +152,14 @@ impl UdpSocket {
     pub fn set_write_timeout(&mut self, timeout_ms: Option<u64>) {
         self.inner.set_write_timeout(timeout_ms)
     }
+
+    /// Moves this socket into or out of nonblocking mode.
+    ///
+    /// For more information, see `TcpStream::set_nonblocking`
+    #[experimental = "recently added"]
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.inner.set_nonblocking(nonblocking)
+    }
 }
 
 impl Clone for UdpSocket {
@@ -0,0 +1,294 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Readiness notifications for many I/O objects at once
+//!
+//! A `Poller` watches any number of sockets and pipes and reports which of
+//! them are ready to be read from or written to, allowing a single thread to
+//! serve many connections. It is usually combined with the nonblocking mode of
+//! the objects being watched (see `TcpStream::set_nonblocking`), so that an
+//! operation started after a readiness notification never blocks the thread.
+//!
+//! Notifications are level-triggered: an object is reported on every call to
+//! `poll` for as long as it remains ready.
+//!
+//! On Linux the poller is implemented with `epoll`, on other unix platforms it
+//! falls back to `poll`. It is not yet supported on Windows, where creating a
+//! `Poller` fails.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # #![allow(experimental)]
+//! use std::io::{TcpListener, Listener, Acceptor};
+//! use std::io::poll::{Poller, READABLE};
+//!
+//! let mut acceptor = TcpListener::bind("127.0.0.1:8482").listen().unwrap();
+//! acceptor.set_nonblocking(true);
+//!
+//! let mut poller = Poller::new().unwrap();
+//! poller.register(&acceptor, 0, READABLE).unwrap();
+//!
+//! let mut clients = Vec::new();
+//! let mut events = Vec::new();
+//! loop {
+//!     poller.poll(&mut events, None).unwrap();
+//!     for event in events.iter() {
+//!         if event.token == 0 {
+//!             let mut stream = acceptor.accept().unwrap();
+//!             stream.set_nonblocking(true);
+//!             poller.register(&stream, clients.len() + 1, READABLE).unwrap();
+//!             clients.push(stream);
+//!         } else {
+//!             let mut buf = [0; 1024];
+//!             let _ = clients[event.token - 1].read(&mut buf);
+//!         }
+//!     }
+//! }
+//! ```
+
+#![experimental]
+
+use prelude::v1::*;
+
+use io::IoResult;
+use io::net::tcp::{TcpStream, TcpAcceptor};
+use io::net::udp::UdpSocket;
+#[cfg(unix)] use io::net::pipe::{UnixStream, UnixAcceptor, UnixDatagram};
+#[cfg(unix)] use io::pipe::PipeStream;
+use sys::poll::Poller as PollerImp;
+use sys::sock_t;
+use sys_common::AsInner;
+
+bitflags! {
+    #[doc = "The kinds of readiness a `Poller` is asked to watch an object for."]
+    flags Interest: u32 {
+        /// Report when the object can be read from (or, for an acceptor,
+        /// when a connection is pending) without blocking.
+        const READABLE = 0b01,
+
+        /// Report when the object can be written to without blocking.
+        const WRITABLE = 0b10,
+    }
+}
+
+/// A readiness notification returned by `Poller::poll`.
+#[derive(Copy, Clone, PartialEq, Eq, Show)]
+pub struct Event {
+    /// The token the object was registered with.
+    pub token: uint,
+    /// Whether the object is ready to be read from.
+    pub readable: bool,
+    /// Whether the object is ready to be written to.
+    pub writable: bool,
+    /// Whether the other end of the object has hung up. Reads will return any
+    /// remaining data followed by `EndOfFile`.
+    pub hangup: bool,
+    /// Whether an error is pending on the object. The next operation on it
+    /// will report the error.
+    pub error: bool,
+}
+
+/// An I/O object which can be registered with a `Poller`.
+pub trait Pollable {
+    #[doc(hidden)]
+    fn poll_source(&self) -> sock_t;
+}
+
+impl Pollable for TcpStream {
+    fn poll_source(&self) -> sock_t { self.as_inner().fd() }
+}
+
+#[cfg(unix)]
+impl Pollable for TcpAcceptor {
+    fn poll_source(&self) -> sock_t { self.as_inner().fd() }
+}
+
+#[cfg(windows)]
+impl Pollable for TcpAcceptor {
+    fn poll_source(&self) -> sock_t { self.as_inner().socket() }
+}
+
+impl Pollable for UdpSocket {
+    fn poll_source(&self) -> sock_t { self.as_inner().fd() }
+}
+
+#[cfg(unix)]
+impl Pollable for UnixStream {
+    fn poll_source(&self) -> sock_t { self.as_inner().fd() }
+}
+
+#[cfg(unix)]
+impl Pollable for UnixAcceptor {
+    fn poll_source(&self) -> sock_t { self.as_inner().fd() }
+}
+
+#[cfg(unix)]
+impl Pollable for UnixDatagram {
+    fn poll_source(&self) -> sock_t { self.as_inner().fd() }
+}
+
+#[cfg(unix)]
+impl Pollable for PipeStream {
+    fn poll_source(&self) -> sock_t { self.as_inner().fd() }
+}
+
+/// A set of I/O objects watched for readiness.
+///
+/// Each object is registered along with a `uint` token chosen by the caller,
+/// which is handed back in the `Event`s reported for that object. An object
+/// may only be registered once with a given poller; use `reregister` to change
+/// its token or interest.
+///
+/// The poller does not keep registered objects alive. An object must be
+/// deregistered before it is dropped, otherwise its underlying descriptor may
+/// be reused by an unrelated object which would then be reported under the
+/// stale token.
+pub struct Poller {
+    inner: PollerImp,
+}
+
+impl Poller {
+    /// Creates a new poller with no registered objects.
+    pub fn new() -> IoResult<Poller> {
+        Ok(Poller { inner: try!(PollerImp::new()) })
+    }
+
+    /// Starts watching `source` for the readiness described by `interest`,
+    /// reporting it under `token`.
+    ///
+    /// # Errors
+    ///
+    /// Fails if `source` is already registered with this poller.
+    pub fn register<P: Pollable>(&mut self, source: &P, token: uint,
+                                 interest: Interest) -> IoResult<()> {
+        self.inner.register(source.poll_source(), token, interest)
+    }
+
+    /// Changes the token and interest of an already registered `source`.
+    pub fn reregister<P: Pollable>(&mut self, source: &P, token: uint,
+                                   interest: Interest) -> IoResult<()> {
+        self.inner.reregister(source.poll_source(), token, interest)
+    }
+
+    /// Stops watching `source`.
+    pub fn deregister<P: Pollable>(&mut self, source: &P) -> IoResult<()> {
+        self.inner.deregister(source.poll_source())
+    }
+
+    /// Waits for at least one registered object to become ready, or for the
+    /// timeout to expire, and replaces the contents of `events` with the
+    /// notifications received. Returns the number of events.
+    ///
+    /// A timeout of `None` waits indefinitely, while `Some(0)` only checks the
+    /// current state of the objects. The call may also return early with no
+    /// events if it is interrupted by a signal.
+    #[experimental = "the timeout argument may change in type and value"]
+    pub fn poll(&mut self, events: &mut Vec<Event>,
+                timeout_ms: Option<u64>) -> IoResult<uint> {
+        events.truncate(0);
+        try!(self.inner.poll(events, timeout_ms));
+        Ok(events.len())
+    }
+}
+
+#[cfg(all(test, unix))]
+#[allow(experimental)]
+mod test {
+    use prelude::v1::*;
+
+    use io::net::tcp::*;
+    use io::net::udp::UdpSocket;
+    use io::pipe::PipeStream;
+    use io::test::*;
+    use io::{Acceptor, Listener, ResourceUnavailable};
+    use super::{Poller, READABLE, WRITABLE};
+
+    #[test]
+    fn poll_times_out() {
+        let addr = next_test_ip4();
+        let a = TcpListener::bind(addr).listen().unwrap();
+        let mut poller = Poller::new().unwrap();
+        poller.register(&a, 0, READABLE).unwrap();
+
+        let mut events = Vec::new();
+        assert_eq!(poller.poll(&mut events, Some(10)).unwrap(), 0);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn poll_tcp() {
+        let addr = next_test_ip4();
+        let mut a = TcpListener::bind(addr).listen().unwrap();
+        a.set_nonblocking(true);
+        assert_eq!(a.accept().err().unwrap().kind, ResourceUnavailable);
+
+        let mut poller = Poller::new().unwrap();
+        poller.register(&a, 7, READABLE).unwrap();
+
+        let mut client = TcpStream::connect(addr).unwrap();
+        let mut events = Vec::new();
+        assert_eq!(poller.poll(&mut events, Some(1000)).unwrap(), 1);
+        assert_eq!(events[0].token, 7);
+        assert!(events[0].readable);
+
+        let mut server = a.accept().unwrap();
+        server.set_nonblocking(true);
+        assert_eq!(server.read(&mut [0]).err().unwrap().kind, ResourceUnavailable);
+
+        poller.deregister(&a).unwrap();
+        poller.register(&server, 8, READABLE | WRITABLE).unwrap();
+        assert_eq!(poller.poll(&mut events, Some(1000)).unwrap(), 1);
+        assert!(!events[0].readable);
+        assert!(events[0].writable);
+
+        client.write(&[99]).unwrap();
+        poller.reregister(&server, 9, READABLE).unwrap();
+        assert_eq!(poller.poll(&mut events, Some(1000)).unwrap(), 1);
+        assert_eq!(events[0].token, 9);
+        assert!(events[0].readable);
+
+        let mut buf = [0];
+        assert_eq!(server.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 99);
+    }
+
+    #[test]
+    fn poll_udp_and_pipes() {
+        let addr = next_test_ip4();
+        let mut server = UdpSocket::bind(addr).unwrap();
+        server.set_nonblocking(true);
+        let mut buf = [0; 1];
+        assert_eq!(server.recv_from(&mut buf).err().unwrap().kind, ResourceUnavailable);
+
+        let mut pipe = PipeStream::pair().unwrap();
+
+        let mut poller = Poller::new().unwrap();
+        poller.register(&server, 1, READABLE).unwrap();
+        poller.register(&pipe.reader, 2, READABLE).unwrap();
+        assert!(poller.register(&pipe.reader, 3, READABLE).is_err());
+
+        let mut client = UdpSocket::bind(next_test_ip4()).unwrap();
+        client.send_to(&[1], addr).unwrap();
+        pipe.writer.write(&[2]).unwrap();
+
+        let mut events = Vec::new();
+        let mut seen = Vec::new();
+        while seen.len() < 2 {
+            assert!(poller.poll(&mut events, Some(1000)).unwrap() > 0);
+            for e in events.iter() {
+                assert!(e.readable);
+                if !seen.contains(&e.token) { seen.push(e.token); }
+            }
+        }
+        seen.sort();
+        assert_eq!(seen, vec![1, 2]);
+    }
+}
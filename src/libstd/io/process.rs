@@ -28,10 +28,12 @@ use io;
 use libc;
 use os;
 use path::BytesContainer;
+use sync::Arc;
 use sync::mpsc::{channel, Receiver};
 use sys::fs::FileDesc;
 use sys::process::Process as ProcessImp;
 use sys;
+use sys_common::AsInner;
 use thread::Thread;
 
 #[cfg(windows)] use std::hash::sip::SipState;
@@ -183,6 +185,36 @@ pub struct Command {
     uid: Option<uint>,
     gid: Option<uint>,
     detach: bool,
+    pgroup: Option<libc::pid_t>,
+    rlimits: Vec<(Resource, u64, u64)>,
+    keep_fds: Vec<libc::c_int>,
+    before_exec: Vec<PreExecHook>,
+}
+
+/// A closure run in the child process between `fork` and `exec`, as registered
+/// with `Command::before_exec`.
+pub type PreExecHook = Arc<Box<Fn() -> IoResult<()> + Send + Sync>>;
+
+/// A resource whose consumption by a child process can be limited with
+/// `Command::rlimit`. Each variant corresponds to the unix `RLIMIT_*` constant
+/// of the same meaning.
+#[derive(Copy, Clone, PartialEq, Eq, Show)]
+pub enum Resource {
+    /// Maximum CPU time in seconds (`RLIMIT_CPU`).
+    CpuTime,
+    /// Maximum size in bytes of files the process may create (`RLIMIT_FSIZE`).
+    FileSize,
+    /// Maximum size in bytes of the data segment (`RLIMIT_DATA`).
+    DataSize,
+    /// Maximum size in bytes of the main thread's stack (`RLIMIT_STACK`).
+    StackSize,
+    /// Maximum size in bytes of core dumps (`RLIMIT_CORE`).
+    CoreSize,
+    /// One more than the highest file descriptor number the process may open
+    /// (`RLIMIT_NOFILE`).
+    OpenFiles,
+    /// Maximum size in bytes of the process's virtual memory (`RLIMIT_AS`).
+    AddressSpace,
 }
 
 // FIXME (#12938): Until DST lands, we cannot decompose &str into & and str, so
@@ -216,6 +248,10 @@ impl Command {
             uid: None,
             gid: None,
             detach: false,
+            pgroup: None,
+            rlimits: Vec::new(),
+            keep_fds: Vec::new(),
+            before_exec: Vec::new(),
         }
     }
 
@@ -327,12 +363,67 @@ impl Command {
     }
 
     /// Sets the child process to be spawned in a detached state. On unix, this
-    /// means that the child is the leader of a new process group.
+    /// means that the child is the leader of a new session and of a new
+    /// process group within it. This takes precedence over `process_group`.
     pub fn detached<'a>(&'a mut self) -> &'a mut Command {
         self.detach = true;
         self
     }
 
+    /// Places the child process in the process group `pgid`. This translates
+    /// to a `setpgid(0, pgid)` call in the child process, so a `pgid` of 0
+    /// makes the child the leader of a new process group whose id is the
+    /// child's pid. Setting this value on windows will cause the spawn to fail.
+    #[experimental = "recently added, unix only"]
+    pub fn process_group<'a>(&'a mut self, pgid: libc::pid_t) -> &'a mut Command {
+        self.pgroup = Some(pgid);
+        self
+    }
+
+    /// Sets the soft and hard limits of `resource` for the child process. This
+    /// translates to a `setrlimit` call in the child process, made before any
+    /// `uid` or `gid` change so that a privileged parent may still raise hard
+    /// limits. Setting this value on windows will cause the spawn to fail.
+    #[experimental = "recently added, unix only"]
+    pub fn rlimit<'a>(&'a mut self, resource: Resource, soft: u64, hard: u64)
+                      -> &'a mut Command {
+        self.rlimits.push((resource, soft, hard));
+        self
+    }
+
+    /// Keeps the file descriptor `fd` open in the child process.
+    ///
+    /// On unix every file descriptor other than stdin, stdout and stderr is
+    /// closed in the child before it executes the program. Descriptors
+    /// registered here are exempt from this, and also have their close-on-exec
+    /// flag cleared. Setting this value on windows will cause the spawn to
+    /// fail.
+    #[experimental = "recently added, unix only"]
+    pub fn keep_fd<'a>(&'a mut self, fd: libc::c_int) -> &'a mut Command {
+        self.keep_fds.push(fd);
+        self
+    }
+
+    /// Registers a closure to be run in the child process after it has been
+    /// fully configured, immediately before the program is executed. Multiple
+    /// closures are run in the order they were registered.
+    ///
+    /// If a closure returns an error the spawn fails, with the error decoded
+    /// from the `errno` value the closure left behind.
+    ///
+    /// The closure runs in a freshly forked copy of this process in which only
+    /// the calling thread exists, so it must restrict itself to
+    /// async-signal-safe operations: in particular it must not allocate, take
+    /// locks or panic. Setting this value on windows will cause the spawn to
+    /// fail.
+    #[experimental = "recently added, unix only"]
+    pub fn before_exec<'a, F>(&'a mut self, f: F) -> &'a mut Command
+        where F: Fn() -> IoResult<()> + Send + Sync + 'static
+    {
+        self.before_exec.push(Arc::new(box f as Box<Fn() -> IoResult<()> + Send + Sync>));
+        self
+    }
+
     /// Executes the command as a child process, which is returned.
     pub fn spawn(&self) -> IoResult<Process> {
         let (their_stdin, our_stdin) = try!(setup_io(self.stdin));
@@ -464,7 +555,18 @@ impl sys::process::ProcessConfig<EnvKey, CString> for Command {
     fn detach(&self) -> bool {
         self.detach
     }
-
+    fn process_group(&self) -> Option<libc::pid_t> {
+        self.pgroup
+    }
+    fn rlimits(&self) -> &[(Resource, u64, u64)] {
+        self.rlimits.as_slice()
+    }
+    fn keep_fds(&self) -> &[libc::c_int] {
+        self.keep_fds.as_slice()
+    }
+    fn before_exec(&self) -> &[PreExecHook] {
+        self.before_exec.as_slice()
+    }
 }
 
 /// The output of a finished process.
@@ -713,21 +815,8 @@ impl Process {
     /// fail.
     pub fn wait_with_output(mut self) -> IoResult<ProcessOutput> {
         drop(self.stdin.take());
-        fn read(stream: Option<io::PipeStream>) -> Receiver<IoResult<Vec<u8>>> {
-            let (tx, rx) = channel();
-            match stream {
-                Some(stream) => {
-                    Thread::spawn(move |:| {
-                        let mut stream = stream;
-                        tx.send(stream.read_to_end()).unwrap();
-                    });
-                }
-                None => tx.send(Ok(Vec::new())).unwrap()
-            }
-            rx
-        }
-        let stdout = read(self.stdout.take());
-        let stderr = read(self.stderr.take());
+        let stdout = read_async(self.stdout.take());
+        let stderr = read_async(self.stderr.take());
 
         let status = try!(self.wait());
 
@@ -765,6 +854,204 @@ impl Drop for Process {
     }
 }
 
+// Reads `stream` to completion on a separate thread, so that several streams
+// can be drained while waiting on a child.
+fn read_async(stream: Option<PipeStream>) -> Receiver<IoResult<Vec<u8>>> {
+    let (tx, rx) = channel();
+    match stream {
+        Some(stream) => {
+            Thread::spawn(move |:| {
+                let mut stream = stream;
+                tx.send(stream.read_to_end()).unwrap();
+            });
+        }
+        None => tx.send(Ok(Vec::new())).unwrap()
+    }
+    rx
+}
+
+/// A builder for a sequence of commands run concurrently, with the stdout of
+/// each one connected to the stdin of the next, like `a | b | c` in a shell.
+///
+/// The stdin configuration of the first command and the stdout configuration
+/// of the last command are honoured, as are the stderr configurations of all
+/// of them; the connecting pipes override everything else.
+///
+/// # Example
+///
+/// ```
+/// use std::io::process::{Command, Pipeline};
+///
+/// let output = match Pipeline::new()
+///                             .command(Command::new("echo").arg("hello"))
+///                             .command(Command::new("tr").arg("a-z").arg("A-Z"))
+///                             .output() {
+///     Ok(output) => output,
+///     Err(e) => panic!("failed to execute pipeline: {}", e),
+/// };
+///
+/// assert!(output.status.success());
+/// assert_eq!(output.output.as_slice(), b"HELLO\n");
+/// ```
+#[derive(Clone)]
+#[experimental = "recently added"]
+pub struct Pipeline {
+    commands: Vec<Command>,
+}
+
+impl Pipeline {
+    /// Constructs a new, empty `Pipeline`.
+    pub fn new() -> Pipeline {
+        Pipeline { commands: Vec::new() }
+    }
+
+    /// Appends a copy of `cmd` to the end of the pipeline.
+    pub fn command<'a>(&'a mut self, cmd: &Command) -> &'a mut Pipeline {
+        self.commands.push(cmd.clone());
+        self
+    }
+
+    /// Spawns every command of the pipeline, returning handles to all of
+    /// them.
+    ///
+    /// If any command fails to spawn, the commands spawned before it are
+    /// killed and the error is returned.
+    pub fn spawn(&self) -> IoResult<PipelineProcess> {
+        if self.commands.is_empty() {
+            return Err(IoError {
+                kind: io::InvalidInput,
+                desc: "cannot spawn an empty pipeline",
+                detail: None,
+            })
+        }
+
+        let last = self.commands.len() - 1;
+        let mut processes: Vec<Process> = Vec::with_capacity(last + 1);
+        let mut prev: Option<PipeStream> = None;
+        for (i, cmd) in self.commands.iter().enumerate() {
+            let mut cmd = cmd.clone();
+            match prev {
+                Some(ref stream) => { cmd.stdin(InheritFd(stream.as_inner().fd())); }
+                None => {}
+            }
+            if i < last {
+                cmd.stdout(CreatePipe(false, true));
+            }
+
+            let mut process = match cmd.spawn() {
+                Ok(process) => process,
+                Err(e) => {
+                    // Kill the commands already started, and reap them so
+                    // they aren't left as zombies.
+                    for p in processes.iter_mut() {
+                        let _ = p.signal_kill();
+                        let _ = p.wait();
+                    }
+                    return Err(e)
+                }
+            };
+
+            // Our copy of the previous read end has been inherited by the
+            // child now, so it's closed here by the replacement.
+            prev = if i < last { process.stdout.take() } else { None };
+            processes.push(process);
+        }
+
+        let stdin = processes[0].stdin.take();
+        let stdout = processes[last].stdout.take();
+        Ok(PipelineProcess {
+            processes: processes,
+            stdin: stdin,
+            stdout: stdout,
+        })
+    }
+
+    /// Runs the pipeline, waiting for all commands to finish and collecting
+    /// the output of the last command as well as the error output of all of
+    /// them. The status is that of `PipelineProcess::wait`.
+    pub fn output(&self) -> IoResult<ProcessOutput> {
+        self.spawn().and_then(|p| p.wait_with_output())
+    }
+
+    /// Runs the pipeline, waiting for all commands to finish and returning the
+    /// status of `PipelineProcess::wait`.
+    pub fn status(&self) -> IoResult<ProcessExit> {
+        self.spawn().and_then(|mut p| p.wait())
+    }
+}
+
+impl fmt::String for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, cmd) in self.commands.iter().enumerate() {
+            if i > 0 { try!(write!(f, " | ")); }
+            try!(write!(f, "{}", cmd));
+        }
+        Ok(())
+    }
+}
+
+/// The running commands of a `Pipeline`.
+#[experimental = "recently added"]
+pub struct PipelineProcess {
+    /// The processes of the pipeline, in the order their commands were added.
+    /// Their `stderr` handles are set up according to each command; the
+    /// connected stdin and stdout handles are not available here.
+    pub processes: Vec<Process>,
+
+    /// Handle to the first command's stdin, if it was configured as
+    /// `CreatePipe`.
+    pub stdin: Option<PipeStream>,
+
+    /// Handle to the last command's stdout, if it was configured as
+    /// `CreatePipe`.
+    pub stdout: Option<PipeStream>,
+}
+
+impl PipelineProcess {
+    /// Waits for every process of the pipeline to exit, returning their
+    /// statuses in pipeline order. The stdin handle is closed first.
+    pub fn wait_all(&mut self) -> IoResult<Vec<ProcessExit>> {
+        drop(self.stdin.take());
+        let mut statuses = Vec::with_capacity(self.processes.len());
+        for p in self.processes.iter_mut() {
+            statuses.push(try!(p.wait()));
+        }
+        Ok(statuses)
+    }
+
+    /// Waits for every process of the pipeline to exit and returns a combined
+    /// status: that of the last command which did not succeed, or success if
+    /// they all did (like the `pipefail` option of bash).
+    pub fn wait(&mut self) -> IoResult<ProcessExit> {
+        let statuses = try!(self.wait_all());
+        Ok(statuses.iter().rev().find(|s| !s.success()).map(|s| *s)
+                   .unwrap_or(ExitStatus(0)))
+    }
+
+    /// Simultaneously waits for the pipeline to exit and collects the
+    /// remaining output of the last command's stdout and of every command's
+    /// stderr, concatenated in pipeline order.
+    pub fn wait_with_output(mut self) -> IoResult<ProcessOutput> {
+        drop(self.stdin.take());
+        let stdout = read_async(self.stdout.take());
+        let stderrs: Vec<_> = self.processes.iter_mut().map(|p| {
+            read_async(p.stderr.take())
+        }).collect();
+
+        let status = try!(self.wait());
+
+        let mut error = Vec::new();
+        for rx in stderrs.iter() {
+            error.push_all(rx.recv().unwrap().unwrap_or(Vec::new()).as_slice());
+        }
+        Ok(ProcessOutput {
+            status: status,
+            output: stdout.recv().unwrap().unwrap_or(Vec::new()),
+            error: error,
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use io::{Truncate, Write, TimedOut, timer, process, FileNotFound};
@@ -777,6 +1064,7 @@ mod tests {
     use str;
     use super::{CreatePipe};
     use super::{InheritFd, Process, PleaseExitSignal, Command, ProcessOutput};
+    use super::{Pipeline, Resource};
     use sync::mpsc::channel;
     use thread::Thread;
     use time::Duration;
@@ -1243,4 +1531,109 @@ mod tests {
         let val = env.get(&EnvKey(CString::from_slice(b"PATH")));
         assert!(val.unwrap() == &CString::from_slice(b"bar"));
     }
+
+    #[cfg(all(unix, not(target_os="android")))]
+    #[test]
+    fn process_group_works() {
+        let mut p = Command::new("sleep").arg("1000")
+                            .process_group(0)
+                            .spawn().unwrap();
+        // The child leads a new group, so signalling the group reaches it.
+        assert!(Process::kill(-p.id(), 0).is_ok());
+        p.signal_kill().unwrap();
+        assert!(!p.wait().unwrap().success());
+    }
+
+    #[cfg(all(unix, not(target_os="android")))]
+    #[test]
+    fn rlimit_works() {
+        let mut cmd = Command::new("/bin/sh");
+        cmd.arg("-c").arg("ulimit -n").rlimit(Resource::OpenFiles, 64, 64);
+        assert_eq!(run_output(cmd), "64\n");
+    }
+
+    #[cfg(all(unix, not(target_os="android")))]
+    #[test]
+    fn keep_fd_works() {
+        use io::pipe::PipeStream;
+        use sys_common::AsInner;
+
+        let mut pair = PipeStream::pair().unwrap();
+        let fd = pair.writer.as_inner().fd();
+        let mut p = Command::new("/bin/sh")
+                            .arg("-c").arg(format!("echo kept >&{}", fd))
+                            .keep_fd(fd)
+                            .spawn().unwrap();
+        assert!(p.wait().unwrap().success());
+        drop(pair.writer);
+        assert_eq!(read_all(&mut pair.reader as &mut Reader), "kept\n");
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn unix_options_fail_on_windows() {
+        assert!(Command::new("test").process_group(0).spawn().is_err());
+    }
+
+    #[cfg(all(unix, not(target_os="android")))]
+    #[test]
+    fn before_exec_works() {
+        use io;
+
+        let mut p = Command::new("true").before_exec(|| Ok(())).spawn().unwrap();
+        assert!(p.wait().unwrap().success());
+
+        let res = Command::new("true").before_exec(|| {
+            Err(io::standard_error(io::OtherIoError))
+        }).spawn();
+        assert!(res.is_err());
+    }
+
+    #[cfg(all(unix, not(target_os="android")))]
+    #[test]
+    fn pipeline_works() {
+        let output = Pipeline::new()
+                              .command(Command::new("echo").arg("foobar"))
+                              .command(Command::new("tr").arg("a-z").arg("A-Z"))
+                              .command(&Command::new("cat"))
+                              .output().unwrap();
+        assert!(output.status.success());
+        assert_eq!(output.output, b"FOOBAR\n".to_vec());
+    }
+
+    #[cfg(all(unix, not(target_os="android")))]
+    #[test]
+    fn pipeline_stdin_works() {
+        let mut p = Pipeline::new()
+                             .command(Command::new("/bin/sh").arg("-c")
+                                                             .arg("read line; echo $line"))
+                             .command(&Command::new("cat"))
+                             .spawn().unwrap();
+        p.stdin.as_mut().unwrap().write("foobar\n".as_bytes()).unwrap();
+        drop(p.stdin.take());
+        let out = read_all(p.stdout.as_mut().unwrap() as &mut Reader);
+        assert_eq!(p.wait_all().unwrap().len(), 2);
+        assert_eq!(out, "foobar\n");
+    }
+
+    #[cfg(all(unix, not(target_os="android")))]
+    #[test]
+    fn pipeline_status_is_last_failure() {
+        let status = Pipeline::new()
+                              .command(&Command::new("false"))
+                              .command(&Command::new("true"))
+                              .status().unwrap();
+        assert!(!status.success());
+
+        let status = Pipeline::new()
+                              .command(&Command::new("true"))
+                              .command(&Command::new("true"))
+                              .status().unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn empty_pipeline_fails() {
+        assert!(Pipeline::new().spawn().is_err());
+    }
 }
@@ -12,6 +12,8 @@
 #![allow(dead_code)]
 
 use io::{self, IoError, IoResult};
+use io::process;
+use libc;
 use prelude::v1::*;
 use sys::{last_error, retry};
 use ffi::CString;
@@ -56,6 +58,14 @@ pub fn short_write(n: uint, desc: &'static str) -> IoError {
     }
 }
 
+pub fn would_block() -> IoError {
+    IoError {
+        kind: io::ResourceUnavailable,
+        desc: "operation would block",
+        detail: None,
+    }
+}
+
 pub fn unimpl() -> IoError {
     IoError {
         kind: io::IoUnavailable,
@@ -106,4 +116,8 @@ pub trait ProcessConfig<K: BytesContainer, V: BytesContainer> {
     fn uid(&self) -> Option<uint>;
     fn gid(&self) -> Option<uint>;
     fn detach(&self) -> bool;
+    fn process_group(&self) -> Option<libc::pid_t>;
+    fn rlimits(&self) -> &[(process::Resource, u64, u64)];
+    fn keep_fds(&self) -> &[libc::c_int];
+    fn before_exec(&self) -> &[process::PreExecHook];
 }
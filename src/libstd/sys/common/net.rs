@@ -26,7 +26,7 @@ use sys::{self, retry, c, sock_t, last_error, last_net_error, last_gai_error, cl
           wrlen, msglen_t, os, wouldblock, set_nonblocking, timer, ms_to_timeval,
           decode_error_detailed};
 use sync::{Arc, Mutex, MutexGuard};
-use sys_common::{self, keep_going, short_write, timeout};
+use sys_common::{self, keep_going, short_write, timeout, would_block};
 use cmp;
 use io;
 
@@ -486,6 +486,36 @@ pub fn write<T, L, W>(fd: sock_t,
     }
 }
 
+// Counterparts of read() and write() for handles in nonblocking mode: a single
+// attempt is made, and if it would block a `ResourceUnavailable` error is
+// returned instead of waiting for the socket to become ready.
+
+pub fn read_nonblocking<T, L, R>(mut lock: L, mut read: R) -> IoResult<uint> where
+    L: FnMut() -> T,
+    R: FnMut(bool) -> libc::c_int,
+{
+    let _guard = lock();
+    match retry(|| read(true)) {
+        -1 if wouldblock() => Err(would_block()),
+        -1 => Err(last_net_error()),
+        0 => Err(sys_common::eof()),
+        n => Ok(n as uint),
+    }
+}
+
+pub fn write_nonblocking<T, L, W>(buf: &[u8], mut lock: L, mut write: W) -> IoResult<()> where
+    L: FnMut() -> T,
+    W: FnMut(bool, *const u8, uint) -> i64,
+{
+    let _guard = lock();
+    match retry(|| write(true, buf.as_ptr(), buf.len())) {
+        -1 if wouldblock() => Err(would_block()),
+        -1 => Err(last_net_error()),
+        n if (n as uint) < buf.len() => Err(short_write(n as uint, "short write")),
+        _ => Ok(()),
+    }
+}
+
 // See http://developerweb.net/viewtopic.php?id=3196 for where this is
 // derived from.
 pub fn connect_timeout(fd: sock_t,
@@ -633,6 +663,7 @@ pub struct TcpStream {
     inner: Arc<Inner>,
     read_deadline: u64,
     write_deadline: u64,
+    nonblocking: bool,
 }
 
 impl TcpStream {
@@ -665,6 +696,7 @@ impl TcpStream {
             inner: Arc::new(Inner::new(fd)),
             read_deadline: 0,
             write_deadline: 0,
+            nonblocking: false,
         }
     }
 
@@ -725,7 +757,11 @@ impl TcpStream {
                        buf.len() as wrlen,
                        flags) as libc::c_int
         };
-        read(fd, self.read_deadline, dolock, doread)
+        if self.nonblocking {
+            read_nonblocking(dolock, doread)
+        } else {
+            read(fd, self.read_deadline, dolock, doread)
+        }
     }
 
     pub fn write(&mut self, buf: &[u8]) -> IoResult<()> {
@@ -738,7 +774,11 @@ impl TcpStream {
                        len as wrlen,
                        flags) as i64
         };
-        write(fd, self.write_deadline, buf, true, dolock, dowrite).map(|_| ())
+        if self.nonblocking {
+            write_nonblocking(buf, dolock, dowrite)
+        } else {
+            write(fd, self.write_deadline, buf, true, dolock, dowrite).map(|_| ())
+        }
     }
     pub fn peer_name(&mut self) -> IoResult<SocketAddr> {
         sockname(self.fd(), libc::getpeername)
@@ -762,6 +802,9 @@ impl TcpStream {
     pub fn set_write_timeout(&mut self, timeout: Option<u64>) {
         self.write_deadline = timeout.map(|a| timer::now() + a).unwrap_or(0);
     }
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.nonblocking = nonblocking;
+    }
 
     pub fn socket_name(&mut self) -> IoResult<SocketAddr> {
         sockname(self.fd(), libc::getsockname)
@@ -774,6 +817,7 @@ impl Clone for TcpStream {
             inner: self.inner.clone(),
             read_deadline: 0,
             write_deadline: 0,
+            nonblocking: false,
         }
     }
 }
@@ -786,6 +830,7 @@ pub struct UdpSocket {
     inner: Arc<Inner>,
     read_deadline: u64,
     write_deadline: u64,
+    nonblocking: bool,
 }
 
 impl UdpSocket {
@@ -797,6 +842,7 @@ impl UdpSocket {
             inner: Arc::new(Inner::new(fd)),
             read_deadline: 0,
             write_deadline: 0,
+            nonblocking: false,
         };
 
         let mut storage = unsafe { mem::zeroed() };
@@ -865,16 +911,25 @@ impl UdpSocket {
         let mut addrlen: libc::socklen_t =
                 mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
 
-        let dolock = |&:| self.lock_nonblocking();
-        let n = try!(read(fd, self.read_deadline, dolock, |nb| unsafe {
-            let flags = if nb {c::MSG_DONTWAIT} else {0};
-            libc::recvfrom(fd,
-                           buf.as_mut_ptr() as *mut libc::c_void,
-                           buf.len() as msglen_t,
-                           flags,
-                           storagep,
-                           &mut addrlen) as libc::c_int
-        }));
+        let nonblocking = self.nonblocking;
+        let deadline = self.read_deadline;
+        let n = {
+            let dolock = |&:| self.lock_nonblocking();
+            let doread = |&mut: nb| unsafe {
+                let flags = if nb {c::MSG_DONTWAIT} else {0};
+                libc::recvfrom(fd,
+                               buf.as_mut_ptr() as *mut libc::c_void,
+                               buf.len() as msglen_t,
+                               flags,
+                               storagep,
+                               &mut addrlen) as libc::c_int
+            };
+            if nonblocking {
+                try!(read_nonblocking(dolock, doread))
+            } else {
+                try!(read(fd, deadline, dolock, doread))
+            }
+        };
         sockaddr_to_addr(&storage, addrlen as uint).and_then(|addr| {
             Ok((n as uint, addr))
         })
@@ -897,6 +952,10 @@ impl UdpSocket {
                          dstlen) as i64
         };
 
+        if self.nonblocking {
+            return write_nonblocking(buf, dolock, dowrite);
+        }
+
         let n = try!(write(fd, self.write_deadline, buf, false, dolock, dowrite));
         if n != buf.len() {
             Err(short_write(n, "couldn't send entire packet at once"))
@@ -945,6 +1004,9 @@ impl UdpSocket {
     pub fn set_write_timeout(&mut self, timeout: Option<u64>) {
         self.write_deadline = timeout.map(|a| timer::now() + a).unwrap_or(0);
     }
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.nonblocking = nonblocking;
+    }
 }
 
 impl Clone for UdpSocket {
@@ -953,6 +1015,7 @@ impl Clone for UdpSocket {
             inner: self.inner.clone(),
             read_deadline: 0,
             write_deadline: 0,
+            nonblocking: false,
         }
     }
 }
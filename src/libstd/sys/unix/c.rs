@@ -14,9 +14,13 @@
 #![allow(non_camel_case_types)]
 
 pub use self::select::fd_set;
+pub use self::resource::{rlimit, rlim_t};
+pub use self::resource::{RLIMIT_CPU, RLIMIT_FSIZE, RLIMIT_DATA, RLIMIT_STACK};
+pub use self::resource::{RLIMIT_CORE, RLIMIT_NOFILE, RLIMIT_AS};
 pub use self::signal::{sigaction, siginfo, sigset_t};
 pub use self::signal::{SA_ONSTACK, SA_RESTART, SA_RESETHAND, SA_NOCLDSTOP};
 pub use self::signal::{SA_NODEFER, SA_NOCLDWAIT, SA_SIGINFO, SIGCHLD};
+pub use self::msg::{msghdr, cmsghdr, iovec, cmsglen_t, cmsg_align};
 
 use libc;
 
@@ -52,6 +56,22 @@ pub const FIOCLEX: libc::c_ulong = 0x5451;
           any(target_arch = "mips", target_arch = "mipsel")))]
 pub const FIOCLEX: libc::c_ulong = 0x6601;
 
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly"))]
+pub const FIONCLEX: libc::c_ulong = 0x20006602;
+#[cfg(any(all(target_os = "linux",
+              any(target_arch = "x86",
+                  target_arch = "x86_64",
+                  target_arch = "arm",
+                  target_arch = "aarch64")),
+          target_os = "android"))]
+pub const FIONCLEX: libc::c_ulong = 0x5450;
+#[cfg(all(target_os = "linux",
+          any(target_arch = "mips", target_arch = "mipsel")))]
+pub const FIONCLEX: libc::c_ulong = 0x6602;
+
 #[cfg(any(target_os = "macos",
           target_os = "ios",
           target_os = "freebsd",
@@ -60,6 +80,38 @@ pub const MSG_DONTWAIT: libc::c_int = 0x80;
 #[cfg(any(target_os = "linux", target_os = "android"))]
 pub const MSG_DONTWAIT: libc::c_int = 0x40;
 
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly"))]
+pub const MSG_CTRUNC: libc::c_int = 0x20;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const MSG_CTRUNC: libc::c_int = 0x8;
+
+// OSX, iOS and DragonFly can't mark received descriptors close-on-exec
+// atomically, so this is 0 there and they are marked after the fact.
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "dragonfly"))]
+pub const MSG_CMSG_CLOEXEC: libc::c_int = 0;
+#[cfg(target_os = "freebsd")]
+pub const MSG_CMSG_CLOEXEC: libc::c_int = 0x40000;
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub const MSG_CMSG_CLOEXEC: libc::c_int = 0x40000000;
+
+pub const SCM_RIGHTS: libc::c_int = 1;
+
+#[cfg(all(target_os = "linux",
+          any(target_arch = "mips", target_arch = "mipsel")))]
+pub const SO_PEERCRED: libc::c_int = 18;
+#[cfg(any(all(target_os = "linux",
+              any(target_arch = "x86",
+                  target_arch = "x86_64",
+                  target_arch = "arm",
+                  target_arch = "aarch64")),
+          target_os = "android"))]
+pub const SO_PEERCRED: libc::c_int = 17;
+
 pub const WNOHANG: libc::c_int = 1;
 
 extern {
@@ -88,6 +140,176 @@ extern {
     pub fn sigaddset(set: *mut sigset_t, signum: libc::c_int) -> libc::c_int;
     pub fn sigdelset(set: *mut sigset_t, signum: libc::c_int) -> libc::c_int;
     pub fn sigemptyset(set: *mut sigset_t) -> libc::c_int;
+
+    pub fn setrlimit(resource: libc::c_int, rlim: *const rlimit) -> libc::c_int;
+    pub fn getrlimit(resource: libc::c_int, rlim: *mut rlimit) -> libc::c_int;
+
+    pub fn socketpair(domain: libc::c_int, ty: libc::c_int,
+                      protocol: libc::c_int,
+                      sv: *mut libc::c_int) -> libc::c_int;
+    pub fn sendmsg(sockfd: libc::c_int, msg: *const msghdr,
+                   flags: libc::c_int) -> libc::ssize_t;
+    pub fn recvmsg(sockfd: libc::c_int, msg: *mut msghdr,
+                   flags: libc::c_int) -> libc::ssize_t;
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[derive(Copy)]
+#[repr(C)]
+pub struct ucred {
+    pub pid: libc::pid_t,
+    pub uid: libc::uid_t,
+    pub gid: libc::gid_t,
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly"))]
+extern {
+    pub fn getpeereid(socket: libc::c_int, euid: *mut libc::uid_t,
+                      egid: *mut libc::gid_t) -> libc::c_int;
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod msg {
+    use libc;
+    use mem;
+
+    pub type cmsglen_t = libc::size_t;
+
+    #[repr(C)]
+    pub struct msghdr {
+        pub msg_name: *mut libc::c_void,
+        pub msg_namelen: libc::socklen_t,
+        pub msg_iov: *mut iovec,
+        pub msg_iovlen: libc::size_t,
+        pub msg_control: *mut libc::c_void,
+        pub msg_controllen: cmsglen_t,
+        pub msg_flags: libc::c_int,
+    }
+
+    #[repr(C)]
+    pub struct cmsghdr {
+        pub cmsg_len: cmsglen_t,
+        pub cmsg_level: libc::c_int,
+        pub cmsg_type: libc::c_int,
+    }
+
+    #[repr(C)]
+    pub struct iovec {
+        pub iov_base: *mut libc::c_void,
+        pub iov_len: libc::size_t,
+    }
+
+    // CMSG_ALIGN: control messages are aligned to the size of a size_t.
+    pub fn cmsg_align(len: uint) -> uint {
+        let align = mem::size_of::<libc::size_t>();
+        (len + align - 1) & !(align - 1)
+    }
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly"))]
+mod msg {
+    use libc;
+    use mem;
+
+    pub type cmsglen_t = libc::socklen_t;
+
+    #[repr(C)]
+    pub struct msghdr {
+        pub msg_name: *mut libc::c_void,
+        pub msg_namelen: libc::socklen_t,
+        pub msg_iov: *mut iovec,
+        pub msg_iovlen: libc::c_int,
+        pub msg_control: *mut libc::c_void,
+        pub msg_controllen: cmsglen_t,
+        pub msg_flags: libc::c_int,
+    }
+
+    #[repr(C)]
+    pub struct cmsghdr {
+        pub cmsg_len: cmsglen_t,
+        pub cmsg_level: libc::c_int,
+        pub cmsg_type: libc::c_int,
+    }
+
+    #[repr(C)]
+    pub struct iovec {
+        pub iov_base: *mut libc::c_void,
+        pub iov_len: libc::size_t,
+    }
+
+    // CMSG_ALIGN: control messages are aligned to 32 bits on OSX and iOS, and
+    // to the size of a long on the BSDs.
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub fn cmsg_align(len: uint) -> uint {
+        let align = mem::size_of::<u32>();
+        (len + align - 1) & !(align - 1)
+    }
+
+    #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+    pub fn cmsg_align(len: uint) -> uint {
+        let align = mem::size_of::<libc::c_long>();
+        (len + align - 1) & !(align - 1)
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod resource {
+    use libc;
+
+    pub type rlim_t = libc::c_ulong;
+
+    #[repr(C)]
+    pub struct rlimit {
+        pub rlim_cur: rlim_t,
+        pub rlim_max: rlim_t,
+    }
+
+    pub const RLIMIT_CPU: libc::c_int = 0;
+    pub const RLIMIT_FSIZE: libc::c_int = 1;
+    pub const RLIMIT_DATA: libc::c_int = 2;
+    pub const RLIMIT_STACK: libc::c_int = 3;
+    pub const RLIMIT_CORE: libc::c_int = 4;
+    #[cfg(not(any(target_arch = "mips", target_arch = "mipsel")))]
+    pub const RLIMIT_NOFILE: libc::c_int = 7;
+    #[cfg(not(any(target_arch = "mips", target_arch = "mipsel")))]
+    pub const RLIMIT_AS: libc::c_int = 9;
+    #[cfg(any(target_arch = "mips", target_arch = "mipsel"))]
+    pub const RLIMIT_NOFILE: libc::c_int = 5;
+    #[cfg(any(target_arch = "mips", target_arch = "mipsel"))]
+    pub const RLIMIT_AS: libc::c_int = 6;
+}
+
+#[cfg(any(target_os = "macos",
+          target_os = "ios",
+          target_os = "freebsd",
+          target_os = "dragonfly"))]
+mod resource {
+    use libc;
+
+    pub type rlim_t = u64;
+
+    #[repr(C)]
+    pub struct rlimit {
+        pub rlim_cur: rlim_t,
+        pub rlim_max: rlim_t,
+    }
+
+    pub const RLIMIT_CPU: libc::c_int = 0;
+    pub const RLIMIT_FSIZE: libc::c_int = 1;
+    pub const RLIMIT_DATA: libc::c_int = 2;
+    pub const RLIMIT_STACK: libc::c_int = 3;
+    pub const RLIMIT_CORE: libc::c_int = 4;
+    pub const RLIMIT_NOFILE: libc::c_int = 8;
+    #[cfg(any(target_os = "macos", target_os = "ios"))]
+    pub const RLIMIT_AS: libc::c_int = 5;
+    #[cfg(any(target_os = "freebsd", target_os = "dragonfly"))]
+    pub const RLIMIT_AS: libc::c_int = 10;
 }
 
 #[cfg(any(target_os = "macos", target_os = "ios"))]
@@ -45,6 +45,7 @@ pub mod helper_signal;
 pub mod mutex;
 pub mod os;
 pub mod pipe;
+pub mod poll;
 pub mod process;
 pub mod rwlock;
 pub mod stack_overflow;
@@ -11,8 +11,11 @@
 use prelude::v1::*;
 
 use ffi::CString;
+use io::net::pipe::{UnixAddr, Credentials};
+use iter::repeat;
 use libc;
 use mem;
+use ptr;
 use sync::{Arc, Mutex};
 use sync::atomic::{AtomicBool, Ordering};
 use io::{self, IoResult, IoError};
@@ -21,7 +24,7 @@ use sys::{self, timer, retry, c, set_nonblocking, wouldblock};
 use sys::fs::{fd_t, FileDesc};
 use sys_common::net::*;
 use sys_common::net::SocketStatus::*;
-use sys_common::{eof, mkerr_libc};
+use sys_common::{eof, mkerr_libc, short_write};
 
 fn unix_socket(ty: libc::c_int) -> IoResult<fd_t> {
     match unsafe { libc::socket(libc::AF_UNIX, ty, 0) } {
@@ -30,7 +33,15 @@ fn unix_socket(ty: libc::c_int) -> IoResult<fd_t> {
     }
 }
 
-fn addr_to_sockaddr_un(addr: &CString,
+// The name of a socket: either a path in the filesystem, or on Linux a name in
+// the abstract namespace, which is not visible in the filesystem.
+#[derive(Copy)]
+enum Addr<'a> {
+    Path(&'a CString),
+    Abstract(&'a [u8]),
+}
+
+fn addr_to_sockaddr_un(addr: Addr,
                        storage: &mut libc::sockaddr_storage)
                        -> IoResult<libc::socklen_t> {
     // the sun_path length is limited to SUN_LEN (with null)
@@ -38,7 +49,10 @@ fn addr_to_sockaddr_un(addr: &CString,
             mem::size_of::<libc::sockaddr_un>());
     let s = unsafe { &mut *(storage as *mut _ as *mut libc::sockaddr_un) };
 
-    let len = addr.len();
+    let len = match addr {
+        Addr::Path(path) => path.len(),
+        Addr::Abstract(name) => name.len(),
+    };
     if len > s.sun_path.len() - 1 {
         return Err(IoError {
             kind: io::InvalidInput,
@@ -47,15 +61,43 @@ fn addr_to_sockaddr_un(addr: &CString,
         })
     }
     s.sun_family = libc::AF_UNIX as libc::sa_family_t;
-    for (slot, value) in s.sun_path.iter_mut().zip(addr.iter()) {
-        *slot = *value;
+    match addr {
+        Addr::Path(path) => {
+            for (slot, value) in s.sun_path.iter_mut().zip(path.iter()) {
+                *slot = *value;
+            }
+        }
+        // abstract names start with a null byte and are not null terminated,
+        // they extend to the length of the address instead
+        Addr::Abstract(name) => {
+            for (slot, value) in s.sun_path.iter_mut().skip(1).zip(name.iter()) {
+                *slot = *value as libc::c_char;
+            }
+        }
     }
 
-    // count the null terminator
+    // count the null terminator, or the leading null byte
     let len = mem::size_of::<libc::sa_family_t>() + len + 1;
     return Ok(len as libc::socklen_t);
 }
 
+fn sockaddr_un_to_addr(storage: &libc::sockaddr_storage,
+                       len: libc::socklen_t) -> UnixAddr {
+    let s = unsafe { &*(storage as *const _ as *const libc::sockaddr_un) };
+    let offset = s.sun_path.as_ptr() as uint - s as *const _ as uint;
+    let len = if len as uint > offset { len as uint - offset } else { 0 };
+    let path: Vec<u8> = s.sun_path.iter().take(len).map(|&b| b as u8).collect();
+
+    match path.first() {
+        None => UnixAddr::Unnamed,
+        Some(&0) => UnixAddr::Abstract(path.slice_from(1).to_vec()),
+        Some(..) => {
+            let end = path.iter().position(|&b| b == 0).unwrap_or(path.len());
+            UnixAddr::Path(Path::new(path.slice_to(end)))
+        }
+    }
+}
+
 struct Inner {
     fd: fd_t,
 
@@ -74,28 +116,30 @@ impl Drop for Inner {
     fn drop(&mut self) { unsafe { let _ = libc::close(self.fd); } }
 }
 
-fn connect(addr: &CString, ty: libc::c_int,
+fn connect(addr: Addr, ty: libc::c_int,
            timeout: Option<u64>) -> IoResult<Inner> {
+    let inner = Inner::new(try!(unix_socket(ty)));
+    try!(connect_fd(inner.fd, addr, timeout));
+    Ok(inner)
+}
+
+fn connect_fd(fd: fd_t, addr: Addr, timeout: Option<u64>) -> IoResult<()> {
     let mut storage = unsafe { mem::zeroed() };
     let len = try!(addr_to_sockaddr_un(addr, &mut storage));
-    let inner = Inner::new(try!(unix_socket(ty)));
     let addrp = &storage as *const _ as *const libc::sockaddr;
 
     match timeout {
         None => {
-            match retry(|| unsafe { libc::connect(inner.fd, addrp, len) }) {
+            match retry(|| unsafe { libc::connect(fd, addrp, len) }) {
                 -1 => Err(super::last_error()),
-                _  => Ok(inner)
+                _  => Ok(())
             }
         }
-        Some(timeout_ms) => {
-            try!(connect_timeout(inner.fd, addrp, len, timeout_ms));
-            Ok(inner)
-        }
+        Some(timeout_ms) => connect_timeout(fd, addrp, len, timeout_ms),
     }
 }
 
-fn bind(addr: &CString, ty: libc::c_int) -> IoResult<Inner> {
+fn bind(addr: Addr, ty: libc::c_int) -> IoResult<Inner> {
     let mut storage = unsafe { mem::zeroed() };
     let len = try!(addr_to_sockaddr_un(addr, &mut storage));
     let inner = Inner::new(try!(unix_socket(ty)));
@@ -108,6 +152,194 @@ fn bind(addr: &CString, ty: libc::c_int) -> IoResult<Inner> {
     }
 }
 
+fn socket_name(fd: fd_t) -> IoResult<UnixAddr> {
+    let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+    let mut len = mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+    match unsafe {
+        libc::getsockname(fd, &mut storage as *mut _ as *mut libc::sockaddr,
+                          &mut len)
+    } {
+        -1 => Err(super::last_error()),
+        _ => Ok(sockaddr_un_to_addr(&storage, len)),
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn peer_credentials(fd: fd_t) -> IoResult<Credentials> {
+    let cred: c::ucred = try!(getsockopt(fd, libc::SOL_SOCKET, c::SO_PEERCRED));
+    Ok(Credentials { pid: Some(cred.pid), uid: cred.uid, gid: cred.gid })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn peer_credentials(fd: fd_t) -> IoResult<Credentials> {
+    let mut uid = 0;
+    let mut gid = 0;
+    match unsafe { c::getpeereid(fd, &mut uid, &mut gid) } {
+        -1 => Err(super::last_error()),
+        _ => Ok(Credentials { pid: None, uid: uid, gid: gid }),
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// File descriptor passing
+////////////////////////////////////////////////////////////////////////////////
+
+// The most descriptors accepted in a single message, SCM_MAX_FD on Linux.
+const MAX_FDS: uint = 253;
+
+// The size of a control message carrying `len` bytes of data (CMSG_SPACE), and
+// the offset of that data from the start of the message (CMSG_DATA).
+fn cmsg_space(len: uint) -> uint {
+    c::cmsg_align(mem::size_of::<c::cmsghdr>()) + c::cmsg_align(len)
+}
+
+fn cmsg_data_offset() -> uint {
+    c::cmsg_align(mem::size_of::<c::cmsghdr>())
+}
+
+// A zeroed buffer of at least `len` bytes for control messages, allocated as
+// words to satisfy the alignment of cmsghdr.
+fn cmsg_buffer(len: uint) -> Vec<libc::size_t> {
+    let word = mem::size_of::<libc::size_t>();
+    repeat(0).take((len + word - 1) / word).collect()
+}
+
+// Sends `buf` along with the descriptors in `fds` as SCM_RIGHTS ancillary
+// data, to `dst` if the socket is not connected. The descriptors are only sent
+// with the first byte of data, so the write is never retried for the
+// remainder of `buf`.
+fn send_fds<T, L>(fd: fd_t, deadline: u64, nonblocking: bool, lock: L,
+                  buf: &[u8], fds: &[fd_t],
+                  dst: Option<(&libc::sockaddr_storage, libc::socklen_t)>)
+                  -> IoResult<uint> where
+    L: FnMut() -> T,
+{
+    if fds.len() > MAX_FDS {
+        return Err(IoError {
+            kind: io::InvalidInput,
+            desc: "too many file descriptors in one message",
+            detail: None,
+        })
+    }
+    let data_len = fds.len() * mem::size_of::<fd_t>();
+    let control_len = if fds.is_empty() { 0 } else { cmsg_space(data_len) };
+    let mut control = cmsg_buffer(control_len);
+    if !fds.is_empty() {
+        unsafe {
+            let cmsg = control.as_mut_ptr() as *mut c::cmsghdr;
+            (*cmsg).cmsg_len = (cmsg_data_offset() + data_len) as c::cmsglen_t;
+            (*cmsg).cmsg_level = libc::SOL_SOCKET;
+            (*cmsg).cmsg_type = c::SCM_RIGHTS;
+            let data = (cmsg as *mut u8).offset(cmsg_data_offset() as int);
+            ptr::copy_nonoverlapping_memory(data as *mut fd_t, fds.as_ptr(),
+                                            fds.len());
+        }
+    }
+
+    let (name, namelen) = match dst {
+        Some((storage, len)) => (storage as *const _ as *mut libc::c_void, len),
+        None => (ptr::null_mut(), 0),
+    };
+    let controlp = control.as_mut_ptr() as *mut libc::c_void;
+    let dowrite = |&mut: nb: bool, buf: *const u8, len: uint| unsafe {
+        let mut iov = c::iovec {
+            iov_base: buf as *mut libc::c_void,
+            iov_len: len as libc::size_t,
+        };
+        let mut msg: c::msghdr = mem::zeroed();
+        msg.msg_name = name;
+        msg.msg_namelen = namelen;
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        if control_len > 0 {
+            msg.msg_control = controlp;
+            msg.msg_controllen = control_len as c::cmsglen_t;
+        }
+        let flags = if nb {c::MSG_DONTWAIT} else {0};
+        c::sendmsg(fd, &msg, flags) as i64
+    };
+
+    if nonblocking {
+        write_nonblocking(buf, lock, dowrite).map(|()| buf.len())
+    } else {
+        write(fd, deadline, buf, false, lock, dowrite)
+    }
+}
+
+// Receives data into `buf` along with any descriptors sent with it. The
+// received descriptors are owned by the caller and are marked close-on-exec.
+fn recv_fds<T, L>(fd: fd_t, deadline: u64, nonblocking: bool, lock: L,
+                  buf: &mut [u8]) -> IoResult<(uint, Vec<fd_t>)> where
+    L: FnMut() -> T,
+{
+    let control_len = cmsg_space(MAX_FDS * mem::size_of::<fd_t>());
+    let mut control = cmsg_buffer(control_len);
+    let controlp = control.as_mut_ptr() as *mut libc::c_void;
+    let mut received_len = 0;
+    let mut msg_flags = 0;
+    let n = {
+        let doread = |&mut: nb: bool| unsafe {
+            let mut iov = c::iovec {
+                iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+                iov_len: buf.len() as libc::size_t,
+            };
+            let mut msg: c::msghdr = mem::zeroed();
+            msg.msg_iov = &mut iov;
+            msg.msg_iovlen = 1;
+            msg.msg_control = controlp;
+            msg.msg_controllen = control_len as c::cmsglen_t;
+            let flags = if nb {c::MSG_DONTWAIT} else {0};
+            let ret = c::recvmsg(fd, &mut msg, flags | c::MSG_CMSG_CLOEXEC);
+            received_len = msg.msg_controllen as uint;
+            msg_flags = msg.msg_flags;
+            ret as libc::c_int
+        };
+        if nonblocking {
+            try!(read_nonblocking(lock, doread))
+        } else {
+            try!(read(fd, deadline, lock, doread))
+        }
+    };
+
+    let mut fds = Vec::new();
+    let base = control.as_ptr() as *const u8;
+    let header = cmsg_data_offset();
+    let mut offset = 0;
+    while offset + header <= received_len {
+        unsafe {
+            let cmsg = base.offset(offset as int) as *const c::cmsghdr;
+            let len = (*cmsg).cmsg_len as uint;
+            if len < header || offset + len > received_len { break }
+            if (*cmsg).cmsg_level == libc::SOL_SOCKET &&
+               (*cmsg).cmsg_type == c::SCM_RIGHTS {
+                let data = base.offset((offset + header) as int) as *const fd_t;
+                for i in range(0, (len - header) / mem::size_of::<fd_t>()) {
+                    let fd = *data.offset(i as int);
+                    if c::MSG_CMSG_CLOEXEC == 0 {
+                        let _ = c::ioctl(fd, c::FIOCLEX);
+                    }
+                    fds.push(fd);
+                }
+            }
+            offset += c::cmsg_align(len);
+        }
+    }
+    // Some of the descriptors sent didn't fit, and were closed by the kernel.
+    // Rather than hand back part of the list as if it were all of it, close
+    // the ones received and report the loss.
+    if msg_flags & c::MSG_CTRUNC != 0 {
+        for &fd in fds.iter() {
+            unsafe { let _ = libc::close(fd); }
+        }
+        return Err(IoError {
+            kind: io::OtherIoError,
+            desc: "file descriptors were truncated",
+            detail: None,
+        })
+    }
+    Ok((n, fds))
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 // Unix Streams
 ////////////////////////////////////////////////////////////////////////////////
@@ -121,7 +353,14 @@ pub struct UnixStream {
 impl UnixStream {
     pub fn connect(addr: &CString,
                    timeout: Option<u64>) -> IoResult<UnixStream> {
-        connect(addr, libc::SOCK_STREAM, timeout).map(|inner| {
+        connect(Addr::Path(addr), libc::SOCK_STREAM, timeout).map(|inner| {
+            UnixStream::new(Arc::new(inner))
+        })
+    }
+
+    pub fn connect_abstract(name: &[u8],
+                            timeout: Option<u64>) -> IoResult<UnixStream> {
+        connect(Addr::Abstract(name), libc::SOCK_STREAM, timeout).map(|inner| {
             UnixStream::new(Arc::new(inner))
         })
     }
@@ -178,6 +417,22 @@ impl UnixStream {
         }
     }
 
+    pub fn send_fds(&mut self, buf: &[u8], fds: &[fd_t]) -> IoResult<uint> {
+        let fd = self.fd();
+        let dolock = |&:| self.lock_nonblocking();
+        send_fds(fd, self.write_deadline, false, dolock, buf, fds, None)
+    }
+
+    pub fn recv_fds(&mut self, buf: &mut [u8]) -> IoResult<(uint, Vec<fd_t>)> {
+        let fd = self.fd();
+        let dolock = |&:| self.lock_nonblocking();
+        recv_fds(fd, self.read_deadline, false, dolock, buf)
+    }
+
+    pub fn peer_credentials(&self) -> IoResult<Credentials> {
+        peer_credentials(self.fd())
+    }
+
     pub fn close_write(&mut self) -> IoResult<()> {
         mkerr_libc(unsafe { libc::shutdown(self.fd(), libc::SHUT_WR) })
     }
@@ -213,7 +468,7 @@ impl Clone for UnixStream {
 
 pub struct UnixListener {
     inner: Inner,
-    path: CString,
+    path: Option<CString>,
 }
 
 // we currently own the CString, so these impls should be safe
@@ -222,8 +477,14 @@ unsafe impl Sync for UnixListener {}
 
 impl UnixListener {
     pub fn bind(addr: &CString) -> IoResult<UnixListener> {
-        bind(addr, libc::SOCK_STREAM).map(|fd| {
-            UnixListener { inner: fd, path: addr.clone() }
+        bind(Addr::Path(addr), libc::SOCK_STREAM).map(|fd| {
+            UnixListener { inner: fd, path: Some(addr.clone()) }
+        })
+    }
+
+    pub fn bind_abstract(name: &[u8]) -> IoResult<UnixListener> {
+        bind(Addr::Abstract(name), libc::SOCK_STREAM).map(|fd| {
+            UnixListener { inner: fd, path: None }
         })
     }
 
@@ -318,9 +579,265 @@ impl Drop for UnixListener {
     fn drop(&mut self) {
         // Unlink the path to the socket to ensure that it doesn't linger. We're
         // careful to unlink the path before we close the file descriptor to
-        // prevent races where we unlink someone else's path.
-        unsafe {
-            let _ = libc::unlink(self.path.as_ptr());
+        // prevent races where we unlink someone else's path. Sockets in the
+        // abstract namespace vanish along with their file descriptor.
+        if let Some(ref path) = self.path {
+            unsafe {
+                let _ = libc::unlink(path.as_ptr());
+            }
+        }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Unix Datagrams
+////////////////////////////////////////////////////////////////////////////////
+
+struct DatagramInner {
+    inner: Inner,
+    path: Option<CString>,
+}
+
+// we currently own the CString, so these impls should be safe
+unsafe impl Send for DatagramInner {}
+unsafe impl Sync for DatagramInner {}
+
+impl Drop for DatagramInner {
+    fn drop(&mut self) {
+        // As with listeners, unlink the path before closing the descriptor.
+        if let Some(ref path) = self.path {
+            unsafe {
+                let _ = libc::unlink(path.as_ptr());
+            }
+        }
+    }
+}
+
+pub struct UnixDatagram {
+    inner: Arc<DatagramInner>,
+    read_deadline: u64,
+    write_deadline: u64,
+    nonblocking: bool,
+}
+
+impl UnixDatagram {
+    pub fn bind(addr: &CString) -> IoResult<UnixDatagram> {
+        bind(Addr::Path(addr), libc::SOCK_DGRAM).map(|inner| {
+            UnixDatagram::new(inner, Some(addr.clone()))
+        })
+    }
+
+    pub fn bind_abstract(name: &[u8]) -> IoResult<UnixDatagram> {
+        bind(Addr::Abstract(name), libc::SOCK_DGRAM).map(|inner| {
+            UnixDatagram::new(inner, None)
+        })
+    }
+
+    pub fn unbound() -> IoResult<UnixDatagram> {
+        unix_socket(libc::SOCK_DGRAM).map(|fd| {
+            UnixDatagram::new(Inner::new(fd), None)
+        })
+    }
+
+    pub fn pair() -> IoResult<(UnixDatagram, UnixDatagram)> {
+        let mut fds = [0; 2];
+        match unsafe {
+            c::socketpair(libc::AF_UNIX, libc::SOCK_DGRAM, 0, fds.as_mut_ptr())
+        } {
+            -1 => Err(super::last_error()),
+            _ => Ok((UnixDatagram::new(Inner::new(fds[0]), None),
+                     UnixDatagram::new(Inner::new(fds[1]), None))),
+        }
+    }
+
+    fn new(inner: Inner, path: Option<CString>) -> UnixDatagram {
+        UnixDatagram {
+            inner: Arc::new(DatagramInner { inner: inner, path: path }),
+            read_deadline: 0,
+            write_deadline: 0,
+            nonblocking: false,
+        }
+    }
+
+    pub fn fd(&self) -> fd_t { self.inner.inner.fd }
+
+    #[cfg(target_os = "linux")]
+    fn lock_nonblocking(&self) {}
+
+    #[cfg(not(target_os = "linux"))]
+    fn lock_nonblocking<'a>(&'a self) -> Guard<'a> {
+        let ret = Guard {
+            fd: self.fd(),
+            guard: unsafe { self.inner.inner.lock.lock().unwrap() },
+        };
+        assert!(set_nonblocking(self.fd(), true).is_ok());
+        ret
+    }
+
+    pub fn connect(&mut self, addr: &CString) -> IoResult<()> {
+        connect_fd(self.fd(), Addr::Path(addr), None)
+    }
+
+    pub fn connect_abstract(&mut self, name: &[u8]) -> IoResult<()> {
+        connect_fd(self.fd(), Addr::Abstract(name), None)
+    }
+
+    pub fn socket_name(&mut self) -> IoResult<UnixAddr> {
+        socket_name(self.fd())
+    }
+
+    pub fn recv_from(&mut self, buf: &mut [u8]) -> IoResult<(uint, UnixAddr)> {
+        let fd = self.fd();
+        let mut storage: libc::sockaddr_storage = unsafe { mem::zeroed() };
+        let storagep = &mut storage as *mut _ as *mut libc::sockaddr;
+        let mut addrlen: libc::socklen_t =
+                mem::size_of::<libc::sockaddr_storage>() as libc::socklen_t;
+
+        let nonblocking = self.nonblocking;
+        let deadline = self.read_deadline;
+        let n = {
+            let dolock = |&:| self.lock_nonblocking();
+            let doread = |&mut: nb| unsafe {
+                let flags = if nb {c::MSG_DONTWAIT} else {0};
+                libc::recvfrom(fd,
+                               buf.as_mut_ptr() as *mut libc::c_void,
+                               buf.len() as libc::size_t,
+                               flags,
+                               storagep,
+                               &mut addrlen) as libc::c_int
+            };
+            if nonblocking {
+                try!(read_nonblocking(dolock, doread))
+            } else {
+                try!(read(fd, deadline, dolock, doread))
+            }
+        };
+        Ok((n, sockaddr_un_to_addr(&storage, addrlen)))
+    }
+
+    pub fn recv(&mut self, buf: &mut [u8]) -> IoResult<uint> {
+        let fd = self.fd();
+        let dolock = |&:| self.lock_nonblocking();
+        let doread = |&mut: nb| unsafe {
+            let flags = if nb {c::MSG_DONTWAIT} else {0};
+            libc::recv(fd,
+                       buf.as_mut_ptr() as *mut libc::c_void,
+                       buf.len() as libc::size_t,
+                       flags) as libc::c_int
+        };
+        if self.nonblocking {
+            read_nonblocking(dolock, doread)
+        } else {
+            read(fd, self.read_deadline, dolock, doread)
+        }
+    }
+
+    pub fn send_to(&mut self, buf: &[u8], dst: &CString) -> IoResult<()> {
+        self.send_to_addr(buf, Addr::Path(dst))
+    }
+
+    pub fn send_to_abstract(&mut self, buf: &[u8], name: &[u8]) -> IoResult<()> {
+        self.send_to_addr(buf, Addr::Abstract(name))
+    }
+
+    fn send_to_addr(&mut self, buf: &[u8], dst: Addr) -> IoResult<()> {
+        let mut storage = unsafe { mem::zeroed() };
+        let dstlen = try!(addr_to_sockaddr_un(dst, &mut storage));
+        let dstp = &storage as *const _ as *const libc::sockaddr;
+
+        let fd = self.fd();
+        let dolock = |&: | self.lock_nonblocking();
+        let dowrite = |&mut: nb, buf: *const u8, len: uint| unsafe {
+            let flags = if nb {c::MSG_DONTWAIT} else {0};
+            libc::sendto(fd,
+                         buf as *const libc::c_void,
+                         len as libc::size_t,
+                         flags,
+                         dstp,
+                         dstlen) as i64
+        };
+        self.finish_send(buf, dolock, dowrite)
+    }
+
+    pub fn send(&mut self, buf: &[u8]) -> IoResult<()> {
+        let fd = self.fd();
+        let dolock = |&: | self.lock_nonblocking();
+        let dowrite = |&mut: nb, buf: *const u8, len: uint| unsafe {
+            let flags = if nb {c::MSG_DONTWAIT} else {0};
+            libc::send(fd,
+                       buf as *const libc::c_void,
+                       len as libc::size_t,
+                       flags) as i64
+        };
+        self.finish_send(buf, dolock, dowrite)
+    }
+
+    // Datagrams are sent whole or not at all, so a short write is an error.
+    fn finish_send<T, L, W>(&self, buf: &[u8], lock: L,
+                            write_fn: W) -> IoResult<()> where
+        L: FnMut() -> T,
+        W: FnMut(bool, *const u8, uint) -> i64,
+    {
+        if self.nonblocking {
+            return write_nonblocking(buf, lock, write_fn);
+        }
+
+        let n = try!(write(self.fd(), self.write_deadline, buf, false, lock, write_fn));
+        if n != buf.len() {
+            Err(short_write(n, "couldn't send entire packet at once"))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn send_fds(&mut self, buf: &[u8], fds: &[fd_t]) -> IoResult<()> {
+        let fd = self.fd();
+        let dolock = |&:| self.lock_nonblocking();
+        let n = try!(send_fds(fd, self.write_deadline, self.nonblocking,
+                              dolock, buf, fds, None));
+        if n != buf.len() {
+            Err(short_write(n, "couldn't send entire packet at once"))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub fn recv_fds(&mut self, buf: &mut [u8]) -> IoResult<(uint, Vec<fd_t>)> {
+        let fd = self.fd();
+        let dolock = |&:| self.lock_nonblocking();
+        recv_fds(fd, self.read_deadline, self.nonblocking, dolock, buf)
+    }
+
+    pub fn peer_credentials(&self) -> IoResult<Credentials> {
+        peer_credentials(self.fd())
+    }
+
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.nonblocking = nonblocking;
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<u64>) {
+        let deadline = timeout.map(|a| timer::now() + a).unwrap_or(0);
+        self.read_deadline = deadline;
+        self.write_deadline = deadline;
+    }
+
+    pub fn set_read_timeout(&mut self, timeout: Option<u64>) {
+        self.read_deadline = timeout.map(|a| timer::now() + a).unwrap_or(0);
+    }
+
+    pub fn set_write_timeout(&mut self, timeout: Option<u64>) {
+        self.write_deadline = timeout.map(|a| timer::now() + a).unwrap_or(0);
+    }
+}
+
+impl Clone for UnixDatagram {
+    fn clone(&self) -> UnixDatagram {
+        UnixDatagram {
+            inner: self.inner.clone(),
+            read_deadline: 0,
+            write_deadline: 0,
+            nonblocking: false,
         }
     }
 }
@@ -0,0 +1,275 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Readiness polling, backed by epoll on Linux and by poll(2) elsewhere.
+
+#![allow(non_camel_case_types)]
+
+use prelude::v1::*;
+
+use cmp;
+use i32;
+use libc::{self, c_int};
+use os;
+
+pub use self::imp::Poller;
+
+// Converts a timeout into the argument expected by epoll_wait() and poll().
+fn timeout_arg(timeout_ms: Option<u64>) -> c_int {
+    match timeout_ms {
+        None => -1,
+        Some(ms) => cmp::min(ms, i32::MAX as u64) as c_int,
+    }
+}
+
+// Both epoll_wait() and poll() are interrupted by signals regardless of
+// SA_RESTART, in which case no events are reported rather than restarting the
+// wait with the full timeout.
+fn interrupted() -> bool {
+    os::errno() == libc::EINTR as uint
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod imp {
+    use prelude::v1::*;
+
+    use io::poll::{Event, Interest, READABLE, WRITABLE};
+    use io::IoResult;
+    use libc::c_int;
+    use super::{timeout_arg, interrupted};
+    use sys::fs::FileDesc;
+    use sys::{last_error, sock_t};
+
+    const EPOLL_CLOEXEC: c_int = 0o2000000;
+
+    const EPOLL_CTL_ADD: c_int = 1;
+    const EPOLL_CTL_DEL: c_int = 2;
+    const EPOLL_CTL_MOD: c_int = 3;
+
+    const EPOLLIN: u32 = 0x001;
+    const EPOLLOUT: u32 = 0x004;
+    const EPOLLERR: u32 = 0x008;
+    const EPOLLHUP: u32 = 0x010;
+    const EPOLLRDHUP: u32 = 0x2000;
+
+    // The maximum number of events retrieved by a single epoll_wait().
+    const MAX_EVENTS: uint = 1024;
+
+    // The kernel's definition of this structure is packed on x86_64 only.
+    #[cfg(target_arch = "x86_64")]
+    #[repr(C, packed)]
+    struct epoll_event {
+        events: u32,
+        data: u64,
+    }
+
+    #[cfg(not(target_arch = "x86_64"))]
+    #[repr(C)]
+    struct epoll_event {
+        events: u32,
+        data: u64,
+    }
+
+    extern {
+        fn epoll_create1(flags: c_int) -> c_int;
+        fn epoll_ctl(epfd: c_int, op: c_int, fd: c_int,
+                     event: *mut epoll_event) -> c_int;
+        fn epoll_wait(epfd: c_int, events: *mut epoll_event,
+                      maxevents: c_int, timeout: c_int) -> c_int;
+    }
+
+    pub struct Poller {
+        epfd: FileDesc,
+        events: Vec<epoll_event>,
+    }
+
+    impl Poller {
+        pub fn new() -> IoResult<Poller> {
+            match unsafe { epoll_create1(EPOLL_CLOEXEC) } {
+                -1 => Err(last_error()),
+                fd => Ok(Poller {
+                    epfd: FileDesc::new(fd, true),
+                    events: Vec::with_capacity(MAX_EVENTS),
+                }),
+            }
+        }
+
+        pub fn register(&mut self, fd: sock_t, token: uint,
+                        interest: Interest) -> IoResult<()> {
+            self.ctl(EPOLL_CTL_ADD, fd, token, interest)
+        }
+
+        pub fn reregister(&mut self, fd: sock_t, token: uint,
+                          interest: Interest) -> IoResult<()> {
+            self.ctl(EPOLL_CTL_MOD, fd, token, interest)
+        }
+
+        pub fn deregister(&mut self, fd: sock_t) -> IoResult<()> {
+            // A non-null event is required by kernels before 2.6.9.
+            let mut event = epoll_event { events: 0, data: 0 };
+            match unsafe { epoll_ctl(self.epfd.fd(), EPOLL_CTL_DEL, fd, &mut event) } {
+                -1 => Err(last_error()),
+                _ => Ok(()),
+            }
+        }
+
+        pub fn poll(&mut self, events: &mut Vec<Event>,
+                    timeout_ms: Option<u64>) -> IoResult<()> {
+            let n = unsafe {
+                epoll_wait(self.epfd.fd(), self.events.as_mut_ptr(),
+                           MAX_EVENTS as c_int, timeout_arg(timeout_ms))
+            };
+            if n == -1 {
+                return if interrupted() { Ok(()) } else { Err(last_error()) }
+            }
+            unsafe { self.events.set_len(n as uint); }
+
+            for e in self.events.iter() {
+                let bits = e.events;
+                events.push(Event {
+                    token: e.data as uint,
+                    readable: bits & EPOLLIN != 0,
+                    writable: bits & EPOLLOUT != 0,
+                    hangup: bits & (EPOLLHUP | EPOLLRDHUP) != 0,
+                    error: bits & EPOLLERR != 0,
+                });
+            }
+            unsafe { self.events.set_len(0); }
+            Ok(())
+        }
+
+        fn ctl(&mut self, op: c_int, fd: sock_t, token: uint,
+               interest: Interest) -> IoResult<()> {
+            let mut bits = EPOLLRDHUP;
+            if interest.contains(READABLE) { bits |= EPOLLIN; }
+            if interest.contains(WRITABLE) { bits |= EPOLLOUT; }
+            let mut event = epoll_event { events: bits, data: token as u64 };
+            match unsafe { epoll_ctl(self.epfd.fd(), op, fd, &mut event) } {
+                -1 => Err(last_error()),
+                _ => Ok(()),
+            }
+        }
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+mod imp {
+    use prelude::v1::*;
+
+    use io::poll::{Event, Interest, READABLE, WRITABLE};
+    use io::{self, IoResult, IoError};
+    use libc::{c_int, c_short, c_uint};
+    use super::{timeout_arg, interrupted};
+    use sys::{last_error, sock_t};
+
+    const POLLIN: c_short = 0x001;
+    const POLLOUT: c_short = 0x004;
+    const POLLERR: c_short = 0x008;
+    const POLLHUP: c_short = 0x010;
+
+    #[repr(C)]
+    struct pollfd {
+        fd: c_int,
+        events: c_short,
+        revents: c_short,
+    }
+
+    extern {
+        fn poll(fds: *mut pollfd, nfds: c_uint, timeout: c_int) -> c_int;
+    }
+
+    // poll(2) keeps no state in the kernel, so the registered descriptors are
+    // kept here, along with their tokens at the same indices.
+    pub struct Poller {
+        fds: Vec<pollfd>,
+        tokens: Vec<uint>,
+    }
+
+    impl Poller {
+        pub fn new() -> IoResult<Poller> {
+            Ok(Poller { fds: Vec::new(), tokens: Vec::new() })
+        }
+
+        pub fn register(&mut self, fd: sock_t, token: uint,
+                        interest: Interest) -> IoResult<()> {
+            if self.position(fd).is_some() {
+                return Err(IoError {
+                    kind: io::PathAlreadyExists,
+                    desc: "file descriptor already registered",
+                    detail: None,
+                })
+            }
+            self.fds.push(pollfd { fd: fd, events: events(interest), revents: 0 });
+            self.tokens.push(token);
+            Ok(())
+        }
+
+        pub fn reregister(&mut self, fd: sock_t, token: uint,
+                          interest: Interest) -> IoResult<()> {
+            let i = try!(self.registered(fd));
+            self.fds[i].events = events(interest);
+            self.tokens[i] = token;
+            Ok(())
+        }
+
+        pub fn deregister(&mut self, fd: sock_t) -> IoResult<()> {
+            let i = try!(self.registered(fd));
+            self.fds.swap_remove(i);
+            self.tokens.swap_remove(i);
+            Ok(())
+        }
+
+        pub fn poll(&mut self, events: &mut Vec<Event>,
+                    timeout_ms: Option<u64>) -> IoResult<()> {
+            let n = unsafe {
+                poll(self.fds.as_mut_ptr(), self.fds.len() as c_uint,
+                     timeout_arg(timeout_ms))
+            };
+            if n == -1 {
+                return if interrupted() { Ok(()) } else { Err(last_error()) }
+            }
+
+            for (pfd, &token) in self.fds.iter().zip(self.tokens.iter()) {
+                let bits = pfd.revents;
+                if bits == 0 { continue }
+                events.push(Event {
+                    token: token,
+                    readable: bits & POLLIN != 0,
+                    writable: bits & POLLOUT != 0,
+                    hangup: bits & POLLHUP != 0,
+                    error: bits & POLLERR != 0,
+                });
+            }
+            Ok(())
+        }
+
+        fn position(&self, fd: sock_t) -> Option<uint> {
+            self.fds.iter().position(|pfd| pfd.fd == fd)
+        }
+
+        fn registered(&self, fd: sock_t) -> IoResult<uint> {
+            match self.position(fd) {
+                Some(i) => Ok(i),
+                None => Err(IoError {
+                    kind: io::FileNotFound,
+                    desc: "file descriptor not registered",
+                    detail: None,
+                }),
+            }
+        }
+    }
+
+    fn events(interest: Interest) -> c_short {
+        let mut bits = 0;
+        if interest.contains(READABLE) { bits |= POLLIN; }
+        if interest.contains(WRITABLE) { bits |= POLLOUT; }
+        bits
+    }
+}
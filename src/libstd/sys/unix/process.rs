@@ -14,7 +14,7 @@ use self::Req::*;
 use collections;
 use ffi::CString;
 use hash::Hash;
-use io::process::{ProcessExit, ExitStatus, ExitSignal};
+use io::process::{ProcessExit, ExitStatus, ExitSignal, Resource};
 use io::{self, IoResult, IoError, EndOfFile};
 use libc::{self, pid_t, c_void, c_int};
 use mem;
@@ -62,7 +62,7 @@ impl Process {
         where C: ProcessConfig<K, V>, P: AsInner<FileDesc>,
               K: BytesContainer + Eq + Hash, V: BytesContainer
     {
-        use libc::funcs::posix88::unistd::{fork, dup2, close, chdir, execvp};
+        use libc::funcs::posix88::unistd::{fork, dup2, close, chdir, execvp, setpgid};
         use libc::funcs::bsd44::getdtablesize;
 
         mod rustrt {
@@ -222,10 +222,46 @@ impl Process {
                 if !setup(out_fd, libc::STDOUT_FILENO) { fail(&mut output) }
                 if !setup(err_fd, libc::STDERR_FILENO) { fail(&mut output) }
 
-                // close all other fds
+                // close all other fds, except for those explicitly requested to
+                // be inherited, which additionally must survive the exec
+                let keep = cfg.keep_fds();
                 for fd in range(3, getdtablesize()).rev() {
-                    if fd != output.fd() {
-                        let _ = close(fd as c_int);
+                    let fd = fd as c_int;
+                    if fd != output.fd() && !keep.contains(&fd) {
+                        let _ = close(fd);
+                    }
+                }
+                for &fd in keep.iter() {
+                    if c::ioctl(fd, c::FIONCLEX) != 0 {
+                        fail(&mut output);
+                    }
+                }
+
+                if cfg.detach() {
+                    // Don't check the error of setsid because it fails if we're the
+                    // process leader already. We just forked so it shouldn't return
+                    // error, but ignore it anyway.
+                    let _ = libc::setsid();
+                } else {
+                    match cfg.process_group() {
+                        Some(pgid) => {
+                            if setpgid(0, pgid) != 0 {
+                                fail(&mut output);
+                            }
+                        }
+                        None => {}
+                    }
+                }
+
+                // Resource limits are applied before dropping privileges so that
+                // hard limits may still be raised when spawning as root.
+                for &(resource, soft, hard) in cfg.rlimits().iter() {
+                    let limit = c::rlimit {
+                        rlim_cur: soft as c::rlim_t,
+                        rlim_max: hard as c::rlim_t,
+                    };
+                    if c::setrlimit(rlimit_resource(resource), &limit) != 0 {
+                        fail(&mut output);
                     }
                 }
 
@@ -258,18 +294,21 @@ impl Process {
                     }
                     None => {}
                 }
-                if cfg.detach() {
-                    // Don't check the error of setsid because it fails if we're the
-                    // process leader already. We just forked so it shouldn't return
-                    // error, but ignore it anyway.
-                    let _ = libc::setsid();
-                }
                 if !dirp.is_null() && chdir(dirp) == -1 {
                     fail(&mut output);
                 }
                 if !envp.is_null() {
                     set_environ(envp);
                 }
+
+                // The hooks are run last so they observe the child exactly as
+                // it will be exec'd. A failing hook is reported to the parent
+                // through whatever errno it left behind.
+                for hook in cfg.before_exec().iter() {
+                    if (**hook)().is_err() {
+                        fail(&mut output);
+                    }
+                }
                 let _ = execvp(*argv, argv as *mut _);
                 fail(&mut output);
             })
@@ -532,6 +571,18 @@ impl Process {
     }
 }
 
+fn rlimit_resource(resource: Resource) -> c_int {
+    match resource {
+        Resource::CpuTime => c::RLIMIT_CPU,
+        Resource::FileSize => c::RLIMIT_FSIZE,
+        Resource::DataSize => c::RLIMIT_DATA,
+        Resource::StackSize => c::RLIMIT_STACK,
+        Resource::CoreSize => c::RLIMIT_CORE,
+        Resource::OpenFiles => c::RLIMIT_NOFILE,
+        Resource::AddressSpace => c::RLIMIT_AS,
+    }
+}
+
 fn with_argv<T,F>(prog: &CString, args: &[CString],
                   cb: F)
                   -> T
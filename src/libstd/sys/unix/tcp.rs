@@ -78,6 +78,7 @@ impl TcpListener {
                         closed: AtomicBool::new(false),
                     }),
                     deadline: 0,
+                    nonblocking: false,
                 })
             }
         }
@@ -91,6 +92,7 @@ impl TcpListener {
 pub struct TcpAcceptor {
     inner: Arc<AcceptorInner>,
     deadline: u64,
+    nonblocking: bool,
 }
 
 struct AcceptorInner {
@@ -130,6 +132,9 @@ impl TcpAcceptor {
                 -1 => return Err(last_net_error()),
                 fd => return Ok(TcpStream::new(fd as sock_t)),
             }
+            if self.nonblocking {
+                return Err(sys_common::would_block())
+            }
             try!(net::await(&[self.fd(), self.inner.reader.fd()],
                        deadline, Readable));
         }
@@ -145,6 +150,10 @@ impl TcpAcceptor {
         self.deadline = timeout.map(|a| sys::timer::now() + a).unwrap_or(0);
     }
 
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.nonblocking = nonblocking;
+    }
+
     pub fn close_accept(&mut self) -> IoResult<()> {
         self.inner.closed.store(true, Ordering::SeqCst);
         let fd = FileDesc::new(self.inner.writer.fd(), false);
@@ -161,6 +170,7 @@ impl Clone for TcpAcceptor {
         TcpAcceptor {
             inner: self.inner.clone(),
             deadline: 0,
+            nonblocking: false,
         }
     }
 }
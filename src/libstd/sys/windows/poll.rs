@@ -0,0 +1,45 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Readiness polling is not yet implemented on windows.
+
+use prelude::v1::*;
+
+use io::IoResult;
+use io::poll::{Event, Interest};
+use sys::sock_t;
+use sys_common::unimpl;
+
+pub struct Poller;
+
+impl Poller {
+    pub fn new() -> IoResult<Poller> {
+        Err(unimpl())
+    }
+
+    pub fn register(&mut self, _fd: sock_t, _token: uint,
+                    _interest: Interest) -> IoResult<()> {
+        Err(unimpl())
+    }
+
+    pub fn reregister(&mut self, _fd: sock_t, _token: uint,
+                      _interest: Interest) -> IoResult<()> {
+        Err(unimpl())
+    }
+
+    pub fn deregister(&mut self, _fd: sock_t) -> IoResult<()> {
+        Err(unimpl())
+    }
+
+    pub fn poll(&mut self, _events: &mut Vec<Event>,
+                _timeout_ms: Option<u64>) -> IoResult<()> {
+        Err(unimpl())
+    }
+}
@@ -138,6 +138,15 @@ impl Process {
             })
         }
 
+        if cfg.process_group().is_some() || !cfg.rlimits().is_empty() ||
+           !cfg.keep_fds().is_empty() || !cfg.before_exec().is_empty() {
+            return Err(IoError {
+                kind: io::IoUnavailable,
+                desc: "unsupported unix process options requested on windows",
+                detail: None,
+            })
+        }
+
         // To have the spawning semantics of unix/windows stay the same, we need to
         // read the *child's* PATH if one is provided. See #15149 for more details.
         let program = cfg.env().and_then(|env| {
@@ -19,7 +19,7 @@ use sync::Arc;
 use sync::atomic::{AtomicBool, Ordering};
 use sys::fs::FileDesc;
 use sys::{self, c, set_nonblocking, wouldblock, timer};
-use sys_common::{self, timeout, eof, net};
+use sys_common::{self, timeout, eof, would_block, net};
 
 pub use sys_common::net::TcpStream;
 
@@ -95,6 +95,7 @@ impl TcpListener {
                         closed: AtomicBool::new(false),
                     }),
                     deadline: 0,
+                    nonblocking: false,
                 })
             }
         }
@@ -114,6 +115,7 @@ impl Drop for TcpListener {
 pub struct TcpAcceptor {
     inner: Arc<AcceptorInner>,
     deadline: u64,
+    nonblocking: bool,
 }
 
 unsafe impl Send for TcpAcceptor {}
@@ -156,7 +158,9 @@ impl TcpAcceptor {
         let events = [self.inner.abort.handle(), self.inner.accept.handle()];
 
         while !self.inner.closed.load(Ordering::SeqCst) {
-            let ms = if self.deadline == 0 {
+            let ms = if self.nonblocking {
+                0
+            } else if self.deadline == 0 {
                 c::WSA_INFINITE as u64
             } else {
                 let now = timer::now();
@@ -167,6 +171,9 @@ impl TcpAcceptor {
                                             ms as libc::DWORD, libc::FALSE)
             };
             match ret {
+                c::WSA_WAIT_TIMEOUT if self.nonblocking => {
+                    return Err(would_block())
+                }
                 c::WSA_WAIT_TIMEOUT => {
                     return Err(timeout("accept timed out"))
                 }
@@ -214,6 +221,10 @@ impl TcpAcceptor {
         self.deadline = timeout.map(|a| timer::now() + a).unwrap_or(0);
     }
 
+    pub fn set_nonblocking(&mut self, nonblocking: bool) {
+        self.nonblocking = nonblocking;
+    }
+
     pub fn close_accept(&mut self) -> IoResult<()> {
         self.inner.closed.store(true, Ordering::SeqCst);
         let ret = unsafe { c::WSASetEvent(self.inner.abort.handle()) };
@@ -230,6 +241,7 @@ impl Clone for TcpAcceptor {
         TcpAcceptor {
             inner: self.inner.clone(),
             deadline: 0,
+            nonblocking: false,
         }
     }
 }
@@ -245,6 +245,15 @@ impl ExpnId {
         let ExpnId(cookie) = self;
         cookie as i32
     }
+
+    /// The position of this expansion in the order expansions were recorded,
+    /// or `None` for `NO_EXPANSION`.
+    pub fn to_index(self) -> Option<uint> {
+        match self {
+            NO_EXPANSION => None,
+            ExpnId(i) => Some(i as uint),
+        }
+    }
 }
 
 pub type FileName = String;
@@ -568,6 +577,16 @@ impl CodeMap {
         ExpnId(expansions.len().to_u32().expect("too many ExpnInfo's!") - 1)
     }
 
+    /// Calls `f` with every macro expansion recorded so far, in the order
+    /// they were recorded.
+    pub fn each_expansion<F>(&self, mut f: F) where
+        F: FnMut(ExpnId, &ExpnInfo),
+    {
+        for (i, info) in self.expansions.borrow().iter().enumerate() {
+            f(ExpnId(i as u32), info);
+        }
+    }
+
     pub fn with_expn_info<T, F>(&self, id: ExpnId, f: F) -> T where
         F: FnOnce(Option<&ExpnInfo>) -> T,
     {
@@ -11,16 +11,20 @@
 pub use self::Level::*;
 pub use self::RenderSpan::*;
 pub use self::ColorConfig::*;
+pub use self::Applicability::*;
 use self::Destination::*;
 
-use codemap::{Pos, Span};
+use codemap::{Pos, Span, NO_EXPANSION};
 use codemap;
 use diagnostics;
 
 use std::cell::{RefCell, Cell};
+use std::cmp;
+use std::collections::HashMap;
 use std::fmt;
-use std::io;
-use std::iter::range;
+use std::io::{self, File};
+use std::iter::{range, repeat};
+use std::rc::Rc;
 use std::string::String;
 use term::WriterWrapper;
 use term;
@@ -28,6 +32,9 @@ use term;
 /// maximum number of lines we will print for each error; arbitrary.
 static MAX_LINES: uint = 6u;
 
+/// the number of columns a tab is expanded to in labeled snippets.
+static TAB_WIDTH: uint = 4u;
+
 #[derive(Clone, Copy)]
 pub enum RenderSpan {
     /// A FullSpan renders with both with an initial line for the
@@ -41,7 +48,7 @@ pub enum RenderSpan {
 }
 
 impl RenderSpan {
-    fn span(self) -> Span {
+    pub fn span(self) -> Span {
         match self {
             FullSpan(s) | FileLine(s) => s
         }
@@ -54,6 +61,60 @@ impl RenderSpan {
     }
 }
 
+/// A location made up of a primary span and any number of secondary spans,
+/// each of which may carry a label, e.g. "first borrow occurs here". The
+/// terminal emitter renders all of them in a single source snippet.
+#[derive(Clone)]
+pub struct MultiSpan {
+    pub primary: Span,
+    pub primary_label: Option<String>,
+    pub labels: Vec<(Span, String)>,
+}
+
+impl MultiSpan {
+    pub fn new(primary: Span) -> MultiSpan {
+        MultiSpan {
+            primary: primary,
+            primary_label: None,
+            labels: Vec::new(),
+        }
+    }
+
+    /// Labels the primary span.
+    pub fn label_primary(&mut self, label: &str) {
+        self.primary_label = Some(label.to_string());
+    }
+
+    /// Adds a secondary span with the given label.
+    pub fn push_label(&mut self, sp: Span, label: &str) {
+        self.labels.push((sp, label.to_string()));
+    }
+}
+
+/// How confident the compiler is that a suggestion is what the user meant.
+#[derive(Copy, PartialEq, Clone, Show)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user intended, and can be
+    /// applied automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user intended, but it is uncertain and
+    /// should be reviewed before being applied.
+    MaybeIncorrect,
+    /// The suggestion contains placeholders like `/* type */` which the user
+    /// has to fill in.
+    HasPlaceholders,
+}
+
+/// A fix-it attached to a diagnostic: replacing the source covered by `span`
+/// with `replacement` addresses the problem.
+#[derive(Clone)]
+pub struct Suggestion {
+    pub msg: String,
+    pub span: Span,
+    pub replacement: String,
+    pub applicability: Applicability,
+}
+
 #[derive(Clone, Copy)]
 pub enum ColorConfig {
     Auto,
@@ -66,6 +127,28 @@ pub trait Emitter {
             msg: &str, code: Option<&str>, lvl: Level);
     fn custom_emit(&mut self, cm: &codemap::CodeMap,
                    sp: RenderSpan, msg: &str, lvl: Level);
+
+    /// Emits a diagnostic pointing at several labeled spans. By default the
+    /// message is reported at the primary span, and each label as a note.
+    fn emit_labeled(&mut self, cm: &codemap::CodeMap, msp: &MultiSpan,
+                    msg: &str, code: Option<&str>, lvl: Level) {
+        self.emit(Some((cm, msp.primary)), msg, code, lvl);
+        if let Some(ref label) = msp.primary_label {
+            self.emit(Some((cm, msp.primary)), label.index(&FullRange), None, Note);
+        }
+        for &(sp, ref label) in msp.labels.iter() {
+            self.emit(Some((cm, sp)), label.index(&FullRange), None, Note);
+        }
+    }
+
+    /// Emits a suggestion attached to the preceding diagnostic. By default
+    /// this is a help message quoting the replacement.
+    fn emit_suggestion(&mut self, cm: &codemap::CodeMap, suggestion: &Suggestion) {
+        // Replacements inserting whole lines end with the line break and the
+        // indentation of the next one, which are left out here.
+        let msg = format!("{}: `{}`", suggestion.msg, suggestion.replacement.trim());
+        self.emit(Some((cm, suggestion.span)), msg.index(&FullRange), None, Help);
+    }
 }
 
 /// This structure is used to signify that a task has panicked with a fatal error
@@ -100,6 +183,14 @@ impl SpanHandler {
         self.handler.emit_with_code(Some((&self.cm, sp)), msg, code, Error);
         self.handler.bump_err_count();
     }
+    pub fn span_err_labeled(&self, msp: &MultiSpan, msg: &str) {
+        self.handler.emit_labeled(&self.cm, msp, msg, None, Error);
+        self.handler.bump_err_count();
+    }
+    pub fn span_err_labeled_with_code(&self, msp: &MultiSpan, msg: &str, code: &str) {
+        self.handler.emit_labeled(&self.cm, msp, msg, Some(code), Error);
+        self.handler.bump_err_count();
+    }
     pub fn span_warn(&self, sp: Span, msg: &str) {
         self.handler.emit(Some((&self.cm, sp)), msg, Warning);
     }
@@ -115,6 +206,16 @@ impl SpanHandler {
     pub fn span_help(&self, sp: Span, msg: &str) {
         self.handler.emit(Some((&self.cm, sp)), msg, Help);
     }
+    /// Suggests replacing the source covered by `sp` with `replacement`.
+    pub fn span_suggestion(&self, sp: Span, msg: &str, replacement: String,
+                           applicability: Applicability) {
+        self.handler.emit_suggestion(&self.cm, &Suggestion {
+            msg: msg.to_string(),
+            span: sp,
+            replacement: replacement,
+            applicability: applicability,
+        });
+    }
     pub fn fileline_note(&self, sp: Span, msg: &str) {
         self.handler.custom_emit(&self.cm, FileLine(sp), msg, Note);
     }
@@ -201,6 +302,13 @@ impl Handler {
                        sp: RenderSpan, msg: &str, lvl: Level) {
         self.emit.borrow_mut().custom_emit(cm, sp, msg, lvl);
     }
+    pub fn emit_labeled(&self, cm: &codemap::CodeMap, msp: &MultiSpan,
+                        msg: &str, code: Option<&str>, lvl: Level) {
+        self.emit.borrow_mut().emit_labeled(cm, msp, msg, code, lvl);
+    }
+    pub fn emit_suggestion(&self, cm: &codemap::CodeMap, suggestion: &Suggestion) {
+        self.emit.borrow_mut().emit_suggestion(cm, suggestion);
+    }
 }
 
 pub fn mk_span_handler(handler: Handler, cm: codemap::CodeMap) -> SpanHandler {
@@ -385,6 +493,177 @@ impl Emitter for EmitterWriter {
             Err(e) => panic!("failed to print diagnostics: {:?}", e),
         }
     }
+
+    fn emit_labeled(&mut self, cm: &codemap::CodeMap, msp: &MultiSpan,
+                    msg: &str, code: Option<&str>, lvl: Level) {
+        match emit_labeled(self, cm, msp, msg, code, lvl) {
+            Ok(()) => {}
+            Err(e) => panic!("failed to print diagnostics: {:?}", e),
+        }
+    }
+
+    fn emit_suggestion(&mut self, cm: &codemap::CodeMap, suggestion: &Suggestion) {
+        match print_suggestion(self, cm, suggestion) {
+            Ok(()) => {}
+            Err(e) => panic!("failed to print diagnostics: {:?}", e),
+        }
+    }
+}
+
+/// Prints a suggestion as a patch: the lines covered by its span, prefixed
+/// with `-`, followed by the same lines with the replacement applied,
+/// prefixed with `+`.
+fn print_suggestion(dst: &mut EmitterWriter, cm: &codemap::CodeMap,
+                    suggestion: &Suggestion) -> io::IoResult<()> {
+    let sp = suggestion.span;
+    let ss = cm.span_to_string(sp);
+    try!(print_diagnostic(dst, ss.index(&FullRange), Help,
+                          suggestion.msg.index(&FullRange), None));
+
+    let lo = cm.lookup_byte_offset(sp.lo);
+    let hi = cm.lookup_byte_offset(sp.hi);
+    let fm = &*lo.fm;
+    let src = fm.src.index(&FullRange);
+    let (lo, hi) = (lo.pos.to_uint(), hi.pos.to_uint());
+    let start = src.index(&(..lo)).rfind('\n').map_or(0, |i| i + 1);
+    let end = src.index(&(hi..)).find('\n').map_or(src.len(), |i| hi + i);
+    let before = src.index(&(start..end));
+    let after = format!("{}{}{}", src.index(&(start..lo)), suggestion.replacement,
+                        src.index(&(hi..end)));
+
+    let first_line = cm.lookup_char_pos(sp.lo).line;
+    try!(print_patch_lines(dst, '-', fm.name.index(&FullRange), first_line, before,
+                           term::color::BRIGHT_RED));
+    print_patch_lines(dst, '+', fm.name.index(&FullRange), first_line,
+                      after.index(&FullRange), term::color::BRIGHT_GREEN)
+}
+
+fn print_patch_lines(dst: &mut EmitterWriter, marker: char, name: &str,
+                     first_line: uint, text: &str,
+                     color: term::color::Color) -> io::IoResult<()> {
+    for (i, line) in text.lines().enumerate() {
+        try!(print_maybe_styled(dst,
+                                format!("{} {}:{} {}\n", marker, name,
+                                        first_line + i, line).index(&FullRange),
+                                term::attr::ForegroundColor(color)));
+    }
+    Ok(())
+}
+
+/// An emitter which passes everything through to another emitter, but also
+/// records machine-applicable suggestions and applies them to the source
+/// files once it is dropped, i.e. when compilation finishes or aborts.
+///
+/// Suggestions pointing into macro expansions or at sources not read from
+/// disk are not applied, and neither are suggestions overlapping one which
+/// has already been applied to the same file.
+pub struct SuggestionApplier {
+    inner: Box<Emitter + Send>,
+    edits: Vec<(String, uint, uint, String)>,
+    // The sources the edits were computed against, by file name, so that
+    // files modified during compilation are left alone.
+    sources: HashMap<String, String>,
+}
+
+impl SuggestionApplier {
+    pub fn new(inner: Box<Emitter + Send>) -> SuggestionApplier {
+        SuggestionApplier {
+            inner: inner,
+            edits: Vec::new(),
+            sources: HashMap::new(),
+        }
+    }
+
+    fn apply(&self) -> io::IoResult<()> {
+        for (name, src) in self.sources.iter() {
+            let edits: Vec<(uint, uint, String)> = self.edits.iter()
+                .filter(|&&(ref file, _, _, _)| file == name)
+                .map(|&(_, lo, hi, ref text)| (lo, hi, text.clone()))
+                .collect();
+
+            let path = Path::new(name.index(&FullRange));
+            let current = try!(File::open(&path).read_to_string());
+            if current != *src {
+                try!(writeln!(&mut io::stderr(),
+                              "warning: not applying suggestions to `{}`, \
+                               which was modified during compilation", name));
+                continue
+            }
+            let fixed = apply_replacements(src.index(&FullRange), edits.index(&FullRange));
+            try!(File::create(&path).write_str(fixed.index(&FullRange)));
+        }
+        Ok(())
+    }
+}
+
+impl Emitter for SuggestionApplier {
+    fn emit(&mut self, cmsp: Option<(&codemap::CodeMap, Span)>,
+            msg: &str, code: Option<&str>, lvl: Level) {
+        self.inner.emit(cmsp, msg, code, lvl);
+    }
+
+    fn custom_emit(&mut self, cm: &codemap::CodeMap,
+                   sp: RenderSpan, msg: &str, lvl: Level) {
+        self.inner.custom_emit(cm, sp, msg, lvl);
+    }
+
+    fn emit_labeled(&mut self, cm: &codemap::CodeMap, msp: &MultiSpan,
+                    msg: &str, code: Option<&str>, lvl: Level) {
+        self.inner.emit_labeled(cm, msp, msg, code, lvl);
+    }
+
+    fn emit_suggestion(&mut self, cm: &codemap::CodeMap, suggestion: &Suggestion) {
+        self.inner.emit_suggestion(cm, suggestion);
+
+        let sp = suggestion.span;
+        if suggestion.applicability != MachineApplicable || sp.expn_id != NO_EXPANSION {
+            return
+        }
+        let lo = cm.lookup_byte_offset(sp.lo);
+        let hi = cm.lookup_byte_offset(sp.hi);
+        if !lo.fm.is_real_file() || lo.fm.start_pos != hi.fm.start_pos {
+            return
+        }
+        if !self.sources.contains_key(&lo.fm.name) {
+            self.sources.insert(lo.fm.name.clone(), lo.fm.src.clone());
+        }
+        self.edits.push((lo.fm.name.clone(), lo.pos.to_uint(), hi.pos.to_uint(),
+                         suggestion.replacement.clone()));
+    }
+}
+
+impl Drop for SuggestionApplier {
+    fn drop(&mut self) {
+        match self.apply() {
+            Ok(()) => {}
+            Err(e) => {
+                let _ = writeln!(&mut io::stderr(),
+                                 "error: failed to apply suggestions: {}", e);
+            }
+        }
+    }
+}
+
+/// Applies edits, given as `(lo, hi, replacement)` byte ranges into `src`, and
+/// returns the resulting source. Duplicate edits are applied once, and edits
+/// overlapping a preceding one are skipped.
+pub fn apply_replacements(src: &str, edits: &[(uint, uint, String)]) -> String {
+    let mut edits: Vec<&(uint, uint, String)> = edits.iter().collect();
+    edits.sort();
+    edits.dedup();
+
+    let mut fixed = String::with_capacity(src.len());
+    let mut pos = 0u;
+    for &&(lo, hi, ref text) in edits.iter() {
+        if lo < pos {
+            continue
+        }
+        fixed.push_str(src.index(&(pos..lo)));
+        fixed.push_str(text.index(&FullRange));
+        pos = hi;
+    }
+    fixed.push_str(src.index(&(pos..)));
+    fixed
 }
 
 fn emit(dst: &mut EmitterWriter, cm: &codemap::CodeMap, rsp: RenderSpan,
@@ -409,11 +688,16 @@ fn emit(dst: &mut EmitterWriter, cm: &codemap::CodeMap, rsp: RenderSpan,
         }
     }
     try!(print_macro_backtrace(dst, cm, sp));
+    print_explain_help(dst, ss.index(&FullRange), code)
+}
+
+fn print_explain_help(dst: &mut EmitterWriter, topic: &str,
+                      code: Option<&str>) -> io::IoResult<()> {
     match code {
         Some(code) =>
             match dst.registry.as_ref().and_then(|registry| registry.find_description(code)) {
                 Some(_) => {
-                    try!(print_diagnostic(dst, ss.index(&FullRange), Help,
+                    try!(print_diagnostic(dst, topic, Help,
                                           format!("pass `--explain {}` to see a detailed \
                                                    explanation", code).index(&FullRange), None));
                 }
@@ -424,6 +708,207 @@ fn emit(dst: &mut EmitterWriter, cm: &codemap::CodeMap, rsp: RenderSpan,
     Ok(())
 }
 
+fn emit_labeled(dst: &mut EmitterWriter, cm: &codemap::CodeMap, msp: &MultiSpan,
+                msg: &str, code: Option<&str>, lvl: Level) -> io::IoResult<()> {
+    let ss = cm.span_to_string(msp.primary);
+    try!(print_diagnostic(dst, ss.index(&FullRange), lvl, msg, code));
+    try!(print_labeled_snippet(dst, cm, msp, lvl));
+    try!(print_macro_backtrace(dst, cm, msp.primary));
+    print_explain_help(dst, ss.index(&FullRange), code)
+}
+
+/// A span marked in a labeled snippet. Spans covering several lines are only
+/// marked on their first line.
+struct Annotation {
+    /// 0-based.
+    line: uint,
+    /// Display columns, with tabs expanded.
+    start_col: uint,
+    end_col: uint,
+    is_primary: bool,
+    label: Option<String>,
+}
+
+/// Prints the lines covered by the spans of `msp` behind a line number gutter,
+/// each followed by markers for the spans on it: `^` for the primary span and
+/// `-` for secondary ones, along with their labels. Lines without a span are
+/// elided, and spans in other files than the primary one are shown after it.
+///
+/// ```text
+/// 4 |     let a = &mut x;
+///   |             ------ first mutable borrow occurs here
+/// 5 |     let b = &mut x;
+///   |             ^^^^^^ second mutable borrow occurs here
+/// ...
+/// 9 | }
+///   | - first borrow ends here
+/// ```
+fn print_labeled_snippet(dst: &mut EmitterWriter, cm: &codemap::CodeMap,
+                         msp: &MultiSpan, lvl: Level) -> io::IoResult<()> {
+    // The files the spans are in, starting with the one of the primary span,
+    // and the annotations in each of them at the same index.
+    let mut files: Vec<Rc<codemap::FileMap>> = Vec::new();
+    let mut annotations: Vec<Vec<Annotation>> = Vec::new();
+
+    let primary = Some((msp.primary, msp.primary_label.as_ref(), true)).into_iter();
+    let secondary = msp.labels.iter().map(|&(sp, ref label)| (sp, Some(label), false));
+    for (sp, label, is_primary) in primary.chain(secondary) {
+        let lo = cm.lookup_char_pos(sp.lo);
+        let hi = cm.lookup_char_pos(sp.hi);
+        let line = lo.line - 1;
+        let text = match lo.file.get_line(line) {
+            Some(text) => text,
+            None => continue,
+        };
+        let start_col = display_col(text.index(&FullRange), lo.col.to_uint());
+        let end_col = if hi.line == lo.line {
+            display_col(text.index(&FullRange), hi.col.to_uint())
+        } else {
+            display_col(text.index(&FullRange), text.chars().count())
+        };
+        let annotation = Annotation {
+            line: line,
+            start_col: start_col,
+            end_col: cmp::max(end_col, start_col + 1),
+            is_primary: is_primary,
+            label: label.map(|label| label.clone()),
+        };
+
+        let i = match files.iter().position(|fm| fm.name == lo.file.name) {
+            Some(i) => i,
+            None => {
+                files.push(lo.file.clone());
+                annotations.push(Vec::new());
+                files.len() - 1
+            }
+        };
+        annotations[i].push(annotation);
+    }
+
+    let max_line = annotations.iter().flat_map(|anns| anns.iter())
+                              .map(|a| a.line + 1).max().unwrap_or(0);
+    let width = max_line.to_string().len();
+
+    for (i, (fm, anns)) in files.iter().zip(annotations.iter()).enumerate() {
+        if i > 0 {
+            try!(write!(&mut dst.dst, "{0:1$} ::: {2}\n", "", width, fm.name));
+        }
+
+        let mut lines: Vec<uint> = anns.iter().map(|a| a.line).collect();
+        lines.sort();
+        lines.dedup();
+
+        let mut prev = None;
+        for &line in lines.iter() {
+            match prev {
+                // Eliding a single line takes as much room as printing it.
+                Some(prev) if line == prev + 2 => {
+                    try!(print_snippet_line(dst, &**fm, prev + 1, width));
+                }
+                Some(prev) if line > prev + 2 => {
+                    try!(write!(&mut dst.dst, "...\n"));
+                }
+                _ => {}
+            }
+            try!(print_snippet_line(dst, &**fm, line, width));
+
+            let mut on_line: Vec<&Annotation> = anns.iter().filter(|a| a.line == line).collect();
+            on_line.sort_by(|a, b| a.start_col.cmp(&b.start_col));
+            try!(print_annotations(dst, on_line.index(&FullRange), width, lvl));
+            prev = Some(line);
+        }
+    }
+    Ok(())
+}
+
+fn print_snippet_line(dst: &mut EmitterWriter, fm: &codemap::FileMap,
+                      line: uint, width: uint) -> io::IoResult<()> {
+    let text = fm.get_line(line).unwrap_or(String::new());
+    let s = format!("{0:>1$} | {2}", line + 1, width,
+                    text.replace("\t", repeat(" ").take(TAB_WIDTH).collect::<String>()
+                                                   .index(&FullRange)));
+    write!(&mut dst.dst, "{}\n", s.trim_right())
+}
+
+/// Prints the markers for the annotations on one line, sorted by column. The
+/// label of the rightmost one goes right after the markers, the others are
+/// printed below, connected to their span by a `|`.
+fn print_annotations(dst: &mut EmitterWriter, anns: &[&Annotation],
+                     width: uint, lvl: Level) -> io::IoResult<()> {
+    let len = anns.iter().map(|a| a.end_col).max().unwrap_or(0);
+    let mut markers: Vec<char> = repeat(' ').take(len).collect();
+    // Secondary markers go first, so that the primary one wins where they
+    // overlap.
+    for &primary in [false, true].iter() {
+        for a in anns.iter().filter(|a| a.is_primary == primary) {
+            let marker = if a.is_primary { '^' } else { '-' };
+            for col in range(a.start_col, a.end_col) {
+                markers[col] = marker;
+            }
+        }
+    }
+    let mut row: String = markers.into_iter().collect();
+    let last = match anns.last() {
+        Some(last) => last,
+        None => return Ok(()),
+    };
+    if let Some(ref label) = last.label {
+        row.push(' ');
+        row.push_str(label.index(&FullRange));
+    }
+    let has_primary = anns.iter().any(|a| a.is_primary);
+    try!(print_annotation_row(dst, row.index(&FullRange), width, has_primary, lvl));
+
+    let labeled: Vec<&Annotation> = anns.init().iter().filter(|a| a.label.is_some())
+                                        .map(|a| *a).collect();
+    for k in range(0, labeled.len()).rev() {
+        let a = labeled[k];
+        let row = connectors(labeled.index(&(..(k + 1))));
+        try!(print_annotation_row(dst, row.index(&FullRange), width, a.is_primary, lvl));
+
+        // The label hides the connectors of the annotations it covers, such as
+        // those starting in the same column, whose labels come further down.
+        let mut row: Vec<char> = connectors(labeled.index(&(..k))).chars().collect();
+        for (i, c) in a.label.as_ref().unwrap().chars().enumerate() {
+            let col = a.start_col + i;
+            if col >= row.len() {
+                let len = row.len();
+                row.extend(repeat(' ').take(col + 1 - len));
+            }
+            row[col] = c;
+        }
+        let row: String = row.into_iter().collect();
+        try!(print_annotation_row(dst, row.index(&FullRange), width, a.is_primary, lvl));
+    }
+    Ok(())
+}
+
+// A row with a `|` below the start of each annotation.
+fn connectors(anns: &[&Annotation]) -> String {
+    let len = anns.iter().map(|a| a.start_col + 1).max().unwrap_or(0);
+    let mut row: Vec<char> = repeat(' ').take(len).collect();
+    for a in anns.iter() {
+        row[a.start_col] = '|';
+    }
+    row.into_iter().collect()
+}
+
+fn print_annotation_row(dst: &mut EmitterWriter, row: &str, width: uint,
+                        is_primary: bool, lvl: Level) -> io::IoResult<()> {
+    try!(write!(&mut dst.dst, "{0:1$} | ", "", width));
+    let color = if is_primary { lvl.color() } else { term::color::BRIGHT_BLUE };
+    print_maybe_styled(dst,
+                       format!("{}\n", row.trim_right()).index(&FullRange),
+                       term::attr::ForegroundColor(color))
+}
+
+// The width of the first `col` characters of `line`, with tabs expanded.
+fn display_col(line: &str, col: uint) -> uint {
+    line.chars().take(col).fold(0, |width, c| {
+        width + if c == '\t' { TAB_WIDTH } else { 1 }
+    })
+}
+
 fn highlight_lines(err: &mut EmitterWriter,
                    cm: &codemap::CodeMap,
                    sp: Span,
@@ -581,3 +1066,152 @@ pub fn expect<T, M>(diag: &SpanHandler, opt: Option<T>, msg: M) -> T where
         None => diag.handler().bug(msg().index(&FullRange)),
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{EmitterWriter, Emitter, MultiSpan, Suggestion, MachineApplicable, Error};
+    use super::apply_replacements;
+
+    use codemap::{CodeMap, Span, BytePos, NO_EXPANSION};
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    struct SharedWriter {
+        buf: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Writer for SharedWriter {
+        fn write(&mut self, bytes: &[u8]) -> io::IoResult<()> {
+            self.buf.lock().unwrap().push_all(bytes);
+            Ok(())
+        }
+    }
+
+    fn render<F>(src: &str, f: F) -> String where F: FnOnce(&mut EmitterWriter, &CodeMap) {
+        let cm = CodeMap::new();
+        let fm = cm.new_filemap("blork.rs".to_string(), src.to_string());
+        let mut pos = 0;
+        for line in src.lines() {
+            fm.next_line(BytePos(pos));
+            pos += line.len() as u32 + 1;
+        }
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        {
+            let mut emitter = EmitterWriter::new(box SharedWriter { buf: buf.clone() }, None);
+            f(&mut emitter, &cm);
+        }
+        let bytes = buf.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    fn sp(lo: u32, hi: u32) -> Span {
+        Span { lo: BytePos(lo), hi: BytePos(hi), expn_id: NO_EXPANSION }
+    }
+
+    #[test]
+    fn labeled_spans_on_several_lines() {
+        let out = render("fn main() {\n    let a = &mut x;\n    let b = &mut x;\n}\n", |e, cm| {
+            let mut msp = MultiSpan::new(sp(44, 50));
+            msp.label_primary("second mutable borrow occurs here");
+            msp.push_label(sp(24, 30), "first mutable borrow occurs here");
+            msp.push_label(sp(52, 53), "first borrow ends here");
+            e.emit_labeled(cm, &msp, "cannot borrow `x` as mutable more than once at a time",
+                           None, Error);
+        });
+        assert_eq!(out, "blork.rs:3:13: 3:19 error: cannot borrow `x` as mutable more than \
+                         once at a time\n\
+                         2 |     let a = &mut x;\n\
+                        \x20 |             ------ first mutable borrow occurs here\n\
+                         3 |     let b = &mut x;\n\
+                        \x20 |             ^^^^^^ second mutable borrow occurs here\n\
+                         4 | }\n\
+                        \x20 | - first borrow ends here\n");
+    }
+
+    #[test]
+    fn labeled_spans_on_one_line() {
+        let out = render("fn main() {\n    foo(&mut x, &mut x);\n}\n", |e, cm| {
+            let mut msp = MultiSpan::new(sp(28, 34));
+            msp.label_primary("second borrow");
+            msp.push_label(sp(20, 26), "first borrow");
+            e.emit_labeled(cm, &msp, "conflicting borrows", None, Error);
+        });
+        assert_eq!(out, "blork.rs:2:17: 2:23 error: conflicting borrows\n\
+                         2 |     foo(&mut x, &mut x);\n\
+                        \x20 |         ------  ^^^^^^ second borrow\n\
+                        \x20 |         |\n\
+                        \x20 |         first borrow\n");
+    }
+
+    #[test]
+    fn labeled_spans_in_one_column() {
+        let out = render("fn main() {\n    foo(&mut x);\n}\n", |e, cm| {
+            let mut msp = MultiSpan::new(sp(20, 26));
+            msp.label_primary("third");
+            msp.push_label(sp(20, 26), "second");
+            msp.push_label(sp(20, 24), "first");
+            e.emit_labeled(cm, &msp, "three borrows", None, Error);
+        });
+        assert_eq!(out, "blork.rs:2:9: 2:15 error: three borrows\n\
+                         2 |     foo(&mut x);\n\
+                        \x20 |         ^^^^^^ first\n\
+                        \x20 |         |\n\
+                        \x20 |         second\n\
+                        \x20 |         |\n\
+                        \x20 |         third\n");
+    }
+
+    #[test]
+    fn labeled_spans_elide_lines() {
+        let src = "fn main() {\n    let x = 1;\n    a();\n    b();\n    x;\n}\n";
+        let out = render(src, |e, cm| {
+            let mut msp = MultiSpan::new(sp(49, 50));
+            msp.push_label(sp(20, 21), "defined here");
+            e.emit_labeled(cm, &msp, "bad", None, Error);
+        });
+        assert_eq!(out, "blork.rs:5:5: 5:6 error: bad\n\
+                         2 |     let x = 1;\n\
+                        \x20 |         - defined here\n\
+                         ...\n\
+                         5 |     x;\n\
+                        \x20 |     ^\n");
+    }
+
+    #[test]
+    fn replacements() {
+        let src = "let x = foo(y);";
+        let edits = vec![(12u, 13u, "&y".to_string()),
+                         (4, 5, "mut x".to_string()),
+                         (12, 13, "&y".to_string()),
+                         (12, 14, "z)".to_string())];
+        assert_eq!(apply_replacements(src, edits.as_slice()), "let mut x = foo(&y);");
+        assert_eq!(apply_replacements(src, &[]), src);
+        assert_eq!(apply_replacements(src, &[(0, 0, "    ".to_string())]),
+                   "    let x = foo(y);");
+    }
+
+    #[test]
+    fn suggestion_patch() {
+        let cm = CodeMap::new();
+        let fm = cm.new_filemap("blork.rs".to_string(),
+                                "fn main() {\n    takes_ref(x);\n}\n".to_string());
+        fm.next_line(BytePos(0));
+        fm.next_line(BytePos(12));
+        fm.next_line(BytePos(30));
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        let mut emitter = EmitterWriter::new(box SharedWriter { buf: buf.clone() }, None);
+        emitter.emit_suggestion(&cm, &Suggestion {
+            msg: "consider borrowing here".to_string(),
+            span: Span { lo: BytePos(26), hi: BytePos(27), expn_id: NO_EXPANSION },
+            replacement: "&x".to_string(),
+            applicability: MachineApplicable,
+        });
+
+        let out = String::from_utf8(buf.lock().unwrap().clone()).unwrap();
+        assert_eq!(out, "blork.rs:2:15: 2:16 help: consider borrowing here\n\
+                         - blork.rs:2     takes_ref(x);\n\
+                         + blork.rs:2     takes_ref(&x);\n");
+    }
+}
@@ -22,6 +22,15 @@ macro_rules! span_err {
     })
 }
 
+#[macro_export]
+macro_rules! span_err_labeled {
+    ($session:expr, $msp:expr, $code:ident, $($message:tt)*) => ({
+        __diagnostic_used!($code);
+        $session.span_err_labeled_with_code($msp, format!($($message)*).as_slice(),
+                                            stringify!($code))
+    })
+}
+
 #[macro_export]
 macro_rules! span_warn {
     ($session:expr, $span:expr, $code:ident, $($message:tt)*) => ({
@@ -0,0 +1,386 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A diagnostic emitter producing JSON output, for use by tools such as IDEs
+//! which need to consume compiler errors.
+//!
+//! Each diagnostic is written as a single JSON object on its own line, with
+//! the following structure:
+//!
+//! ```ignore
+//! {
+//!     "message": "mismatched types: ...",
+//!     "code": { "code": "E0308", "explanation": "..." } | null,
+//!     "level": "error",
+//!     "spans": [{
+//!         "file_name": "src/main.rs",
+//!         "byte_start": 120, "byte_end": 125,
+//!         "line_start": 7, "line_end": 7,
+//!         "column_start": 13, "column_end": 18,
+//!         "is_primary": true,
+//!         "label": null,
+//!         "suggested_replacement": null,
+//!         "suggestion_applicability": null,
+//!         "expansion": {
+//!             "span": { ... },
+//!             "macro_decl_name": "foo!",
+//!             "def_site_span": { ... } | null
+//!         } | null
+//!     }],
+//!     "children": [ ... ]
+//! }
+//! ```
+//!
+//! Notes and help messages emitted directly after a diagnostic are reported
+//! as its `children` rather than as diagnostics of their own. Byte offsets
+//! are relative to the start of the file, lines and columns start at 1, and
+//! the end of a span is exclusive. The `expansion` of a span describes the
+//! macro invocation it originated from, and may itself have an expansion if
+//! that invocation was produced by another macro.
+//!
+//! Suggestions are reported as `help` children whose span carries the
+//! `suggested_replacement` for the source it covers, along with its
+//! `suggestion_applicability`: `MachineApplicable`, `MaybeIncorrect` or
+//! `HasPlaceholders`.
+
+use codemap::{self, Pos, Span, CodeMap};
+use diagnostic::{Emitter, RenderSpan, MultiSpan, Suggestion, Level, Note, Help, Fatal, Bug};
+use diagnostics::registry::Registry;
+
+use serialize::json::as_json;
+use std::io;
+use std::string::String;
+
+pub struct JsonEmitter {
+    dst: Box<Writer + Send>,
+    registry: Option<Registry>,
+    // The diagnostic waiting for notes and help messages to be attached to it,
+    // which is written out once the next diagnostic starts.
+    pending: Option<Diagnostic>,
+}
+
+impl JsonEmitter {
+    pub fn stderr(registry: Option<Registry>) -> JsonEmitter {
+        JsonEmitter::new(box io::stderr(), registry)
+    }
+
+    pub fn new(dst: Box<Writer + Send>, registry: Option<Registry>) -> JsonEmitter {
+        JsonEmitter {
+            dst: dst,
+            registry: registry,
+            pending: None,
+        }
+    }
+
+    fn add(&mut self, diagnostic: Diagnostic, lvl: Level) {
+        match (lvl, self.pending.as_mut()) {
+            (Note, Some(parent)) | (Help, Some(parent)) => {
+                parent.children.push(diagnostic);
+                return
+            }
+            _ => {}
+        }
+        self.flush();
+        self.pending = Some(diagnostic);
+
+        // The handler panics right after emitting these, so nothing can be
+        // attached to them.
+        if lvl == Fatal || lvl == Bug {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Some(diagnostic) = self.pending.take() {
+            match writeln!(&mut self.dst, "{}", as_json(&diagnostic)) {
+                Ok(()) => {}
+                Err(e) => panic!("failed to print diagnostics: {:?}", e),
+            }
+        }
+    }
+
+    fn code(&self, code: Option<&str>) -> Option<DiagnosticCode> {
+        code.map(|code| {
+            let explanation = self.registry.as_ref()
+                                  .and_then(|registry| registry.find_description(code));
+            DiagnosticCode {
+                code: code.to_string(),
+                explanation: explanation.map(|s| s.to_string()),
+            }
+        })
+    }
+}
+
+impl Emitter for JsonEmitter {
+    fn emit(&mut self, cmsp: Option<(&CodeMap, Span)>,
+            msg: &str, code: Option<&str>, lvl: Level) {
+        let diagnostic = Diagnostic {
+            message: msg.to_string(),
+            code: self.code(code),
+            level: lvl.to_string(),
+            spans: match cmsp {
+                Some((cm, sp)) => vec![DiagnosticSpan::from_span(cm, sp, true, None)],
+                None => Vec::new(),
+            },
+            children: Vec::new(),
+        };
+        self.add(diagnostic, lvl);
+    }
+
+    fn custom_emit(&mut self, cm: &CodeMap,
+                   sp: RenderSpan, msg: &str, lvl: Level) {
+        let diagnostic = Diagnostic {
+            message: msg.to_string(),
+            code: None,
+            level: lvl.to_string(),
+            spans: vec![DiagnosticSpan::from_span(cm, sp.span(), true, None)],
+            children: Vec::new(),
+        };
+        self.add(diagnostic, lvl);
+    }
+
+    fn emit_labeled(&mut self, cm: &CodeMap, msp: &MultiSpan,
+                    msg: &str, code: Option<&str>, lvl: Level) {
+        let mut spans = vec![DiagnosticSpan::from_span(cm, msp.primary, true,
+                                                       msp.primary_label.clone())];
+        for &(sp, ref label) in msp.labels.iter() {
+            spans.push(DiagnosticSpan::from_span(cm, sp, false, Some(label.clone())));
+        }
+        let diagnostic = Diagnostic {
+            message: msg.to_string(),
+            code: self.code(code),
+            level: lvl.to_string(),
+            spans: spans,
+            children: Vec::new(),
+        };
+        self.add(diagnostic, lvl);
+    }
+
+    fn emit_suggestion(&mut self, cm: &CodeMap, suggestion: &Suggestion) {
+        let mut span = DiagnosticSpan::from_span(cm, suggestion.span, true, None);
+        span.suggested_replacement = Some(suggestion.replacement.clone());
+        span.suggestion_applicability = Some(format!("{:?}", suggestion.applicability));
+        let diagnostic = Diagnostic {
+            message: suggestion.msg.clone(),
+            code: None,
+            level: Help.to_string(),
+            spans: vec![span],
+            children: Vec::new(),
+        };
+        self.add(diagnostic, Help);
+    }
+}
+
+impl Drop for JsonEmitter {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[derive(RustcEncodable)]
+struct Diagnostic {
+    /// The primary message.
+    message: String,
+    code: Option<DiagnosticCode>,
+    /// "error: internal compiler error", "error", "warning", "note", "help".
+    level: String,
+    spans: Vec<DiagnosticSpan>,
+    /// Associated notes and help messages.
+    children: Vec<Diagnostic>,
+}
+
+#[derive(RustcEncodable)]
+struct DiagnosticCode {
+    /// The error code, e.g. "E0308".
+    code: String,
+    /// The long explanation printed by `--explain`, if there is one.
+    explanation: Option<String>,
+}
+
+#[derive(RustcEncodable)]
+struct DiagnosticSpan {
+    file_name: String,
+    byte_start: uint,
+    byte_end: uint,
+    /// 1-based.
+    line_start: uint,
+    line_end: uint,
+    /// 1-based, in characters.
+    column_start: uint,
+    column_end: uint,
+    /// Whether this is the span the diagnostic is about, rather than a
+    /// secondary span pointing at a related location.
+    is_primary: bool,
+    /// The label of a secondary span, e.g. "first borrow occurs here".
+    label: Option<String>,
+    /// The text a suggestion replaces the span with.
+    suggested_replacement: Option<String>,
+    /// How confident the suggestion is, if this is a suggestion.
+    suggestion_applicability: Option<String>,
+    /// The macro invocation this span was expanded from, if any.
+    expansion: Option<Box<DiagnosticSpanMacroExpansion>>,
+}
+
+#[derive(RustcEncodable)]
+struct DiagnosticSpanMacroExpansion {
+    /// The span of the macro invocation.
+    span: DiagnosticSpan,
+    /// The name of the macro, e.g. "foo!" or "#[derive(Eq)]".
+    macro_decl_name: String,
+    /// The span of the macro definition, if it is defined in source code.
+    def_site_span: Option<DiagnosticSpan>,
+}
+
+impl DiagnosticSpan {
+    fn from_span(cm: &CodeMap, sp: Span, is_primary: bool,
+                 label: Option<String>) -> DiagnosticSpan {
+        let start = cm.lookup_char_pos(sp.lo);
+        let end = cm.lookup_char_pos(sp.hi);
+        let expansion = cm.with_expn_info(sp.expn_id, |info| {
+            info.map(|ei| {
+                let (pre, post) = match ei.callee.format {
+                    codemap::MacroAttribute => ("#[", "]"),
+                    codemap::MacroBang => ("", "!")
+                };
+                (ei.call_site, format!("{}{}{}", pre, ei.callee.name, post), ei.callee.span)
+            })
+        });
+        DiagnosticSpan {
+            file_name: start.file.name.clone(),
+            byte_start: (sp.lo - start.file.start_pos).to_uint(),
+            byte_end: (sp.hi - start.file.start_pos).to_uint(),
+            line_start: start.line,
+            line_end: end.line,
+            column_start: start.col.to_uint() + 1,
+            column_end: end.col.to_uint() + 1,
+            is_primary: is_primary,
+            label: label,
+            suggested_replacement: None,
+            suggestion_applicability: None,
+            expansion: expansion.map(|(call_site, name, def_site)| {
+                box DiagnosticSpanMacroExpansion {
+                    span: DiagnosticSpan::from_span(cm, call_site, false, None),
+                    macro_decl_name: name,
+                    def_site_span: def_site.map(|sp| {
+                        DiagnosticSpan::from_span(cm, sp, false, None)
+                    }),
+                }
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use codemap::{CodeMap, Span, BytePos, NO_EXPANSION};
+    use diagnostic::{Emitter, MultiSpan, Suggestion, Error, Note, Warning, MaybeIncorrect};
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    struct SharedWriter {
+        buf: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Writer for SharedWriter {
+        fn write(&mut self, bytes: &[u8]) -> io::IoResult<()> {
+            self.buf.lock().unwrap().push_all(bytes);
+            Ok(())
+        }
+    }
+
+    fn emit_all<F>(f: F) -> String where F: FnOnce(&mut JsonEmitter, &CodeMap) {
+        let cm = CodeMap::new();
+        let fm = cm.new_filemap("blork.rs".to_string(),
+                                "fn main() {\n    let x = 1u;\n}\n".to_string());
+        fm.next_line(BytePos(0));
+        fm.next_line(BytePos(12));
+        fm.next_line(BytePos(28));
+
+        let buf = Arc::new(Mutex::new(Vec::new()));
+        {
+            let mut emitter = JsonEmitter::new(box SharedWriter { buf: buf.clone() }, None);
+            f(&mut emitter, &cm);
+        }
+        let bytes = buf.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn one_object_per_diagnostic() {
+        let out = emit_all(|emitter, cm| {
+            let sp = Span { lo: BytePos(20), hi: BytePos(21), expn_id: NO_EXPANSION };
+            emitter.emit(Some((cm, sp)), "unused variable: `x`", None, Warning);
+            emitter.emit(None, "aborting", Some("E0001"), Error);
+        });
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("{\"message\":\"unused variable: `x`\",\"code\":null,\
+                                     \"level\":\"warning\",\"spans\":[{\"file_name\":\
+                                     \"blork.rs\",\"byte_start\":20,\"byte_end\":21,\
+                                     \"line_start\":2,\"line_end\":2,\"column_start\":9,\
+                                     \"column_end\":10,\"is_primary\":true,"));
+        assert!(lines[1].contains("\"code\":{\"code\":\"E0001\",\"explanation\":null}"));
+        assert!(lines[1].contains("\"spans\":[]"));
+    }
+
+    #[test]
+    fn notes_become_children() {
+        let out = emit_all(|emitter, cm| {
+            let sp = Span { lo: BytePos(16), hi: BytePos(17), expn_id: NO_EXPANSION };
+            emitter.emit(Some((cm, sp)), "first", None, Error);
+            emitter.emit(Some((cm, sp)), "a note", None, Note);
+            emitter.emit(None, "second", None, Error);
+        });
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].contains("\"children\":[{\"message\":\"a note\""));
+        assert!(lines[1].ends_with("\"children\":[]}"));
+    }
+
+    #[test]
+    fn suggestions_become_children() {
+        let out = emit_all(|emitter, cm| {
+            let sp = Span { lo: BytePos(20), hi: BytePos(21), expn_id: NO_EXPANSION };
+            emitter.emit(Some((cm, sp)), "unresolved name `y`", None, Error);
+            emitter.emit_suggestion(cm, &Suggestion {
+                msg: "a local variable with a similar name exists".to_string(),
+                span: sp,
+                replacement: "x".to_string(),
+                applicability: MaybeIncorrect,
+            });
+        });
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("\"children\":[{\"message\":\"a local variable with a \
+                                   similar name exists\",\"code\":null,\"level\":\"help\""));
+        assert!(lines[0].contains("\"suggested_replacement\":\"x\",\
+                                   \"suggestion_applicability\":\"MaybeIncorrect\""));
+    }
+
+    #[test]
+    fn labeled_spans() {
+        let out = emit_all(|emitter, cm| {
+            let mut msp = MultiSpan::new(Span { lo: BytePos(20), hi: BytePos(21),
+                                                expn_id: NO_EXPANSION });
+            msp.label_primary("use occurs here");
+            msp.push_label(Span { lo: BytePos(16), hi: BytePos(17), expn_id: NO_EXPANSION },
+                           "declared here");
+            emitter.emit_labeled(cm, &msp, "bad use of `x`", None, Error);
+        });
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].contains("\"column_start\":9,\"column_end\":10,\"is_primary\":true,\
+                                   \"label\":\"use occurs here\""));
+        assert!(lines[0].contains("\"column_start\":5,\"column_end\":6,\"is_primary\":false,\
+                                   \"label\":\"declared here\""));
+    }
+}
@@ -63,6 +63,7 @@ pub mod codemap;
 pub mod config;
 pub mod diagnostic;
 pub mod feature_gate;
+pub mod json;
 pub mod fold;
 pub mod owned_slice;
 pub mod parse;
@@ -0,0 +1,75 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Runtime support for `-C instrument-coverage`, see
+// src/librustc_trans/trans/coverage.rs for the format of the profile.
+
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+// The counters of one codegen unit. Must match the record emitted by
+// `coverage::finalize`.
+struct rust_coverage_unit {
+    const char *name;
+    uint64_t *counters;
+    uint64_t len;
+    struct rust_coverage_unit *next;
+};
+
+// Units register from constructors, before `main` runs, so no locking is
+// needed.
+static struct rust_coverage_unit *rust_coverage_units = NULL;
+
+static void
+rust_coverage_write(void) {
+    const char *path = getenv("RUST_COVERAGE_FILE");
+    struct rust_coverage_unit *unit;
+    FILE *f;
+
+    if (path == NULL || *path == '\0') {
+        path = "default.rustcov";
+    }
+    // Profiles are appended to, so that the runs of several programs, or
+    // of one program several times, can be reported on together.
+    f = fopen(path, "a");
+    if (f == NULL) {
+        fprintf(stderr, "couldn't write coverage profile to %s\n", path);
+        return;
+    }
+    for (unit = rust_coverage_units; unit != NULL; unit = unit->next) {
+        uint64_t i;
+        fprintf(f, "%s %llu", unit->name, (unsigned long long)unit->len);
+        for (i = 0; i < unit->len; i++) {
+            fprintf(f, " %llu", (unsigned long long)unit->counters[i]);
+        }
+        fputc('\n', f);
+    }
+    fclose(f);
+}
+
+void
+rust_coverage_register(struct rust_coverage_unit *unit) {
+    if (rust_coverage_units == NULL) {
+        atexit(rust_coverage_write);
+    }
+    unit->next = rust_coverage_units;
+    rust_coverage_units = unit;
+}
+
+//
+// Local Variables:
+// mode: C++
+// fill-column: 78;
+// indent-tabs-mode: nil
+// c-basic-offset: 4
+// buffer-file-coding-system: utf-8-unix
+// End:
+//
@@ -0,0 +1,52 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+// Runtime support for `-C profile-generate`, see
+// src/librustc_trans/trans/pgo.rs. The profile itself is written by the
+// profiling runtime of compiler-rt.
+
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+
+// Called from a constructor that runs before those of the profiling
+// runtime, which reads LLVM_PROFILE_FILE when the program starts.
+void
+rust_pgo_set_default_dir(const char *dir) {
+    const char *file = getenv("LLVM_PROFILE_FILE");
+    const char *name = "/default-%p.profraw";
+    char *path;
+
+    if (file != NULL && *file != '\0') {
+        return;
+    }
+    path = malloc(strlen(dir) + strlen(name) + 1);
+    if (path == NULL) {
+        return;
+    }
+    strcpy(path, dir);
+    strcat(path, name);
+#if defined(_WIN32)
+    _putenv_s("LLVM_PROFILE_FILE", path);
+#else
+    setenv("LLVM_PROFILE_FILE", path, 1);
+#endif
+    free(path);
+}
+
+//
+// Local Variables:
+// mode: C++
+// fill-column: 78;
+// indent-tabs-mode: nil
+// c-basic-offset: 4
+// buffer-file-coding-system: utf-8-unix
+// End:
+//
@@ -13,6 +13,7 @@
 #include "llvm/Object/ObjectFile.h"
 #include "llvm/IR/DiagnosticInfo.h"
 #include "llvm/IR/DiagnosticPrinter.h"
+#include "llvm/ProfileData/InstrProfReader.h"
 
 #if LLVM_VERSION_MINOR >= 5
 #include "llvm/IR/CallSite.h"
@@ -927,3 +928,44 @@ extern "C" void LLVMWriteSMDiagnosticToString(LLVMSMDiagnosticRef d, RustStringR
     raw_rust_string_ostream os(str);
     unwrap(d)->print("", os);
 }
+
+extern "C" IndexedInstrProfReader*
+LLVMRustOpenProfileData(const char *Path) {
+#if LLVM_VERSION_MINOR >= 6
+    ErrorOr<std::unique_ptr<IndexedInstrProfReader>> reader_or =
+        IndexedInstrProfReader::create(Path);
+    if (!reader_or) {
+        LLVMRustSetLastError(reader_or.getError().message().c_str());
+        return nullptr;
+    }
+    return reader_or.get().release();
+#else
+    std::unique_ptr<IndexedInstrProfReader> reader;
+    std::error_code err = IndexedInstrProfReader::create(Path, reader);
+    if (err) {
+        LLVMRustSetLastError(err.message().c_str());
+        return nullptr;
+    }
+    return reader.release();
+#endif
+}
+
+// Fills in the `len` counts of the function `name` whose hash is `hash`, and
+// returns false if the profile has no such function.
+extern "C" bool
+LLVMRustGetFunctionCounts(IndexedInstrProfReader *reader,
+                          const char *name,
+                          uint64_t hash,
+                          uint64_t *counts,
+                          size_t len) {
+    std::vector<uint64_t> found;
+    if (reader->getFunctionCounts(name, hash, found) || found.size() != len)
+        return false;
+    std::copy(found.begin(), found.end(), counts);
+    return true;
+}
+
+extern "C" void
+LLVMRustDestroyProfileData(IndexedInstrProfReader *reader) {
+    delete reader;
+}
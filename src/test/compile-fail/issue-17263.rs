@@ -14,10 +14,8 @@ fn main() {
     let mut x = box Foo { a: 1, b: 2 };
     let (a, b) = (&mut x.a, &mut x.b);
     //~^ ERROR cannot borrow `x` (here through borrowing `x.b`) as mutable more than once at a time
-    //~^^ NOTE previous borrow of `x` occurs here (through borrowing `x.a`)
 
     let mut foo = box Foo { a: 1, b: 2 };
     let (c, d) = (&mut foo.a, &foo.b);
     //~^ ERROR cannot borrow `foo` (here through borrowing `foo.b`) as immutable
-    //~^^ NOTE previous borrow of `foo` occurs here (through borrowing `foo.a`)
 }
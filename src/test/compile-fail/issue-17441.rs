@@ -14,7 +14,7 @@ fn main() {
     //~^^ HELP consider using an implicit coercion to `&[usize]` instead
     let _bar = box 1u as std::fmt::Show;
     //~^ ERROR cast to unsized type: `Box<usize>` as `core::fmt::Show`
-    //~^^ HELP did you mean `Box<core::fmt::Show>`?
+    //~^^ HELP try casting to a box
     let _baz = 1u as std::fmt::Show;
     //~^ ERROR cast to unsized type: `usize` as `core::fmt::Show`
     //~^^ HELP consider using a box or reference as appropriate
@@ -0,0 +1,26 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+mod shapes {
+//~^ HELP consider importing it: `use shapes::Square;`
+    pub struct Square;
+
+    mod inner {
+        pub fn hidden() {}
+    }
+}
+
+fn main() {
+    let _s: Square = shapes::Square;
+    //~^ ERROR use of undeclared type name `Square`
+    hidden();
+    //~^ ERROR unresolved name `hidden`
+}
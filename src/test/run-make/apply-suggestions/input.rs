@@ -0,0 +1,30 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn takes_ref(x: &uint) -> uint { *x }
+
+mod shapes {
+    pub fn area(w: uint, h: uint) -> uint { w * h }
+
+    pub mod report {
+        pub fn describe(w: uint, h: uint) -> uint {
+            area(w, h)
+        }
+    }
+}
+
+fn main() {
+    let x = 1u;
+    let y = takes_ref(x);
+    let v = Vec::new();
+    v.push(y);
+    v.push(area(2, 3));
+    v.push(shapes::report::describe(2, 3));
+}
@@ -0,0 +1,34 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn classify(n: u32) -> &'static str {
+    if n % 2 == 0 {
+        "even"
+    } else {
+        "odd"
+    }
+}
+
+fn check(n: u32) {
+    if n > 100 {
+        panic!("too big");
+    }
+}
+
+fn main() {
+    for i in range(0u32, 3) {
+        println!("{}", classify(i));
+        check(i);
+    }
+    match 3u32 {
+        0 => { println!("zero"); }
+        _ => { println!("other"); }
+    }
+}
@@ -0,0 +1,42 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+mod shapes {
+    pub trait Area {
+        fn area(&self) -> uint;
+    }
+
+    pub struct Square(pub uint);
+
+    impl Area for Square {
+        fn area(&self) -> uint { self.0 * self.0 }
+    }
+
+    pub fn total<T: Area>(shapes: &[T]) -> uint {
+        shapes.iter().fold(0, |sum, s| sum + s.area())
+    }
+}
+
+mod words {
+    pub fn greeting() -> String {
+        format!("hello {}", "world")
+    }
+}
+
+mod numbers {
+    pub fn triple(x: uint) -> uint { x * 3 }
+}
+
+fn main() {
+    let squares = [shapes::Square(2), shapes::Square(3)];
+    println!("{}", shapes::total(&squares));
+    println!("{}", words::greeting());
+    println!("{}", numbers::triple(7));
+}
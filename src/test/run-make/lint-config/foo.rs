@@ -0,0 +1,34 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+fn main() {
+    let a = 1u;
+}
+
+mod quiet {
+    pub fn f() {
+        let b = 2u;
+    }
+
+    mod inner {
+        fn g() {
+            let c = 3u;
+        }
+    }
+
+    #[deny(unused_variables)]
+    fn h() {
+        let d = 4u;
+    }
+}
+
+mod loud {
+    fn unused() {}
+}
@@ -0,0 +1,43 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![crate_name = "foo"]
+
+use std::default::Default;
+
+pub fn removed() {}
+
+pub fn changed(x: u32) -> u32 { x }
+
+pub fn bounded<T>(t: T) -> T { t }
+
+pub struct Point {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point {
+    pub fn new(x: i32, y: i32) -> Point { Point { x: x, y: y } }
+}
+
+impl Default for Point {
+    fn default() -> Point { Point::new(0, 0) }
+}
+
+pub struct Meters(pub u32);
+
+pub trait Shape {
+    fn area(&self) -> f64;
+}
+
+pub enum Color {
+    Red,
+    Green,
+}
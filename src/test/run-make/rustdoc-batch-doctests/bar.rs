@@ -0,0 +1,27 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+#![crate_name = "bar"]
+
+//! An example that doesn't compile only fails on its own.
+//!
+//! ```
+//! let x: int = 1;
+//! assert_eq!(x, 1);
+//! ```
+//!
+//! ```
+//! let y = undefined_variable;
+//! ```
+//!
+//! ```
+//! assert!(true);
+//! ```
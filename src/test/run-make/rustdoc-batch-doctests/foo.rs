@@ -0,0 +1,57 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+#![crate_name = "foo"]
+#![crate_type = "lib"]
+
+//! Examples that share a program must still behave as if they had one each.
+//!
+//! ```
+//! struct Point { x: int }
+//! assert_eq!(foo::double(Point { x: 2 }.x), 4);
+//! ```
+//!
+//! ```
+//! use foo::double;
+//! // The same name as in the example above.
+//! struct Point { x: int, y: int }
+//! let p = Point { x: 1, y: 2 };
+//! assert_eq!(double(p.x), p.y);
+//! ```
+//!
+//! ```
+//! // Only the program of a batch is told which example to run, so this
+//! // fails if the example was compiled on its own.
+//! let (mut a, mut b) = (std::os::args().len(), 0u);
+//! std::mem::swap(&mut a, &mut b);
+//! assert_eq!(b, 2);
+//! ```
+//!
+//! ```should_fail
+//! panic!("only this example fails");
+//! ```
+//!
+//! ```no_run
+//! loop {}
+//! ```
+//!
+//! ```
+//! fn main() {
+//!     assert_eq!(foo::double(0), 0);
+//! }
+//! ```
+
+/// Doubles `x`.
+///
+/// ```
+/// assert_eq!(foo::double(21), 42);
+/// ```
+pub fn double(x: int) -> int { x * 2 }
@@ -0,0 +1,29 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Links to [`bar::Baz`], [its method](bar::Baz::frob), [`Kind::Big`] and
+//! [`Vec::push`], but not to [`Missing`].
+
+pub mod bar {
+    /// Made by [`super::make`], see [`Kind`](crate::Kind).
+    pub struct Baz;
+
+    impl Baz {
+        pub fn frob(&self) {}
+    }
+}
+
+pub enum Kind {
+    Big,
+    Small,
+}
+
+/// Returns a [`bar::Baz`].
+pub fn make() -> bar::Baz { bar::Baz }
@@ -0,0 +1,14 @@
+#!/bin/sh
+set -e
+
+# $1 is the TMPDIR
+DOC=$1/doc/foo
+
+grep -F 'href="../foo/bar/struct.Baz.html"' $DOC/index.html > /dev/null
+grep -F 'href="../foo/bar/struct.Baz.html#method.frob"' $DOC/index.html > /dev/null
+grep -F 'href="../foo/enum.Kind.html#variant.Big"' $DOC/index.html > /dev/null
+grep -F 'struct.Vec.html#method.push"' $DOC/index.html > /dev/null
+grep -F '[<code>Missing</code>]' $DOC/index.html > /dev/null
+grep -F 'href="../../foo/fn.make.html"' $DOC/bar/struct.Baz.html > /dev/null
+grep -F 'href="../../foo/enum.Kind.html"' $DOC/bar/struct.Baz.html > /dev/null
+grep -F 'href="../foo/bar/struct.Baz.html"' $DOC/fn.make.html > /dev/null
@@ -22,4 +22,12 @@ pub mod bar {
 
     /// *wow*
     pub trait Doge { }
+
+    /// Such struct
+    pub struct Shibe<'a, T: Clone> {
+        pub name: &'a str,
+        pub treats: Vec<T>,
+    }
+
+    impl<'a, T: Clone> Doge for Shibe<'a, T> { }
 }
@@ -0,0 +1,51 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+#![crate_name = "foo"]
+
+//! Widgets and the tools to make them.
+
+/// Tools to make widgets.
+pub mod tools {
+    /// Makes a `Widget` of the given size.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let hidden_setup = 1;
+    /// let w = foo::tools::make(3);
+    /// ```
+    pub fn make(size: uint) -> ::Widget { ::Widget { size: size } }
+
+    pub fn undocumented() {}
+}
+
+/// A widget.
+pub struct Widget {
+    /// How big it is.
+    pub size: uint,
+}
+
+/// A length.
+pub struct Meters(pub uint);
+
+/// Lists the tools, and shares the name of their module.
+pub fn tools() {}
+
+impl Widget {
+    /// Doubles the **size** of the widget.
+    pub fn grow(&mut self) { self.size *= 2 }
+}
+
+mod private {
+    /// Never documented.
+    pub fn hidden() {}
+}
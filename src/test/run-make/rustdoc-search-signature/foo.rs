@@ -0,0 +1,29 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+#![crate_name = "foo"]
+
+pub fn first<T>(v: Vec<T>) -> Option<T> { v.into_iter().next() }
+
+pub fn length(s: &str) -> uint { s.len() }
+
+pub fn nothing(_: uint) {}
+
+pub struct Widget;
+
+impl Widget {
+    pub fn size(&self) -> uint { 0 }
+}
+
+pub trait Shape {
+    fn area(&self) -> f64;
+    fn scaled(&self, factor: f64) -> Self;
+}
@@ -0,0 +1,32 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+pub mod bar {
+    pub struct Baz;
+
+    impl Baz {
+        pub fn frob(&self) -> uint { 1 }
+    }
+}
+
+pub enum Kind {
+    Big,
+    Small,
+}
+
+pub fn make() -> bar::Baz { bar::Baz }
+
+pub fn size(k: Kind) -> uint {
+    match k {
+        Kind::Big => make().frob(),
+        Kind::Small => 0,
+    }
+}
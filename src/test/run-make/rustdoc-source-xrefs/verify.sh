@@ -0,0 +1,15 @@
+#!/bin/sh
+set -e
+
+# $1 is the TMPDIR
+DOC=$1/doc
+SRC=$DOC/src/foo/foo.rs.html
+
+grep -F "<a class='ident' href='../../src/foo/foo.rs.html#13'>Baz</a>" $SRC > /dev/null
+grep -F "<a class='ident' href='../../src/foo/foo.rs.html#16'>frob</a>" $SRC > /dev/null
+grep -F "<a class='ident' href='../../src/foo/foo.rs.html#20'>Kind</a>" $SRC > /dev/null
+grep -F "<a class='ident' href='../../src/foo/foo.rs.html#21'>Big</a>" $SRC > /dev/null
+grep -F "<a class='ident' href='../../src/foo/foo.rs.html#25'>make</a>" $SRC > /dev/null
+grep -F "<a href='../../src/foo/foo.rs.html#25'>foo.rs:25</a>" $DOC/foo/bar/struct.Baz.html > /dev/null
+grep -F "<a href='../src/foo/foo.rs.html#29'>foo.rs:29</a>" $DOC/foo/fn.make.html > /dev/null
+grep -F "<a href='../src/foo/foo.rs.html#27'>foo.rs:27</a>" $DOC/foo/enum.Kind.html > /dev/null
@@ -0,0 +1,37 @@
+// Copyright 2015 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution and at
+// http://rust-lang.org/COPYRIGHT.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+/// A point in the plane.
+pub struct Point {
+    pub x: int,
+    pub y: int,
+}
+
+pub trait Shape {
+    fn area(&self) -> int;
+}
+
+pub trait Polygon: Shape {
+    fn corners(&self) -> uint;
+}
+
+impl Shape for Point {
+    fn area(&self) -> int { 0 }
+}
+
+/// Adds up the coordinates.
+fn sum(p: &Point) -> int {
+    p.x + p.y
+}
+
+fn main() {
+    let p = Point { x: 1, y: 2 };
+    println!("{} {}", sum(&p), p.area());
+}